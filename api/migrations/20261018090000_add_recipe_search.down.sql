@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TYPE t_search_highlight;
+DROP INDEX step_search_document_idx;
+ALTER TABLE step DROP COLUMN search_document;
+DROP INDEX ingredient_search_document_idx;
+ALTER TABLE ingredient DROP COLUMN search_document;
+DROP INDEX recipe_search_document_idx;
+ALTER TABLE recipe DROP COLUMN search_document;
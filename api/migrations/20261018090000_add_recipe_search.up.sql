@@ -0,0 +1,21 @@
+-- Add up migration script here
+ALTER TABLE recipe ADD COLUMN search_document tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'B')
+) STORED;
+CREATE INDEX recipe_search_document_idx ON recipe USING GIN (search_document);
+
+ALTER TABLE ingredient ADD COLUMN search_document tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'C')
+) STORED;
+CREATE INDEX ingredient_search_document_idx ON ingredient USING GIN (search_document);
+
+ALTER TABLE step ADD COLUMN search_document tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', instruction), 'D')
+) STORED;
+CREATE INDEX step_search_document_idx ON step USING GIN (search_document);
+
+CREATE TYPE t_search_highlight AS (
+    field TEXT,
+    snippet TEXT
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TYPE t_tag;
+DROP TABLE recipe_tag;
+DROP TABLE tag;
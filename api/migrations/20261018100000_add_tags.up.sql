@@ -0,0 +1,18 @@
+-- Add up migration script here
+CREATE TABLE tag (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE recipe_tag (
+    recipe integer REFERENCES recipe(id) ON DELETE CASCADE,
+    tag integer REFERENCES tag(id) ON DELETE CASCADE,
+    PRIMARY KEY (recipe, tag)
+);
+
+CREATE INDEX recipe_tag_tag_idx ON recipe_tag (tag);
+
+CREATE TYPE t_tag AS (
+    id integer,
+    name TEXT
+);
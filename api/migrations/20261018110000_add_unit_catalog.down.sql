@@ -0,0 +1,10 @@
+-- Add down migration script here
+DELETE FROM unit WHERE name <> 'grams';
+ALTER TABLE unit
+    DROP COLUMN to_base_offset,
+    DROP COLUMN to_base_factor,
+    DROP COLUMN system,
+    DROP COLUMN dimension,
+    DROP COLUMN abbreviation;
+DROP TYPE measurement_system;
+DROP TYPE unit_dimension;
@@ -0,0 +1,40 @@
+-- Add up migration script here
+CREATE TYPE unit_dimension AS ENUM ('mass', 'volume', 'count', 'length', 'temperature');
+CREATE TYPE measurement_system AS ENUM ('metric', 'imperial');
+
+-- Quantities convert to the base unit of their dimension as
+-- (quantity + to_base_offset) * to_base_factor
+ALTER TABLE unit
+    ADD COLUMN abbreviation TEXT UNIQUE,
+    ADD COLUMN dimension unit_dimension,
+    ADD COLUMN system measurement_system,
+    ADD COLUMN to_base_factor double precision NOT NULL DEFAULT 1,
+    ADD COLUMN to_base_offset double precision NOT NULL DEFAULT 0;
+
+UPDATE unit SET abbreviation = 'g', dimension = 'mass', system = 'metric' WHERE name = 'grams';
+
+INSERT INTO unit (name, abbreviation, dimension, system, to_base_factor, to_base_offset) VALUES
+    ('milligrams', 'mg', 'mass', 'metric', 0.001, 0),
+    ('kilograms', 'kg', 'mass', 'metric', 1000, 0),
+    ('ounces', 'oz', 'mass', 'imperial', 28.349523125, 0),
+    ('pounds', 'lb', 'mass', 'imperial', 453.59237, 0),
+    ('milliliters', 'ml', 'volume', 'metric', 1, 0),
+    ('liters', 'l', 'volume', 'metric', 1000, 0),
+    ('teaspoons', 'tsp', 'volume', 'imperial', 4.92892159375, 0),
+    ('tablespoons', 'tbsp', 'volume', 'imperial', 14.78676478125, 0),
+    ('fluid ounces', 'fl oz', 'volume', 'imperial', 29.5735295625, 0),
+    ('cups', 'c', 'volume', 'imperial', 236.5882365, 0),
+    ('pints', 'pt', 'volume', 'imperial', 473.176473, 0),
+    ('quarts', 'qt', 'volume', 'imperial', 946.352946, 0),
+    ('gallons', 'gal', 'volume', 'imperial', 3785.411784, 0),
+    ('each', 'ea', 'count', NULL, 1, 0),
+    ('dozen', 'doz', 'count', NULL, 12, 0),
+    ('millimeters', 'mm', 'length', 'metric', 1, 0),
+    ('centimeters', 'cm', 'length', 'metric', 10, 0),
+    ('meters', 'm', 'length', 'metric', 1000, 0),
+    ('inches', 'in', 'length', 'imperial', 25.4, 0),
+    ('celsius', '°C', 'temperature', 'metric', 1, 0),
+    ('fahrenheit', '°F', 'temperature', 'imperial', 0.5555555555555556, -32)
+ON CONFLICT (name) DO NOTHING;
+
+ALTER TABLE unit ALTER COLUMN dimension SET NOT NULL;
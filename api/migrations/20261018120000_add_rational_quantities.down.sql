@@ -0,0 +1,14 @@
+-- Add down migration script here
+ALTER TYPE t_recipe_ingredient ALTER ATTRIBUTE quantity TYPE integer;
+
+ALTER TABLE recipe_ingredient
+    DROP CONSTRAINT recipe_ingredient_quantity_check,
+    ALTER COLUMN quantity TYPE integer
+        USING round((quantity).numerator::numeric / (quantity).denominator)::integer;
+
+ALTER TABLE recipe
+    DROP CONSTRAINT recipe_yield_quantity_check,
+    ALTER COLUMN yield_quantity TYPE integer
+        USING round((yield_quantity).numerator::numeric / (yield_quantity).denominator)::integer;
+
+DROP TYPE t_quantity;
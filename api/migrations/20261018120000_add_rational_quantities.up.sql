@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TYPE t_quantity AS (
+    numerator bigint,
+    denominator bigint
+);
+
+ALTER TABLE recipe
+    ALTER COLUMN yield_quantity TYPE t_quantity
+        USING ROW(yield_quantity, 1)::t_quantity,
+    ADD CONSTRAINT recipe_yield_quantity_check
+        CHECK ((yield_quantity).numerator >= 0 AND (yield_quantity).denominator > 0);
+
+ALTER TABLE recipe_ingredient
+    ALTER COLUMN quantity TYPE t_quantity
+        USING ROW(quantity, 1)::t_quantity,
+    ADD CONSTRAINT recipe_ingredient_quantity_check
+        CHECK ((quantity).numerator >= 0 AND (quantity).denominator > 0);
+
+ALTER TYPE t_recipe_ingredient ALTER ATTRIBUTE quantity TYPE t_quantity;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TYPE t_step DROP ATTRIBUTE group_name;
+ALTER TYPE t_recipe_ingredient DROP ATTRIBUTE group_name;
+DROP TYPE t_recipe_group;
+ALTER TABLE step DROP COLUMN recipe_group;
+ALTER TABLE recipe_ingredient DROP COLUMN recipe_group;
+DROP TABLE recipe_group;
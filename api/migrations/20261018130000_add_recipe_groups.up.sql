@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE TABLE recipe_group (
+    id SERIAL PRIMARY KEY,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    ordinal integer NOT NULL,
+    UNIQUE (recipe, name)
+);
+
+ALTER TABLE recipe_ingredient
+    ADD COLUMN recipe_group integer REFERENCES recipe_group(id) ON DELETE SET NULL;
+
+ALTER TABLE step
+    ADD COLUMN recipe_group integer REFERENCES recipe_group(id) ON DELETE SET NULL;
+
+CREATE TYPE t_recipe_group AS (
+    id integer,
+    name TEXT,
+    ordinal integer
+);
+
+ALTER TYPE t_recipe_ingredient ADD ATTRIBUTE group_name TEXT;
+
+ALTER TYPE t_step ADD ATTRIBUTE group_name TEXT;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TYPE t_recipe_ingredient DROP ATTRIBUTE sub_recipe_id;
+ALTER TABLE recipe_ingredient DROP COLUMN sub_recipe;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE recipe_ingredient
+    ADD COLUMN sub_recipe integer REFERENCES recipe(id) ON DELETE SET NULL;
+
+CREATE INDEX recipe_ingredient_sub_recipe_idx ON recipe_ingredient (sub_recipe);
+
+ALTER TYPE t_recipe_ingredient ADD ATTRIBUTE sub_recipe_id integer;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TYPE t_step DROP ATTRIBUTE image;
+ALTER TABLE step DROP COLUMN image;
+ALTER TABLE recipe DROP COLUMN image;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE recipe ADD COLUMN image text;
+ALTER TABLE step ADD COLUMN image text;
+
+ALTER TYPE t_step ADD ATTRIBUTE image text;
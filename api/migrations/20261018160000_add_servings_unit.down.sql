@@ -0,0 +1,2 @@
+-- Add down migration script here
+DELETE FROM unit WHERE name = 'servings';
@@ -0,0 +1,4 @@
+-- Add up migration script here
+INSERT INTO unit (name, abbreviation, dimension, system, to_base_factor, to_base_offset) VALUES
+    ('servings', NULL, 'count', NULL, 1, 0)
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE shopping_list_item;
+DROP TABLE shopping_list_recipe;
+DROP TABLE shopping_list;
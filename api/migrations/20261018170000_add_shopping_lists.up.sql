@@ -0,0 +1,29 @@
+-- Add up migration script here
+CREATE TABLE shopping_list (
+    id SERIAL PRIMARY KEY,
+    owner integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+
+CREATE INDEX shopping_list_owner_idx ON shopping_list (owner);
+
+CREATE TABLE shopping_list_recipe (
+    id SERIAL PRIMARY KEY,
+    shopping_list integer NOT NULL REFERENCES shopping_list(id) ON DELETE CASCADE,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    scale t_quantity NOT NULL
+        CHECK ((scale).numerator > 0 AND (scale).denominator > 0)
+);
+
+CREATE TABLE shopping_list_item (
+    id SERIAL PRIMARY KEY,
+    shopping_list integer NOT NULL REFERENCES shopping_list(id) ON DELETE CASCADE,
+    ingredient integer NOT NULL REFERENCES ingredient(id),
+    quantity t_quantity
+        CHECK ((quantity).numerator >= 0 AND (quantity).denominator > 0),
+    units integer REFERENCES unit(id),
+    checked boolean NOT NULL DEFAULT false,
+    ordinal integer NOT NULL
+);
+
+CREATE INDEX shopping_list_item_shopping_list_idx ON shopping_list_item (shopping_list);
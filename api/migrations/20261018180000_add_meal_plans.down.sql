@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE meal_plan_entry;
+DROP TYPE meal_slot;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TYPE meal_slot AS ENUM ('breakfast', 'lunch', 'dinner', 'snack');
+
+CREATE TABLE meal_plan_entry (
+    id SERIAL PRIMARY KEY,
+    owner integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    slot meal_slot NOT NULL,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    servings t_quantity NOT NULL
+        CHECK ((servings).numerator > 0 AND (servings).denominator > 0)
+);
+
+CREATE INDEX meal_plan_entry_owner_date_idx ON meal_plan_entry (owner, date);
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE pantry_item (
+    id SERIAL PRIMARY KEY,
+    owner integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    ingredient integer NOT NULL REFERENCES ingredient(id),
+    quantity t_quantity NOT NULL
+        CHECK ((quantity).numerator >= 0 AND (quantity).denominator > 0),
+    units integer NOT NULL REFERENCES unit(id),
+    location TEXT NOT NULL DEFAULT '',
+    expires_on DATE
+);
+
+CREATE INDEX pantry_item_owner_idx ON pantry_item (owner);
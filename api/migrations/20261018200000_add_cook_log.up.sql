@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE cook_log (
+    id SERIAL PRIMARY KEY,
+    owner integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    cooked_on DATE NOT NULL,
+    servings t_quantity NOT NULL
+        CHECK ((servings).numerator > 0 AND (servings).denominator > 0),
+    notes TEXT NOT NULL DEFAULT '',
+    modifications TEXT NOT NULL DEFAULT '',
+    rating smallint CHECK (rating BETWEEN 1 AND 5)
+);
+
+CREATE INDEX cook_log_recipe_idx ON cook_log (recipe);
+CREATE INDEX cook_log_owner_cooked_on_idx ON cook_log (owner, cooked_on);
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE review (
+    id SERIAL PRIMARY KEY,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    reviewer integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    rating smallint NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    body TEXT NOT NULL DEFAULT '',
+    reply TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    replied_at TIMESTAMPTZ,
+    UNIQUE (recipe, reviewer)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE collection_recipe;
+DROP TABLE collection;
+DROP TABLE favorite;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+CREATE TABLE favorite (
+    app_user integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (app_user, recipe)
+);
+
+CREATE TABLE collection (
+    id SERIAL PRIMARY KEY,
+    owner integer NOT NULL REFERENCES app_user(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    shared boolean NOT NULL DEFAULT false
+);
+
+CREATE INDEX collection_owner_idx ON collection (owner);
+
+CREATE TABLE collection_recipe (
+    collection integer NOT NULL REFERENCES collection(id) ON DELETE CASCADE,
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    ordinal integer NOT NULL,
+    PRIMARY KEY (collection, recipe)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP FUNCTION recipe_lineage;
+DROP TYPE t_recipe_ancestor;
+ALTER TABLE recipe DROP COLUMN forked_from;
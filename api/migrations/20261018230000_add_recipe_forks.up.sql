@@ -0,0 +1,32 @@
+-- Add up migration script here
+ALTER TABLE recipe
+    ADD COLUMN forked_from integer REFERENCES recipe(id) ON DELETE SET NULL;
+
+CREATE INDEX recipe_forked_from_idx ON recipe (forked_from);
+
+CREATE TYPE t_recipe_ancestor AS (
+    id integer,
+    title TEXT,
+    author t_app_user
+);
+
+-- The recipe `parent` and the recipes it was in turn forked from, nearest
+-- first.
+CREATE FUNCTION recipe_lineage(parent integer) RETURNS t_recipe_ancestor[]
+LANGUAGE sql STABLE AS $$
+    WITH RECURSIVE ancestor AS (
+        SELECT p.id, p.title, p.author, p.forked_from, 1 AS depth
+        FROM recipe p
+        WHERE p.id = parent
+        UNION ALL
+        SELECT p.id, p.title, p.author, p.forked_from, a.depth + 1
+        FROM recipe p
+        JOIN ancestor a ON p.id = a.forked_from
+    )
+    SELECT COALESCE(
+        array_agg((a.id, a.title, (au.id, au.name)::t_app_user)::t_recipe_ancestor ORDER BY a.depth),
+        '{}'
+    )
+    FROM ancestor a
+    JOIN app_user au ON au.id = a.author
+$$;
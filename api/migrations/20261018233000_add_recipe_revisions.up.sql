@@ -0,0 +1,8 @@
+-- Add up migration script here
+CREATE TABLE recipe_revision (
+    recipe integer NOT NULL REFERENCES recipe(id) ON DELETE CASCADE,
+    revision integer NOT NULL,
+    snapshot JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (recipe, revision)
+);
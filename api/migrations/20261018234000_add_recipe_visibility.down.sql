@@ -0,0 +1,24 @@
+-- Add down migration script here
+ALTER TYPE t_recipe_ancestor DROP ATTRIBUTE draft, DROP ATTRIBUTE visibility;
+
+CREATE OR REPLACE FUNCTION recipe_lineage(parent integer) RETURNS t_recipe_ancestor[]
+LANGUAGE sql STABLE AS $$
+    WITH RECURSIVE ancestor AS (
+        SELECT p.id, p.title, p.author, p.forked_from, 1 AS depth
+        FROM recipe p
+        WHERE p.id = parent
+        UNION ALL
+        SELECT p.id, p.title, p.author, p.forked_from, a.depth + 1
+        FROM recipe p
+        JOIN ancestor a ON p.id = a.forked_from
+    )
+    SELECT COALESCE(
+        array_agg((a.id, a.title, (au.id, au.name)::t_app_user)::t_recipe_ancestor ORDER BY a.depth),
+        '{}'
+    )
+    FROM ancestor a
+    JOIN app_user au ON au.id = a.author
+$$;
+
+ALTER TABLE recipe DROP COLUMN draft, DROP COLUMN visibility;
+DROP TYPE recipe_visibility;
@@ -0,0 +1,36 @@
+-- Add up migration script here
+CREATE TYPE recipe_visibility AS ENUM ('private', 'unlisted', 'public');
+
+-- Recipes written before visibility existed were readable by anyone.
+ALTER TABLE recipe
+    ADD COLUMN visibility recipe_visibility NOT NULL DEFAULT 'public',
+    ADD COLUMN draft boolean NOT NULL DEFAULT false;
+
+CREATE INDEX recipe_listed_idx ON recipe (id) WHERE visibility = 'public' AND NOT draft;
+
+-- Ancestors carry their visibility so forks don't reveal hidden recipes.
+ALTER TYPE t_recipe_ancestor
+    ADD ATTRIBUTE visibility recipe_visibility,
+    ADD ATTRIBUTE draft boolean;
+
+CREATE OR REPLACE FUNCTION recipe_lineage(parent integer) RETURNS t_recipe_ancestor[]
+LANGUAGE sql STABLE AS $$
+    WITH RECURSIVE ancestor AS (
+        SELECT p.id, p.title, p.author, p.visibility, p.draft, p.forked_from, 1 AS depth
+        FROM recipe p
+        WHERE p.id = parent
+        UNION ALL
+        SELECT p.id, p.title, p.author, p.visibility, p.draft, p.forked_from, a.depth + 1
+        FROM recipe p
+        JOIN ancestor a ON p.id = a.forked_from
+    )
+    SELECT COALESCE(
+        array_agg(
+            (a.id, a.title, (au.id, au.name)::t_app_user, a.visibility, a.draft)::t_recipe_ancestor
+            ORDER BY a.depth
+        ),
+        '{}'
+    )
+    FROM ancestor a
+    JOIN app_user au ON au.id = a.author
+$$;
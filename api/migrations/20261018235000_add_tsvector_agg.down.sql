@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP AGGREGATE tsvector_agg (tsvector);
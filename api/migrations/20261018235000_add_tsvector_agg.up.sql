@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE AGGREGATE tsvector_agg (tsvector) (
+    SFUNC = tsvector_concat,
+    STYPE = tsvector,
+    INITCOND = ''
+);
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Query, State},
+    http::header,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{error::AppError, extract::ExtractAuthUser, AppState};
+
+/// File name suggested to clients saving an exported archive.
+const ARCHIVE_FILE_NAME: &str = "stockpot-library.zip";
+
+#[derive(Debug, Deserialize)]
+pub struct ImportArchiveParams {
+    #[serde(default)]
+    pub on_conflict: domain::archive::OnConflict,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetImportedRecipe {
+    pub title: String,
+    /// One of `created`, `renamed`, `overwritten`, `skipped` or `failed`.
+    pub outcome: String,
+    /// The title given to a renamed recipe.
+    pub new_title: Option<String>,
+    /// Why the recipe failed to import.
+    pub error: Option<String>,
+    pub recipe_id: Option<i32>,
+    pub warnings: Vec<String>,
+}
+
+impl From<domain::archive::ImportedRecipe> for GetImportedRecipe {
+    fn from(value: domain::archive::ImportedRecipe) -> Self {
+        let (outcome, new_title, error) = match value.outcome {
+            domain::archive::ImportOutcome::Created => ("created", None, None),
+            domain::archive::ImportOutcome::Renamed(title) => ("renamed", Some(title), None),
+            domain::archive::ImportOutcome::Overwritten => ("overwritten", None, None),
+            domain::archive::ImportOutcome::Skipped => ("skipped", None, None),
+            domain::archive::ImportOutcome::Failed(error) => ("failed", None, Some(error)),
+        };
+        Self {
+            title: value.title,
+            outcome: outcome.to_owned(),
+            new_title,
+            error,
+            recipe_id: value.recipe_id,
+            warnings: value.warnings,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImportArchiveResponse {
+    pub recipes: Vec<GetImportedRecipe>,
+}
+
+impl From<domain::archive::ImportReport> for ImportArchiveResponse {
+    fn from(value: domain::archive::ImportReport) -> Self {
+        Self {
+            recipes: value.recipes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/recipe/archive", get(export_archive).post(import_archive))
+        .layer(DefaultBodyLimit::max(domain::archive::MAX_ARCHIVE_BYTES))
+}
+
+/// Downloads every recipe the user owns, with their images, as a zip file.
+pub async fn export_archive(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+) -> anyhow::Result<impl IntoResponse, AppError> {
+    let archive = state.archive_service.export_library(auth_user.user).await?;
+    let bytes = tokio::task::spawn_blocking(move || archive.to_bytes())
+        .await
+        .map_err(|e| AppError::Unexpected(e.to_string()))??;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", ARCHIVE_FILE_NAME),
+            ),
+        ],
+        bytes,
+    ))
+}
+
+/// Recreates the recipes of an uploaded archive for the user.
+pub async fn import_archive(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(params): Query<ImportArchiveParams>,
+    body: Bytes,
+) -> anyhow::Result<Json<ImportArchiveResponse>, AppError> {
+    let archive = tokio::task::spawn_blocking(move || domain::archive::Archive::from_bytes(&body))
+        .await
+        .map_err(|e| AppError::Unexpected(e.to_string()))??;
+    Ok(Json(
+        state
+            .archive_service
+            .import_library(archive, auth_user.user, params.on_conflict)
+            .await?
+            .into(),
+    ))
+}
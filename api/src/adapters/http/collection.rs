@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{adapters, core::domain};
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetCollectionRecipe {
+    pub recipe_id: i32,
+    pub title: String,
+}
+
+impl From<domain::collection::CollectionRecipe> for GetCollectionRecipe {
+    fn from(value: domain::collection::CollectionRecipe) -> Self {
+        Self {
+            recipe_id: value.recipe_id,
+            title: value.title,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetCollection {
+    pub id: i32,
+    pub name: String,
+    pub owner: adapters::http::user::GetUser,
+    pub shared: bool,
+    pub recipes: Vec<GetCollectionRecipe>,
+}
+
+impl From<domain::collection::Collection> for GetCollection {
+    fn from(value: domain::collection::Collection) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            name: value.name,
+            owner: value.owner.into(),
+            shared: value.shared,
+            recipes: value.recipes.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutateCollection {
+    pub name: String,
+    /// Whether anyone may read the collection.
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Deserialize)]
+pub struct AddCollectionRecipe {
+    pub recipe_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct ReorderCollectionRecipes {
+    pub recipe_ids: Vec<i32>,
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/collection", get(get_collections))
+        .route("/collection", post(create_collection))
+        .route("/collection/:id", get(get_collection))
+        .route("/collection/:id", post(update_collection))
+        .route("/collection/:id", delete(delete_collection))
+        .route("/collection/:id/recipe", post(add_recipe))
+        .route("/collection/:id/recipe/:recipe_id", delete(remove_recipe))
+        .route("/collection/:id/order", post(reorder_recipes))
+        .route("/favorites", get(get_favorites))
+        .route("/recipe/:id/favorite", post(add_favorite))
+        .route("/recipe/:id/favorite", delete(remove_favorite))
+}
+
+pub async fn get_collections(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+) -> anyhow::Result<Json<Vec<GetCollection>>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .get_collections(auth_user.user)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn create_collection(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<MutateCollection>,
+) -> anyhow::Result<(StatusCode, Json<GetCollection>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .collection_service
+                .create_collection(request.name, request.shared, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+/// Shared collections can be read without signing in.
+pub async fn get_collection(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .get_collection(id, auth_user.map(|x| x.0.user))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_collection(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateCollection>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .update_collection(id, request.name, request.shared, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_collection(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .delete_collection(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn add_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<AddCollectionRecipe>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .add_recipe(id, request.recipe_id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn remove_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, recipe_id)): Path<(i32, i32)>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .remove_recipe(id, recipe_id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn reorder_recipes(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<ReorderCollectionRecipes>,
+) -> anyhow::Result<Json<GetCollection>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .reorder_recipes(id, request.recipe_ids, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+/// The signed in user's favorite recipes, most recently added first.
+pub async fn get_favorites(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+) -> anyhow::Result<Json<Vec<GetCollectionRecipe>>, AppError> {
+    Ok(Json(
+        state
+            .collection_service
+            .get_favorites(auth_user.user)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn add_favorite(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<StatusCode, AppError> {
+    state
+        .collection_service
+        .add_favorite(id, auth_user.user)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_favorite(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<StatusCode, AppError> {
+    state
+        .collection_service
+        .remove_favorite(id, auth_user.user)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
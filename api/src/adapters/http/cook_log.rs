@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{adapters, core::domain};
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetCookLogEntry {
+    pub id: i32,
+    pub cook: adapters::http::user::GetUser,
+    pub recipe_id: i32,
+    pub recipe_title: String,
+    pub cooked_on: NaiveDate,
+    pub servings: domain::Quantity,
+    pub notes: String,
+    pub modifications: String,
+    pub rating: Option<i16>,
+}
+
+impl From<domain::cook_log::CookLogEntry> for GetCookLogEntry {
+    fn from(value: domain::cook_log::CookLogEntry) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            cook: value.owner.into(),
+            recipe_id: value.recipe_id,
+            recipe_title: value.recipe_title,
+            cooked_on: value.cooked_on,
+            servings: value.servings,
+            notes: value.notes,
+            modifications: value.modifications,
+            rating: value.rating,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutateCookLogEntry {
+    pub recipe_id: i32,
+    pub cooked_on: NaiveDate,
+    pub servings: domain::Quantity,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub modifications: String,
+    pub rating: Option<i16>,
+}
+
+impl domain::cook_log::CookLogEntry {
+    fn from_request(value: MutateCookLogEntry, id: Option<i32>, owner: domain::User) -> Self {
+        Self {
+            id,
+            owner,
+            recipe_id: value.recipe_id,
+            recipe_title: String::new(),
+            cooked_on: value.cooked_on,
+            servings: value.servings,
+            notes: value.notes,
+            modifications: value.modifications,
+            rating: value.rating,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct GetCookStats {
+    pub times_cooked: i64,
+    pub last_cooked: Option<NaiveDate>,
+    pub average_rating: Option<f64>,
+}
+
+impl From<domain::cook_log::CookStats> for GetCookStats {
+    fn from(value: domain::cook_log::CookStats) -> Self {
+        Self {
+            times_cooked: value.times_cooked,
+            last_cooked: value.last_cooked,
+            average_rating: value.average_rating,
+        }
+    }
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/recipe/:id/cook-log", get(get_recipe_history))
+        .route("/user/:id/cook-log", get(get_user_history))
+        .route("/cook-log", post(create_entry))
+        .route("/cook-log/:id", get(get_entry))
+        .route("/cook-log/:id", post(update_entry))
+        .route("/cook-log/:id", delete(delete_entry))
+}
+
+async fn get_history(
+    state: &AppState,
+    query: domain::cook_log::CookLogQuery,
+    auth_user: Option<ExtractAuthUser>,
+) -> anyhow::Result<Json<Vec<GetCookLogEntry>>, AppError> {
+    Ok(Json(
+        state
+            .cook_log_service
+            .get_history(query, auth_user.map(|x| x.0.user))
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+/// Everyone's history of making a recipe, most recent first.
+pub async fn get_recipe_history(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<Vec<GetCookLogEntry>>, AppError> {
+    get_history(
+        &state,
+        domain::cook_log::CookLogQuery::Recipe(id),
+        auth_user,
+    )
+    .await
+}
+
+/// Everything a user has made, most recent first.
+pub async fn get_user_history(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<Vec<GetCookLogEntry>>, AppError> {
+    get_history(&state, domain::cook_log::CookLogQuery::Owner(id), auth_user).await
+}
+
+pub async fn create_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<MutateCookLogEntry>,
+) -> anyhow::Result<(StatusCode, Json<GetCookLogEntry>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .cook_log_service
+                .create_entry(domain::cook_log::CookLogEntry::from_request(
+                    request,
+                    None,
+                    auth_user.user,
+                ))
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn get_entry(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetCookLogEntry>, AppError> {
+    Ok(Json(
+        state
+            .cook_log_service
+            .get_entry(id, auth_user.map(|x| x.0.user))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateCookLogEntry>,
+) -> anyhow::Result<Json<GetCookLogEntry>, AppError> {
+    Ok(Json(
+        state
+            .cook_log_service
+            .update_entry(domain::cook_log::CookLogEntry::from_request(
+                request,
+                Some(id),
+                auth_user.user,
+            ))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetCookLogEntry>, AppError> {
+    Ok(Json(
+        state
+            .cook_log_service
+            .delete_entry(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
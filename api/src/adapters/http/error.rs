@@ -1,5 +1,9 @@
 use axum::{
-    extract::{path::ErrorKind, rejection::PathRejection},
+    extract::{
+        multipart::MultipartError,
+        path::ErrorKind,
+        rejection::{PathRejection, QueryRejection},
+    },
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
@@ -26,9 +30,21 @@ impl From<&AppError> for ErrorResponse {
             AppError::PathParseError(ref rejection) => Self {
                 error: rejection.to_string(),
             },
+            AppError::QueryParseError(ref rejection) => Self {
+                error: rejection.body_text(),
+            },
+            AppError::BadRequest(ref error) => Self {
+                error: error.clone(),
+            },
             AppError::Unauthorized(ref error) => Self {
                 error: error.clone(),
             },
+            AppError::PayloadTooLarge(ref error) => Self {
+                error: error.clone(),
+            },
+            AppError::UnsupportedMediaType(ref error) => Self {
+                error: error.clone(),
+            },
         }
     }
 }
@@ -42,6 +58,14 @@ pub enum AppError {
     #[error("{0}")]
     PathParseError(PathRejection),
     #[error("{0}")]
+    QueryParseError(QueryRejection),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    PayloadTooLarge(String),
+    #[error("{0}")]
+    UnsupportedMediaType(String),
+    #[error("{0}")]
     Unexpected(String),
 }
 
@@ -70,11 +94,182 @@ impl From<domain::recipe::Error> for AppError {
     fn from(value: domain::recipe::Error) -> Self {
         match value {
             domain::recipe::Error::RecipeNotFound(_) => Self::EntityNotFound(value.to_string()),
+            domain::recipe::Error::InvalidQuery(_)
+            | domain::recipe::Error::UnknownUnit(_)
+            | domain::recipe::Error::InvalidScale(_)
+            | domain::recipe::Error::InvalidRecipe(_)
+            | domain::recipe::Error::InvalidImport(_) => Self::BadRequest(value.to_string()),
             domain::recipe::Error::Unexpected => Self::Unexpected(value.to_string()),
         }
     }
 }
 
+impl From<domain::tag::Error> for AppError {
+    fn from(value: domain::tag::Error) -> Self {
+        match value {
+            domain::tag::Error::TagNotFound(_) => Self::EntityNotFound(value.to_string()),
+            domain::tag::Error::InvalidTag(_) => Self::BadRequest(value.to_string()),
+            domain::tag::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::tag::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::unit::Error> for AppError {
+    fn from(value: domain::unit::Error) -> Self {
+        match value {
+            domain::unit::Error::UnknownUnit(_) | domain::unit::Error::IncompatibleUnits(_, _) => {
+                Self::BadRequest(value.to_string())
+            }
+            domain::unit::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::media::Error> for AppError {
+    fn from(value: domain::media::Error) -> Self {
+        match value {
+            domain::media::Error::RecipeNotFound(_)
+            | domain::media::Error::StepNotFound(_)
+            | domain::media::Error::MediaNotFound(_) => Self::EntityNotFound(value.to_string()),
+            domain::media::Error::UnsupportedContentType(_) => {
+                Self::UnsupportedMediaType(value.to_string())
+            }
+            domain::media::Error::TooLarge(_) => Self::PayloadTooLarge(value.to_string()),
+            domain::media::Error::InvalidImage(_) => Self::BadRequest(value.to_string()),
+            domain::media::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::archive::Error> for AppError {
+    fn from(value: domain::archive::Error) -> Self {
+        match value {
+            domain::archive::Error::InvalidArchive(_)
+            | domain::archive::Error::UnsupportedVersion(_) => Self::BadRequest(value.to_string()),
+            domain::archive::Error::TooLarge(_) => Self::PayloadTooLarge(value.to_string()),
+            domain::archive::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::shopping_list::Error> for AppError {
+    fn from(value: domain::shopping_list::Error) -> Self {
+        match value {
+            domain::shopping_list::Error::ShoppingListNotFound(_)
+            | domain::shopping_list::Error::ItemNotFound(_)
+            | domain::shopping_list::Error::RecipeNotFound(_) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::shopping_list::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::shopping_list::Error::UnknownUnit(_)
+            | domain::shopping_list::Error::CannotAddRecipe(_)
+            | domain::shopping_list::Error::InvalidShoppingList(_) => {
+                Self::BadRequest(value.to_string())
+            }
+            domain::shopping_list::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::meal_plan::Error> for AppError {
+    fn from(value: domain::meal_plan::Error) -> Self {
+        match value {
+            domain::meal_plan::Error::EntryNotFound(_)
+            | domain::meal_plan::Error::RecipeNotFound(_) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::meal_plan::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::meal_plan::Error::InvalidMealPlan(_) => Self::BadRequest(value.to_string()),
+            domain::meal_plan::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::pantry::Error> for AppError {
+    fn from(value: domain::pantry::Error) -> Self {
+        match value {
+            domain::pantry::Error::ItemNotFound(_) | domain::pantry::Error::RecipeNotFound(_) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::pantry::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::pantry::Error::UnknownUnit(_)
+            | domain::pantry::Error::IncompatibleUnits(_, _)
+            | domain::pantry::Error::InsufficientStock(_)
+            | domain::pantry::Error::CannotCook(_)
+            | domain::pantry::Error::InvalidPantryItem(_) => Self::BadRequest(value.to_string()),
+            domain::pantry::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::cook_log::Error> for AppError {
+    fn from(value: domain::cook_log::Error) -> Self {
+        match value {
+            domain::cook_log::Error::EntryNotFound(_)
+            | domain::cook_log::Error::RecipeNotFound(_) => Self::EntityNotFound(value.to_string()),
+            domain::cook_log::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::cook_log::Error::InvalidCookLog(_) => Self::BadRequest(value.to_string()),
+            domain::cook_log::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::review::Error> for AppError {
+    fn from(value: domain::review::Error) -> Self {
+        match value {
+            domain::review::Error::ReviewNotFound(_) | domain::review::Error::RecipeNotFound(_) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::review::Error::NotReviewer(_) | domain::review::Error::NotAuthor(_) => {
+                Self::Unauthorized(value.to_string())
+            }
+            domain::review::Error::OwnRecipe(_)
+            | domain::review::Error::AlreadyReviewed(_)
+            | domain::review::Error::InvalidReview(_) => Self::BadRequest(value.to_string()),
+            domain::review::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::collection::Error> for AppError {
+    fn from(value: domain::collection::Error) -> Self {
+        match value {
+            domain::collection::Error::CollectionNotFound(_)
+            | domain::collection::Error::RecipeNotFound(_)
+            | domain::collection::Error::NotInCollection(_) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::collection::Error::NotOwner(_) => Self::Unauthorized(value.to_string()),
+            domain::collection::Error::InvalidCollection(_) => Self::BadRequest(value.to_string()),
+            domain::collection::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<domain::revision::Error> for AppError {
+    fn from(value: domain::revision::Error) -> Self {
+        match value {
+            domain::revision::Error::RecipeNotFound(_)
+            | domain::revision::Error::RevisionNotFound(_, _) => {
+                Self::EntityNotFound(value.to_string())
+            }
+            domain::revision::Error::NotAuthor(_) => Self::Unauthorized(value.to_string()),
+            domain::revision::Error::CannotRevert(_) => Self::BadRequest(value.to_string()),
+            domain::revision::Error::Unexpected => Self::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<MultipartError> for AppError {
+    fn from(value: MultipartError) -> Self {
+        match value.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => Self::PayloadTooLarge(value.body_text()),
+            _ => Self::BadRequest(value.body_text()),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let body = Json(ErrorResponse::from(&self));
@@ -93,6 +288,10 @@ impl IntoResponse for AppError {
 
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             },
+            Self::QueryParseError(rejection) => rejection.status(),
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
         };
         (status, body).into_response()
@@ -36,6 +36,24 @@ where
     }
 }
 
+pub struct Query<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::extract::Query::<T>::from_request_parts(parts, state).await {
+            Ok(value) => Ok(Self(value.0)),
+            Err(rejection) => Err(AppError::QueryParseError(rejection)),
+        }
+    }
+}
+
 pub struct ExtractAuthUser(pub domain::AuthUser);
 
 #[async_trait]
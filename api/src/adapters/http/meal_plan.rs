@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path, Query},
+    shopping_list::GetShoppingList,
+    AppState,
+};
+
+const CALENDAR_FILE_NAME: &str = "meal-plan.ics";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetMealPlanEntry {
+    pub id: i32,
+    pub date: NaiveDate,
+    pub slot: domain::meal_plan::MealSlot,
+    pub recipe_id: i32,
+    pub recipe_title: String,
+    pub servings: domain::Quantity,
+}
+
+impl From<domain::meal_plan::MealPlanEntry> for GetMealPlanEntry {
+    fn from(value: domain::meal_plan::MealPlanEntry) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            date: value.date,
+            slot: value.slot,
+            recipe_id: value.recipe_id,
+            recipe_title: value.recipe_title,
+            servings: value.servings,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutateMealPlanEntry {
+    pub date: NaiveDate,
+    pub slot: domain::meal_plan::MealSlot,
+    pub recipe_id: i32,
+    pub servings: domain::Quantity,
+}
+
+impl domain::meal_plan::MealPlanEntry {
+    fn from_request(value: MutateMealPlanEntry, id: Option<i32>, owner: domain::User) -> Self {
+        Self {
+            id,
+            owner,
+            date: value.date,
+            slot: value.slot,
+            recipe_id: value.recipe_id,
+            recipe_title: String::new(),
+            servings: value.servings,
+        }
+    }
+}
+
+/// A date range, both ends included.
+#[derive(Debug, Deserialize)]
+pub struct MealPlanRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl MealPlanRange {
+    fn query(&self, owner: &domain::User) -> Result<domain::meal_plan::MealPlanQuery, AppError> {
+        Ok(domain::meal_plan::MealPlanQuery {
+            owner_id: owner
+                .id
+                .ok_or_else(|| AppError::Unexpected("user has no id".to_owned()))?,
+            from: self.from,
+            to: self.to,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GenerateShoppingList {
+    #[serde(flatten)]
+    pub range: MealPlanRange,
+    /// Names the new list; by default it is named after the date range.
+    pub name: Option<String>,
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/meal-plan", get(get_meal_plan))
+        .route("/meal-plan", post(create_entry))
+        .route("/meal-plan/calendar.ics", get(export_calendar))
+        .route("/meal-plan/shopping-list", post(generate_shopping_list))
+        .route("/meal-plan/:id", get(get_entry))
+        .route("/meal-plan/:id", post(update_entry))
+        .route("/meal-plan/:id", delete(delete_entry))
+}
+
+pub async fn get_meal_plan(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(range): Query<MealPlanRange>,
+) -> anyhow::Result<Json<Vec<GetMealPlanEntry>>, AppError> {
+    Ok(Json(
+        state
+            .meal_plan_service
+            .get_meal_plan(range.query(&auth_user.user)?)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+/// The planned meals as an iCalendar file for calendar apps.
+pub async fn export_calendar(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(range): Query<MealPlanRange>,
+) -> anyhow::Result<impl IntoResponse, AppError> {
+    let entries = state
+        .meal_plan_service
+        .get_meal_plan(range.query(&auth_user.user)?)
+        .await?;
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/calendar; charset=utf-8".to_owned(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", CALENDAR_FILE_NAME),
+            ),
+        ],
+        domain::meal_plan::to_icalendar(&entries, chrono::Utc::now()),
+    ))
+}
+
+pub async fn generate_shopping_list(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<GenerateShoppingList>,
+) -> anyhow::Result<(StatusCode, Json<GetShoppingList>), AppError> {
+    let query = request.range.query(&auth_user.user)?;
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .meal_plan_service
+                .generate_shopping_list(query, request.name, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn create_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<MutateMealPlanEntry>,
+) -> anyhow::Result<(StatusCode, Json<GetMealPlanEntry>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .meal_plan_service
+                .create_entry(domain::meal_plan::MealPlanEntry::from_request(
+                    request,
+                    None,
+                    auth_user.user,
+                ))
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn get_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetMealPlanEntry>, AppError> {
+    Ok(Json(
+        state
+            .meal_plan_service
+            .get_entry(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateMealPlanEntry>,
+) -> anyhow::Result<Json<GetMealPlanEntry>, AppError> {
+    Ok(Json(
+        state
+            .meal_plan_service
+            .update_entry(domain::meal_plan::MealPlanEntry::from_request(
+                request,
+                Some(id),
+                auth_user.user,
+            ))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_entry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetMealPlanEntry>, AppError> {
+    Ok(Json(
+        state
+            .meal_plan_service
+            .delete_entry(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{error::AppError, extract::ExtractAuthUser, AppState};
+
+/// Room for the multipart framing around an image of the largest size.
+const MAX_UPLOAD_BODY_BYTES: usize = domain::media::MAX_IMAGE_BYTES + 64 * 1024;
+/// Name of the multipart field carrying an uploaded image.
+const IMAGE_FIELD: &str = "image";
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+pub struct GetImage {
+    pub url: String,
+    pub thumbnail_url: String,
+}
+
+impl GetImage {
+    pub fn from_key(key: &str) -> GetImage {
+        GetImage {
+            url: format!("/media/{}", key),
+            thumbnail_url: format!("/media/{}", domain::media::thumbnail_key(key)),
+        }
+    }
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/media/*key", get(get_media))
+        .route(
+            "/recipe/:id/image",
+            post(set_recipe_image).delete(remove_recipe_image),
+        )
+        .route(
+            "/recipe/:id/step/:step_id/image",
+            post(set_step_image).delete(remove_step_image),
+        )
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BODY_BYTES))
+}
+
+/// Reads the `image` field of a multipart upload, stopping as soon as it
+/// grows past the size limit.
+async fn read_image(mut multipart: Multipart) -> Result<domain::media::ImageUpload, AppError> {
+    while let Some(mut field) = multipart.next_field().await? {
+        if field.name() != Some(IMAGE_FIELD) {
+            continue;
+        }
+        let content_type = field.content_type().unwrap_or_default().to_owned();
+        domain::media::ImageFormat::from_content_type(&content_type)?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await? {
+            if bytes.len() + chunk.len() > domain::media::MAX_IMAGE_BYTES {
+                return Err(domain::media::Error::TooLarge(domain::media::MAX_IMAGE_BYTES).into());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return tokio::task::spawn_blocking(move || {
+            domain::media::ImageUpload::new(&content_type, bytes)
+        })
+        .await
+        .map_err(|e| AppError::Unexpected(e.to_string()))?
+        .map_err(AppError::from);
+    }
+    Err(AppError::BadRequest(format!(
+        "missing multipart field `{}`",
+        IMAGE_FIELD
+    )))
+}
+
+/// Fails unless the recipe exists and belongs to `user`. Recipes hidden from
+/// `user` are not found, so as not to give away that they exist.
+async fn authorize(state: &AppState, id: i32, user: &domain::User) -> Result<(), AppError> {
+    let recipe = state
+        .recipe_service
+        .get_recipe(id, Some(user.clone()))
+        .await?;
+    if recipe.author != *user {
+        return Err(AppError::Unauthorized(format!(
+            "Unable to change images of recipe belonging to another user {}",
+            recipe.author.name
+        )));
+    }
+    Ok(())
+}
+
+pub async fn get_media(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+) -> anyhow::Result<impl IntoResponse, AppError> {
+    let media = state.media_service.get_media(&key).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, media.content_type),
+            // Keys are never reused, so what is stored under one never changes.
+            (
+                header::CACHE_CONTROL,
+                "public, max-age=31536000, immutable".to_owned(),
+            ),
+        ],
+        media.bytes,
+    ))
+}
+
+pub async fn set_recipe_image(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    multipart: Multipart,
+) -> anyhow::Result<Json<GetImage>, AppError> {
+    authorize(&state, id, &auth_user.user).await?;
+    let image = read_image(multipart).await?;
+    let key = state.media_service.set_recipe_image(id, image).await?;
+    Ok(Json(GetImage::from_key(&key)))
+}
+
+pub async fn remove_recipe_image(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<StatusCode, AppError> {
+    authorize(&state, id, &auth_user.user).await?;
+    state.media_service.remove_recipe_image(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn set_step_image(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, step_id)): Path<(i32, i32)>,
+    multipart: Multipart,
+) -> anyhow::Result<Json<GetImage>, AppError> {
+    authorize(&state, id, &auth_user.user).await?;
+    let image = read_image(multipart).await?;
+    let key = state
+        .media_service
+        .set_step_image(id, step_id, image)
+        .await?;
+    Ok(Json(GetImage::from_key(&key)))
+}
+
+pub async fn remove_step_image(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, step_id)): Path<(i32, i32)>,
+) -> anyhow::Result<StatusCode, AppError> {
+    authorize(&state, id, &auth_user.user).await?;
+    state.media_service.remove_step_image(id, step_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,17 @@
+pub mod archive;
+pub mod collection;
+pub mod cook_log;
 pub mod error;
 pub mod extract;
+pub mod meal_plan;
+pub mod media;
+pub mod pantry;
 pub mod recipe;
+pub mod review;
+pub mod revision;
+pub mod shopping_list;
+pub mod tag;
+pub mod unit;
 pub mod user;
 
 use std::{net::SocketAddr, sync::Arc};
@@ -20,24 +31,69 @@ pub struct App {
 pub struct AppState {
     user_service: Arc<dyn port::UserService + Send + Sync>,
     auth_user_service: Arc<dyn port::AuthUserService + Send + Sync>,
-    recipe_service: Box<dyn port::RecipeService + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    tag_service: Box<dyn port::TagService + Send + Sync>,
+    unit_service: Arc<dyn port::UnitService + Send + Sync>,
+    media_service: Arc<dyn port::MediaService + Send + Sync>,
+    archive_service: Box<dyn port::ArchiveService + Send + Sync>,
+    shopping_list_service: Arc<dyn port::ShoppingListService + Send + Sync>,
+    meal_plan_service: Box<dyn port::MealPlanService + Send + Sync>,
+    pantry_service: Box<dyn port::PantryService + Send + Sync>,
+    cook_log_service: Box<dyn port::CookLogService + Send + Sync>,
+    review_service: Box<dyn port::ReviewService + Send + Sync>,
+    collection_service: Box<dyn port::CollectionService + Send + Sync>,
+    revision_service: Box<dyn port::RevisionService + Send + Sync>,
 }
 
 impl App {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_service: Arc<dyn port::UserService + Send + Sync>,
         auth_user_service: Arc<dyn port::AuthUserService + Send + Sync>,
-        recipe_service: Box<dyn port::RecipeService + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+        tag_service: Box<dyn port::TagService + Send + Sync>,
+        unit_service: Arc<dyn port::UnitService + Send + Sync>,
+        media_service: Arc<dyn port::MediaService + Send + Sync>,
+        archive_service: Box<dyn port::ArchiveService + Send + Sync>,
+        shopping_list_service: Arc<dyn port::ShoppingListService + Send + Sync>,
+        meal_plan_service: Box<dyn port::MealPlanService + Send + Sync>,
+        pantry_service: Box<dyn port::PantryService + Send + Sync>,
+        cook_log_service: Box<dyn port::CookLogService + Send + Sync>,
+        review_service: Box<dyn port::ReviewService + Send + Sync>,
+        collection_service: Box<dyn port::CollectionService + Send + Sync>,
+        revision_service: Box<dyn port::RevisionService + Send + Sync>,
     ) -> App {
         Self {
             state: AppState {
                 user_service,
                 auth_user_service,
                 recipe_service,
+                tag_service,
+                unit_service,
+                media_service,
+                archive_service,
+                shopping_list_service,
+                meal_plan_service,
+                pantry_service,
+                cook_log_service,
+                review_service,
+                collection_service,
+                revision_service,
             },
             router: Router::new()
                 .merge(user::build_routes())
                 .merge(recipe::build_routes())
+                .merge(tag::build_routes())
+                .merge(unit::build_routes())
+                .merge(media::build_routes())
+                .merge(archive::build_routes())
+                .merge(shopping_list::build_routes())
+                .merge(meal_plan::build_routes())
+                .merge(pantry::build_routes())
+                .merge(cook_log::build_routes())
+                .merge(review::build_routes())
+                .merge(collection::build_routes())
+                .merge(revision::build_routes())
                 .layer(CorsLayer::permissive()),
         }
     }
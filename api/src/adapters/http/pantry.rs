@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path, Query},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetPantryItem {
+    pub id: i32,
+    pub ingredient_id: i32,
+    pub name: String,
+    pub quantity: domain::Quantity,
+    pub units: String,
+    pub location: String,
+    pub expires_on: Option<NaiveDate>,
+}
+
+impl From<domain::pantry::PantryItem> for GetPantryItem {
+    fn from(value: domain::pantry::PantryItem) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            ingredient_id: value.ingredient.id.unwrap_or(-1),
+            name: value.ingredient.name,
+            quantity: value.quantity,
+            units: value.units.name,
+            location: value.location,
+            expires_on: value.expires_on,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutatePantryItem {
+    pub name: String,
+    pub quantity: domain::Quantity,
+    pub units: String,
+    #[serde(default)]
+    pub location: String,
+    pub expires_on: Option<NaiveDate>,
+}
+
+impl domain::pantry::PantryItem {
+    fn from_request(value: MutatePantryItem, id: Option<i32>, owner: domain::User) -> Self {
+        Self {
+            id,
+            owner,
+            ingredient: domain::recipe::Ingredient {
+                id: None,
+                name: value.name,
+            },
+            quantity: value.quantity,
+            units: domain::recipe::Unit {
+                id: None,
+                name: value.units,
+            },
+            location: value.location,
+            expires_on: value.expires_on,
+        }
+    }
+}
+
+/// An amount to put into or take out of stock, in the item's units unless
+/// others are given.
+#[derive(Deserialize)]
+pub struct AdjustPantryItem {
+    pub add: Option<domain::Quantity>,
+    pub remove: Option<domain::Quantity>,
+    pub units: Option<String>,
+}
+
+impl AdjustPantryItem {
+    fn change(&self) -> Result<domain::pantry::StockChange, AppError> {
+        match (self.add, self.remove) {
+            (Some(add), None) => Ok(domain::pantry::StockChange::Add(add)),
+            (None, Some(remove)) => Ok(domain::pantry::StockChange::Remove(remove)),
+            _ => Err(AppError::BadRequest(
+                "exactly one of `add` or `remove` is required".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PantryParams {
+    pub location: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ExpiringParams {
+    /// How many days ahead to look; by default a week.
+    pub days: Option<i64>,
+}
+
+/// A recipe to cook, optionally scaled to a yield or by a factor.
+#[derive(Deserialize)]
+pub struct CookRecipe {
+    pub recipe_id: i32,
+    #[serde(rename = "yield")]
+    pub target_yield: Option<domain::Quantity>,
+    pub factor: Option<domain::Quantity>,
+    /// Reports what cooking would take without touching the pantry.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl CookRecipe {
+    fn scale(&self) -> Result<Option<domain::recipe::ScaleBy>, AppError> {
+        match (self.target_yield, self.factor) {
+            (Some(target_yield), None) => Ok(Some(domain::recipe::ScaleBy::Yield(target_yield))),
+            (None, Some(factor)) => Ok(Some(domain::recipe::ScaleBy::Factor(factor))),
+            (None, None) => Ok(None),
+            _ => Err(AppError::BadRequest(
+                "at most one of `yield` or `factor` may be given".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetShortfall {
+    pub name: String,
+    pub quantity: domain::Quantity,
+    pub units: String,
+}
+
+impl From<domain::pantry::Shortfall> for GetShortfall {
+    fn from(value: domain::pantry::Shortfall) -> Self {
+        Self {
+            name: value.ingredient.name,
+            quantity: value.quantity,
+            units: value.units.name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetCookReport {
+    pub recipe_id: i32,
+    pub title: String,
+    pub scale: domain::Quantity,
+    pub used: Vec<GetPantryItem>,
+    pub shortfalls: Vec<GetShortfall>,
+}
+
+impl From<domain::pantry::CookReport> for GetCookReport {
+    fn from(value: domain::pantry::CookReport) -> Self {
+        Self {
+            recipe_id: value.recipe_id,
+            title: value.title,
+            scale: value.scale,
+            used: value.used.into_iter().map(|x| x.into()).collect(),
+            shortfalls: value.shortfalls.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+fn owner_id(owner: &domain::User) -> Result<i32, AppError> {
+    owner
+        .id
+        .ok_or_else(|| AppError::Unexpected("user has no id".to_owned()))
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/pantry", get(get_pantry))
+        .route("/pantry", post(create_item))
+        .route("/pantry/expiring", get(get_expiring))
+        .route("/pantry/cook", post(cook_recipe))
+        .route("/pantry/:id", get(get_item))
+        .route("/pantry/:id", post(update_item))
+        .route("/pantry/:id", delete(delete_item))
+        .route("/pantry/:id/adjust", post(adjust_item))
+}
+
+pub async fn get_pantry(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(params): Query<PantryParams>,
+) -> anyhow::Result<Json<Vec<GetPantryItem>>, AppError> {
+    let query = domain::pantry::PantryQuery {
+        owner_id: owner_id(&auth_user.user)?,
+        location: params.location,
+        expires_by: None,
+    };
+    Ok(Json(
+        state
+            .pantry_service
+            .get_pantry(query)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+/// The items still in stock that expire within the coming days, or have
+/// already expired, soonest first.
+pub async fn get_expiring(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(params): Query<ExpiringParams>,
+) -> anyhow::Result<Json<Vec<GetPantryItem>>, AppError> {
+    let query = domain::pantry::PantryQuery::expiring(
+        owner_id(&auth_user.user)?,
+        chrono::Utc::now().date_naive(),
+        params.days.unwrap_or(domain::pantry::DEFAULT_EXPIRING_DAYS),
+    )?;
+    Ok(Json(
+        state
+            .pantry_service
+            .get_pantry(query)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn create_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<MutatePantryItem>,
+) -> anyhow::Result<(StatusCode, Json<GetPantryItem>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .pantry_service
+                .create_item(domain::pantry::PantryItem::from_request(
+                    request,
+                    None,
+                    auth_user.user,
+                ))
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn get_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetPantryItem>, AppError> {
+    Ok(Json(
+        state
+            .pantry_service
+            .get_item(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutatePantryItem>,
+) -> anyhow::Result<Json<GetPantryItem>, AppError> {
+    Ok(Json(
+        state
+            .pantry_service
+            .update_item(domain::pantry::PantryItem::from_request(
+                request,
+                Some(id),
+                auth_user.user,
+            ))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn adjust_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<AdjustPantryItem>,
+) -> anyhow::Result<Json<GetPantryItem>, AppError> {
+    let change = request.change()?;
+    Ok(Json(
+        state
+            .pantry_service
+            .adjust_item(id, change, request.units, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetPantryItem>, AppError> {
+    Ok(Json(
+        state
+            .pantry_service
+            .delete_item(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn cook_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<CookRecipe>,
+) -> anyhow::Result<Json<GetCookReport>, AppError> {
+    let scale = request.scale()?;
+    Ok(Json(
+        state
+            .pantry_service
+            .cook_recipe(request.recipe_id, scale, request.dry_run, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
@@ -1,9 +1,10 @@
 use serde_with::{self};
-use std::{collections::HashSet, fmt::Display, sync::Arc};
+use std::{collections::HashSet, sync::Arc};
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
     routing::{delete, get, post},
     Json, Router,
 };
@@ -11,28 +12,21 @@ use serde::{Deserialize, Serialize};
 
 use crate::{adapters, core::domain};
 
-use super::{error::AppError, extract::ExtractAuthUser, AppState};
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Units {
-    Grams,
-}
-
-impl Display for Units {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = format!("{:?}", self).to_lowercase();
-        write!(f, "{}", s)
-    }
-}
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Query},
+    AppState,
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct GetRecipeIngredient {
     pub id: i32,
     pub ingredient: String,
-    pub quantity: i32,
+    pub quantity: domain::Quantity,
     pub units: String,
     pub preparation: String,
+    #[serde(default)]
+    pub sub_recipe_id: Option<i32>,
 }
 
 impl From<domain::recipe::RecipeIngredient> for GetRecipeIngredient {
@@ -43,6 +37,7 @@ impl From<domain::recipe::RecipeIngredient> for GetRecipeIngredient {
             quantity: value.quantity,
             units: value.units.name,
             preparation: value.preparation,
+            sub_recipe_id: value.sub_recipe_id,
         }
     }
 }
@@ -52,6 +47,8 @@ pub struct GetStep {
     pub id: i32,
     pub ordinal: i32,
     pub instruction: String,
+    #[serde(default)]
+    pub image: Option<adapters::http::media::GetImage>,
 }
 
 impl From<domain::recipe::Step> for GetStep {
@@ -60,12 +57,39 @@ impl From<domain::recipe::Step> for GetStep {
             id: value.id.unwrap_or(-1),
             ordinal: value.ordinal,
             instruction: value.instruction,
+            image: value
+                .image
+                .map(|x| adapters::http::media::GetImage::from_key(&x)),
         }
     }
 }
 
-#[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetRecipeGroup {
+    pub name: String,
+    pub ingredients: HashSet<GetRecipeIngredient>,
+    pub steps: HashSet<GetStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRecipeAncestor {
+    pub id: i32,
+    pub title: String,
+    pub author: adapters::http::user::GetUser,
+}
+
+impl From<domain::recipe::RecipeAncestor> for GetRecipeAncestor {
+    fn from(value: domain::recipe::RecipeAncestor) -> Self {
+        Self {
+            id: value.id,
+            title: value.title,
+            author: value.author.into(),
+        }
+    }
+}
+
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct GetRecipe {
     pub id: i32,
     pub title: String,
@@ -77,14 +101,68 @@ pub struct GetRecipe {
     pub cook_time: Option<chrono::Duration>,
     #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
     pub inactive_time: Option<chrono::Duration>,
-    pub yield_quantity: i32,
+    pub yield_quantity: domain::Quantity,
     pub yield_units: String,
     pub ingredients: HashSet<GetRecipeIngredient>,
     pub steps: HashSet<GetStep>,
+    #[serde(default)]
+    pub groups: Vec<GetRecipeGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub image: Option<adapters::http::media::GetImage>,
+    #[serde(default)]
+    pub cook_stats: adapters::http::cook_log::GetCookStats,
+    #[serde(default)]
+    pub review_stats: adapters::http::review::GetReviewStats,
+    /// Whether the signed in user has favorited the recipe; left out for
+    /// anonymous requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorited: Option<bool>,
+    /// The recipe this one was forked from, if it still exists.
+    #[serde(default)]
+    pub forked_from: Option<i32>,
+    /// The recipes this one descends from, nearest first.
+    #[serde(default)]
+    pub lineage: Vec<GetRecipeAncestor>,
+    #[serde(default)]
+    pub visibility: domain::recipe::Visibility,
+    #[serde(default)]
+    pub draft: bool,
 }
 
 impl From<domain::Recipe> for GetRecipe {
-    fn from(value: domain::Recipe) -> Self {
+    fn from(mut value: domain::Recipe) -> Self {
+        value.groups.sort_by_key(|x| x.ordinal);
+        let mut groups: Vec<GetRecipeGroup> = value
+            .groups
+            .into_iter()
+            .map(|x| GetRecipeGroup {
+                name: x.name,
+                ingredients: HashSet::new(),
+                steps: HashSet::new(),
+            })
+            .collect();
+        let mut ingredients = HashSet::new();
+        for ingredient in value.ingredients {
+            match groups
+                .iter_mut()
+                .find(|x| ingredient.group.as_ref() == Some(&x.name))
+            {
+                Some(group) => group.ingredients.insert(ingredient.into()),
+                None => ingredients.insert(ingredient.into()),
+            };
+        }
+        let mut steps = HashSet::new();
+        for step in value.steps {
+            match groups
+                .iter_mut()
+                .find(|x| step.group.as_ref() == Some(&x.name))
+            {
+                Some(group) => group.steps.insert(step.into()),
+                None => steps.insert(step.into()),
+            };
+        }
         Self {
             id: value.id.unwrap_or(-1),
             title: value.title,
@@ -95,8 +173,412 @@ impl From<domain::Recipe> for GetRecipe {
             inactive_time: value.inactive_time,
             yield_quantity: value.yield_quantity,
             yield_units: value.yield_units.name,
-            ingredients: value.ingredients.into_iter().map(|x| x.into()).collect(),
-            steps: value.steps.into_iter().map(|x| x.into()).collect(),
+            ingredients,
+            steps,
+            groups,
+            tags: value.tags.into_iter().map(|x| x.name).collect(),
+            image: value
+                .image
+                .map(|x| adapters::http::media::GetImage::from_key(&x)),
+            cook_stats: value.cook_stats.into(),
+            review_stats: value.review_stats.into(),
+            favorited: None,
+            forked_from: value.forked_from,
+            lineage: value.lineage.into_iter().map(|x| x.into()).collect(),
+            visibility: value.visibility,
+            draft: value.draft,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRecipePage {
+    pub recipes: Vec<GetRecipe>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+    pub next_page: Option<i64>,
+}
+
+impl From<domain::recipe::RecipePage> for GetRecipePage {
+    fn from(value: domain::recipe::RecipePage) -> Self {
+        let next_page = value.next_page();
+        Self {
+            recipes: value.recipes.into_iter().map(|x| x.into()).collect(),
+            page: value.page,
+            limit: value.limit,
+            total_count: value.total_count,
+            next_page,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeSortKey {
+    Title,
+    Newest,
+    TotalTime,
+    Rating,
+}
+
+impl From<RecipeSortKey> for domain::recipe::RecipeSort {
+    fn from(value: RecipeSortKey) -> Self {
+        match value {
+            RecipeSortKey::Title => Self::Title,
+            RecipeSortKey::Newest => Self::Newest,
+            RecipeSortKey::TotalTime => Self::TotalTime,
+            RecipeSortKey::Rating => Self::Rating,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetRecipesParams {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+    pub author: Option<i32>,
+    pub title: Option<String>,
+    /// Upper bound, in seconds, on prep, cook and inactive time combined.
+    pub max_total_time: Option<i64>,
+    pub yield_units: Option<String>,
+    /// Comma separated tag names.
+    pub tags: Option<String>,
+    pub sort: Option<RecipeSortKey>,
+}
+
+impl From<GetRecipesParams> for domain::recipe::RecipeQuery {
+    fn from(value: GetRecipesParams) -> Self {
+        let default = Self::default();
+        Self {
+            viewer_id: None,
+            author_id: value.author,
+            title: value.title,
+            max_total_time: value.max_total_time.map(chrono::Duration::seconds),
+            yield_units: value.yield_units,
+            tags: value
+                .tags
+                .map(|x| x.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            sort: value.sort.map(|x| x.into()),
+            page: value.page.unwrap_or(default.page),
+            limit: value.limit.unwrap_or(default.limit),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchRecipesParams {
+    pub q: String,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl From<SearchRecipesParams> for domain::recipe::RecipeSearchQuery {
+    fn from(value: SearchRecipesParams) -> Self {
+        let default = domain::recipe::RecipeQuery::default();
+        Self {
+            viewer_id: None,
+            text: value.q,
+            page: value.page.unwrap_or(default.page),
+            limit: value.limit.unwrap_or(default.limit),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetSearchHighlight {
+    pub field: String,
+    pub snippet: String,
+}
+
+impl From<domain::recipe::SearchHighlight> for GetSearchHighlight {
+    fn from(value: domain::recipe::SearchHighlight) -> Self {
+        Self {
+            field: value.field,
+            snippet: value.snippet,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRecipeSearchResult {
+    pub recipe: GetRecipe,
+    pub rank: f32,
+    pub highlights: Vec<GetSearchHighlight>,
+}
+
+impl From<domain::recipe::RecipeSearchResult> for GetRecipeSearchResult {
+    fn from(value: domain::recipe::RecipeSearchResult) -> Self {
+        Self {
+            recipe: value.recipe.into(),
+            rank: value.rank,
+            highlights: value.highlights.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRecipeSearchPage {
+    pub results: Vec<GetRecipeSearchResult>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+    pub next_page: Option<i64>,
+}
+
+impl From<domain::recipe::RecipeSearchPage> for GetRecipeSearchPage {
+    fn from(value: domain::recipe::RecipeSearchPage) -> Self {
+        let next_page = value.next_page();
+        Self {
+            results: value.results.into_iter().map(|x| x.into()).collect(),
+            page: value.page,
+            limit: value.limit,
+            total_count: value.total_count,
+            next_page,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetRecipesByIngredientsParams {
+    /// Comma separated ingredient names.
+    pub ingredients: String,
+    pub max_missing: Option<i64>,
+    pub ignore_staples: Option<bool>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl From<GetRecipesByIngredientsParams> for domain::recipe::IngredientMatchQuery {
+    fn from(value: GetRecipesByIngredientsParams) -> Self {
+        let default = domain::recipe::RecipeQuery::default();
+        Self {
+            viewer_id: None,
+            ingredients: value.ingredients.split(',').map(String::from).collect(),
+            ignored: if value.ignore_staples.unwrap_or(true) {
+                domain::recipe::STAPLE_INGREDIENTS
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect()
+            } else {
+                vec![]
+            },
+            max_missing: value.max_missing,
+            page: value.page.unwrap_or(default.page),
+            limit: value.limit.unwrap_or(default.limit),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetIngredientMatch {
+    pub recipe: GetRecipe,
+    pub covered: i64,
+    pub required: i64,
+    pub missing: Vec<String>,
+}
+
+impl From<domain::recipe::IngredientMatch> for GetIngredientMatch {
+    fn from(value: domain::recipe::IngredientMatch) -> Self {
+        Self {
+            recipe: value.recipe.into(),
+            covered: value.covered,
+            required: value.required,
+            missing: value.missing,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetIngredientMatchPage {
+    pub results: Vec<GetIngredientMatch>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+    pub next_page: Option<i64>,
+}
+
+impl From<domain::recipe::IngredientMatchPage> for GetIngredientMatchPage {
+    fn from(value: domain::recipe::IngredientMatchPage) -> Self {
+        let next_page = value.next_page();
+        Self {
+            results: value.results.into_iter().map(|x| x.into()).collect(),
+            page: value.page,
+            limit: value.limit,
+            total_count: value.total_count,
+            next_page,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParseIngredientLines {
+    pub lines: Vec<String>,
+}
+
+/// An ingredient read from a line of text, shaped like the ingredients of a
+/// recipe request so it can be used in one directly.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetParsedIngredient {
+    pub line: String,
+    pub ingredient: String,
+    pub quantity: domain::Quantity,
+    pub quantity_max: Option<domain::Quantity>,
+    pub units: String,
+    pub preparation: String,
+    pub warnings: Vec<String>,
+}
+
+impl GetParsedIngredient {
+    fn new(line: String, value: domain::ingredient_line::ParsedIngredient) -> Self {
+        Self {
+            line,
+            ingredient: value.ingredient.ingredient.name,
+            quantity: value.ingredient.quantity,
+            quantity_max: value.quantity_max,
+            units: value.ingredient.units.name,
+            preparation: value.ingredient.preparation,
+            warnings: value.warnings,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetParsedIngredients {
+    pub ingredients: Vec<GetParsedIngredient>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetUnmappedField {
+    pub field: String,
+    pub reason: String,
+}
+
+impl From<domain::recipe::UnmappedField> for GetUnmappedField {
+    fn from(value: domain::recipe::UnmappedField) -> Self {
+        Self {
+            field: value.field,
+            reason: value.reason,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportRecipeResponse {
+    pub recipe: GetRecipe,
+    pub unmapped: Vec<GetUnmappedField>,
+}
+
+impl From<domain::recipe::RecipeImport> for ImportRecipeResponse {
+    fn from(value: domain::recipe::RecipeImport) -> Self {
+        Self {
+            recipe: value.recipe.into(),
+            unmapped: value.unmapped.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementSystem {
+    Metric,
+    Imperial,
+}
+
+impl From<MeasurementSystem> for domain::unit::MeasurementSystem {
+    fn from(value: MeasurementSystem) -> Self {
+        match value {
+            MeasurementSystem::Metric => Self::Metric,
+            MeasurementSystem::Imperial => Self::Imperial,
+        }
+    }
+}
+
+/// The representations `GET /recipe/:id` can return.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipeFormat {
+    Json,
+    #[serde(alias = "json-ld")]
+    JsonLd,
+    Html,
+    Cooklang,
+    #[serde(alias = "md")]
+    Markdown,
+}
+
+impl RecipeFormat {
+    /// The format the `Accept` header prefers most, falling back to JSON.
+    fn from_accept(headers: &HeaderMap) -> RecipeFormat {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|x| x.to_str().ok())
+            .unwrap_or_default();
+        let mut best: Option<(RecipeFormat, f32)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';');
+            let format = match parts
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "application/json" => RecipeFormat::Json,
+                "application/ld+json" => RecipeFormat::JsonLd,
+                "text/html" => RecipeFormat::Html,
+                "text/cooklang" => RecipeFormat::Cooklang,
+                // Markdown reads fine as plain text, e.g. pasted into a chat.
+                // Cooklang is only served when asked for by name, even though
+                // it goes out as plain text.
+                "text/markdown" | "text/plain" => RecipeFormat::Markdown,
+                _ => continue,
+            };
+            let quality = parts
+                .filter_map(|x| x.trim().strip_prefix("q="))
+                .find_map(|x| x.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && best.is_none_or(|(_, x)| quality > x) {
+                best = Some((format, quality));
+            }
+        }
+        best.map_or(RecipeFormat::Json, |(format, _)| format)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetRecipeParams {
+    pub units: Option<MeasurementSystem>,
+    #[serde(default)]
+    pub expand: bool,
+    /// Overrides the format asked for by the `Accept` header.
+    pub format: Option<RecipeFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCooklangParams {
+    /// Names the recipe when the document has no `title` metadata.
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScaleRecipeParams {
+    #[serde(rename = "yield")]
+    pub target_yield: Option<domain::Quantity>,
+    pub factor: Option<domain::Quantity>,
+    #[serde(default)]
+    pub promote_units: bool,
+}
+
+impl ScaleRecipeParams {
+    fn scale(&self) -> Result<domain::recipe::ScaleBy, AppError> {
+        match (self.target_yield, self.factor) {
+            (Some(target_yield), None) => Ok(domain::recipe::ScaleBy::Yield(target_yield)),
+            (None, Some(factor)) => Ok(domain::recipe::ScaleBy::Factor(factor)),
+            _ => Err(AppError::BadRequest(
+                "exactly one of `yield` or `factor` is required".to_owned(),
+            )),
         }
     }
 }
@@ -104,9 +586,11 @@ impl From<domain::Recipe> for GetRecipe {
 #[derive(Deserialize)]
 pub struct CreateRecipeIngredient {
     pub ingredient: String,
-    pub quantity: i32,
-    pub units: Units,
+    pub quantity: domain::Quantity,
+    pub units: String,
     pub preparation: String,
+    #[serde(default)]
+    pub sub_recipe_id: Option<i32>,
 }
 
 impl From<CreateRecipeIngredient> for domain::recipe::RecipeIngredient {
@@ -121,9 +605,11 @@ impl From<CreateRecipeIngredient> for domain::recipe::RecipeIngredient {
             quantity: value.quantity,
             units: domain::recipe::Unit {
                 id: None,
-                name: value.units.to_string(),
+                name: value.units,
             },
             preparation: value.preparation,
+            group: None,
+            sub_recipe_id: value.sub_recipe_id,
         }
     }
 }
@@ -141,10 +627,25 @@ impl From<CreateStep> for domain::recipe::Step {
             recipe_id: None,
             ordinal: value.ordinal,
             instruction: value.instruction,
+            group: None,
+            image: None,
         }
     }
 }
 
+#[derive(Deserialize)]
+pub struct CreateRecipeGroup {
+    pub name: String,
+    #[serde(default)]
+    pub ingredients: Vec<CreateRecipeIngredient>,
+    #[serde(default)]
+    pub steps: Vec<CreateStep>,
+}
+
+fn tag_from_name(name: String) -> domain::Tag {
+    domain::Tag { id: None, name }
+}
+
 #[serde_with::serde_as]
 #[derive(Deserialize)]
 pub struct CreateRecipe {
@@ -156,14 +657,45 @@ pub struct CreateRecipe {
     pub cook_time: Option<chrono::Duration>,
     #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
     pub inactive_time: Option<chrono::Duration>,
-    pub yield_quantity: i32,
-    pub yield_units: Units,
+    pub yield_quantity: domain::Quantity,
+    pub yield_units: String,
     pub ingredients: Vec<CreateRecipeIngredient>,
     pub steps: Vec<CreateStep>,
+    #[serde(default)]
+    pub groups: Vec<CreateRecipeGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub visibility: domain::recipe::Visibility,
+    #[serde(default)]
+    pub draft: bool,
 }
 
 impl domain::Recipe {
     fn from_create(value: CreateRecipe, author: domain::User) -> Self {
+        let mut ingredients: Vec<domain::recipe::RecipeIngredient> =
+            value.ingredients.into_iter().map(|x| x.into()).collect();
+        let mut steps: Vec<domain::recipe::Step> =
+            value.steps.into_iter().map(|x| x.into()).collect();
+        let mut groups = Vec::with_capacity(value.groups.len());
+        for (i, group) in value.groups.into_iter().enumerate() {
+            let name = group.name;
+            ingredients.extend(group.ingredients.into_iter().map(|x| {
+                domain::recipe::RecipeIngredient {
+                    group: Some(name.clone()),
+                    ..x.into()
+                }
+            }));
+            steps.extend(group.steps.into_iter().map(|x| domain::recipe::Step {
+                group: Some(name.clone()),
+                ..x.into()
+            }));
+            groups.push(domain::recipe::RecipeGroup {
+                id: None,
+                name,
+                ordinal: i as i32 + 1,
+            });
+        }
         Self {
             id: None,
             title: value.title,
@@ -175,10 +707,19 @@ impl domain::Recipe {
             yield_quantity: value.yield_quantity,
             yield_units: domain::recipe::Unit {
                 id: None,
-                name: value.yield_units.to_string(),
+                name: value.yield_units,
             },
-            ingredients: value.ingredients.into_iter().map(|x| x.into()).collect(),
-            steps: value.steps.into_iter().map(|x| x.into()).collect(),
+            ingredients,
+            steps,
+            groups,
+            tags: value.tags.into_iter().map(tag_from_name).collect(),
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: value.visibility,
+            draft: value.draft,
         }
     }
 }
@@ -187,9 +728,11 @@ impl domain::Recipe {
 pub struct UpdateRecipeIngredient {
     pub id: Option<i32>,
     pub ingredient: String,
-    pub quantity: i32,
-    pub units: Units,
+    pub quantity: domain::Quantity,
+    pub units: String,
     pub preparation: String,
+    #[serde(default)]
+    pub sub_recipe_id: Option<i32>,
 }
 
 impl From<UpdateRecipeIngredient> for domain::recipe::RecipeIngredient {
@@ -204,9 +747,11 @@ impl From<UpdateRecipeIngredient> for domain::recipe::RecipeIngredient {
             quantity: value.quantity,
             units: domain::recipe::Unit {
                 id: None,
-                name: value.units.to_string(),
+                name: value.units,
             },
             preparation: value.preparation,
+            group: None,
+            sub_recipe_id: value.sub_recipe_id,
         }
     }
 }
@@ -225,10 +770,21 @@ impl From<UpdateStep> for domain::recipe::Step {
             recipe_id: None,
             ordinal: value.ordinal,
             instruction: value.instruction,
+            group: None,
+            image: None,
         }
     }
 }
 
+#[derive(Deserialize)]
+pub struct UpdateRecipeGroup {
+    pub name: String,
+    #[serde(default)]
+    pub ingredients: Vec<UpdateRecipeIngredient>,
+    #[serde(default)]
+    pub steps: Vec<UpdateStep>,
+}
+
 #[serde_with::serde_as]
 #[derive(Deserialize)]
 pub struct UpdateRecipe {
@@ -241,15 +797,45 @@ pub struct UpdateRecipe {
     pub cook_time: Option<chrono::Duration>,
     #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
     pub inactive_time: Option<chrono::Duration>,
-    pub yield_quantity: i32,
-    pub yield_units: Units,
+    pub yield_quantity: domain::Quantity,
+    pub yield_units: String,
     pub ingredients: Vec<UpdateRecipeIngredient>,
     pub steps: Vec<UpdateStep>,
+    #[serde(default)]
+    pub groups: Vec<UpdateRecipeGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Left as they were when omitted.
+    pub visibility: Option<domain::recipe::Visibility>,
+    pub draft: Option<bool>,
 }
 
 impl domain::Recipe {
     fn from_update(value: UpdateRecipe, author: domain::User) -> Self {
         let id = value.id;
+        let mut ingredients: Vec<domain::recipe::RecipeIngredient> =
+            value.ingredients.into_iter().map(|x| x.into()).collect();
+        let mut steps: Vec<domain::recipe::Step> =
+            value.steps.into_iter().map(|x| x.into()).collect();
+        let mut groups = Vec::with_capacity(value.groups.len());
+        for (i, group) in value.groups.into_iter().enumerate() {
+            let name = group.name;
+            ingredients.extend(group.ingredients.into_iter().map(|x| {
+                domain::recipe::RecipeIngredient {
+                    group: Some(name.clone()),
+                    ..x.into()
+                }
+            }));
+            steps.extend(group.steps.into_iter().map(|x| domain::recipe::Step {
+                group: Some(name.clone()),
+                ..x.into()
+            }));
+            groups.push(domain::recipe::RecipeGroup {
+                id: None,
+                name,
+                ordinal: i as i32 + 1,
+            });
+        }
         Self {
             id: Some(value.id),
             title: value.title,
@@ -261,26 +847,31 @@ impl domain::Recipe {
             yield_quantity: value.yield_quantity,
             yield_units: domain::recipe::Unit {
                 id: None,
-                name: value.yield_units.to_string(),
+                name: value.yield_units,
             },
-            ingredients: value
-                .ingredients
+            ingredients: ingredients
                 .into_iter()
-                .map(|x| {
-                    let mut i: domain::recipe::RecipeIngredient = x.into();
+                .map(|mut i| {
                     i.recipe_id = Some(id);
                     i
                 })
                 .collect(),
-            steps: value
-                .steps
+            steps: steps
                 .into_iter()
-                .map(|x| {
-                    let mut i: domain::recipe::Step = x.into();
+                .map(|mut i| {
                     i.recipe_id = Some(id);
                     i
                 })
                 .collect(),
+            groups,
+            tags: value.tags.into_iter().map(tag_from_name).collect(),
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: Default::default(),
+            draft: false,
         }
     }
 }
@@ -288,32 +879,170 @@ impl domain::Recipe {
 pub fn build_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/recipe", get(get_recipes))
+        .route("/recipe/search", get(search_recipes))
+        .route("/recipe/by-ingredients", get(get_recipes_by_ingredients))
         .route("/recipe/:id", get(get_recipe))
+        .route("/recipe/:id/scaled", get(get_scaled_recipe))
         .route("/recipe", post(create_recipe))
+        .route("/recipe/import", post(import_recipe))
+        .route("/recipe/import/cooklang", post(import_cooklang))
+        .route("/ingredient/parse", post(parse_ingredient_lines))
         .route("/recipe/:id", post(update_recipe))
         .route("/recipe/:id", delete(delete_recipe))
+        .route("/recipe/:id/fork", post(fork_recipe))
+}
+
+/// Sets `favorited` on each recipe when the request is signed in.
+async fn mark_favorites<'a>(
+    state: &AppState,
+    auth_user: Option<ExtractAuthUser>,
+    recipes: impl IntoIterator<Item = &'a mut GetRecipe>,
+) -> Result<(), AppError> {
+    if let Some(ExtractAuthUser(auth_user)) = auth_user {
+        let recipes: Vec<&mut GetRecipe> = recipes.into_iter().collect();
+        let favorited = state
+            .collection_service
+            .get_favorited(recipes.iter().map(|x| x.id).collect(), auth_user.user)
+            .await?;
+        for recipe in recipes {
+            recipe.favorited = Some(favorited.contains(&recipe.id));
+        }
+    }
+    Ok(())
 }
 
 pub async fn get_recipes(
     State(state): State<Arc<AppState>>,
-) -> anyhow::Result<Json<Vec<GetRecipe>>, AppError> {
-    Ok(Json(
-        state
-            .recipe_service
-            .get_recipes()
-            .await?
-            .into_iter()
-            .map(|x| x.into())
-            .collect(),
-    ))
+    auth_user: Option<ExtractAuthUser>,
+    Query(params): Query<GetRecipesParams>,
+) -> anyhow::Result<Json<GetRecipePage>, AppError> {
+    let query = domain::recipe::RecipeQuery {
+        viewer_id: auth_user.as_ref().and_then(|x| x.0.user.id),
+        ..params.into()
+    };
+    let mut page: GetRecipePage = state.recipe_service.get_recipes(query).await?.into();
+    mark_favorites(&state, auth_user, &mut page.recipes).await?;
+    Ok(Json(page))
+}
+
+pub async fn search_recipes(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Query(params): Query<SearchRecipesParams>,
+) -> anyhow::Result<Json<GetRecipeSearchPage>, AppError> {
+    let mut page: GetRecipeSearchPage = state
+        .recipe_service
+        .search_recipes(domain::recipe::RecipeSearchQuery {
+            viewer_id: auth_user.as_ref().and_then(|x| x.0.user.id),
+            ..params.into()
+        })
+        .await?
+        .into();
+    mark_favorites(
+        &state,
+        auth_user,
+        page.results.iter_mut().map(|x| &mut x.recipe),
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+pub async fn get_recipes_by_ingredients(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Query(params): Query<GetRecipesByIngredientsParams>,
+) -> anyhow::Result<Json<GetIngredientMatchPage>, AppError> {
+    let mut page: GetIngredientMatchPage = state
+        .recipe_service
+        .get_recipes_by_ingredients(domain::recipe::IngredientMatchQuery {
+            viewer_id: auth_user.as_ref().and_then(|x| x.0.user.id),
+            ..params.into()
+        })
+        .await?
+        .into();
+    mark_favorites(
+        &state,
+        auth_user,
+        page.results.iter_mut().map(|x| &mut x.recipe),
+    )
+    .await?;
+    Ok(Json(page))
 }
 
 pub async fn get_recipe(
     State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
     Path(id): Path<i32>,
+    Query(params): Query<GetRecipeParams>,
+    headers: HeaderMap,
+) -> anyhow::Result<Response, AppError> {
+    let viewer = auth_user.as_ref().map(|x| x.0.user.clone());
+    let mut recipe = state.recipe_service.get_recipe(id, viewer.clone()).await?;
+    if params.expand {
+        recipe = state.recipe_service.expand_recipe(recipe, viewer).await?;
+    }
+    let recipe = match params.units {
+        Some(system) => {
+            state
+                .unit_service
+                .convert_recipe(recipe, system.into())
+                .await?
+        }
+        None => recipe,
+    };
+    let format = params
+        .format
+        .unwrap_or_else(|| RecipeFormat::from_accept(&headers));
+    let image_url = recipe
+        .image
+        .as_deref()
+        .map(|x| adapters::http::media::GetImage::from_key(x).url);
+    let response = match format {
+        RecipeFormat::Json => {
+            let mut recipe = [GetRecipe::from(recipe)];
+            mark_favorites(&state, auth_user, &mut recipe).await?;
+            let [recipe] = recipe;
+            Json(recipe).into_response()
+        }
+        RecipeFormat::JsonLd => (
+            [(header::CONTENT_TYPE, "application/ld+json")],
+            domain::schema_org::to_json_ld(&recipe, image_url.as_deref()).to_string(),
+        )
+            .into_response(),
+        RecipeFormat::Html => {
+            Html(domain::html::render_recipe(&recipe, image_url.as_deref())).into_response()
+        }
+        RecipeFormat::Cooklang => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            domain::cooklang::to_cooklang(&recipe),
+        )
+            .into_response(),
+        RecipeFormat::Markdown => (
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            domain::markdown::render_recipe(&recipe, image_url.as_deref()),
+        )
+            .into_response(),
+    };
+    Ok(([(header::VARY, "accept")], response).into_response())
+}
+
+pub async fn get_scaled_recipe(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+    Query(params): Query<ScaleRecipeParams>,
 ) -> anyhow::Result<Json<GetRecipe>, AppError> {
     Ok(Json(
-        state.recipe_service.get_recipe_by_id(id).await?.into(),
+        state
+            .recipe_service
+            .scale_recipe(
+                id,
+                auth_user.map(|x| x.0.user),
+                params.scale()?,
+                params.promote_units,
+            )
+            .await?
+            .into(),
     ))
 }
 
@@ -334,6 +1063,93 @@ pub async fn create_recipe(
     ))
 }
 
+/// Copies another recipe, or one of the user's own, into a new recipe
+/// belonging to the signed in user.
+pub async fn fork_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<(StatusCode, Json<GetRecipe>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .recipe_service
+                .fork_recipe(id, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn parse_ingredient_lines(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ParseIngredientLines>,
+) -> anyhow::Result<Json<GetParsedIngredients>, AppError> {
+    let parsed = state
+        .recipe_service
+        .parse_ingredient_lines(request.lines.clone())
+        .await?;
+    Ok(Json(GetParsedIngredients {
+        ingredients: request
+            .lines
+            .into_iter()
+            .zip(parsed)
+            .map(|(line, parsed)| GetParsedIngredient::new(line, parsed))
+            .collect(),
+    }))
+}
+
+/// Imports a schema.org recipe sent either as JSON-LD or as the HTML page
+/// embedding it, judged by the content type or, failing that, the body.
+pub async fn import_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    headers: HeaderMap,
+    body: String,
+) -> anyhow::Result<(StatusCode, Json<ImportRecipeResponse>), AppError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let document = if content_type.contains("html") {
+        domain::schema_org::Document::Html(body)
+    } else if content_type.contains("json") || body.trim_start().starts_with(['{', '[']) {
+        domain::schema_org::Document::JsonLd(body)
+    } else {
+        domain::schema_org::Document::Html(body)
+    };
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .recipe_service
+                .import_recipe(document, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn import_cooklang(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Query(params): Query<ImportCooklangParams>,
+    body: String,
+) -> anyhow::Result<(StatusCode, Json<ImportRecipeResponse>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .recipe_service
+                .import_cooklang(body, params.title, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
 pub async fn update_recipe(
     State(state): State<Arc<AppState>>,
     ExtractAuthUser(auth_user): ExtractAuthUser,
@@ -341,7 +1157,7 @@ pub async fn update_recipe(
 ) -> anyhow::Result<Json<GetRecipe>, AppError> {
     let recipe = state
         .recipe_service
-        .get_recipe_by_id(recipe_request.id)
+        .get_recipe(recipe_request.id, Some(auth_user.user.clone()))
         .await?;
 
     if recipe.author != auth_user.user {
@@ -351,13 +1167,19 @@ pub async fn update_recipe(
         ))));
     }
 
-    Ok(Json(
-        state
-            .recipe_service
-            .update_recipe(domain::Recipe::from_update(recipe_request, auth_user.user))
-            .await?
-            .into(),
-    ))
+    let updated = state
+        .recipe_service
+        .update_recipe(domain::Recipe {
+            visibility: recipe_request.visibility.unwrap_or(recipe.visibility),
+            draft: recipe_request.draft.unwrap_or(recipe.draft),
+            ..domain::Recipe::from_update(recipe_request, auth_user.user)
+        })
+        .await?;
+    state
+        .media_service
+        .delete_unused_media(&recipe, &updated)
+        .await?;
+    Ok(Json(updated.into()))
 }
 
 pub async fn delete_recipe(
@@ -365,7 +1187,10 @@ pub async fn delete_recipe(
     ExtractAuthUser(auth_user): ExtractAuthUser,
     Path(id): Path<i32>,
 ) -> anyhow::Result<Json<GetRecipe>, AppError> {
-    let recipe = state.recipe_service.get_recipe_by_id(id).await?;
+    let recipe = state
+        .recipe_service
+        .get_recipe(id, Some(auth_user.user.clone()))
+        .await?;
 
     if recipe.author != auth_user.user {
         return Err(AppError::Unauthorized(String::from(format!(
@@ -374,7 +1199,7 @@ pub async fn delete_recipe(
         ))));
     }
 
-    Ok(Json(
-        state.recipe_service.delete_recipe_by_id(id).await?.into(),
-    ))
+    let recipe = state.recipe_service.delete_recipe_by_id(id).await?;
+    state.media_service.delete_recipe_media(&recipe).await?;
+    Ok(Json(recipe.into()))
 }
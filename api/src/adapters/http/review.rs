@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{adapters, core::domain};
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetReview {
+    pub id: i32,
+    pub recipe_id: i32,
+    pub reviewer: adapters::http::user::GetUser,
+    pub rating: i16,
+    pub body: String,
+    pub reply: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub replied_at: Option<DateTime<Utc>>,
+}
+
+impl From<domain::review::Review> for GetReview {
+    fn from(value: domain::review::Review) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            recipe_id: value.recipe_id,
+            reviewer: value.reviewer.into(),
+            rating: value.rating,
+            body: value.body,
+            reply: value.reply,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            replied_at: value.replied_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutateReview {
+    pub rating: i16,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl domain::review::Review {
+    fn from_request(
+        value: MutateReview,
+        id: Option<i32>,
+        recipe_id: i32,
+        reviewer: domain::User,
+    ) -> Self {
+        Self {
+            id,
+            recipe_id,
+            reviewer,
+            rating: value.rating,
+            body: value.body,
+            reply: None,
+            created_at: None,
+            updated_at: None,
+            replied_at: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReplyToReview {
+    /// Left out, null or blank to remove the reply.
+    #[serde(default)]
+    pub reply: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct GetReviewStats {
+    pub review_count: i64,
+    pub average_rating: Option<f64>,
+}
+
+impl From<domain::review::ReviewStats> for GetReviewStats {
+    fn from(value: domain::review::ReviewStats) -> Self {
+        Self {
+            review_count: value.review_count,
+            average_rating: value.average_rating,
+        }
+    }
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/recipe/:id/reviews", get(get_reviews))
+        .route("/recipe/:id/reviews", post(create_review))
+        .route("/review/:id", get(get_review))
+        .route("/review/:id", post(update_review))
+        .route("/review/:id", delete(delete_review))
+        .route("/review/:id/reply", post(reply_to_review))
+}
+
+/// The reviews of a recipe, most recent first.
+pub async fn get_reviews(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<Vec<GetReview>>, AppError> {
+    Ok(Json(
+        state
+            .review_service
+            .get_reviews(id, auth_user.map(|x| x.0.user))
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn create_review(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateReview>,
+) -> anyhow::Result<(StatusCode, Json<GetReview>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .review_service
+                .create_review(domain::review::Review::from_request(
+                    request,
+                    None,
+                    id,
+                    auth_user.user,
+                ))
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn get_review(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetReview>, AppError> {
+    Ok(Json(
+        state
+            .review_service
+            .get_review(id, auth_user.map(|x| x.0.user))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_review(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateReview>,
+) -> anyhow::Result<Json<GetReview>, AppError> {
+    Ok(Json(
+        state
+            .review_service
+            .update_review(domain::review::Review::from_request(
+                request,
+                Some(id),
+                -1,
+                auth_user.user,
+            ))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_review(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetReview>, AppError> {
+    Ok(Json(
+        state
+            .review_service
+            .delete_review(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn reply_to_review(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<ReplyToReview>,
+) -> anyhow::Result<Json<GetReview>, AppError> {
+    Ok(Json(
+        state
+            .review_service
+            .reply_to_review(id, request.reply, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path, Query},
+    recipe::GetRecipe,
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRevisionSummary {
+    pub revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub title: String,
+}
+
+impl From<domain::revision::RevisionSummary> for GetRevisionSummary {
+    fn from(value: domain::revision::RevisionSummary) -> Self {
+        Self {
+            revision: value.revision,
+            created_at: value.created_at,
+            title: value.title,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetRevisionIngredient {
+    pub ingredient: String,
+    pub quantity: domain::Quantity,
+    pub units: String,
+    pub preparation: String,
+    pub group: Option<String>,
+    pub sub_recipe_id: Option<i32>,
+}
+
+impl From<domain::revision::SnapshotIngredient> for GetRevisionIngredient {
+    fn from(value: domain::revision::SnapshotIngredient) -> Self {
+        Self {
+            ingredient: value.ingredient,
+            quantity: value.quantity,
+            units: value.units,
+            preparation: value.preparation,
+            group: value.group,
+            sub_recipe_id: value.sub_recipe_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetRevisionStep {
+    pub ordinal: i32,
+    pub instruction: String,
+    pub group: Option<String>,
+}
+
+impl From<domain::revision::SnapshotStep> for GetRevisionStep {
+    fn from(value: domain::revision::SnapshotStep) -> Self {
+        Self {
+            ordinal: value.ordinal,
+            instruction: value.instruction,
+            group: value.group,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRevisionGroup {
+    pub name: String,
+    pub ordinal: i32,
+}
+
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRecipeRevision {
+    pub recipe_id: i32,
+    pub revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub prep_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub cook_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub inactive_time: Option<chrono::Duration>,
+    pub yield_quantity: domain::Quantity,
+    pub yield_units: String,
+    pub ingredients: Vec<GetRevisionIngredient>,
+    pub steps: Vec<GetRevisionStep>,
+    pub groups: Vec<GetRevisionGroup>,
+    pub tags: Vec<String>,
+}
+
+impl From<domain::revision::RecipeRevision> for GetRecipeRevision {
+    fn from(value: domain::revision::RecipeRevision) -> Self {
+        let snapshot = value.snapshot;
+        Self {
+            recipe_id: value.recipe_id,
+            revision: value.revision,
+            created_at: value.created_at,
+            title: snapshot.title,
+            description: snapshot.description,
+            prep_time: snapshot.prep_time,
+            cook_time: snapshot.cook_time,
+            inactive_time: snapshot.inactive_time,
+            yield_quantity: snapshot.yield_quantity,
+            yield_units: snapshot.yield_units,
+            ingredients: snapshot.ingredients.into_iter().map(|x| x.into()).collect(),
+            steps: snapshot.steps.into_iter().map(|x| x.into()).collect(),
+            groups: snapshot
+                .groups
+                .into_iter()
+                .map(|x| GetRevisionGroup {
+                    name: x.name,
+                    ordinal: x.ordinal,
+                })
+                .collect(),
+            tags: snapshot.tags,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetFieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetChange<T> {
+    pub from: T,
+    pub to: T,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetListDiff<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    pub changed: Vec<GetChange<T>>,
+}
+
+impl<T, U: From<T>> From<domain::revision::ListDiff<T>> for GetListDiff<U> {
+    fn from(value: domain::revision::ListDiff<T>) -> Self {
+        Self {
+            added: value.added.into_iter().map(|x| x.into()).collect(),
+            removed: value.removed.into_iter().map(|x| x.into()).collect(),
+            changed: value
+                .changed
+                .into_iter()
+                .map(|x| GetChange {
+                    from: x.from.into(),
+                    to: x.to.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetRevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub fields: Vec<GetFieldChange>,
+    pub ingredients: GetListDiff<GetRevisionIngredient>,
+    pub steps: GetListDiff<GetRevisionStep>,
+}
+
+impl From<domain::revision::RevisionDiff> for GetRevisionDiff {
+    fn from(value: domain::revision::RevisionDiff) -> Self {
+        Self {
+            from: value.from,
+            to: value.to,
+            fields: value
+                .fields
+                .into_iter()
+                .map(|x| GetFieldChange {
+                    field: x.field,
+                    from: x.from,
+                    to: x.to,
+                })
+                .collect(),
+            ingredients: value.ingredients.into(),
+            steps: value.steps.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiffParams {
+    pub from: i32,
+    pub to: i32,
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/recipe/:id/revisions", get(get_revisions))
+        .route("/recipe/:id/revisions/diff", get(diff_revisions))
+        .route("/recipe/:id/revisions/:revision", get(get_revision))
+        .route(
+            "/recipe/:id/revisions/:revision/revert",
+            post(revert_recipe),
+        )
+}
+
+/// The saved versions of a recipe, newest first.
+pub async fn get_revisions(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<Vec<GetRevisionSummary>>, AppError> {
+    Ok(Json(
+        state
+            .revision_service
+            .get_revisions(id, auth_user.map(|x| x.0.user))
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn get_revision(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path((id, revision)): Path<(i32, i32)>,
+) -> anyhow::Result<Json<GetRecipeRevision>, AppError> {
+    Ok(Json(
+        state
+            .revision_service
+            .get_revision(id, revision, auth_user.map(|x| x.0.user))
+            .await?
+            .into(),
+    ))
+}
+
+/// What changed between two revisions, e.g. `?from=1&to=3`.
+pub async fn diff_revisions(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+    Path(id): Path<i32>,
+    Query(params): Query<DiffParams>,
+) -> anyhow::Result<Json<GetRevisionDiff>, AppError> {
+    Ok(Json(
+        state
+            .revision_service
+            .diff_revisions(id, params.from, params.to, auth_user.map(|x| x.0.user))
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn revert_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, revision)): Path<(i32, i32)>,
+) -> anyhow::Result<Json<GetRecipe>, AppError> {
+    let previous = state
+        .recipe_service
+        .get_recipe(id, Some(auth_user.user.clone()))
+        .await?;
+    let reverted = state
+        .revision_service
+        .revert_recipe(id, revision, auth_user.user)
+        .await?;
+    state
+        .media_service
+        .delete_unused_media(&previous, &reverted)
+        .await?;
+    Ok(Json(reverted.into()))
+}
@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetShoppingListItem {
+    pub id: i32,
+    pub ingredient_id: i32,
+    pub name: String,
+    pub quantity: Option<domain::Quantity>,
+    pub units: Option<String>,
+    pub checked: bool,
+    pub ordinal: i32,
+}
+
+impl From<domain::shopping_list::ShoppingListItem> for GetShoppingListItem {
+    fn from(value: domain::shopping_list::ShoppingListItem) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            ingredient_id: value.ingredient.id.unwrap_or(-1),
+            name: value.ingredient.name,
+            quantity: value.quantity,
+            units: value.units.map(|x| x.name),
+            checked: value.checked,
+            ordinal: value.ordinal,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetShoppingListRecipe {
+    pub recipe_id: i32,
+    pub title: String,
+    pub scale: domain::Quantity,
+}
+
+impl From<domain::shopping_list::ShoppingListRecipe> for GetShoppingListRecipe {
+    fn from(value: domain::shopping_list::ShoppingListRecipe) -> Self {
+        Self {
+            recipe_id: value.recipe_id,
+            title: value.title,
+            scale: value.scale,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetShoppingList {
+    pub id: i32,
+    pub name: String,
+    pub items: Vec<GetShoppingListItem>,
+    pub recipes: Vec<GetShoppingListRecipe>,
+}
+
+impl From<domain::shopping_list::ShoppingList> for GetShoppingList {
+    fn from(value: domain::shopping_list::ShoppingList) -> Self {
+        Self {
+            id: value.id.unwrap_or(-1),
+            name: value.name,
+            items: value.items.into_iter().map(|x| x.into()).collect(),
+            recipes: value.recipes.into_iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutateShoppingList {
+    pub name: String,
+}
+
+/// A recipe to add to a list, optionally scaled to a yield or by a factor.
+#[derive(Deserialize)]
+pub struct AddShoppingListRecipe {
+    pub recipe_id: i32,
+    #[serde(rename = "yield")]
+    pub target_yield: Option<domain::Quantity>,
+    pub factor: Option<domain::Quantity>,
+}
+
+impl AddShoppingListRecipe {
+    fn scale(&self) -> Result<Option<domain::recipe::ScaleBy>, AppError> {
+        match (self.target_yield, self.factor) {
+            (Some(target_yield), None) => Ok(Some(domain::recipe::ScaleBy::Yield(target_yield))),
+            (None, Some(factor)) => Ok(Some(domain::recipe::ScaleBy::Factor(factor))),
+            (None, None) => Ok(None),
+            _ => Err(AppError::BadRequest(
+                "at most one of `yield` or `factor` may be given".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AddShoppingListItem {
+    pub name: String,
+    pub quantity: Option<domain::Quantity>,
+    pub units: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateShoppingListItem {
+    pub name: Option<String>,
+    pub quantity: Option<domain::Quantity>,
+    pub units: Option<String>,
+    pub checked: Option<bool>,
+}
+
+impl From<UpdateShoppingListItem> for domain::shopping_list::ItemUpdate {
+    fn from(value: UpdateShoppingListItem) -> Self {
+        Self {
+            name: value.name,
+            quantity: value.quantity,
+            units: value.units,
+            checked: value.checked,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReorderShoppingListItems {
+    pub item_ids: Vec<i32>,
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/shopping-list", get(get_shopping_lists))
+        .route("/shopping-list", post(create_shopping_list))
+        .route("/shopping-list/:id", get(get_shopping_list))
+        .route("/shopping-list/:id", post(rename_shopping_list))
+        .route("/shopping-list/:id", delete(delete_shopping_list))
+        .route("/shopping-list/:id/recipe", post(add_recipe))
+        .route("/shopping-list/:id/item", post(add_item))
+        .route("/shopping-list/:id/item/:item_id", post(update_item))
+        .route("/shopping-list/:id/item/:item_id", delete(remove_item))
+        .route("/shopping-list/:id/order", post(reorder_items))
+}
+
+pub async fn get_shopping_lists(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+) -> anyhow::Result<Json<Vec<GetShoppingList>>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .get_shopping_lists(auth_user.user)
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn create_shopping_list(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Json(request): Json<MutateShoppingList>,
+) -> anyhow::Result<(StatusCode, Json<GetShoppingList>), AppError> {
+    Ok((
+        StatusCode::CREATED,
+        Json(
+            state
+                .shopping_list_service
+                .create_shopping_list(request.name, auth_user.user)
+                .await?
+                .into(),
+        ),
+    ))
+}
+
+pub async fn get_shopping_list(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .get_shopping_list(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn rename_shopping_list(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<MutateShoppingList>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .rename_shopping_list(id, request.name, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn delete_shopping_list(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .delete_shopping_list(id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn add_recipe(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<AddShoppingListRecipe>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    let scale = request.scale()?;
+    Ok(Json(
+        state
+            .shopping_list_service
+            .add_recipe(id, request.recipe_id, scale, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn add_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<AddShoppingListItem>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .add_item(
+                id,
+                request.name,
+                request.quantity,
+                request.units,
+                auth_user.user,
+            )
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn update_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, item_id)): Path<(i32, i32)>,
+    Json(request): Json<UpdateShoppingListItem>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .update_item(id, item_id, request.into(), auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn remove_item(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path((id, item_id)): Path<(i32, i32)>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .remove_item(id, item_id, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
+
+pub async fn reorder_items(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(request): Json<ReorderShoppingListItems>,
+) -> anyhow::Result<Json<GetShoppingList>, AppError> {
+    Ok(Json(
+        state
+            .shopping_list_service
+            .reorder_items(id, request.item_ids, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
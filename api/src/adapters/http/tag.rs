@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{
+    error::AppError,
+    extract::{ExtractAuthUser, Path},
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GetTag {
+    pub id: i32,
+    pub name: String,
+    pub recipe_count: i64,
+}
+
+impl From<domain::tag::TagUsage> for GetTag {
+    fn from(value: domain::tag::TagUsage) -> Self {
+        Self {
+            id: value.tag.id.unwrap_or(-1),
+            name: value.tag.name,
+            recipe_count: value.recipe_count,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RenameTag {
+    pub name: String,
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/tag", get(get_tags))
+        .route("/tag/:id", post(rename_tag))
+}
+
+pub async fn get_tags(
+    State(state): State<Arc<AppState>>,
+    auth_user: Option<ExtractAuthUser>,
+) -> anyhow::Result<Json<Vec<GetTag>>, AppError> {
+    Ok(Json(
+        state
+            .tag_service
+            .get_tags(auth_user.map(|x| x.0.user))
+            .await?
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
+
+pub async fn rename_tag(
+    State(state): State<Arc<AppState>>,
+    ExtractAuthUser(auth_user): ExtractAuthUser,
+    Path(id): Path<i32>,
+    Json(rename_request): Json<RenameTag>,
+) -> anyhow::Result<Json<GetTag>, AppError> {
+    Ok(Json(
+        state
+            .tag_service
+            .rename_tag(id, rename_request.name, auth_user.user)
+            .await?
+            .into(),
+    ))
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use axum::{extract::State, routing::get, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain;
+
+use super::{error::AppError, AppState};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct GetUnit {
+    pub id: i32,
+    pub name: String,
+    pub abbreviation: Option<String>,
+    pub dimension: String,
+    pub system: Option<String>,
+}
+
+impl From<domain::unit::UnitDefinition> for GetUnit {
+    fn from(value: domain::unit::UnitDefinition) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            abbreviation: value.abbreviation,
+            dimension: format!("{:?}", value.dimension).to_lowercase(),
+            system: value.system.map(|x| format!("{:?}", x).to_lowercase()),
+        }
+    }
+}
+
+pub fn build_routes() -> Router<Arc<AppState>> {
+    Router::new().route("/unit", get(get_units))
+}
+
+pub async fn get_units(
+    State(state): State<Arc<AppState>>,
+) -> anyhow::Result<Json<Vec<GetUnit>>, AppError> {
+    Ok(Json(
+        state
+            .unit_service
+            .get_units()
+            .await?
+            .units
+            .into_iter()
+            .map(|x| x.into())
+            .collect(),
+    ))
+}
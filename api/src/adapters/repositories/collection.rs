@@ -0,0 +1,339 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use sqlx::{FromRow, Postgres, Row, Transaction};
+
+pub struct PostgresCollectionRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresCollectionRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresCollectionRepository {
+        PostgresCollectionRepository { db_pool }
+    }
+
+    /// Loads the collection with the given id and its recipes.
+    async fn load(&self, id: i32) -> Result<domain::collection::Collection, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                c.id as id,
+                c.name as name,
+                c.shared as shared,
+                au.id as owner_id,
+                au.name as owner_name
+            FROM collection c
+            JOIN app_user au ON au.id = c.owner
+            WHERE c.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        let recipes = sqlx::query_as(
+            r#"
+            SELECT
+                r.id as recipe_id,
+                r.title as title,
+                r.author as author_id,
+                r.visibility as visibility,
+                r.draft as draft
+            FROM collection_recipe cr
+            JOIN recipe r ON r.id = cr.recipe
+            WHERE cr.collection = $1
+            ORDER BY cr.ordinal
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        Ok(domain::collection::Collection {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            owner: domain::User {
+                id: row.try_get("owner_id")?,
+                name: row.try_get("owner_name")?,
+            },
+            shared: row.try_get("shared")?,
+            recipes,
+        })
+    }
+
+    async fn get(
+        &self,
+        id: i32,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        self.load(id).await.map_err(|e| match e {
+            sqlx::Error::RowNotFound => domain::collection::Error::CollectionNotFound(id),
+            _ => {
+                log::error!("Failed to find collection `{}` due to: {}", id, e);
+                domain::collection::Error::Unexpected
+            }
+        })
+    }
+
+    /// Replaces the recipes of the collection with its current ones, numbered
+    /// in order.
+    async fn save_recipes(
+        tx: &mut Transaction<'_, Postgres>,
+        collection: &domain::collection::Collection,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let recipe_ids: Vec<i32> = collection.recipes.iter().map(|x| x.recipe_id).collect();
+        sqlx::query("DELETE FROM collection_recipe WHERE collection = $1")
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO collection_recipe (collection, recipe, ordinal)
+            SELECT $1, r.recipe, r.ordinal
+            FROM unnest($2::integer[]) WITH ORDINALITY AS r(recipe, ordinal)
+            "#,
+        )
+        .bind(id)
+        .bind(&recipe_ids)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl port::CollectionRepository for PostgresCollectionRepository {
+    async fn get_collections(
+        &self,
+        owner_id: i32,
+    ) -> Result<Vec<domain::collection::Collection>, domain::collection::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                c.id as id,
+                c.name as name,
+                c.shared as shared,
+                au.id as owner_id,
+                au.name as owner_name,
+                r.id as recipe_id,
+                r.title as title,
+                r.author as author_id,
+                r.visibility as visibility,
+                r.draft as draft
+            FROM collection c
+            JOIN app_user au ON au.id = c.owner
+            LEFT JOIN collection_recipe cr ON cr.collection = c.id
+            LEFT JOIN recipe r ON r.id = cr.recipe
+            WHERE c.owner = $1
+            ORDER BY c.name, c.id, cr.ordinal
+            "#,
+        )
+        .bind(owner_id)
+        .fetch_all(&self.db_pool)
+        .await;
+        // Each collection spans one row per recipe, or a single row without
+        // a recipe when it is empty.
+        let collect = |rows: Vec<sqlx::postgres::PgRow>| {
+            let mut collections: Vec<domain::collection::Collection> = Vec::new();
+            for row in rows {
+                let id: Option<i32> = row.try_get("id")?;
+                if collections.last().map(|x| x.id) != Some(id) {
+                    collections.push(domain::collection::Collection {
+                        id,
+                        name: row.try_get("name")?,
+                        owner: domain::User {
+                            id: row.try_get("owner_id")?,
+                            name: row.try_get("owner_name")?,
+                        },
+                        shared: row.try_get("shared")?,
+                        recipes: vec![],
+                    });
+                }
+                if row.try_get::<Option<i32>, _>("recipe_id")?.is_some() {
+                    let recipe = domain::collection::CollectionRecipe::from_row(&row)?;
+                    if let Some(collection) = collections.last_mut() {
+                        collection.recipes.push(recipe);
+                    }
+                }
+            }
+            Ok(collections)
+        };
+        rows.and_then(collect).map_err(|e: sqlx::Error| {
+            log::error!(
+                "Failed to find collections of user `{}` due to: {}",
+                owner_id,
+                e
+            );
+            domain::collection::Error::Unexpected
+        })
+    }
+
+    async fn get_collection(
+        &self,
+        id: i32,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        self.get(id).await
+    }
+
+    async fn create_collection(
+        &self,
+        collection: domain::collection::Collection,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to create collection {:?} due to: {}", collection, e);
+            domain::collection::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO collection (owner, name, shared) VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(collection.owner.id)
+        .bind(&collection.name)
+        .bind(collection.shared)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(unexpected)?;
+        Self::save_recipes(&mut tx, &collection, id)
+            .await
+            .map_err(unexpected)?;
+        tx.commit().await.map_err(unexpected)?;
+        self.get(id).await
+    }
+
+    async fn update_collection(
+        &self,
+        collection: domain::collection::Collection,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let id = collection.id.ok_or(domain::collection::Error::Unexpected)?;
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to update collection {:?} due to: {}", collection, e);
+            domain::collection::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let updated = sqlx::query("UPDATE collection SET name = $2, shared = $3 WHERE id = $1")
+            .bind(id)
+            .bind(&collection.name)
+            .bind(collection.shared)
+            .execute(&mut *tx)
+            .await
+            .map_err(unexpected)?;
+        if updated.rows_affected() == 0 {
+            return Err(domain::collection::Error::CollectionNotFound(id));
+        }
+        Self::save_recipes(&mut tx, &collection, id)
+            .await
+            .map_err(unexpected)?;
+        tx.commit().await.map_err(unexpected)?;
+        self.get(id).await
+    }
+
+    async fn delete_collection(
+        &self,
+        id: i32,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let collection = self.get(id).await?;
+        sqlx::query("DELETE FROM collection WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete collection `{}` due to: {}", id, e);
+                domain::collection::Error::Unexpected
+            })?;
+        Ok(collection)
+    }
+
+    async fn get_favorites(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<domain::collection::CollectionRecipe>, domain::collection::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                r.id as recipe_id,
+                r.title as title,
+                r.author as author_id,
+                r.visibility as visibility,
+                r.draft as draft
+            FROM favorite f
+            JOIN recipe r ON r.id = f.recipe
+            WHERE f.app_user = $1
+            ORDER BY f.created_at DESC, r.id DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to find favorites of user `{}` due to: {}",
+                user_id,
+                e
+            );
+            domain::collection::Error::Unexpected
+        })
+    }
+
+    async fn add_favorite(
+        &self,
+        user_id: i32,
+        recipe_id: i32,
+    ) -> Result<(), domain::collection::Error> {
+        sqlx::query(
+            "INSERT INTO favorite (app_user, recipe) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(recipe_id)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to favorite recipe `{}` for user `{}` due to: {}",
+                recipe_id,
+                user_id,
+                e
+            );
+            domain::collection::Error::Unexpected
+        })?;
+        Ok(())
+    }
+
+    async fn remove_favorite(
+        &self,
+        user_id: i32,
+        recipe_id: i32,
+    ) -> Result<(), domain::collection::Error> {
+        sqlx::query("DELETE FROM favorite WHERE app_user = $1 AND recipe = $2")
+            .bind(user_id)
+            .bind(recipe_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "Failed to unfavorite recipe `{}` for user `{}` due to: {}",
+                    recipe_id,
+                    user_id,
+                    e
+                );
+                domain::collection::Error::Unexpected
+            })?;
+        Ok(())
+    }
+
+    async fn get_favorited(
+        &self,
+        user_id: i32,
+        recipe_ids: Vec<i32>,
+    ) -> Result<Vec<i32>, domain::collection::Error> {
+        sqlx::query_scalar("SELECT recipe FROM favorite WHERE app_user = $1 AND recipe = ANY($2)")
+            .bind(user_id)
+            .bind(&recipe_ids)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "Failed to find favorites of user `{}` due to: {}",
+                    user_id,
+                    e
+                );
+                domain::collection::Error::Unexpected
+            })
+    }
+}
@@ -0,0 +1,160 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresCookLogRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresCookLogRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresCookLogRepository {
+        PostgresCookLogRepository { db_pool }
+    }
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT
+        c.id as id,
+        au.id as owner_id,
+        au.name as owner_name,
+        r.id as recipe_id,
+        r.title as recipe_title,
+        c.cooked_on as cooked_on,
+        c.servings as servings,
+        c.notes as notes,
+        c.modifications as modifications,
+        c.rating as rating
+    FROM cook_log c
+    JOIN app_user au ON au.id = c.owner
+    JOIN recipe r ON r.id = c.recipe
+    "#;
+
+#[async_trait]
+impl port::CookLogRepository for PostgresCookLogRepository {
+    async fn get_entries(
+        &self,
+        query: domain::cook_log::CookLogQuery,
+        viewer_id: Option<i32>,
+    ) -> Result<Vec<domain::cook_log::CookLogEntry>, domain::cook_log::Error> {
+        // A user's history lists recipes, so unlisted ones are left out of
+        // it as they are of other listings.
+        let (filter, id, visible) = match query {
+            domain::cook_log::CookLogQuery::Recipe(id) => {
+                ("c.recipe", id, domain::recipe::visible_to_sql("$2"))
+            }
+            domain::cook_log::CookLogQuery::Owner(id) => {
+                ("c.owner", id, domain::recipe::listed_to_sql("$2"))
+            }
+        };
+        sqlx::query_as(&format!(
+            r#"
+            {} WHERE {} = $1 AND {}
+            ORDER BY c.cooked_on DESC, c.id DESC
+            "#,
+            SELECT_ENTRY, filter, visible
+        ))
+        .bind(id)
+        .bind(viewer_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find cook log {:?} due to: {}", query, e);
+            domain::cook_log::Error::Unexpected
+        })
+    }
+
+    async fn get_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        sqlx::query_as(&format!("{} WHERE c.id = $1", SELECT_ENTRY))
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::cook_log::Error::EntryNotFound(id),
+                _ => {
+                    log::error!("Failed to find cook log entry `{}` due to: {}", id, e);
+                    domain::cook_log::Error::Unexpected
+                }
+            })
+    }
+
+    async fn create_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO cook_log (owner, recipe, cooked_on, servings, notes, modifications, rating)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(entry.owner.id)
+        .bind(entry.recipe_id)
+        .bind(entry.cooked_on)
+        .bind(entry.servings)
+        .bind(&entry.notes)
+        .bind(&entry.modifications)
+        .bind(entry.rating)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to create cook log entry {:?} due to: {}", entry, e);
+            domain::cook_log::Error::Unexpected
+        })?;
+        self.get_entry(id).await
+    }
+
+    async fn update_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let id = entry.id.ok_or(domain::cook_log::Error::Unexpected)?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE cook_log SET
+                recipe = $2,
+                cooked_on = $3,
+                servings = $4,
+                notes = $5,
+                modifications = $6,
+                rating = $7
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(entry.recipe_id)
+        .bind(entry.cooked_on)
+        .bind(entry.servings)
+        .bind(&entry.notes)
+        .bind(&entry.modifications)
+        .bind(entry.rating)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to update cook log entry {:?} due to: {}", entry, e);
+            domain::cook_log::Error::Unexpected
+        })?;
+        if updated.rows_affected() == 0 {
+            return Err(domain::cook_log::Error::EntryNotFound(id));
+        }
+        self.get_entry(id).await
+    }
+
+    async fn delete_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let entry = self.get_entry(id).await?;
+        sqlx::query("DELETE FROM cook_log WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete cook log entry `{}` due to: {}", id, e);
+                domain::cook_log::Error::Unexpected
+            })?;
+        Ok(entry)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresMealPlanRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresMealPlanRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresMealPlanRepository {
+        PostgresMealPlanRepository { db_pool }
+    }
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT
+        e.id as id,
+        au.id as owner_id,
+        au.name as owner_name,
+        e.date as date,
+        e.slot as slot,
+        r.id as recipe_id,
+        r.title as recipe_title,
+        e.servings as servings
+    FROM meal_plan_entry e
+    JOIN app_user au ON au.id = e.owner
+    JOIN recipe r ON r.id = e.recipe
+    "#;
+
+#[async_trait]
+impl port::MealPlanRepository for PostgresMealPlanRepository {
+    async fn get_entries(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+    ) -> Result<Vec<domain::meal_plan::MealPlanEntry>, domain::meal_plan::Error> {
+        sqlx::query_as(&format!(
+            "{} WHERE e.owner = $1 AND e.date BETWEEN $2 AND $3 ORDER BY e.date, e.slot, e.id",
+            SELECT_ENTRY
+        ))
+        .bind(query.owner_id)
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find meal plan {:?} due to: {}", query, e);
+            domain::meal_plan::Error::Unexpected
+        })
+    }
+
+    async fn get_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        sqlx::query_as(&format!("{} WHERE e.id = $1", SELECT_ENTRY))
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::meal_plan::Error::EntryNotFound(id),
+                _ => {
+                    log::error!("Failed to find meal plan entry `{}` due to: {}", id, e);
+                    domain::meal_plan::Error::Unexpected
+                }
+            })
+    }
+
+    async fn create_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO meal_plan_entry (owner, date, slot, recipe, servings)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(entry.owner.id)
+        .bind(entry.date)
+        .bind(entry.slot)
+        .bind(entry.recipe_id)
+        .bind(entry.servings)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to create meal plan entry {:?} due to: {}", entry, e);
+            domain::meal_plan::Error::Unexpected
+        })?;
+        self.get_entry(id).await
+    }
+
+    async fn update_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let id = entry.id.ok_or(domain::meal_plan::Error::Unexpected)?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE meal_plan_entry
+            SET date = $2, slot = $3, recipe = $4, servings = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(entry.date)
+        .bind(entry.slot)
+        .bind(entry.recipe_id)
+        .bind(entry.servings)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to update meal plan entry {:?} due to: {}", entry, e);
+            domain::meal_plan::Error::Unexpected
+        })?;
+        if updated.rows_affected() == 0 {
+            return Err(domain::meal_plan::Error::EntryNotFound(id));
+        }
+        self.get_entry(id).await
+    }
+
+    async fn delete_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let entry = self.get_entry(id).await?;
+        sqlx::query("DELETE FROM meal_plan_entry WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete meal plan entry `{}` due to: {}", id, e);
+                domain::meal_plan::Error::Unexpected
+            })?;
+        Ok(entry)
+    }
+}
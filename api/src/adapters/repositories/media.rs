@@ -0,0 +1,74 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresMediaRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresMediaRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresMediaRepository {
+        PostgresMediaRepository { db_pool }
+    }
+}
+
+#[async_trait]
+impl port::MediaRepository for PostgresMediaRepository {
+    async fn set_recipe_image(
+        &self,
+        recipe_id: i32,
+        key: Option<String>,
+    ) -> Result<Option<String>, domain::media::Error> {
+        let previous: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            UPDATE recipe r SET image = $2
+            FROM (SELECT id, image FROM recipe WHERE id = $1 FOR UPDATE) old
+            WHERE r.id = old.id
+            RETURNING old.image
+            "#,
+        )
+        .bind(recipe_id)
+        .bind(&key)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to set image of recipe `{}` due to: {}",
+                recipe_id,
+                e
+            );
+            domain::media::Error::Unexpected
+        })?;
+        previous.ok_or(domain::media::Error::RecipeNotFound(recipe_id))
+    }
+
+    async fn set_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+        key: Option<String>,
+    ) -> Result<Option<String>, domain::media::Error> {
+        let previous: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            UPDATE step s SET image = $3
+            FROM (SELECT id, image FROM step WHERE id = $2 AND recipe = $1 FOR UPDATE) old
+            WHERE s.id = old.id
+            RETURNING old.image
+            "#,
+        )
+        .bind(recipe_id)
+        .bind(step_id)
+        .bind(&key)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to set image of step `{}` in recipe `{}` due to: {}",
+                step_id,
+                recipe_id,
+                e
+            );
+            domain::media::Error::Unexpected
+        })?;
+        previous.ok_or(domain::media::Error::StepNotFound(step_id))
+    }
+}
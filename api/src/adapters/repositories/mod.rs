@@ -4,3 +4,23 @@ mod auth;
 pub use auth::PostgresAuthUserRepository;
 mod recipe;
 pub use recipe::PostgresRecipeRepository;
+mod tag;
+pub use tag::PostgresTagRepository;
+mod unit;
+pub use unit::PostgresUnitRepository;
+mod media;
+pub use media::PostgresMediaRepository;
+mod shopping_list;
+pub use shopping_list::PostgresShoppingListRepository;
+mod meal_plan;
+pub use meal_plan::PostgresMealPlanRepository;
+mod pantry;
+pub use pantry::PostgresPantryRepository;
+mod cook_log;
+pub use cook_log::PostgresCookLogRepository;
+mod review;
+pub use review::PostgresReviewRepository;
+mod collection;
+pub use collection::PostgresCollectionRepository;
+mod revision;
+pub use revision::PostgresRevisionRepository;
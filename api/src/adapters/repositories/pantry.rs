@@ -0,0 +1,218 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use sqlx::{Postgres, Transaction};
+
+pub struct PostgresPantryRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresPantryRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresPantryRepository {
+        PostgresPantryRepository { db_pool }
+    }
+
+    /// The id of the item's ingredient, adding the ingredient if it is new.
+    async fn ingredient_id(
+        tx: &mut Transaction<'_, Postgres>,
+        item: &domain::pantry::PantryItem,
+    ) -> Result<i32, sqlx::Error> {
+        match item.ingredient.id {
+            Some(id) => Ok(id),
+            None => {
+                sqlx::query_scalar(
+                    r#"
+                    INSERT INTO ingredient (name) VALUES ($1)
+                    ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+                    RETURNING id
+                    "#,
+                )
+                .bind(&item.ingredient.name)
+                .fetch_one(&mut **tx)
+                .await
+            }
+        }
+    }
+
+    /// Writes an item, returning whether it was found.
+    async fn save(
+        tx: &mut Transaction<'_, Postgres>,
+        item: &domain::pantry::PantryItem,
+        id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let ingredient_id = Self::ingredient_id(tx, item).await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE pantry_item SET
+                ingredient = $2,
+                quantity = $3,
+                units = (SELECT id FROM unit WHERE name = $4),
+                location = $5,
+                expires_on = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(ingredient_id)
+        .bind(item.quantity)
+        .bind(&item.units.name)
+        .bind(&item.location)
+        .bind(item.expires_on)
+        .execute(&mut **tx)
+        .await?;
+        Ok(updated.rows_affected() > 0)
+    }
+}
+
+const SELECT_ITEM: &str = r#"
+    SELECT
+        p.id as id,
+        au.id as owner_id,
+        au.name as owner_name,
+        i.id as ingredient_id,
+        i.name as ingredient_name,
+        p.quantity as quantity,
+        u.id as units_id,
+        u.name as units_name,
+        p.location as location,
+        p.expires_on as expires_on
+    FROM pantry_item p
+    JOIN app_user au ON au.id = p.owner
+    JOIN ingredient i ON i.id = p.ingredient
+    JOIN unit u ON u.id = p.units
+    "#;
+
+#[async_trait]
+impl port::PantryRepository for PostgresPantryRepository {
+    async fn get_items(
+        &self,
+        query: domain::pantry::PantryQuery,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error> {
+        let order = match query.expires_by {
+            Some(_) => "p.expires_on, i.name, p.id",
+            None => "p.location, i.name, p.expires_on NULLS LAST, p.id",
+        };
+        sqlx::query_as(&format!(
+            r#"
+            {}
+            WHERE p.owner = $1
+                AND ($2::text IS NULL OR p.location = $2)
+                AND ($3::date IS NULL OR (p.expires_on <= $3 AND (p.quantity).numerator > 0))
+            ORDER BY {}
+            "#,
+            SELECT_ITEM, order
+        ))
+        .bind(query.owner_id)
+        .bind(&query.location)
+        .bind(query.expires_by)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find pantry items {:?} due to: {}", query, e);
+            domain::pantry::Error::Unexpected
+        })
+    }
+
+    async fn get_item(&self, id: i32) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        sqlx::query_as(&format!("{} WHERE p.id = $1", SELECT_ITEM))
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::pantry::Error::ItemNotFound(id),
+                _ => {
+                    log::error!("Failed to find pantry item `{}` due to: {}", id, e);
+                    domain::pantry::Error::Unexpected
+                }
+            })
+    }
+
+    async fn create_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to create pantry item {:?} due to: {}", item, e);
+            domain::pantry::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let ingredient_id = Self::ingredient_id(&mut tx, &item)
+            .await
+            .map_err(unexpected)?;
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO pantry_item (owner, ingredient, quantity, units, location, expires_on)
+            VALUES ($1, $2, $3, (SELECT id FROM unit WHERE name = $4), $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(item.owner.id)
+        .bind(ingredient_id)
+        .bind(item.quantity)
+        .bind(&item.units.name)
+        .bind(&item.location)
+        .bind(item.expires_on)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(unexpected)?;
+        tx.commit().await.map_err(unexpected)?;
+        self.get_item(id).await
+    }
+
+    async fn update_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let id = item.id.ok_or(domain::pantry::Error::Unexpected)?;
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to update pantry item {:?} due to: {}", item, e);
+            domain::pantry::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        if !Self::save(&mut tx, &item, id).await.map_err(unexpected)? {
+            return Err(domain::pantry::Error::ItemNotFound(id));
+        }
+        tx.commit().await.map_err(unexpected)?;
+        self.get_item(id).await
+    }
+
+    async fn update_items(
+        &self,
+        items: Vec<domain::pantry::PantryItem>,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to update pantry items {:?} due to: {}", items, e);
+            domain::pantry::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let mut ids = Vec::with_capacity(items.len());
+        for item in &items {
+            let id = item.id.ok_or(domain::pantry::Error::Unexpected)?;
+            if !Self::save(&mut tx, item, id).await.map_err(unexpected)? {
+                return Err(domain::pantry::Error::ItemNotFound(id));
+            }
+            ids.push(id);
+        }
+        tx.commit().await.map_err(unexpected)?;
+        let mut updated = Vec::with_capacity(ids.len());
+        for id in ids {
+            updated.push(self.get_item(id).await?);
+        }
+        Ok(updated)
+    }
+
+    async fn delete_item(
+        &self,
+        id: i32,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let item = self.get_item(id).await?;
+        sqlx::query("DELETE FROM pantry_item WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete pantry item `{}` due to: {}", id, e);
+                domain::pantry::Error::Unexpected
+            })?;
+        Ok(item)
+    }
+}
@@ -1,23 +1,10 @@
 use crate::core::{domain, port};
 use async_trait::async_trait;
-use sqlx::{Postgres, QueryBuilder};
+use sqlx::{Postgres, QueryBuilder, Transaction};
 
-pub struct PostgresRecipeRepository {
-    db_pool: sqlx::postgres::PgPool,
-}
-
-impl PostgresRecipeRepository {
-    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresRecipeRepository {
-        PostgresRecipeRepository { db_pool }
-    }
-}
-
-#[async_trait]
-impl port::RecipeRepository for PostgresRecipeRepository {
-    async fn get_recipes(&self) -> Result<Vec<domain::Recipe>, domain::recipe::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT
+/// Every column needed to build a `domain::Recipe` from the persisted tables
+/// joined in `RECIPE_TABLES`.
+const RECIPE_COLUMNS: &str = r#"
                 r.id as id,
                 r.title as title,
                 r.description as description,
@@ -27,7 +14,12 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 EXTRACT(EPOCH FROM r.inactive_time)::bigint as inactive_time,
                 r.yield_quantity as yield_quantity,
                 (ru.id, ru.name)::t_unit as yield_units,
-                array(SELECT (s.id, s.recipe, s.ordinal, s.instruction)::t_step FROM step s WHERE s.recipe = r.id) as steps,
+                array(
+                    SELECT (s.id, s.recipe, s.ordinal, s.instruction, sg.name, s.image)::t_step
+                    FROM step s
+                    LEFT JOIN recipe_group sg ON sg.id = s.recipe_group
+                    WHERE s.recipe = r.id
+                ) as steps,
                 array(
                     SELECT
                     (
@@ -36,73 +28,498 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                         (i.id, i.name)::t_ingredient,
                         ri.quantity,
                         (riu.id, riu.name)::t_unit,
-                        ri.preparation
+                        ri.preparation,
+                        rig.name,
+                        ri.sub_recipe
                     )::t_recipe_ingredient
                     FROM recipe_ingredient ri
                     JOIN ingredient i ON i.id = ri.ingredient
                     JOIN unit riu ON ri.units = riu.id
+                    LEFT JOIN recipe_group rig ON rig.id = ri.recipe_group
                     WHERE ri.recipe = r.id
-                ) as ingredients
-            FROM
+                ) as ingredients,
+                array(
+                    SELECT (g.id, g.name, g.ordinal)::t_recipe_group
+                    FROM recipe_group g
+                    WHERE g.recipe = r.id
+                    ORDER BY g.ordinal
+                ) as groups,
+                array(
+                    SELECT (t.id, t.name)::t_tag
+                    FROM recipe_tag rt
+                    JOIN tag t ON t.id = rt.tag
+                    WHERE rt.recipe = r.id
+                    ORDER BY t.name
+                ) as tags,
+                r.image as image,
+                r.visibility as visibility,
+                r.draft as draft,
+                r.forked_from as forked_from,
+                recipe_lineage(r.forked_from) as lineage,
+                (SELECT count(*) FROM cook_log c WHERE c.recipe = r.id) as times_cooked,
+                (SELECT max(c.cooked_on) FROM cook_log c WHERE c.recipe = r.id) as last_cooked,
+                (
+                    SELECT round(avg(c.rating), 2)::float8 FROM cook_log c WHERE c.recipe = r.id
+                ) as average_rating,
+                (SELECT count(*) FROM review v WHERE v.recipe = r.id) as review_count,
+                (
+                    SELECT round(avg(v.rating), 2)::float8 FROM review v WHERE v.recipe = r.id
+                ) as review_rating
+            "#;
+
+/// Recipe tables, aliasing the recipe as `r` and its yield unit as `ru` so
+/// callers can append their own `WHERE`/`ORDER BY` clauses.
+const RECIPE_TABLES: &str = r#"
                 recipe AS r
                 JOIN app_user au ON r.author = au.id
-                JOIN unit ru ON r.yield_units = ru.id;
+                JOIN unit ru ON r.yield_units = ru.id
+            "#;
+
+/// Total time of a recipe `r`, treating missing times as zero.
+const TOTAL_TIME: &str =
+    "(COALESCE(r.prep_time, '0') + COALESCE(r.cook_time, '0') + COALESCE(r.inactive_time, '0'))";
+
+/// Records the saved recipe as its next revision.
+async fn save_revision(
+    tx: &mut Transaction<'_, Postgres>,
+    recipe: &domain::Recipe,
+) -> Result<(), domain::recipe::Error> {
+    let unexpected = |e: &dyn std::fmt::Display| {
+        log::error!(
+            "Failed to save revision of recipe {:?} due to: {}",
+            recipe.id,
+            e
+        );
+        domain::recipe::Error::Unexpected
+    };
+    let snapshot = domain::revision::RecipeSnapshot::from_recipe(recipe);
+    let snapshot = serde_json::to_string(&snapshot).map_err(|e| unexpected(&e))?;
+    sqlx::query(
+        r#"
+        INSERT INTO recipe_revision (recipe, revision, snapshot)
+        SELECT $1, COALESCE(max(revision), 0) + 1, $2::jsonb
+        FROM recipe_revision
+        WHERE recipe = $1
+        "#,
+    )
+    .bind(recipe.id)
+    .bind(snapshot)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| unexpected(&e))?;
+    Ok(())
+}
+
+/// Everything searchable about recipe `r` as one document `d.document`:
+/// its title, description, ingredient names and step instructions, so that
+/// the terms of a query may be found in different fields.
+const SEARCH_DOCUMENT: &str = r#"
+                CROSS JOIN LATERAL (
+                    SELECT r.search_document
+                        || (
+                            SELECT tsvector_agg(i.search_document)
+                            FROM recipe_ingredient ri
+                            JOIN ingredient i ON i.id = ri.ingredient
+                            WHERE ri.recipe = r.id
+                        )
+                        || (SELECT tsvector_agg(s.search_document) FROM step s WHERE s.recipe = r.id)
+                        AS document
+                ) d
+            "#;
+
+/// Joins each recipe `r` to `m`, its count of required ingredients, how many of
+/// those are in the `$1` name array and the names of the rest. Ingredients in
+/// the `$2` name array are not counted at all.
+const INGREDIENT_COVERAGE: &str = r#"
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE lower(i.name) = ANY($1)) AS covered,
+                        COUNT(*) AS required,
+                        COALESCE(
+                            array_agg(i.name ORDER BY i.name) FILTER (WHERE NOT lower(i.name) = ANY($1)),
+                            '{}'
+                        ) AS missing
+                    FROM recipe_ingredient ri
+                    JOIN ingredient i ON i.id = ri.ingredient
+                    WHERE ri.recipe = r.id AND NOT lower(i.name) = ANY($2)
+                ) m
+            "#;
+
+/// Keeps recipes joined with `INGREDIENT_COVERAGE` that use at least one of the
+/// given ingredients and miss no more than `$3` (if not null) of the rest.
+const INGREDIENT_COVERAGE_MATCHES: &str =
+    "m.covered > 0 AND ($3::bigint IS NULL OR m.required - m.covered <= $3)";
+
+/// Pushes the `i_recipe_group` CTE upserting the groups of a recipe being
+/// written by `i_recipe`, optionally pruning groups no longer on it. Groups
+/// are matched by name, so items can move between them freely.
+fn push_recipe_groups(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    recipe: &domain::Recipe,
+    prune: bool,
+) {
+    let names: Vec<String> = recipe.groups.iter().map(|g| g.name.clone()).collect();
+    let ordinals: Vec<i32> = recipe.groups.iter().map(|g| g.ordinal).collect();
+    query_builder
+        .push(
+            r#"
+            i_recipe_group AS (
+                INSERT INTO recipe_group (recipe, name, ordinal)
+                SELECT (SELECT id FROM i_recipe), g.name, g.ordinal
+                FROM unnest("#,
+        )
+        .push_bind(names)
+        .push("::text[], ")
+        .push_bind(ordinals)
+        .push(
+            r#"::integer[]) AS g(name, ordinal)
+                ON CONFLICT (recipe, name) DO UPDATE SET ordinal = EXCLUDED.ordinal
+                RETURNING *
+            ),
             "#,
+        );
+    if prune {
+        query_builder.push(
+            r#"
+            d_recipe_group AS (
+                DELETE FROM recipe_group g
+                USING i_recipe
+                WHERE g.recipe = i_recipe.id AND g.id NOT IN (SELECT id FROM i_recipe_group)
+            ),
+            "#,
+        );
+    }
+}
+
+/// Pushes the `i_tag` and `i_recipe_tag` CTEs upserting the tags of a recipe
+/// being written by `i_recipe`, optionally pruning tags no longer on it.
+fn push_recipe_tags(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    recipe: &domain::Recipe,
+    prune: bool,
+) {
+    let names: Vec<String> = recipe.tags.iter().map(|tag| tag.name.clone()).collect();
+    query_builder
+        .push(
+            r#"
+            i_ins_tag AS (
+                INSERT INTO tag (name) SELECT unnest("#,
+        )
+        .push_bind(names.clone())
+        .push(
+            r#"::text[])
+                ON CONFLICT (name) DO NOTHING RETURNING *
+            ),
+            i_tag AS (
+                SELECT * FROM i_ins_tag
+                UNION ALL
+                SELECT * FROM tag WHERE name = ANY("#,
         )
+        .push_bind(names)
+        .push(
+            r#")
+            ),
+            i_recipe_tag AS (
+                INSERT INTO recipe_tag (recipe, tag)
+                SELECT (SELECT id FROM i_recipe), id FROM i_tag
+                ON CONFLICT DO NOTHING
+                RETURNING *
+            )
+            "#,
+        );
+    if prune {
+        query_builder.push(
+            r#",
+            d_recipe_tag AS (
+                DELETE FROM recipe_tag rt
+                USING i_recipe
+                WHERE rt.recipe = i_recipe.id AND rt.tag NOT IN (SELECT id FROM i_tag)
+            )
+            "#,
+        );
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn push_recipe_filters(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    query: &domain::recipe::RecipeQuery,
+) {
+    query_builder.push(" WHERE TRUE");
+    query_builder
+        .push(" AND (")
+        .push(domain::recipe::SQL_LISTED)
+        .push(" OR r.author = ")
+        .push_bind(query.viewer_id)
+        .push(")");
+    if let Some(author_id) = query.author_id {
+        query_builder.push(" AND r.author = ").push_bind(author_id);
+    }
+    if let Some(title) = &query.title {
+        query_builder
+            .push(" AND r.title ILIKE ")
+            .push_bind(format!("%{}%", escape_like(title)));
+    }
+    if let Some(max_total_time) = query.max_total_time {
+        query_builder
+            .push(" AND ")
+            .push(TOTAL_TIME)
+            .push(" <= ")
+            .push_bind(max_total_time);
+    }
+    if let Some(yield_units) = &query.yield_units {
+        query_builder
+            .push(" AND ru.name = ")
+            .push_bind(yield_units.clone());
+    }
+    for tag in &query.tags {
+        query_builder
+            .push(
+                " AND EXISTS (SELECT 1 FROM recipe_tag rt JOIN tag t ON t.id = rt.tag \
+                WHERE rt.recipe = r.id AND t.name = ",
+            )
+            .push_bind(tag.clone())
+            .push(")");
+    }
+}
+
+pub struct PostgresRecipeRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresRecipeRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresRecipeRepository {
+        PostgresRecipeRepository { db_pool }
+    }
+}
+
+#[async_trait]
+impl port::RecipeRepository for PostgresRecipeRepository {
+    async fn get_recipes(
+        &self,
+        query: domain::recipe::RecipeQuery,
+    ) -> Result<domain::recipe::RecipePage, domain::recipe::Error> {
+        let mut count_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            SELECT COUNT(*)
+            FROM
+                recipe AS r
+                JOIN unit ru ON r.yield_units = ru.id
+            "#,
+        );
+        push_recipe_filters(&mut count_builder, &query);
+        let total_count: i64 = count_builder
+            .build_query_scalar()
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to count recipes for {:?} due to: {}", query, e);
+                domain::recipe::Error::Unexpected
+            })?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("SELECT ");
+        query_builder
+            .push(RECIPE_COLUMNS)
+            .push(" FROM ")
+            .push(RECIPE_TABLES);
+        push_recipe_filters(&mut query_builder, &query);
+        match query.sort {
+            Some(domain::recipe::RecipeSort::Title) => {
+                query_builder.push(" ORDER BY r.title, r.id");
+            }
+            Some(domain::recipe::RecipeSort::Newest) => {
+                query_builder.push(" ORDER BY r.id DESC");
+            }
+            Some(domain::recipe::RecipeSort::TotalTime) => {
+                query_builder
+                    .push(" ORDER BY ")
+                    .push(TOTAL_TIME)
+                    .push(", r.id");
+            }
+            Some(domain::recipe::RecipeSort::Rating) => {
+                query_builder
+                    .push(" ORDER BY review_rating DESC NULLS LAST, review_count DESC, r.id");
+            }
+            None => {
+                query_builder.push(" ORDER BY r.id");
+            }
+        }
+        query_builder
+            .push(" LIMIT ")
+            .push_bind(query.limit)
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+
+        let recipes = query_builder
+            .build_query_as()
             .fetch_all(&self.db_pool)
             .await
             .map_err(|e| {
-                log::error!("Failed to find recipes due to: {}", e);
+                log::error!("Failed to find recipes for {:?} due to: {}", query, e);
                 domain::recipe::Error::Unexpected
-            })
+            })?;
+
+        Ok(domain::recipe::RecipePage {
+            recipes,
+            page: query.page,
+            limit: query.limit,
+            total_count,
+        })
     }
 
     async fn get_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error> {
-        sqlx::query_as(
+        sqlx::query_as(&format!(
+            "SELECT {} FROM {} WHERE r.id = $1",
+            RECIPE_COLUMNS, RECIPE_TABLES
+        ))
+        .bind(id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => domain::recipe::Error::RecipeNotFound(id),
+            _ => {
+                log::error!("Failed to find recipe by id `{}` due to: {}", id, e);
+                domain::recipe::Error::Unexpected
+            }
+        })
+    }
+
+    async fn search_recipes(
+        &self,
+        query: domain::recipe::RecipeSearchQuery,
+    ) -> Result<domain::recipe::RecipeSearchPage, domain::recipe::Error> {
+        let total_count: i64 = sqlx::query_scalar(&format!(
             r#"
+            WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query)
+            SELECT COUNT(*) FROM recipe AS r CROSS JOIN q {}
+            WHERE d.document @@ q.query AND {}
+            "#,
+            SEARCH_DOCUMENT,
+            domain::recipe::listed_to_sql("$2")
+        ))
+        .bind(&query.text)
+        .bind(query.viewer_id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to count recipes matching {:?} due to: {}", query, e);
+            domain::recipe::Error::Unexpected
+        })?;
+
+        let results = sqlx::query_as(&format!(
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query)
             SELECT
-                r.id as id,
-                r.title as title,
-                r.description as description,
-                (au.id, au.name)::t_app_user as author,
-                EXTRACT(EPOCH FROM r.prep_time)::bigint as prep_time,
-                EXTRACT(EPOCH FROM r.cook_time)::bigint as cook_time,
-                EXTRACT(EPOCH FROM r.inactive_time)::bigint as inactive_time,
-                r.yield_quantity as yield_quantity,
-                (ru.id, ru.name)::t_unit as yield_units,
-                array(SELECT (s.id, s.recipe, s.ordinal, s.instruction)::t_step FROM step s WHERE s.recipe = r.id) as steps,
+                {},
+                ts_rank(d.document, q.query) as rank,
                 array(
-                    SELECT
-                    (
-                        ri.id,
-                        ri.recipe,
-                        (i.id, i.name)::t_ingredient,
-                        ri.quantity,
-                        (riu.id, riu.name)::t_unit,
-                        ri.preparation
-                    )::t_recipe_ingredient
-                    FROM recipe_ingredient ri
-                    JOIN ingredient i ON i.id = ri.ingredient
-                    JOIN unit riu ON ri.units = riu.id
-                    WHERE ri.recipe = r.id
-                ) as ingredients
-            FROM
-                recipe AS r
-                JOIN app_user au ON r.author = au.id
-                JOIN unit ru ON r.yield_units = ru.id
-            WHERE r.id = $1;
+                    SELECT (h.field, h.snippet)::t_search_highlight FROM (
+                        SELECT 'title' AS field, ts_headline('english', r.title, q.query) AS snippet, 0 AS position
+                        UNION ALL
+                        SELECT 'description', ts_headline('english', r.description, q.query), 1
+                        UNION ALL
+                        SELECT 'ingredient', ts_headline('english', i.name, q.query), 2
+                        FROM recipe_ingredient ri
+                        JOIN ingredient i ON i.id = ri.ingredient
+                        WHERE ri.recipe = r.id
+                        UNION ALL
+                        SELECT 'step', ts_headline('english', s.instruction, q.query), 3 + s.ordinal
+                        FROM step s
+                        WHERE s.recipe = r.id
+                    ) h
+                    -- Fields holding any of the query's terms.
+                    WHERE h.snippet LIKE '%<b>%'
+                    ORDER BY h.position
+                ) as highlights
+            FROM {} CROSS JOIN q {}
+            WHERE d.document @@ q.query AND {}
+            ORDER BY rank DESC, r.id
+            LIMIT $2 OFFSET $3
             "#,
-        ).bind(id)
-            .fetch_one(&self.db_pool)
-            .await.map_err(|e| {
-                match e {
-                    sqlx::Error::RowNotFound => domain::recipe::Error::RecipeNotFound(id),
-                    _ => {
-                        log::error!("Failed to find recipe by id `{}` due to: {}", id, e);
-                        domain::recipe::Error::Unexpected
-                    }
-                }
-            })
+            RECIPE_COLUMNS,
+            RECIPE_TABLES,
+            SEARCH_DOCUMENT,
+            domain::recipe::listed_to_sql("$4")
+        ))
+        .bind(&query.text)
+        .bind(query.limit)
+        .bind(query.offset())
+        .bind(query.viewer_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to search recipes for {:?} due to: {}", query, e);
+            domain::recipe::Error::Unexpected
+        })?;
+
+        Ok(domain::recipe::RecipeSearchPage {
+            results,
+            page: query.page,
+            limit: query.limit,
+            total_count,
+        })
+    }
+
+    async fn get_recipes_by_ingredients(
+        &self,
+        query: domain::recipe::IngredientMatchQuery,
+    ) -> Result<domain::recipe::IngredientMatchPage, domain::recipe::Error> {
+        let total_count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM recipe AS r {} WHERE {} AND {}",
+            INGREDIENT_COVERAGE,
+            INGREDIENT_COVERAGE_MATCHES,
+            domain::recipe::listed_to_sql("$4")
+        ))
+        .bind(&query.ingredients)
+        .bind(&query.ignored)
+        .bind(query.max_missing)
+        .bind(query.viewer_id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to count recipes matching {:?} due to: {}", query, e);
+            domain::recipe::Error::Unexpected
+        })?;
+
+        let results = sqlx::query_as(&format!(
+            r#"
+            SELECT {}, m.covered as covered, m.required as required, m.missing as missing
+            FROM {} {}
+            WHERE {} AND {}
+            ORDER BY m.covered DESC, m.required - m.covered, r.id
+            LIMIT $4 OFFSET $5
+            "#,
+            RECIPE_COLUMNS,
+            RECIPE_TABLES,
+            INGREDIENT_COVERAGE,
+            INGREDIENT_COVERAGE_MATCHES,
+            domain::recipe::listed_to_sql("$6")
+        ))
+        .bind(&query.ingredients)
+        .bind(&query.ignored)
+        .bind(query.max_missing)
+        .bind(query.limit)
+        .bind(query.offset())
+        .bind(query.viewer_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find recipes matching {:?} due to: {}", query, e);
+            domain::recipe::Error::Unexpected
+        })?;
+
+        Ok(domain::recipe::IngredientMatchPage {
+            results,
+            page: query.page,
+            limit: query.limit,
+            total_count,
+        })
     }
 
     async fn create_recipe(
@@ -145,7 +562,10 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                     cook_time,
                     inactive_time,
                     yield_quantity,
-                    yield_units
+                    yield_units,
+                    visibility,
+                    draft,
+                    forked_from
                 )
                 VALUES (
             "#,
@@ -169,26 +589,33 @@ impl port::RecipeRepository for PostgresRecipeRepository {
             .push_bind(recipe.yield_quantity)
             .push("(SELECT id FROM unit WHERE name = ")
             .push_bind_unseparated(&recipe.yield_units.name)
-            .push_unseparated(")");
+            .push_unseparated(")")
+            .push_bind(recipe.visibility)
+            .push_bind(recipe.draft)
+            .push_bind(recipe.forked_from);
         query_builder.push(" ) RETURNING *),");
+        push_recipe_groups(&mut query_builder, &recipe, false);
 
         query_builder.push(
             r#"
             i_step AS (
-                INSERT INTO step (recipe, ordinal, instruction)
+                INSERT INTO step (recipe, ordinal, instruction, recipe_group)
             "#,
         );
         query_builder.push_values(recipe.steps.to_vec(), |mut b, step| {
             b.push("(SELECT id FROM i_recipe)")
                 .push_bind(step.ordinal)
-                .push_bind(step.instruction);
+                .push_bind(step.instruction)
+                .push("(SELECT id FROM i_recipe_group WHERE name = ")
+                .push_bind_unseparated(step.group)
+                .push_unseparated(")");
         });
         query_builder.push(" RETURNING *),");
 
         query_builder.push(
             r#"
             i_recipe_ingredient AS (
-                INSERT INTO recipe_ingredient (recipe, ingredient, quantity, units, preparation)
+                INSERT INTO recipe_ingredient (recipe, ingredient, quantity, units, preparation, recipe_group, sub_recipe)
                 VALUES
             "#,
         );
@@ -204,10 +631,16 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 .push_bind_unseparated(recipe_ingredient.units.name)
                 .push_unseparated(")")
                 .push_bind(recipe_ingredient.preparation)
+                .push("(SELECT id FROM i_recipe_group WHERE name = ")
+                .push_bind_unseparated(recipe_ingredient.group)
+                .push_unseparated(")")
+                .push_bind(recipe_ingredient.sub_recipe_id)
                 .push_unseparated(")");
         }
-        query_builder.push(" RETURNING *)");
-        query_builder.push(r#"
+        query_builder.push(" RETURNING *),");
+        push_recipe_tags(&mut query_builder, &recipe, false);
+        query_builder.push(
+            r#"
             SELECT
                 r.id as id,
                 r.title as title,
@@ -218,7 +651,11 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 EXTRACT(EPOCH FROM r.inactive_time)::bigint as inactive_time,
                 r.yield_quantity as yield_quantity,
                 (ru.id, ru.name)::t_unit as yield_units,
-                array(SELECT (s.id, s.recipe, s.ordinal, s.instruction)::t_step FROM i_step s) as steps,
+                array(
+                    SELECT (s.id, s.recipe, s.ordinal, s.instruction, sg.name, s.image)::t_step
+                    FROM i_step s
+                    LEFT JOIN i_recipe_group sg ON sg.id = s.recipe_group
+                ) as steps,
                 array(
                     SELECT
                     (
@@ -227,26 +664,55 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                         (i.id, i.name)::t_ingredient,
                         ri.quantity,
                         (riu.id, riu.name)::t_unit,
-                        ri.preparation
+                        ri.preparation,
+                        rig.name,
+                        ri.sub_recipe
                     )::t_recipe_ingredient
                     FROM i_recipe_ingredient ri
                     JOIN i_ingredient i ON i.id = ri.ingredient
                     JOIN unit riu ON ri.units = riu.id
-                ) as ingredients
+                    LEFT JOIN i_recipe_group rig ON rig.id = ri.recipe_group
+                ) as ingredients,
+                array(
+                    SELECT (g.id, g.name, g.ordinal)::t_recipe_group
+                    FROM i_recipe_group g
+                    ORDER BY g.ordinal
+                ) as groups,
+                array(SELECT (t.id, t.name)::t_tag FROM i_tag t ORDER BY t.name) as tags,
+                r.image as image,
+                r.visibility as visibility,
+                r.draft as draft,
+                r.forked_from as forked_from,
+                recipe_lineage(r.forked_from) as lineage,
+                (SELECT count(*) FROM cook_log c WHERE c.recipe = r.id) as times_cooked,
+                (SELECT max(c.cooked_on) FROM cook_log c WHERE c.recipe = r.id) as last_cooked,
+                (
+                    SELECT round(avg(c.rating), 2)::float8 FROM cook_log c WHERE c.recipe = r.id
+                ) as average_rating,
+                (SELECT count(*) FROM review v WHERE v.recipe = r.id) as review_count,
+                (
+                    SELECT round(avg(v.rating), 2)::float8 FROM review v WHERE v.recipe = r.id
+                ) as review_rating
             FROM
                 i_recipe AS r
                 JOIN app_user au ON r.author = au.id
                 JOIN unit ru ON r.yield_units = ru.id;
-            "#);
+            "#,
+        );
 
-        query_builder
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to create recipe {:?} due to: {}", recipe, e);
+            domain::recipe::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let created: domain::Recipe = query_builder
             .build_query_as()
-            .fetch_one(&self.db_pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| {
-                log::error!("Failed to create recipe {:?} due to: {}", recipe, e);
-                domain::recipe::Error::Unexpected
-            })
+            .map_err(unexpected)?;
+        save_revision(&mut tx, &created).await?;
+        tx.commit().await.map_err(unexpected)?;
+        Ok(created)
     }
 
     async fn update_recipe(
@@ -290,7 +756,9 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                     cook_time,
                     inactive_time,
                     yield_quantity,
-                    yield_units
+                    yield_units,
+                    visibility,
+                    draft
                 )
                 VALUES (
             "#,
@@ -315,7 +783,9 @@ impl port::RecipeRepository for PostgresRecipeRepository {
             .push_bind(recipe.yield_quantity)
             .push("(SELECT id FROM unit WHERE name = ")
             .push_bind_unseparated(&recipe.yield_units.name)
-            .push_unseparated(")");
+            .push_unseparated(")")
+            .push_bind(recipe.visibility)
+            .push_bind(recipe.draft);
         query_builder.push(
             r#"
             ) ON CONFLICT (id) DO UPDATE SET
@@ -326,15 +796,18 @@ impl port::RecipeRepository for PostgresRecipeRepository {
             cook_time = EXCLUDED.cook_time,
             inactive_time = EXCLUDED.inactive_time,
             yield_quantity = EXCLUDED.yield_quantity,
-            yield_units = EXCLUDED.yield_units
+            yield_units = EXCLUDED.yield_units,
+            visibility = EXCLUDED.visibility,
+            draft = EXCLUDED.draft
             RETURNING *),
             "#,
         );
+        push_recipe_groups(&mut query_builder, &recipe, true);
 
         query_builder.push(
             r#"
             i_step AS (
-                INSERT INTO step (id, recipe, ordinal, instruction)
+                INSERT INTO step (id, recipe, ordinal, instruction, recipe_group)
             "#,
         );
         query_builder.push_values(&recipe.steps, |mut b, step| {
@@ -345,14 +818,18 @@ impl port::RecipeRepository for PostgresRecipeRepository {
             }
             b.push("(SELECT id FROM i_recipe)")
                 .push_bind(step.ordinal)
-                .push_bind(&step.instruction);
+                .push_bind(&step.instruction)
+                .push("(SELECT id FROM i_recipe_group WHERE name = ")
+                .push_bind_unseparated(&step.group)
+                .push_unseparated(")");
         });
         query_builder.push(
             r#"
              ON CONFLICT (id) DO UPDATE SET
             recipe = EXCLUDED.recipe,
             ordinal = EXCLUDED.ordinal,
-            instruction = EXCLUDED.instruction
+            instruction = EXCLUDED.instruction,
+            recipe_group = EXCLUDED.recipe_group
             RETURNING *),
             d_step AS (
                 DELETE FROM step s USING i_recipe WHERE recipe = i_recipe.id AND s.id NOT IN (SELECT id FROM i_step)
@@ -363,7 +840,7 @@ impl port::RecipeRepository for PostgresRecipeRepository {
         query_builder.push(
             r#"
             i_recipe_ingredient AS (
-                INSERT INTO recipe_ingredient (id, recipe, ingredient, quantity, units, preparation)
+                INSERT INTO recipe_ingredient (id, recipe, ingredient, quantity, units, preparation, recipe_group, sub_recipe)
                 VALUES
             "#,
         );
@@ -384,6 +861,10 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 .push_bind_unseparated(&recipe_ingredient.units.name)
                 .push_unseparated(")")
                 .push_bind(&recipe_ingredient.preparation)
+                .push("(SELECT id FROM i_recipe_group WHERE name = ")
+                .push_bind_unseparated(&recipe_ingredient.group)
+                .push_unseparated(")")
+                .push_bind(recipe_ingredient.sub_recipe_id)
                 .push_unseparated(")");
         }
         query_builder.push(
@@ -393,7 +874,9 @@ impl port::RecipeRepository for PostgresRecipeRepository {
             ingredient = EXCLUDED.ingredient,
             quantity = EXCLUDED.quantity,
             units = EXCLUDED.units,
-            preparation = EXCLUDED.preparation
+            preparation = EXCLUDED.preparation,
+            recipe_group = EXCLUDED.recipe_group,
+            sub_recipe = EXCLUDED.sub_recipe
             RETURNING *),
             d_recipe_ingredient AS (
                 DELETE FROM recipe_ingredient ri
@@ -401,10 +884,12 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 WHERE recipe = i_recipe.id AND ri.id NOT IN (
                     SELECT id FROM i_recipe_ingredient
                 )
-            )
+            ),
             "#,
         );
-        query_builder.push(r#"
+        push_recipe_tags(&mut query_builder, &recipe, true);
+        query_builder.push(
+            r#"
             SELECT
                 r.id as id,
                 r.title as title,
@@ -415,7 +900,11 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                 EXTRACT(EPOCH FROM r.inactive_time)::bigint as inactive_time,
                 r.yield_quantity as yield_quantity,
                 (ru.id, ru.name)::t_unit as yield_units,
-                array(SELECT (s.id, s.recipe, s.ordinal, s.instruction)::t_step FROM i_step s) as steps,
+                array(
+                    SELECT (s.id, s.recipe, s.ordinal, s.instruction, sg.name, s.image)::t_step
+                    FROM i_step s
+                    LEFT JOIN i_recipe_group sg ON sg.id = s.recipe_group
+                ) as steps,
                 array(
                     SELECT
                     (
@@ -424,26 +913,86 @@ impl port::RecipeRepository for PostgresRecipeRepository {
                         (i.id, i.name)::t_ingredient,
                         ri.quantity,
                         (riu.id, riu.name)::t_unit,
-                        ri.preparation
+                        ri.preparation,
+                        rig.name,
+                        ri.sub_recipe
                     )::t_recipe_ingredient
                     FROM i_recipe_ingredient ri
                     JOIN i_ingredient i ON i.id = ri.ingredient
                     JOIN unit riu ON ri.units = riu.id
-                ) as ingredients
+                    LEFT JOIN i_recipe_group rig ON rig.id = ri.recipe_group
+                ) as ingredients,
+                array(
+                    SELECT (g.id, g.name, g.ordinal)::t_recipe_group
+                    FROM i_recipe_group g
+                    ORDER BY g.ordinal
+                ) as groups,
+                array(SELECT (t.id, t.name)::t_tag FROM i_tag t ORDER BY t.name) as tags,
+                r.image as image,
+                r.visibility as visibility,
+                r.draft as draft,
+                r.forked_from as forked_from,
+                recipe_lineage(r.forked_from) as lineage,
+                (SELECT count(*) FROM cook_log c WHERE c.recipe = r.id) as times_cooked,
+                (SELECT max(c.cooked_on) FROM cook_log c WHERE c.recipe = r.id) as last_cooked,
+                (
+                    SELECT round(avg(c.rating), 2)::float8 FROM cook_log c WHERE c.recipe = r.id
+                ) as average_rating,
+                (SELECT count(*) FROM review v WHERE v.recipe = r.id) as review_count,
+                (
+                    SELECT round(avg(v.rating), 2)::float8 FROM review v WHERE v.recipe = r.id
+                ) as review_rating
             FROM
                 i_recipe AS r
                 JOIN app_user au ON r.author = au.id
                 JOIN unit ru ON r.yield_units = ru.id;
-            "#);
+            "#,
+        );
 
-        query_builder
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to update recipe {:?} due to: {}", recipe, e);
+            domain::recipe::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        // Holding the recipe row until commit keeps concurrent updates from
+        // taking the same revision number.
+        sqlx::query("SELECT id FROM recipe WHERE id = $1 FOR UPDATE")
+            .bind(recipe.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(unexpected)?;
+        // Recipes saved before revisions were kept get their current
+        // contents recorded first, so the update can be undone.
+        let has_revisions: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM recipe_revision WHERE recipe = $1)")
+                .bind(recipe.id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(unexpected)?;
+        if !has_revisions {
+            if let Some(id) = recipe.id {
+                let previous: domain::Recipe = sqlx::query_as(&format!(
+                    "SELECT {} FROM {} WHERE r.id = $1",
+                    RECIPE_COLUMNS, RECIPE_TABLES
+                ))
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::RowNotFound => domain::recipe::Error::RecipeNotFound(id),
+                    _ => unexpected(e),
+                })?;
+                save_revision(&mut tx, &previous).await?;
+            }
+        }
+        let updated: domain::Recipe = query_builder
             .build_query_as()
-            .fetch_one(&self.db_pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| {
-                log::error!("Failed to update recipe {:?} due to: {}", recipe, e);
-                domain::recipe::Error::Unexpected
-            })
+            .map_err(unexpected)?;
+        save_revision(&mut tx, &updated).await?;
+        tx.commit().await.map_err(unexpected)?;
+        Ok(updated)
     }
 
     async fn delete_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error> {
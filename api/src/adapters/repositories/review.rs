@@ -0,0 +1,168 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresReviewRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresReviewRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresReviewRepository {
+        PostgresReviewRepository { db_pool }
+    }
+}
+
+const SELECT_REVIEW: &str = r#"
+    SELECT
+        v.id as id,
+        v.recipe as recipe_id,
+        au.id as reviewer_id,
+        au.name as reviewer_name,
+        v.rating as rating,
+        v.body as body,
+        v.reply as reply,
+        v.created_at as created_at,
+        v.updated_at as updated_at,
+        v.replied_at as replied_at
+    FROM review v
+    JOIN app_user au ON au.id = v.reviewer
+    "#;
+
+#[async_trait]
+impl port::ReviewRepository for PostgresReviewRepository {
+    async fn get_reviews(
+        &self,
+        recipe_id: i32,
+    ) -> Result<Vec<domain::review::Review>, domain::review::Error> {
+        sqlx::query_as(&format!(
+            "{} WHERE v.recipe = $1 ORDER BY v.created_at DESC, v.id DESC",
+            SELECT_REVIEW
+        ))
+        .bind(recipe_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to find reviews of recipe `{}` due to: {}",
+                recipe_id,
+                e
+            );
+            domain::review::Error::Unexpected
+        })
+    }
+
+    async fn get_review(&self, id: i32) -> Result<domain::review::Review, domain::review::Error> {
+        sqlx::query_as(&format!("{} WHERE v.id = $1", SELECT_REVIEW))
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::review::Error::ReviewNotFound(id),
+                _ => {
+                    log::error!("Failed to find review `{}` due to: {}", id, e);
+                    domain::review::Error::Unexpected
+                }
+            })
+    }
+
+    async fn create_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO review (recipe, reviewer, rating, body)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(review.recipe_id)
+        .bind(review.reviewer.id)
+        .bind(review.rating)
+        .bind(&review.body)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            // Another request reviewed the recipe after the service checked.
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                domain::review::Error::AlreadyReviewed(review.recipe_id)
+            }
+            _ => {
+                log::error!("Failed to create review {:?} due to: {}", review, e);
+                domain::review::Error::Unexpected
+            }
+        })?;
+        self.get_review(id).await
+    }
+
+    async fn update_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let id = review.id.ok_or(domain::review::Error::Unexpected)?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE review SET
+                rating = $2,
+                body = $3,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(review.rating)
+        .bind(&review.body)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to update review {:?} due to: {}", review, e);
+            domain::review::Error::Unexpected
+        })?;
+        if updated.rows_affected() == 0 {
+            return Err(domain::review::Error::ReviewNotFound(id));
+        }
+        self.get_review(id).await
+    }
+
+    async fn update_reply(
+        &self,
+        id: i32,
+        reply: Option<String>,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE review SET
+                reply = $2,
+                replied_at = CASE WHEN $2 IS NULL THEN NULL ELSE now() END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(&reply)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to reply to review `{}` due to: {}", id, e);
+            domain::review::Error::Unexpected
+        })?;
+        if updated.rows_affected() == 0 {
+            return Err(domain::review::Error::ReviewNotFound(id));
+        }
+        self.get_review(id).await
+    }
+
+    async fn delete_review(
+        &self,
+        id: i32,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let review = self.get_review(id).await?;
+        sqlx::query("DELETE FROM review WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete review `{}` due to: {}", id, e);
+                domain::review::Error::Unexpected
+            })?;
+        Ok(review)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresRevisionRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresRevisionRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresRevisionRepository {
+        PostgresRevisionRepository { db_pool }
+    }
+}
+
+#[async_trait]
+impl port::RevisionRepository for PostgresRevisionRepository {
+    async fn get_revisions(
+        &self,
+        recipe_id: i32,
+    ) -> Result<Vec<domain::revision::RevisionSummary>, domain::revision::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT revision, created_at, snapshot->>'title' as title
+            FROM recipe_revision
+            WHERE recipe = $1
+            ORDER BY revision DESC
+            "#,
+        )
+        .bind(recipe_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!(
+                "Failed to find revisions of recipe `{}` due to: {}",
+                recipe_id,
+                e
+            );
+            domain::revision::Error::Unexpected
+        })
+    }
+
+    async fn get_revision(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+    ) -> Result<domain::revision::RecipeRevision, domain::revision::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT recipe as recipe_id, revision, created_at, snapshot::text as snapshot
+            FROM recipe_revision
+            WHERE recipe = $1 AND revision = $2
+            "#,
+        )
+        .bind(recipe_id)
+        .bind(revision)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                domain::revision::Error::RevisionNotFound(recipe_id, revision)
+            }
+            _ => {
+                log::error!(
+                    "Failed to find revision `{}` of recipe `{}` due to: {}",
+                    revision,
+                    recipe_id,
+                    e
+                );
+                domain::revision::Error::Unexpected
+            }
+        })
+    }
+}
@@ -0,0 +1,289 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use sqlx::{PgConnection, Postgres, Row, Transaction};
+
+pub struct PostgresShoppingListRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresShoppingListRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresShoppingListRepository {
+        PostgresShoppingListRepository { db_pool }
+    }
+
+    /// Loads the items and recipes of the list with the given id.
+    async fn load(
+        conn: &mut PgConnection,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT sl.id as id, sl.name as name, au.id as owner_id, au.name as owner_name
+            FROM shopping_list sl
+            JOIN app_user au ON au.id = sl.owner
+            WHERE sl.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+        let items = sqlx::query_as(
+            r#"
+            SELECT
+                si.id as id,
+                i.id as ingredient_id,
+                i.name as ingredient_name,
+                si.quantity as quantity,
+                u.id as units_id,
+                u.name as units_name,
+                si.checked as checked,
+                si.ordinal as ordinal
+            FROM shopping_list_item si
+            JOIN ingredient i ON i.id = si.ingredient
+            LEFT JOIN unit u ON u.id = si.units
+            WHERE si.shopping_list = $1
+            ORDER BY si.ordinal, si.id
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+        let recipes = sqlx::query_as(
+            r#"
+            SELECT sr.id as id, r.id as recipe_id, r.title as title, sr.scale as scale
+            FROM shopping_list_recipe sr
+            JOIN recipe r ON r.id = sr.recipe
+            WHERE sr.shopping_list = $1
+            ORDER BY sr.id
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(domain::shopping_list::ShoppingList {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            owner: domain::User {
+                id: row.try_get("owner_id")?,
+                name: row.try_get("owner_name")?,
+            },
+            items,
+            recipes,
+        })
+    }
+
+    async fn get(
+        &self,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let load = async {
+            let mut conn = self.db_pool.acquire().await?;
+            Self::load(&mut conn, id).await
+        };
+        load.await.map_err(|e| match e {
+            sqlx::Error::RowNotFound => domain::shopping_list::Error::ShoppingListNotFound(id),
+            _ => {
+                log::error!("Failed to find shopping list `{}` due to: {}", id, e);
+                domain::shopping_list::Error::Unexpected
+            }
+        })
+    }
+
+    /// Writes the list's items and any newly added recipes, removing the
+    /// items that are no longer on it.
+    async fn save_contents(
+        tx: &mut Transaction<'_, Postgres>,
+        shopping_list: &domain::shopping_list::ShoppingList,
+        id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let kept: Vec<i32> = shopping_list.items.iter().filter_map(|x| x.id).collect();
+        sqlx::query("DELETE FROM shopping_list_item WHERE shopping_list = $1 AND id <> ALL($2)")
+            .bind(id)
+            .bind(&kept)
+            .execute(&mut **tx)
+            .await?;
+        for item in &shopping_list.items {
+            let ingredient_id: i32 = match item.ingredient.id {
+                Some(ingredient_id) => ingredient_id,
+                None => {
+                    sqlx::query_scalar(
+                        r#"
+                        INSERT INTO ingredient (name) VALUES ($1)
+                        ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+                        RETURNING id
+                        "#,
+                    )
+                    .bind(&item.ingredient.name)
+                    .fetch_one(&mut **tx)
+                    .await?
+                }
+            };
+            let units = item.units.as_ref().map(|x| &x.name);
+            match item.id {
+                Some(item_id) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE shopping_list_item SET
+                            ingredient = $3,
+                            quantity = $4,
+                            units = (SELECT id FROM unit WHERE name = $5),
+                            checked = $6,
+                            ordinal = $7
+                        WHERE id = $1 AND shopping_list = $2
+                        "#,
+                    )
+                    .bind(item_id)
+                    .bind(id)
+                    .bind(ingredient_id)
+                    .bind(item.quantity)
+                    .bind(units)
+                    .bind(item.checked)
+                    .bind(item.ordinal)
+                    .execute(&mut **tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO shopping_list_item
+                            (shopping_list, ingredient, quantity, units, checked, ordinal)
+                        VALUES ($1, $2, $3, (SELECT id FROM unit WHERE name = $4), $5, $6)
+                        "#,
+                    )
+                    .bind(id)
+                    .bind(ingredient_id)
+                    .bind(item.quantity)
+                    .bind(units)
+                    .bind(item.checked)
+                    .bind(item.ordinal)
+                    .execute(&mut **tx)
+                    .await?;
+                }
+            }
+        }
+        for recipe in shopping_list.recipes.iter().filter(|x| x.id.is_none()) {
+            sqlx::query(
+                "INSERT INTO shopping_list_recipe (shopping_list, recipe, scale) VALUES ($1, $2, $3)",
+            )
+            .bind(id)
+            .bind(recipe.recipe_id)
+            .bind(recipe.scale)
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl port::ShoppingListRepository for PostgresShoppingListRepository {
+    async fn get_shopping_lists(
+        &self,
+        owner_id: i32,
+    ) -> Result<Vec<domain::shopping_list::ShoppingList>, domain::shopping_list::Error> {
+        let ids: Vec<i32> =
+            sqlx::query_scalar("SELECT id FROM shopping_list WHERE owner = $1 ORDER BY name, id")
+                .bind(owner_id)
+                .fetch_all(&self.db_pool)
+                .await
+                .map_err(|e| {
+                    log::error!(
+                        "Failed to find shopping lists of user `{}` due to: {}",
+                        owner_id,
+                        e
+                    );
+                    domain::shopping_list::Error::Unexpected
+                })?;
+        let mut shopping_lists = Vec::with_capacity(ids.len());
+        for id in ids {
+            shopping_lists.push(self.get(id).await?);
+        }
+        Ok(shopping_lists)
+    }
+
+    async fn get_shopping_list(
+        &self,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.get(id).await
+    }
+
+    async fn create_shopping_list(
+        &self,
+        shopping_list: domain::shopping_list::ShoppingList,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!(
+                "Failed to create shopping list {:?} due to: {}",
+                shopping_list,
+                e
+            );
+            domain::shopping_list::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO shopping_list (owner, name) VALUES ($1, $2) RETURNING id",
+        )
+        .bind(shopping_list.owner.id)
+        .bind(&shopping_list.name)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(unexpected)?;
+        Self::save_contents(&mut tx, &shopping_list, id)
+            .await
+            .map_err(unexpected)?;
+        tx.commit().await.map_err(unexpected)?;
+        self.get(id).await
+    }
+
+    async fn modify_shopping_list(
+        &self,
+        id: i32,
+        modify: port::shopping_list::ShoppingListChange,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to update shopping list `{}` due to: {}", id, e);
+            domain::shopping_list::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+        // Holding the list row until commit makes concurrent changes wait for
+        // this one, rather than saving over it from a stale copy.
+        sqlx::query("SELECT id FROM shopping_list WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::shopping_list::Error::ShoppingListNotFound(id),
+                _ => unexpected(e),
+            })?;
+        let mut shopping_list = Self::load(&mut tx, id).await.map_err(unexpected)?;
+        modify(&mut shopping_list)?;
+        sqlx::query("UPDATE shopping_list SET name = $2 WHERE id = $1")
+            .bind(id)
+            .bind(&shopping_list.name)
+            .execute(&mut *tx)
+            .await
+            .map_err(unexpected)?;
+        Self::save_contents(&mut tx, &shopping_list, id)
+            .await
+            .map_err(unexpected)?;
+        tx.commit().await.map_err(unexpected)?;
+        self.get(id).await
+    }
+
+    async fn delete_shopping_list(
+        &self,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let shopping_list = self.get(id).await?;
+        sqlx::query("DELETE FROM shopping_list WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete shopping list `{}` due to: {}", id, e);
+                domain::shopping_list::Error::Unexpected
+            })?;
+        Ok(shopping_list)
+    }
+}
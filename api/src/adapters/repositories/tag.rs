@@ -0,0 +1,143 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresTagRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresTagRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresTagRepository {
+        PostgresTagRepository { db_pool }
+    }
+}
+
+const SELECT_TAG_USAGE: &str = r#"
+    SELECT t.id as id, t.name as name, COUNT(rt.recipe) as recipe_count
+    FROM tag t
+    LEFT JOIN recipe_tag rt ON rt.tag = t.id
+    "#;
+
+#[async_trait]
+impl port::TagRepository for PostgresTagRepository {
+    async fn get_tags(
+        &self,
+        viewer_id: Option<i32>,
+    ) -> Result<Vec<domain::tag::TagUsage>, domain::tag::Error> {
+        let listed = domain::recipe::listed_to_sql("$1");
+        sqlx::query_as(&format!(
+            r#"
+            SELECT
+                t.id as id,
+                t.name as name,
+                COUNT(r.id) FILTER (WHERE {listed}) as recipe_count
+            FROM tag t
+            LEFT JOIN recipe_tag rt ON rt.tag = t.id
+            LEFT JOIN recipe r ON r.id = rt.recipe
+            GROUP BY t.id
+            HAVING COUNT(r.id) FILTER (WHERE {listed}) > 0 OR COUNT(r.id) = 0
+            ORDER BY t.name
+            "#,
+            listed = listed
+        ))
+        .bind(viewer_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find tags due to: {}", e);
+            domain::tag::Error::Unexpected
+        })
+    }
+
+    async fn rename_tag(
+        &self,
+        id: i32,
+        name: String,
+        owner_id: i32,
+    ) -> Result<domain::tag::TagUsage, domain::tag::Error> {
+        let unexpected = |e: sqlx::Error| {
+            log::error!("Failed to rename tag `{}` to `{}` due to: {}", id, name, e);
+            domain::tag::Error::Unexpected
+        };
+        let mut tx = self.db_pool.begin().await.map_err(unexpected)?;
+
+        sqlx::query("SELECT id FROM tag WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => domain::tag::Error::TagNotFound(id),
+                _ => unexpected(e),
+            })?;
+
+        let existing: Option<i32> =
+            sqlx::query_scalar("SELECT id FROM tag WHERE name = $1 AND id <> $2")
+                .bind(&name)
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(unexpected)?;
+
+        let shared: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM recipe_tag rt
+                JOIN recipe r ON r.id = rt.recipe
+                WHERE rt.tag = ANY($1) AND r.author <> $2
+            )
+            "#,
+        )
+        .bind(existing.into_iter().chain([id]).collect::<Vec<i32>>())
+        .bind(owner_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(unexpected)?;
+        if shared {
+            return Err(domain::tag::Error::NotOwner(id));
+        }
+
+        let renamed_id = match existing {
+            Some(target) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO recipe_tag (recipe, tag)
+                    SELECT recipe, $2 FROM recipe_tag WHERE tag = $1
+                    ON CONFLICT DO NOTHING
+                    "#,
+                )
+                .bind(id)
+                .bind(target)
+                .execute(&mut *tx)
+                .await
+                .map_err(unexpected)?;
+                sqlx::query("DELETE FROM tag WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(unexpected)?;
+                target
+            }
+            None => {
+                sqlx::query("UPDATE tag SET name = $2 WHERE id = $1")
+                    .bind(id)
+                    .bind(&name)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(unexpected)?;
+                id
+            }
+        };
+
+        let tag_usage = sqlx::query_as(&format!(
+            "{} WHERE t.id = $1 GROUP BY t.id",
+            SELECT_TAG_USAGE
+        ))
+        .bind(renamed_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(unexpected)?;
+
+        tx.commit().await.map_err(unexpected)?;
+        Ok(tag_usage)
+    }
+}
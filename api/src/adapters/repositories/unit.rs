@@ -0,0 +1,31 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct PostgresUnitRepository {
+    db_pool: sqlx::postgres::PgPool,
+}
+
+impl PostgresUnitRepository {
+    pub fn new(db_pool: sqlx::postgres::PgPool) -> PostgresUnitRepository {
+        PostgresUnitRepository { db_pool }
+    }
+}
+
+#[async_trait]
+impl port::UnitRepository for PostgresUnitRepository {
+    async fn get_units(&self) -> Result<Vec<domain::unit::UnitDefinition>, domain::unit::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, name, abbreviation, dimension, system, to_base_factor, to_base_offset
+            FROM unit
+            ORDER BY dimension, to_base_factor, name
+            "#,
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to find units due to: {}", e);
+            domain::unit::Error::Unexpected
+        })
+    }
+}
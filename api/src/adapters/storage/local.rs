@@ -0,0 +1,82 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use async_trait::async_trait;
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::core::{domain, port};
+
+/// Keeps media as files beneath a directory, one file per key.
+pub struct LocalMediaStore {
+    root: PathBuf,
+}
+
+impl LocalMediaStore {
+    pub fn new(root: impl Into<PathBuf>) -> LocalMediaStore {
+        LocalMediaStore { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, domain::media::Error> {
+        domain::media::validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+fn unexpected(action: &str, key: &str, e: std::io::Error) -> domain::media::Error {
+    log::error!("Failed to {} media `{}` due to: {}", action, key, e);
+    domain::media::Error::Unexpected
+}
+
+#[async_trait]
+impl port::MediaStore for LocalMediaStore {
+    async fn put(
+        &self,
+        key: &str,
+        media: domain::media::Media,
+    ) -> Result<(), domain::media::Error> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| unexpected("store", key, e))?;
+        }
+        // Write beside the destination and rename, so readers never see a
+        // partially written file.
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        let partial = path.with_extension(format!("{}.partial", suffix));
+        tokio::fs::write(&partial, &media.bytes)
+            .await
+            .map_err(|e| unexpected("store", key, e))?;
+        if let Err(e) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(unexpected("store", key, e));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<domain::media::Media, domain::media::Error> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(domain::media::Media {
+                content_type: domain::media::content_type_for_key(key).to_owned(),
+                bytes,
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(domain::media::Error::MediaNotFound(key.to_owned()))
+            }
+            Err(e) => Err(unexpected("read", key, e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), domain::media::Error> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(unexpected("delete", key, e)),
+        }
+    }
+}
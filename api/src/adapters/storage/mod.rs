@@ -0,0 +1,4 @@
+mod local;
+pub use local::LocalMediaStore;
+mod s3;
+pub use s3::{S3Config, S3MediaStore};
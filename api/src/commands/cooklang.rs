@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use super::DatabaseOptions;
+
+#[derive(Parser)]
+pub struct RootCommand {
+    #[clap(flatten)]
+    pub db: DatabaseOptions,
+
+    #[clap(subcommand)]
+    pub action: Action,
+}
+
+#[derive(Parser)]
+pub enum Action {
+    /// Create a recipe from a Cooklang file
+    Import {
+        #[clap(value_parser, value_name = "FILE")]
+        file: PathBuf,
+
+        /// The user the recipe is created for
+        #[clap(long, value_parser, value_name = "USERNAME")]
+        username: String,
+
+        /// Names the recipe when the file has no `title` metadata
+        #[clap(long, value_parser, value_name = "TITLE")]
+        title: Option<String>,
+    },
+    /// Write a recipe as Cooklang
+    Export {
+        #[clap(value_parser, value_name = "RECIPE_ID")]
+        id: i32,
+
+        /// Where to write the recipe, instead of standard output
+        #[clap(long, short, value_parser, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
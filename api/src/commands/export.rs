@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use super::{DatabaseOptions, MediaOptions};
+
+/// The formats a recipe can be exported in.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// Markdown, for wikis and chat.
+    Markdown,
+    /// A Cooklang document.
+    Cooklang,
+    /// A standalone, printable HTML page.
+    Html,
+    /// schema.org JSON-LD.
+    JsonLd,
+}
+
+#[derive(Parser)]
+pub struct RootCommand {
+    #[clap(flatten)]
+    pub db: DatabaseOptions,
+
+    #[clap(flatten)]
+    pub media: MediaOptions,
+
+    #[clap(
+        value_parser,
+        value_name = "RECIPE_ID",
+        required_unless_present = "username"
+    )]
+    pub id: Option<i32>,
+
+    /// Export every recipe this user owns as a zip archive instead
+    #[clap(long, value_parser, value_name = "USERNAME", conflicts_with = "id")]
+    pub username: Option<String>,
+
+    #[clap(long, short, value_enum, default_value = "markdown")]
+    pub format: ExportFormat,
+
+    /// Where to write the recipe, instead of standard output
+    #[clap(long, short, value_parser, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use super::{DatabaseOptions, MediaOptions};
+
+/// What to do with an archived recipe whose title the user already has.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OnConflict {
+    /// Keep the existing recipe.
+    Skip,
+    /// Import the archived recipe under a numbered title.
+    Rename,
+    /// Replace the existing recipe with the archived one.
+    Overwrite,
+}
+
+#[derive(Parser)]
+pub struct RootCommand {
+    #[clap(flatten)]
+    pub db: DatabaseOptions,
+
+    #[clap(flatten)]
+    pub media: MediaOptions,
+
+    /// An archive written by `stockpot export --username`
+    #[clap(value_parser, value_name = "ARCHIVE")]
+    pub archive: PathBuf,
+
+    /// The user the recipes are created for
+    #[clap(long, value_parser, value_name = "USERNAME")]
+    pub username: String,
+
+    #[clap(long, value_enum, default_value = "skip")]
+    pub on_conflict: OnConflict,
+}
@@ -1,5 +1,10 @@
+use std::path::PathBuf;
+
 use clap::{AppSettings, Parser};
 
+pub mod cooklang;
+pub mod export;
+pub mod import;
 pub mod server;
 
 #[derive(Parser)]
@@ -17,4 +22,105 @@ pub struct RootCommand {
 #[derive(Parser)]
 pub enum SubCommand {
     Server(server::RootCommand),
+    /// Import and export recipes as Cooklang
+    Cooklang(cooklang::RootCommand),
+    /// Write a recipe as Markdown, Cooklang, HTML or JSON-LD, or a user's
+    /// whole library as an archive
+    Export(export::RootCommand),
+    /// Recreate the recipes of a library archive for a user
+    Import(import::RootCommand),
+}
+
+/// Database connection settings for commands that work on the database
+/// directly rather than through the server.
+#[derive(Parser)]
+pub struct DatabaseOptions {
+    #[clap(
+        long,
+        value_parser,
+        default_value = "localhost",
+        env = "DB_HOST",
+        value_name = "HOST"
+    )]
+    pub db_host: String,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "5432",
+        env = "DB_PORT",
+        value_name = "PORT"
+    )]
+    pub db_port: u16,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "postgres",
+        env = "DB_USERNAME",
+        value_name = "USERNAME"
+    )]
+    pub db_username: String,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "postgres",
+        env = "DB_PASSWORD",
+        value_name = "PASSWORD"
+    )]
+    pub db_password: String,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "stockpot",
+        env = "DB_DATABASE",
+        value_name = "DATABASE"
+    )]
+    pub db_database: String,
+}
+
+/// Media store settings for commands that read or write uploaded images
+/// directly rather than through the server.
+#[derive(Parser)]
+pub struct MediaOptions {
+    #[clap(
+        long,
+        value_enum,
+        default_value = "local",
+        env = "MEDIA_STORE",
+        value_name = "STORE"
+    )]
+    pub media_store: server::MediaStoreKind,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "media",
+        env = "MEDIA_DIR",
+        value_name = "DIRECTORY"
+    )]
+    pub media_dir: PathBuf,
+
+    #[clap(long, value_parser, env = "S3_ENDPOINT", value_name = "URL")]
+    pub s3_endpoint: Option<String>,
+
+    #[clap(long, value_parser, env = "S3_BUCKET", value_name = "BUCKET")]
+    pub s3_bucket: Option<String>,
+
+    #[clap(
+        long,
+        value_parser,
+        default_value = "us-east-1",
+        env = "S3_REGION",
+        value_name = "REGION"
+    )]
+    pub s3_region: String,
+
+    #[clap(long, value_parser, env = "S3_ACCESS_KEY", value_name = "ACCESS_KEY")]
+    pub s3_access_key: Option<String>,
+
+    #[clap(long, value_parser, env = "S3_SECRET_KEY", value_name = "SECRET_KEY")]
+    pub s3_secret_key: Option<String>,
 }
@@ -1,6 +1,15 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+/// Where uploaded media is kept.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum MediaStoreKind {
+    /// Files beneath `MEDIA_DIR`.
+    Local,
+    /// Objects in an S3-compatible bucket.
+    S3,
+}
 
 #[derive(Parser)]
 pub struct RootCommand {
@@ -59,4 +68,40 @@ pub struct RootCommand {
         value_name = "SECRET"
     )]
     pub jwt_token_secret: String,
+
+    #[clap(
+        value_enum,
+        default_value = "local",
+        env = "MEDIA_STORE",
+        value_name = "STORE"
+    )]
+    pub media_store: MediaStoreKind,
+
+    #[clap(
+        value_parser,
+        default_value = "media",
+        env = "MEDIA_DIR",
+        value_name = "DIRECTORY"
+    )]
+    pub media_dir: PathBuf,
+
+    #[clap(value_parser, env = "S3_ENDPOINT", value_name = "URL")]
+    pub s3_endpoint: Option<String>,
+
+    #[clap(value_parser, env = "S3_BUCKET", value_name = "BUCKET")]
+    pub s3_bucket: Option<String>,
+
+    #[clap(
+        value_parser,
+        default_value = "us-east-1",
+        env = "S3_REGION",
+        value_name = "REGION"
+    )]
+    pub s3_region: String,
+
+    #[clap(value_parser, env = "S3_ACCESS_KEY", value_name = "ACCESS_KEY")]
+    pub s3_access_key: Option<String>,
+
+    #[clap(value_parser, env = "S3_SECRET_KEY", value_name = "SECRET_KEY")]
+    pub s3_secret_key: Option<String>,
 }
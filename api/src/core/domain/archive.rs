@@ -0,0 +1,628 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use thiserror::Error;
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{
+    media::{self, Media},
+    recipe::{self, Ingredient, RecipeGroup, RecipeIngredient, Step, Unit, Visibility},
+    Recipe, Tag, User,
+};
+
+/// Version of the archive layout written by this build. Archives from newer
+/// versions are refused rather than half read.
+pub const ARCHIVE_VERSION: u32 = 1;
+/// Largest archive accepted, in bytes, both as uploaded and once all of its
+/// entries are uncompressed.
+pub const MAX_ARCHIVE_BYTES: usize = 512 * 1024 * 1024;
+/// Largest manifest accepted once uncompressed, in bytes.
+const MAX_MANIFEST_BYTES: u64 = 64 * 1024 * 1024;
+const MANIFEST_PATH: &str = "manifest.json";
+const MEDIA_PREFIX: &str = "media/";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("invalid archive: {0}")]
+    InvalidArchive(String),
+    #[error("archive version {0} is newer than supported version {ARCHIVE_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("archive is larger than the {0} byte limit")]
+    TooLarge(usize),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(_: recipe::Error) -> Self {
+        Self::Unexpected
+    }
+}
+
+impl From<media::Error> for Error {
+    fn from(_: media::Error) -> Self {
+        Self::Unexpected
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(value: zip::result::ZipError) -> Self {
+        Self::InvalidArchive(value.to_string())
+    }
+}
+
+/// What to do with an archived recipe whose title the importing user
+/// already has a recipe by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Keep the existing recipe and leave the archived one out.
+    #[default]
+    Skip,
+    /// Import the archived recipe under a numbered title, e.g. "Soup (2)".
+    Rename,
+    /// Replace the existing recipe with the archived one.
+    Overwrite,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedIngredient {
+    pub ingredient: String,
+    pub quantity: super::Quantity,
+    pub units: String,
+    pub preparation: String,
+    pub group: Option<String>,
+    /// `id` of the archived recipe this ingredient stands for.
+    pub sub_recipe: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedStep {
+    pub ordinal: i32,
+    pub instruction: String,
+    pub group: Option<String>,
+    /// Path of the step's photo within the archive.
+    pub image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedGroup {
+    pub name: String,
+    pub ordinal: i32,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedRecipe {
+    /// The recipe's id where it was exported from, which sub-recipe
+    /// references within the archive point at.
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub prep_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub cook_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub inactive_time: Option<chrono::Duration>,
+    pub yield_quantity: super::Quantity,
+    pub yield_units: String,
+    pub ingredients: Vec<ArchivedIngredient>,
+    pub steps: Vec<ArchivedStep>,
+    #[serde(default)]
+    pub groups: Vec<ArchivedGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Path of the recipe's image within the archive.
+    pub image: Option<String>,
+    /// Archives made before recipes had a visibility hold public, published
+    /// recipes.
+    #[serde(default)]
+    pub visibility: Visibility,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// Where a stored media key is kept within an archive.
+fn media_path(key: &str) -> String {
+    format!("{}{}", MEDIA_PREFIX, key)
+}
+
+impl ArchivedRecipe {
+    pub fn from_recipe(recipe: &Recipe) -> ArchivedRecipe {
+        ArchivedRecipe {
+            id: recipe.id.unwrap_or_default(),
+            title: recipe.title.clone(),
+            description: recipe.description.clone(),
+            prep_time: recipe.prep_time,
+            cook_time: recipe.cook_time,
+            inactive_time: recipe.inactive_time,
+            yield_quantity: recipe.yield_quantity,
+            yield_units: recipe.yield_units.name.clone(),
+            ingredients: recipe
+                .ingredients
+                .iter()
+                .map(|x| ArchivedIngredient {
+                    ingredient: x.ingredient.name.clone(),
+                    quantity: x.quantity,
+                    units: x.units.name.clone(),
+                    preparation: x.preparation.clone(),
+                    group: x.group.clone(),
+                    sub_recipe: x.sub_recipe_id,
+                })
+                .collect(),
+            steps: recipe
+                .steps
+                .iter()
+                .map(|x| ArchivedStep {
+                    ordinal: x.ordinal,
+                    instruction: x.instruction.clone(),
+                    group: x.group.clone(),
+                    image: x.image.as_deref().map(media_path),
+                })
+                .collect(),
+            groups: recipe
+                .groups
+                .iter()
+                .map(|x| ArchivedGroup {
+                    name: x.name.clone(),
+                    ordinal: x.ordinal,
+                })
+                .collect(),
+            tags: recipe.tags.iter().map(|x| x.name.clone()).collect(),
+            image: recipe.image.as_deref().map(media_path),
+            visibility: recipe.visibility,
+            draft: recipe.draft,
+        }
+    }
+
+    /// The ids of the archived recipes this one uses as ingredients.
+    pub fn sub_recipes(&self) -> impl Iterator<Item = i32> + '_ {
+        self.ingredients.iter().filter_map(|x| x.sub_recipe)
+    }
+
+    /// A new recipe by `author` with this one's contents and no images.
+    /// `sub_recipe_ids` maps archived recipe ids to the ids they were
+    /// imported as; references to recipes missing from it are dropped.
+    pub fn to_recipe(&self, author: User, sub_recipe_ids: &HashMap<i32, i32>) -> Recipe {
+        Recipe {
+            id: None,
+            title: self.title.clone(),
+            description: self.description.clone(),
+            author,
+            prep_time: self.prep_time,
+            cook_time: self.cook_time,
+            inactive_time: self.inactive_time,
+            yield_quantity: self.yield_quantity,
+            yield_units: Unit {
+                id: None,
+                name: self.yield_units.clone(),
+            },
+            ingredients: self
+                .ingredients
+                .iter()
+                .map(|x| RecipeIngredient {
+                    id: None,
+                    recipe_id: None,
+                    ingredient: Ingredient {
+                        id: None,
+                        name: x.ingredient.clone(),
+                    },
+                    quantity: x.quantity,
+                    units: Unit {
+                        id: None,
+                        name: x.units.clone(),
+                    },
+                    preparation: x.preparation.clone(),
+                    group: x.group.clone(),
+                    sub_recipe_id: x.sub_recipe.and_then(|id| sub_recipe_ids.get(&id).copied()),
+                })
+                .collect(),
+            steps: self
+                .steps
+                .iter()
+                .map(|x| Step {
+                    id: None,
+                    recipe_id: None,
+                    ordinal: x.ordinal,
+                    instruction: x.instruction.clone(),
+                    group: x.group.clone(),
+                    image: None,
+                })
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|x| RecipeGroup {
+                    id: None,
+                    name: x.name.clone(),
+                    ordinal: x.ordinal,
+                })
+                .collect(),
+            tags: self
+                .tags
+                .iter()
+                .map(|name| Tag {
+                    id: None,
+                    name: name.clone(),
+                })
+                .collect(),
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: self.visibility,
+            draft: self.draft,
+        }
+    }
+}
+
+/// The index of an archive: who it was exported from and their recipes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    /// Name of the user the recipes were exported from.
+    pub author: String,
+    pub recipes: Vec<ArchivedRecipe>,
+}
+
+/// A user's recipe library along with the images it refers to, keyed by
+/// their path within the archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archive {
+    pub manifest: Manifest,
+    pub media: HashMap<String, Media>,
+}
+
+impl Archive {
+    /// An archive of `recipes`, ready for their images to be added.
+    pub fn new(author: &User, recipes: &[Recipe]) -> Archive {
+        Archive {
+            manifest: Manifest {
+                version: ARCHIVE_VERSION,
+                exported_at: chrono::Utc::now(),
+                author: author.name.clone(),
+                recipes: recipes.iter().map(ArchivedRecipe::from_recipe).collect(),
+            },
+            media: HashMap::new(),
+        }
+    }
+
+    /// Adds the stored media at `key` under the path the manifest gives it.
+    pub fn add_media(&mut self, key: &str, media: Media) {
+        self.media.insert(media_path(key), media);
+    }
+
+    /// Writes the archive as a zip file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file(MANIFEST_PATH, options)?;
+        let manifest = serde_json::to_vec_pretty(&self.manifest).map_err(|e| {
+            log::error!("Failed to write archive manifest due to: {}", e);
+            Error::Unexpected
+        })?;
+        writer.write_all(&manifest).map_err(|_| Error::Unexpected)?;
+        let mut paths: Vec<&String> = self.media.keys().collect();
+        paths.sort();
+        for path in paths {
+            // Images are compressed already.
+            let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+            writer.start_file(path.as_str(), options)?;
+            writer
+                .write_all(&self.media[path].bytes)
+                .map_err(|_| Error::Unexpected)?;
+        }
+        Ok(writer.finish()?.into_inner())
+    }
+
+    /// Reads an archive written by `to_bytes`, checking the size of each
+    /// entry as it goes. Only media the manifest refers to is read, once per
+    /// path.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Archive, Error> {
+        if bytes.len() > MAX_ARCHIVE_BYTES {
+            return Err(Error::TooLarge(MAX_ARCHIVE_BYTES));
+        }
+        Self::read_within(bytes, MAX_ARCHIVE_BYTES)
+    }
+
+    /// Reads an archive, failing with `TooLarge` once its entries add up to
+    /// more than `budget` bytes uncompressed.
+    fn read_within(bytes: &[u8], budget: usize) -> Result<Archive, Error> {
+        let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+        let mut remaining = budget as u64;
+        let mut read = |zip: &mut ZipArchive<Cursor<&[u8]>>, path: &str, limit: u64| {
+            let entry = zip
+                .by_name(path)
+                .map_err(|_| Error::InvalidArchive(format!("missing `{}`", path)))?;
+            let mut contents = Vec::new();
+            entry
+                .take(limit.min(remaining) + 1)
+                .read_to_end(&mut contents)
+                .map_err(|e| Error::InvalidArchive(e.to_string()))?;
+            let size = contents.len() as u64;
+            if size > limit {
+                return Err(Error::InvalidArchive(format!(
+                    "`{}` is larger than {} bytes",
+                    path, limit
+                )));
+            }
+            if size > remaining {
+                return Err(Error::TooLarge(budget));
+            }
+            remaining -= size;
+            Ok(contents)
+        };
+
+        let manifest = read(&mut zip, MANIFEST_PATH, MAX_MANIFEST_BYTES)?;
+        let version: serde_json::Value = serde_json::from_slice(&manifest)
+            .map_err(|e| Error::InvalidArchive(format!("{}: {}", MANIFEST_PATH, e)))?;
+        let version = version["version"]
+            .as_u64()
+            .ok_or_else(|| Error::InvalidArchive(format!("{} has no version", MANIFEST_PATH)))?;
+        if version > ARCHIVE_VERSION as u64 {
+            return Err(Error::UnsupportedVersion(
+                version.min(u32::MAX as u64) as u32
+            ));
+        }
+        let manifest: Manifest = serde_json::from_slice(&manifest)
+            .map_err(|e| Error::InvalidArchive(format!("{}: {}", MANIFEST_PATH, e)))?;
+
+        let mut media = HashMap::new();
+        let paths = manifest.recipes.iter().flat_map(|recipe| {
+            recipe
+                .image
+                .iter()
+                .chain(recipe.steps.iter().filter_map(|x| x.image.as_ref()))
+        });
+        for path in paths {
+            if media.contains_key(path) {
+                continue;
+            }
+            let key = path.strip_prefix(MEDIA_PREFIX).unwrap_or_default();
+            media::validate_key(key)
+                .map_err(|_| Error::InvalidArchive(format!("bad media path `{}`", path)))?;
+            let bytes = read(&mut zip, path, media::MAX_IMAGE_BYTES as u64)?;
+            media.insert(
+                path.clone(),
+                Media {
+                    content_type: media::content_type_for_key(key).to_owned(),
+                    bytes,
+                },
+            );
+        }
+        Ok(Archive { manifest, media })
+    }
+}
+
+/// What became of one archived recipe on import.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    Created,
+    /// Created under a new title because the original was taken.
+    Renamed(String),
+    Overwritten,
+    Skipped,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRecipe {
+    /// The title the recipe had in the archive.
+    pub title: String,
+    pub outcome: ImportOutcome,
+    /// The recipe now holding the archived one's contents, or the existing
+    /// recipe it was skipped in favour of.
+    pub recipe_id: Option<i32>,
+    /// Parts of the recipe that could not be carried over.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportReport {
+    pub recipes: Vec<ImportedRecipe>,
+}
+
+/// The archived recipes in an order that imports the recipes others use as
+/// ingredients first. Recipes caught in a cycle come last, in archive order.
+pub fn import_order(recipes: &[ArchivedRecipe]) -> Vec<&ArchivedRecipe> {
+    let archived: Vec<i32> = recipes.iter().map(|x| x.id).collect();
+    let mut ordered: Vec<&ArchivedRecipe> = Vec::with_capacity(recipes.len());
+    let mut remaining: Vec<&ArchivedRecipe> = recipes.iter().collect();
+    loop {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|recipe| {
+            recipe.sub_recipes().all(|id| {
+                id == recipe.id || !archived.contains(&id) || ordered.iter().any(|x| x.id == id)
+            })
+        });
+        if ready.is_empty() {
+            ordered.extend(blocked);
+            return ordered;
+        }
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn recipe(id: i32, title: &str, sub_recipe: Option<i32>) -> Recipe {
+        Recipe {
+            id: Some(id),
+            title: title.to_owned(),
+            description: Some("Tasty".to_owned()),
+            author: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            prep_time: Some(chrono::Duration::minutes(5)),
+            cook_time: None,
+            inactive_time: None,
+            yield_quantity: 2.into(),
+            yield_units: Unit {
+                id: Some(1),
+                name: "servings".to_owned(),
+            },
+            ingredients: vec![RecipeIngredient {
+                id: Some(7),
+                recipe_id: Some(id),
+                ingredient: Ingredient {
+                    id: Some(3),
+                    name: "stock".to_owned(),
+                },
+                quantity: "1 1/2".parse().unwrap(),
+                units: Unit {
+                    id: Some(2),
+                    name: "cups".to_owned(),
+                },
+                preparation: "warm".to_owned(),
+                group: None,
+                sub_recipe_id: sub_recipe,
+            }],
+            steps: vec![Step {
+                id: Some(9),
+                recipe_id: Some(id),
+                ordinal: 1,
+                instruction: "Heat it".to_owned(),
+                group: None,
+                image: Some(format!("recipe/{}/step.png", id)),
+            }],
+            groups: vec![],
+            tags: vec![Tag {
+                id: Some(4),
+                name: "soup".to_owned(),
+            }],
+            image: Some(format!("recipe/{}/hero.jpg", id)),
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: Default::default(),
+            draft: false,
+        }
+    }
+
+    fn media(bytes: &[u8]) -> Media {
+        Media {
+            content_type: "image/png".to_owned(),
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let recipes = [recipe(4, "Soup", Some(5)), recipe(5, "Stock", None)];
+        let mut archive = Archive::new(&recipes[0].author, &recipes);
+        archive.add_media("recipe/4/hero.jpg", media(b"hero"));
+        archive.add_media("recipe/4/step.png", media(b"step"));
+        archive.add_media("recipe/5/hero.jpg", media(b"stock"));
+        archive.add_media("recipe/5/step.png", media(b"stock step"));
+
+        let read = Archive::from_bytes(&archive.to_bytes().unwrap()).unwrap();
+        assert_eq!(read.manifest, archive.manifest);
+        assert_eq!(read.media.len(), 4);
+        assert_eq!(read.media["media/recipe/4/hero.jpg"].bytes, b"hero");
+        assert_eq!(
+            read.media["media/recipe/4/hero.jpg"].content_type,
+            "image/jpeg"
+        );
+        assert_eq!(
+            read.manifest.recipes[0].steps[0].image.as_deref(),
+            Some("media/recipe/4/step.png")
+        );
+
+        let imported = read.manifest.recipes[0].to_recipe(
+            User {
+                id: Some(2),
+                name: "Someone".to_owned(),
+            },
+            &HashMap::from([(5, 50)]),
+        );
+        assert_eq!(imported.id, None);
+        assert_eq!(imported.author.id, Some(2));
+        assert_eq!(imported.image, None);
+        assert_eq!(imported.ingredients[0].sub_recipe_id, Some(50));
+        assert_eq!(imported.ingredients[0].quantity, "1 1/2".parse().unwrap());
+        assert_eq!(imported.ingredients[0].units.name, "cups");
+        assert_eq!(imported.steps[0].instruction, "Heat it");
+        assert_eq!(imported.tags[0].name, "soup");
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_bad_archives() {
+        assert!(matches!(
+            Archive::from_bytes(b"not a zip").unwrap_err(),
+            Error::InvalidArchive(_)
+        ));
+
+        let mut archive = Archive::new(&recipe(1, "Soup", None).author, &[recipe(1, "Soup", None)]);
+        let bytes = archive.to_bytes().unwrap();
+        assert!(matches!(
+            Archive::from_bytes(&bytes).unwrap_err(),
+            Error::InvalidArchive(x) if x.contains("media/recipe/1/hero.jpg")
+        ));
+
+        archive.manifest.version = ARCHIVE_VERSION + 1;
+        assert!(matches!(
+            Archive::from_bytes(&archive.to_bytes().unwrap()).unwrap_err(),
+            Error::UnsupportedVersion(_)
+        ));
+
+        archive.manifest.version = ARCHIVE_VERSION;
+        archive.manifest.recipes[0].image = Some("media/../../etc/passwd".to_owned());
+        archive.manifest.recipes[0].steps[0].image = None;
+        assert!(matches!(
+            Archive::from_bytes(&archive.to_bytes().unwrap()).unwrap_err(),
+            Error::InvalidArchive(x) if x.contains("bad media path")
+        ));
+    }
+
+    #[test]
+    fn test_from_bytes_limits_uncompressed_size() {
+        let recipes = [recipe(4, "Soup", None), recipe(5, "Stew", None)];
+        let mut archive = Archive::new(&recipes[0].author, &recipes);
+        for recipe in archive.manifest.recipes.iter_mut() {
+            recipe.image = Some("media/recipe/4/hero.jpg".to_owned());
+            recipe.steps[0].image = None;
+        }
+        archive.add_media("recipe/4/hero.jpg", media(&[0; 100_000]));
+        let bytes = archive.to_bytes().unwrap();
+        let manifest_len = serde_json::to_vec_pretty(&archive.manifest).unwrap().len();
+
+        // Media shared by both recipes is only read once.
+        let read = Archive::read_within(&bytes, manifest_len + 100_000).unwrap();
+        assert_eq!(read.media.len(), 1);
+
+        archive.manifest.recipes[1].image = Some("media/recipe/5/hero.jpg".to_owned());
+        archive.add_media("recipe/5/hero.jpg", media(&[0; 100_000]));
+        assert!(matches!(
+            Archive::read_within(&archive.to_bytes().unwrap(), manifest_len + 150_000).unwrap_err(),
+            Error::TooLarge(_)
+        ));
+    }
+
+    #[test]
+    fn test_import_order() {
+        let recipes: Vec<ArchivedRecipe> = [
+            recipe(1, "Soup", Some(2)),
+            recipe(2, "Stock", Some(3)),
+            recipe(3, "Bones", Some(99)),
+            recipe(4, "Loop A", Some(5)),
+            recipe(5, "Loop B", Some(4)),
+        ]
+        .iter()
+        .map(ArchivedRecipe::from_recipe)
+        .collect();
+        let order: Vec<i32> = import_order(&recipes).iter().map(|x| x.id).collect();
+        assert_eq!(order, vec![3, 2, 1, 4, 5]);
+    }
+}
@@ -0,0 +1,214 @@
+use sqlx::prelude::FromRow;
+use thiserror::Error;
+
+use super::{
+    recipe::{self, Visibility},
+    User,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("collection with id `{0}` not found")]
+    CollectionNotFound(i32),
+    #[error("collection with id `{0}` belongs to another user")]
+    NotOwner(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("recipe with id `{0}` is not in the collection")]
+    NotInCollection(i32),
+    #[error("invalid collection: {0}")]
+    InvalidCollection(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            _ => Self::Unexpected,
+        }
+    }
+}
+
+/// A recipe in a collection or among a user's favorites.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct CollectionRecipe {
+    pub recipe_id: i32,
+    pub title: String,
+    pub author_id: i32,
+    pub visibility: Visibility,
+    pub draft: bool,
+}
+
+impl CollectionRecipe {
+    /// Whether the user with `viewer_id` may read the recipe.
+    pub fn is_visible_to(&self, viewer_id: Option<i32>) -> bool {
+        recipe::is_visible(Some(self.author_id), self.visibility, self.draft, viewer_id)
+    }
+}
+
+/// A named, ordered set of recipes, e.g. a personal cookbook. Shared
+/// collections can be read by anyone but changed only by their owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collection {
+    pub id: Option<i32>,
+    pub name: String,
+    pub owner: User,
+    pub shared: bool,
+    /// Kept in display order.
+    pub recipes: Vec<CollectionRecipe>,
+}
+
+/// Trims a collection name, failing if nothing is left.
+pub fn validate_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidCollection(
+            "name must not be empty".to_owned(),
+        ));
+    }
+    Ok(name.to_owned())
+}
+
+impl Collection {
+    pub fn new(name: String, shared: bool, owner: User) -> Self {
+        Self {
+            id: None,
+            name,
+            owner,
+            shared,
+            recipes: vec![],
+        }
+    }
+
+    /// Appends a recipe to the end of the collection, unless it is already in it.
+    pub fn add_recipe(&mut self, recipe: CollectionRecipe) {
+        if !self.recipes.iter().any(|x| x.recipe_id == recipe.recipe_id) {
+            self.recipes.push(recipe);
+        }
+    }
+
+    pub fn remove_recipe(&mut self, recipe_id: i32) -> Result<CollectionRecipe, Error> {
+        let index = self
+            .recipes
+            .iter()
+            .position(|x| x.recipe_id == recipe_id)
+            .ok_or(Error::NotInCollection(recipe_id))?;
+        Ok(self.recipes.remove(index))
+    }
+
+    /// Leaves out the recipes the user with `viewer_id` may not read. They
+    /// stay in the collection, so this is only for showing it.
+    pub fn without_hidden_recipes(mut self, viewer_id: Option<i32>) -> Collection {
+        self.recipes.retain(|x| x.is_visible_to(viewer_id));
+        self
+    }
+
+    /// Puts the recipes in the order of `recipe_ids`, which must name every
+    /// recipe in the collection exactly once.
+    pub fn reorder(&mut self, recipe_ids: &[i32]) -> Result<(), Error> {
+        let mut given = recipe_ids.to_vec();
+        given.sort_unstable();
+        let mut existing: Vec<i32> = self.recipes.iter().map(|x| x.recipe_id).collect();
+        existing.sort_unstable();
+        if given != existing {
+            return Err(Error::InvalidCollection(
+                "the new order must list every recipe exactly once".to_owned(),
+            ));
+        }
+        self.recipes.sort_by_key(|x| {
+            recipe_ids
+                .iter()
+                .position(|id| x.recipe_id == *id)
+                .unwrap_or_default()
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn recipe(recipe_id: i32, title: &str) -> CollectionRecipe {
+        CollectionRecipe {
+            recipe_id,
+            title: title.to_owned(),
+            author_id: 1,
+            visibility: Visibility::Public,
+            draft: false,
+        }
+    }
+
+    fn collection() -> Collection {
+        let mut collection = Collection::new(
+            "Weeknights".to_owned(),
+            false,
+            User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+        );
+        for (recipe_id, title) in [(4, "Soup"), (7, "Bread"), (9, "Stew")] {
+            collection.add_recipe(recipe(recipe_id, title));
+        }
+        collection
+    }
+
+    fn recipe_ids(collection: &Collection) -> Vec<i32> {
+        collection.recipes.iter().map(|x| x.recipe_id).collect()
+    }
+
+    #[test]
+    fn test_add_and_remove_recipes() {
+        let mut collection = collection();
+        collection.add_recipe(recipe(7, "Bread"));
+        assert_eq!(recipe_ids(&collection), vec![4, 7, 9]);
+        assert_eq!(collection.remove_recipe(7).unwrap().title, "Bread");
+        assert_eq!(recipe_ids(&collection), vec![4, 9]);
+        assert_eq!(
+            collection.remove_recipe(7).unwrap_err(),
+            Error::NotInCollection(7)
+        );
+    }
+
+    #[test]
+    fn test_reorder() {
+        let mut collection = collection();
+        collection.reorder(&[9, 4, 7]).unwrap();
+        assert_eq!(recipe_ids(&collection), vec![9, 4, 7]);
+        for invalid in [vec![9, 4], vec![9, 4, 4], vec![9, 4, 7, 8]] {
+            assert!(matches!(
+                collection.reorder(&invalid),
+                Err(Error::InvalidCollection(_))
+            ));
+        }
+        assert_eq!(recipe_ids(&collection), vec![9, 4, 7]);
+    }
+
+    #[test]
+    fn test_without_hidden_recipes() {
+        let mut collection = collection();
+        collection.recipes[0].author_id = 2;
+        collection.recipes[0].visibility = Visibility::Private;
+        collection.recipes[1].author_id = 2;
+        collection.recipes[1].draft = true;
+        collection.recipes[2].visibility = Visibility::Unlisted;
+        assert_eq!(
+            recipe_ids(&collection.clone().without_hidden_recipes(None)),
+            vec![9]
+        );
+        assert_eq!(
+            recipe_ids(&collection.clone().without_hidden_recipes(Some(2))),
+            vec![4, 7, 9]
+        );
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("  Desserts ").unwrap(), "Desserts");
+        assert!(validate_name(" ").is_err());
+    }
+}
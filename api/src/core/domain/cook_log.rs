@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use thiserror::Error;
+
+use super::{recipe, Quantity, User};
+
+/// The lowest and highest rating a cook can give.
+pub const RATINGS: std::ops::RangeInclusive<i16> = 1..=5;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("cook log entry with id `{0}` not found")]
+    EntryNotFound(i32),
+    #[error("cook log entry with id `{0}` belongs to another user")]
+    NotOwner(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("invalid cook log entry: {0}")]
+    InvalidCookLog(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            _ => Self::Unexpected,
+        }
+    }
+}
+
+/// A record of a user making a recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CookLogEntry {
+    pub id: Option<i32>,
+    pub owner: User,
+    pub recipe_id: i32,
+    pub recipe_title: String,
+    pub cooked_on: NaiveDate,
+    pub servings: Quantity,
+    pub notes: String,
+    /// What the cook did differently from the recipe.
+    pub modifications: String,
+    pub rating: Option<i16>,
+}
+
+impl<'r> FromRow<'r, PgRow> for CookLogEntry {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(CookLogEntry {
+            id: row.try_get("id")?,
+            owner: User {
+                id: row.try_get("owner_id")?,
+                name: row.try_get("owner_name")?,
+            },
+            recipe_id: row.try_get("recipe_id")?,
+            recipe_title: row.try_get("recipe_title")?,
+            cooked_on: row.try_get("cooked_on")?,
+            servings: row.try_get("servings")?,
+            notes: row.try_get("notes")?,
+            modifications: row.try_get("modifications")?,
+            rating: row.try_get("rating")?,
+        })
+    }
+}
+
+impl CookLogEntry {
+    /// Trims the entry's text, failing if its servings or rating are out of
+    /// range.
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.notes = self.notes.trim().to_owned();
+        self.modifications = self.modifications.trim().to_owned();
+        if !self.servings.is_positive() {
+            return Err(Error::InvalidCookLog(
+                "servings must be greater than zero".to_owned(),
+            ));
+        }
+        if self.rating.is_some_and(|x| !RATINGS.contains(&x)) {
+            return Err(Error::InvalidCookLog(format!(
+                "rating must be between {} and {}",
+                RATINGS.start(),
+                RATINGS.end()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whose history to read; entries are either of one recipe or by one user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CookLogQuery {
+    Recipe(i32),
+    Owner(i32),
+}
+
+/// How often a recipe has been made, by anyone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookStats {
+    pub times_cooked: i64,
+    pub last_cooked: Option<NaiveDate>,
+    /// Of the entries that were rated, to two decimal places.
+    pub average_rating: Option<f64>,
+}
+
+impl<'r> FromRow<'r, PgRow> for CookStats {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(CookStats {
+            times_cooked: row.try_get("times_cooked")?,
+            last_cooked: row.try_get("last_cooked")?,
+            average_rating: row.try_get("average_rating")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn entry(rating: Option<i16>, servings: Quantity) -> CookLogEntry {
+        CookLogEntry {
+            id: None,
+            owner: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            recipe_id: 5,
+            recipe_title: "Soup".to_owned(),
+            cooked_on: NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            servings,
+            notes: " Too salty \n".to_owned(),
+            modifications: String::new(),
+            rating,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut valid = entry(Some(5), 2.into());
+        assert!(valid.validate().is_ok());
+        assert_eq!(valid.notes, "Too salty");
+        assert!(entry(None, 2.into()).validate().is_ok());
+        for mut invalid in [
+            entry(Some(0), 2.into()),
+            entry(Some(6), 2.into()),
+            entry(None, 0.into()),
+        ] {
+            assert!(matches!(invalid.validate(), Err(Error::InvalidCookLog(_))));
+        }
+    }
+}
@@ -0,0 +1,929 @@
+use std::collections::HashMap;
+
+use super::{
+    html, ingredient_line,
+    recipe::{
+        Error, Ingredient, RecipeGroup, RecipeImport, RecipeIngredient, Step, Unit, UnmappedField,
+    },
+    schema_org,
+    unit::UnitCatalog,
+    Quantity, Recipe, Tag, User,
+};
+
+/// Unit given to yields that count people served.
+const SERVING_UNITS: &str = "servings";
+
+/// A piece of a Cooklang step.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    /// `@name{quantity%unit}(preparation)`
+    Ingredient {
+        name: String,
+        amount: Option<String>,
+        preparation: Option<String>,
+    },
+    /// `#name{}`
+    Cookware(String),
+    /// `~name{quantity%unit}`
+    Timer {
+        name: String,
+        amount: Option<String>,
+    },
+}
+
+/// Reads the component starting with the sigil at `chars[start]`, returning
+/// it and the index just past it. Names run to the end of a word, or up to
+/// braces when there are any, so they may span several words.
+fn read_component(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let sigil = chars[start];
+    let rest = &chars[start + 1..];
+    let braced = rest.iter().position(|c| *c == '{').and_then(|open| {
+        let name = &rest[..open];
+        if name
+            .iter()
+            .any(|c| matches!(c, '@' | '#' | '~' | '}' | '.' | ',' | ';' | '!' | '?'))
+        {
+            return None;
+        }
+        let close = open + rest[open..].iter().position(|c| *c == '}')?;
+        let amount: String = rest[open + 1..close].iter().collect();
+        Some((name.iter().collect::<String>(), Some(amount), close + 1))
+    });
+    let (name, amount, mut end) = match braced {
+        Some(found) => found,
+        None => {
+            let length = rest
+                .iter()
+                .position(|c| !(c.is_alphanumeric() || *c == '_' || *c == '-'))
+                .unwrap_or(rest.len());
+            let name: String = rest[..length].iter().collect();
+            let name = name.trim_end_matches('-').to_owned();
+            let length = name.chars().count();
+            (name, None, length)
+        }
+    };
+    let name = name.trim().to_owned();
+    let amount = amount
+        .map(|x| x.trim().to_owned())
+        .filter(|x| !x.is_empty());
+    if name.is_empty() && !(sigil == '~' && amount.is_some()) {
+        return None;
+    }
+    let token = match sigil {
+        '@' => {
+            let mut preparation = None;
+            if rest.get(end) == Some(&'(') {
+                if let Some(close) = rest[end..].iter().position(|c| *c == ')') {
+                    let text: String = rest[end + 1..end + close].iter().collect();
+                    preparation = Some(text.trim().to_owned()).filter(|x| !x.is_empty());
+                    end += close + 1;
+                }
+            }
+            Token::Ingredient {
+                name,
+                amount,
+                preparation,
+            }
+        }
+        '#' => Token::Cookware(name),
+        _ => Token::Timer { name, amount },
+    };
+    Some((token, start + 1 + end))
+}
+
+/// Splits a step into text and components, dropping the backslashes that
+/// escape characters that would otherwise start one.
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && i + 1 < chars.len() {
+            plain.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        if matches!(c, '@' | '#' | '~') {
+            if let Some((token, next)) = read_component(&chars, i) {
+                if !plain.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut plain)));
+                }
+                tokens.push(token);
+                i = next;
+                continue;
+            }
+        }
+        plain.push(c);
+        i += 1;
+    }
+    if !plain.is_empty() {
+        tokens.push(Token::Text(plain));
+    }
+    tokens
+}
+
+/// Whether `chars[i]` is preceded by an escaping backslash.
+fn is_escaped(chars: &[char], i: usize) -> bool {
+    chars[..i].iter().rev().take_while(|c| **c == '\\').count() % 2 == 1
+}
+
+/// Removes `[- block comments -]`, which may span lines.
+fn strip_block_comments(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut stripped = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '[' && chars.get(i + 1) == Some(&'-') && !is_escaped(&chars, i) {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|j| chars[*j] == '-' && chars[j + 1] == ']');
+            match end {
+                Some(end) => {
+                    i = end + 2;
+                    continue;
+                }
+                None => break,
+            }
+        }
+        stripped.push(chars[i]);
+        i += 1;
+    }
+    stripped
+}
+
+/// Removes a `-- line comment`.
+fn strip_line_comment(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let end = (0..chars.len().saturating_sub(1))
+        .find(|i| chars[*i] == '-' && chars[i + 1] == '-' && !is_escaped(&chars, *i))
+        .unwrap_or(chars.len());
+    chars[..end].iter().collect()
+}
+
+/// Reads durations written as "1 hour 30 minutes", "1h30m", "90 min" or in
+/// ISO 8601. A bare number is taken as minutes. Times too long to store are
+/// not read.
+pub fn parse_time(text: &str) -> Option<chrono::Duration> {
+    if let Some(duration) = schema_org::parse_duration(text) {
+        return Some(duration);
+    }
+    let text = text.to_lowercase();
+    let mut seconds = 0.0;
+    let mut rest = text.trim();
+    let mut any = false;
+    while !rest.is_empty() {
+        let number_length = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value: f64 = rest[..number_length].parse().ok()?;
+        rest = rest[number_length..].trim_start();
+        let unit_length = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_length] {
+            "" | "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            "d" | "day" | "days" => 86400.0,
+            _ => return None,
+        };
+        seconds += value * scale;
+        any = true;
+        rest = rest[unit_length..].trim_start_matches([' ', ',']);
+        rest = rest.strip_prefix("and ").unwrap_or(rest);
+    }
+    if !any {
+        return None;
+    }
+    chrono::Duration::try_seconds(seconds.round() as i64)
+}
+
+struct CooklangReader<'a> {
+    catalog: &'a UnitCatalog,
+    unmapped: Vec<UnmappedField>,
+    metadata: Vec<(String, String)>,
+    notes: Vec<String>,
+    ingredients: Vec<RecipeIngredient>,
+    steps: Vec<Step>,
+    groups: Vec<RecipeGroup>,
+    group: Option<String>,
+}
+
+impl<'a> CooklangReader<'a> {
+    fn new(catalog: &'a UnitCatalog) -> Self {
+        Self {
+            catalog,
+            unmapped: vec![],
+            metadata: vec![],
+            notes: vec![],
+            ingredients: vec![],
+            steps: vec![],
+            groups: vec![],
+            group: None,
+        }
+    }
+
+    fn unmapped(&mut self, field: impl Into<String>, reason: impl Into<String>) {
+        self.unmapped.push(UnmappedField {
+            field: field.into(),
+            reason: reason.into(),
+        });
+    }
+
+    fn metadata(&mut self, entry: &str) {
+        if let Some((key, value)) = entry.split_once(':') {
+            let key = key.trim().to_lowercase().replace(['_', '-'], " ");
+            self.metadata.push((key, value.trim().to_owned()));
+        }
+    }
+
+    fn section(&mut self, name: &str) {
+        if name.is_empty() {
+            self.group = None;
+            return;
+        }
+        if !self.groups.iter().any(|x| x.name == name) {
+            self.groups.push(RecipeGroup {
+                id: None,
+                name: name.to_owned(),
+                ordinal: self.groups.len() as i32 + 1,
+            });
+        }
+        self.group = Some(name.to_owned());
+    }
+
+    /// Reads a paragraph as a step. Paragraphs made only of ingredients, as
+    /// in a list up front, add the ingredients without adding a step.
+    fn paragraph(&mut self, lines: &mut Vec<String>) {
+        if lines.is_empty() {
+            return;
+        }
+        let mut instruction = String::new();
+        let mut only_ingredients = true;
+        for token in tokenize(&lines.join(" ")) {
+            if !matches!(&token, Token::Ingredient { .. } | Token::Text(_))
+                || matches!(&token, Token::Text(text) if text.chars().any(char::is_alphanumeric))
+            {
+                only_ingredients = false;
+            }
+            match token {
+                Token::Text(text) => instruction.push_str(&text),
+                Token::Ingredient {
+                    name,
+                    amount,
+                    preparation,
+                } => {
+                    instruction.push_str(&name);
+                    self.ingredient(name, amount, preparation);
+                }
+                Token::Cookware(name) => instruction.push_str(&name),
+                Token::Timer { name, amount } => match amount {
+                    Some(amount) => instruction.push_str(&amount.replacen('%', " ", 1)),
+                    None => instruction.push_str(&name),
+                },
+            }
+        }
+        lines.clear();
+        let instruction = instruction.split_whitespace().collect::<Vec<_>>().join(" ");
+        if only_ingredients {
+            return;
+        }
+        self.steps.push(Step {
+            id: None,
+            recipe_id: None,
+            ordinal: self.steps.len() as i32 + 1,
+            instruction,
+            group: self.group.clone(),
+            image: None,
+        });
+    }
+
+    fn amount(&mut self, name: &str, amount: Option<String>) -> (Quantity, String) {
+        let fallback = (1.into(), ingredient_line::DEFAULT_UNITS.to_owned());
+        let field = format!("@{}", name);
+        let amount = match amount {
+            Some(amount) => amount,
+            None => {
+                self.unmapped(field, "no quantity, imported as 1 each");
+                return fallback;
+            }
+        };
+        let (quantity, units) = amount.split_once('%').unwrap_or((&amount, ""));
+        let (quantity, units) = (quantity.trim(), units.trim());
+        let parsed = match ingredient_line::parse_amount(quantity, self.catalog) {
+            Some(parsed) if parsed.rest.is_empty() => parsed,
+            _ => {
+                self.unmapped(
+                    field,
+                    format!("cannot read quantity `{}`, imported as 1 each", quantity),
+                );
+                return fallback;
+            }
+        };
+        if let Some(quantity_max) = parsed.quantity_max {
+            self.unmapped(
+                field.as_str(),
+                format!(
+                    "`{}` is a range up to {}, imported as {}",
+                    quantity, quantity_max, parsed.quantity
+                ),
+            );
+        }
+        if units.is_empty() {
+            return (parsed.quantity, ingredient_line::DEFAULT_UNITS.to_owned());
+        }
+        let units = match ingredient_line::parse_amount(&format!("1 {}", units), self.catalog) {
+            Some(x) if x.rest.is_empty() && x.units.is_some() => x.units.unwrap_or_default(),
+            _ => {
+                self.unmapped(field, format!("unknown unit `{}`, imported as each", units));
+                ingredient_line::DEFAULT_UNITS.to_owned()
+            }
+        };
+        (parsed.quantity, units)
+    }
+
+    /// Adds an ingredient, adding to one already used in the same section
+    /// rather than listing it twice.
+    fn ingredient(&mut self, name: String, amount: Option<String>, preparation: Option<String>) {
+        let (quantity, units) = self.amount(&name, amount);
+        let preparation = preparation.unwrap_or_default();
+        let group = self.group.clone();
+        let existing = self.ingredients.iter_mut().find(|x| {
+            x.ingredient.name == name
+                && x.units.name == units
+                && x.preparation == preparation
+                && x.group == group
+        });
+        if let Some(existing) = existing {
+            if let Some(total) = existing.quantity.checked_add(quantity) {
+                existing.quantity = total;
+                return;
+            }
+        }
+        self.ingredients.push(RecipeIngredient {
+            id: None,
+            recipe_id: None,
+            ingredient: Ingredient { id: None, name },
+            quantity,
+            units: Unit {
+                id: None,
+                name: units,
+            },
+            preparation,
+            group,
+            sub_recipe_id: None,
+        });
+    }
+
+    fn read(mut self, text: &str) -> Result<Self, Error> {
+        let text = strip_block_comments(&text.replace("\r\n", "\n"));
+        let mut lines = text.lines().peekable();
+        while lines.peek().is_some_and(|x| x.trim().is_empty()) {
+            lines.next();
+        }
+        if lines.peek().is_some_and(|x| x.trim() == "---") {
+            lines.next();
+            for line in lines.by_ref() {
+                if line.trim() == "---" {
+                    break;
+                }
+                self.metadata(line);
+            }
+        }
+        let mut paragraph = vec![];
+        for line in lines {
+            let line = strip_line_comment(line);
+            let line = line.trim();
+            if line.is_empty() {
+                self.paragraph(&mut paragraph);
+            } else if let Some(entry) = line.strip_prefix(">>") {
+                self.metadata(entry);
+            } else if let Some(note) = line.strip_prefix('>') {
+                self.notes.push(note.trim().to_owned());
+            } else if line.starts_with('=') {
+                self.paragraph(&mut paragraph);
+                self.section(line.trim_matches('=').trim());
+            } else {
+                paragraph.push(line.to_owned());
+            }
+        }
+        self.paragraph(&mut paragraph);
+        Ok(self)
+    }
+
+    fn recipe_yield(&mut self, key: &str, value: &str) -> Option<(Quantity, String)> {
+        let amount = ingredient_line::parse_amount(value, self.catalog);
+        let amount = match amount {
+            Some(amount) => amount,
+            None => {
+                self.unmapped(key, format!("cannot read a yield from `{}`", value));
+                return None;
+            }
+        };
+        let units = match amount.units {
+            Some(units) => units,
+            None if amount.rest.is_empty() || key != "yield" => SERVING_UNITS.to_owned(),
+            None => {
+                self.unmapped(
+                    key,
+                    format!("unknown unit in `{}`, imported as each", value),
+                );
+                ingredient_line::DEFAULT_UNITS.to_owned()
+            }
+        };
+        Some((amount.quantity, units))
+    }
+
+    fn into_import(mut self, title: Option<&str>, author: User) -> Result<RecipeImport, Error> {
+        let mut recipe_title = title.map(str::to_owned);
+        let mut description = None;
+        let mut prep_time = None;
+        let mut cook_time = None;
+        let mut inactive_time = None;
+        let mut recipe_yield = None;
+        let mut tags = vec![];
+        for (key, value) in std::mem::take(&mut self.metadata) {
+            let time = |reader: &mut Self| {
+                let time = parse_time(&value);
+                if time.is_none() {
+                    reader.unmapped(key.as_str(), format!("cannot read a time from `{}`", value));
+                }
+                time
+            };
+            match key.as_str() {
+                "title" => recipe_title = Some(value.clone()),
+                "description" => description = Some(value.clone()),
+                "prep time" => prep_time = time(&mut self),
+                "cook time" => cook_time = time(&mut self),
+                "inactive time" => inactive_time = time(&mut self),
+                "servings" | "serves" | "yield" => {
+                    recipe_yield = self.recipe_yield(&key, &value).or(recipe_yield)
+                }
+                "tags" => tags.extend(
+                    value
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .split(',')
+                        .map(|x| x.trim().trim_matches(['"', '\'']).to_owned())
+                        .filter(|x| !x.is_empty())
+                        .map(|name| Tag { id: None, name }),
+                ),
+                _ => self.unmapped(key.as_str(), "not supported"),
+            }
+        }
+        let title = recipe_title
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty())
+            .ok_or_else(|| {
+                Error::InvalidImport("recipe has no title metadata and none was given".to_owned())
+            })?;
+        if !self.notes.is_empty() {
+            let notes = self.notes.join("\n");
+            description = Some(match description {
+                Some(description) => format!("{}\n\n{}", description, notes),
+                None => notes,
+            });
+        }
+        let (yield_quantity, yield_units) = recipe_yield.unwrap_or_else(|| {
+            self.unmapped("servings", "missing, imported as 1 serving");
+            (1.into(), SERVING_UNITS.to_owned())
+        });
+        Ok(RecipeImport {
+            recipe: Recipe {
+                id: None,
+                title,
+                description,
+                author,
+                prep_time,
+                cook_time,
+                inactive_time,
+                yield_quantity,
+                yield_units: Unit {
+                    id: None,
+                    name: yield_units,
+                },
+                ingredients: self.ingredients,
+                steps: self.steps,
+                groups: self.groups,
+                tags,
+                image: None,
+                cook_stats: Default::default(),
+                review_stats: Default::default(),
+                forked_from: None,
+                lineage: vec![],
+                visibility: Default::default(),
+                draft: false,
+            },
+            unmapped: self.unmapped,
+        })
+    }
+}
+
+/// Reads a Cooklang document into a recipe by `author`, noting everything
+/// that could not be carried over. `title` is used when the document has no
+/// `title` metadata of its own.
+pub fn parse_recipe(
+    text: &str,
+    title: Option<&str>,
+    author: User,
+    catalog: &UnitCatalog,
+) -> Result<RecipeImport, Error> {
+    CooklangReader::new(catalog)
+        .read(text)?
+        .into_import(title, author)
+}
+
+/// Escapes text so that none of it reads as Cooklang markup.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut previous = None;
+    for (i, c) in text.chars().enumerate() {
+        let starts_line = i == 0 && matches!(c, '>' | '=');
+        if matches!(c, '\\' | '@' | '#' | '~')
+            || starts_line
+            || c == '-' && matches!(previous, Some('-') | Some('['))
+        {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        previous = Some(c);
+    }
+    escaped
+}
+
+fn format_quantity(quantity: Quantity) -> String {
+    if quantity.denominator() == 1 {
+        quantity.numerator().to_string()
+    } else {
+        format!("{}/{}", quantity.numerator(), quantity.denominator())
+    }
+}
+
+fn format_ingredient(ingredient: &RecipeIngredient) -> String {
+    let mut text = format!(
+        "@{}{{{}",
+        ingredient.ingredient.name,
+        format_quantity(ingredient.quantity)
+    );
+    if ingredient.units.name != ingredient_line::DEFAULT_UNITS {
+        text.push('%');
+        text.push_str(&ingredient.units.name);
+    }
+    text.push('}');
+    if !ingredient.preparation.is_empty() {
+        text.push_str(&format!("({})", ingredient.preparation));
+    }
+    text
+}
+
+/// Where `name` first appears as a whole word in `text`, at or after `from`.
+fn find_word(text: &str, name: &str, from: usize) -> Option<usize> {
+    let mut start = from;
+    while let Some(found) = text.get(start..)?.find(name) {
+        let at = start + found;
+        let end = at + name.len();
+        let before = text[..at].chars().next_back();
+        let after = text[end..].chars().next();
+        if !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric) {
+            return Some(at);
+        }
+        start = at + name.chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+/// Writes a recipe as a Cooklang document that `parse_recipe` reads back.
+/// Ingredients are marked where a step of their group names them, and
+/// listed at the top of their section otherwise.
+pub fn to_cooklang(recipe: &Recipe) -> String {
+    let sections = schema_org::step_sections(recipe);
+    let steps: Vec<&Step> = sections
+        .iter()
+        .flat_map(|(_, x)| x.iter().copied())
+        .collect();
+
+    // Each step's text with the ingredients it names, by position.
+    let mut marks: Vec<Vec<(usize, usize, &RecipeIngredient)>> = vec![vec![]; steps.len()];
+    let mut listed: HashMap<Option<&str>, Vec<&RecipeIngredient>> = HashMap::new();
+    for ingredient in &recipe.ingredients {
+        let name = &ingredient.ingredient.name;
+        let found = steps.iter().enumerate().find_map(|(i, step)| {
+            if step.group != ingredient.group || name.is_empty() {
+                return None;
+            }
+            let mut from = 0;
+            while let Some(at) = find_word(&step.instruction, name, from) {
+                let end = at + name.len();
+                if !marks[i].iter().any(|(s, e, _)| at < *e && *s < end) {
+                    return Some((i, at, end));
+                }
+                from = end;
+            }
+            None
+        });
+        match found {
+            Some((i, start, end)) => marks[i].push((start, end, ingredient)),
+            None => listed
+                .entry(ingredient.group.as_deref())
+                .or_default()
+                .push(ingredient),
+        }
+    }
+
+    let mut text = "---\n".to_owned();
+    let mut metadata = |key: &str, value: &str| {
+        text.push_str(&format!("{}: {}\n", key, value.replace('\n', " ")));
+    };
+    metadata("title", &recipe.title);
+    if let Some(description) = &recipe.description {
+        metadata("description", description);
+    }
+    if recipe.yield_units.name == SERVING_UNITS {
+        metadata("servings", &format_quantity(recipe.yield_quantity));
+    } else {
+        metadata(
+            "yield",
+            &format!(
+                "{} {}",
+                format_quantity(recipe.yield_quantity),
+                recipe.yield_units.name
+            ),
+        );
+    }
+    for (key, time) in [
+        ("prep time", recipe.prep_time),
+        ("cook time", recipe.cook_time),
+        ("inactive time", recipe.inactive_time),
+    ] {
+        if let Some(time) = time {
+            metadata(key, &html::describe_duration(time));
+        }
+    }
+    if !recipe.tags.is_empty() {
+        let tags: Vec<&str> = recipe.tags.iter().map(|x| x.name.as_str()).collect();
+        metadata("tags", &tags.join(", "));
+    }
+    text.push_str("---\n");
+
+    let mut list = |text: &mut String, group| {
+        if let Some(ingredients) = listed.remove(&group) {
+            text.push('\n');
+            for ingredient in ingredients {
+                text.push_str(&format_ingredient(ingredient));
+                text.push('\n');
+            }
+        }
+    };
+    list(&mut text, None);
+    let mut current = None;
+    let mut step_index = 0;
+    for (group, section_steps) in &sections {
+        if *group != current {
+            text.push_str(&format!("\n== {} ==\n", group.unwrap_or_default()));
+            current = *group;
+            list(&mut text, *group);
+        }
+        for step in section_steps {
+            let mut step_marks = marks[step_index].clone();
+            step_marks.sort_by_key(|(start, _, _)| *start);
+            let instruction = step.instruction.replace('\n', " ");
+            let mut line = String::new();
+            let mut position = 0;
+            for (start, end, ingredient) in step_marks {
+                line.push_str(&escape(&instruction[position..start]));
+                line.push_str(&format_ingredient(ingredient));
+                position = end;
+            }
+            line.push_str(&escape(&instruction[position..]));
+            text.push('\n');
+            text.push_str(&line);
+            text.push('\n');
+            step_index += 1;
+        }
+    }
+    let mut groups: Vec<&RecipeGroup> = recipe.groups.iter().collect();
+    groups.sort_by_key(|x| x.ordinal);
+    for group in groups {
+        let name = Some(group.name.as_str());
+        if !sections.iter().any(|(x, _)| *x == name) {
+            text.push_str(&format!("\n== {} ==\n", group.name));
+            list(&mut text, name);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::unit::test_catalog;
+
+    fn author() -> User {
+        User {
+            id: Some(1),
+            name: "Matt".to_owned(),
+        }
+    }
+
+    const PANCAKES: &str = "\
+---
+title: Pancakes
+servings: 4
+prep time: 10 minutes
+tags: breakfast, sweet
+---
+>> cook time: 1h5m
+> Best eaten warm.
+
+@butter{2%tbsp}(melted)
+
+Whisk @flour{250%g} with @eggs{2} and @whole milk{1 1/2%cups} in a #large bowl{}. -- no lumps
+Add the butter and a pinch of @salt.
+
+== Cooking ==
+
+Heat a #pan and cook for ~{3%minutes} per side, using @butter{1%tbsp}.
+Serve \\@home [- hidden -]with @syrup{a lot}.
+";
+
+    #[test]
+    fn test_parse_time() {
+        let minutes = |x| Some(chrono::Duration::minutes(x));
+        assert_eq!(parse_time("10 minutes"), minutes(10));
+        assert_eq!(parse_time("1 hr 30 min"), minutes(90));
+        assert_eq!(parse_time("1h30m"), minutes(90));
+        assert_eq!(parse_time("2 hours, and 5 mins"), minutes(125));
+        assert_eq!(parse_time("45"), minutes(45));
+        assert_eq!(parse_time("PT20M"), minutes(20));
+        for text in ["", "soon", "10 fortnights", "99999999999999999 days"] {
+            assert_eq!(parse_time(text), None, "{}", text);
+        }
+
+        let text = ">> title: Toast\n>> prep time: 99999999999999999 days\n\nToast the bread.";
+        let import = parse_recipe(text, None, author(), &test_catalog()).unwrap();
+        assert_eq!(import.recipe.prep_time, None);
+        assert!(import.unmapped.iter().any(|x| x.field == "prep time"));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Mix @flour{2%cups}(sifted) in a #big bowl{} for ~{5%min}, not \\@here"),
+            vec![
+                Token::Text("Mix ".to_owned()),
+                Token::Ingredient {
+                    name: "flour".to_owned(),
+                    amount: Some("2%cups".to_owned()),
+                    preparation: Some("sifted".to_owned()),
+                },
+                Token::Text(" in a ".to_owned()),
+                Token::Cookware("big bowl".to_owned()),
+                Token::Text(" for ".to_owned()),
+                Token::Timer {
+                    name: "".to_owned(),
+                    amount: Some("5%min".to_owned()),
+                },
+                Token::Text(", not @here".to_owned()),
+            ]
+        );
+        assert_eq!(
+            tokenize("Add @salt and #pot."),
+            vec![
+                Token::Text("Add ".to_owned()),
+                Token::Ingredient {
+                    name: "salt".to_owned(),
+                    amount: None,
+                    preparation: None,
+                },
+                Token::Text(" and ".to_owned()),
+                Token::Cookware("pot".to_owned()),
+                Token::Text(".".to_owned()),
+            ]
+        );
+        assert_eq!(
+            tokenize("email @ home"),
+            vec![Token::Text("email @ home".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_parse_recipe() {
+        let import = parse_recipe(PANCAKES, None, author(), &test_catalog()).unwrap();
+        let recipe = import.recipe;
+        assert_eq!(recipe.title, "Pancakes");
+        assert_eq!(recipe.description.as_deref(), Some("Best eaten warm."));
+        assert_eq!(recipe.yield_quantity, 4.into());
+        assert_eq!(recipe.yield_units.name, "servings");
+        assert_eq!(recipe.prep_time, Some(chrono::Duration::minutes(10)));
+        assert_eq!(recipe.cook_time, Some(chrono::Duration::minutes(65)));
+        let tags: Vec<&str> = recipe.tags.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(tags, ["breakfast", "sweet"]);
+
+        let ingredients: Vec<String> = recipe
+            .ingredients
+            .iter()
+            .map(|x| {
+                format!(
+                    "{} {} {} ({}) {:?}",
+                    x.quantity, x.units.name, x.ingredient.name, x.preparation, x.group
+                )
+            })
+            .collect();
+        assert_eq!(
+            ingredients,
+            [
+                "2 tablespoons butter (melted) None",
+                "250 grams flour () None",
+                "2 each eggs () None",
+                "1 1/2 cups whole milk () None",
+                "1 each salt () None",
+                "1 tablespoons butter () Some(\"Cooking\")",
+                "1 each syrup () Some(\"Cooking\")",
+            ]
+        );
+        let fields: Vec<&str> = import.unmapped.iter().map(|x| x.field.as_str()).collect();
+        assert_eq!(fields, ["@salt", "@syrup"]);
+
+        let steps: Vec<(i32, &str, Option<&str>)> = recipe
+            .steps
+            .iter()
+            .map(|x| (x.ordinal, x.instruction.as_str(), x.group.as_deref()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (
+                    1,
+                    "Whisk flour with eggs and whole milk in a large bowl. Add the butter and a pinch of salt.",
+                    None
+                ),
+                (
+                    2,
+                    "Heat a pan and cook for 3 minutes per side, using butter. Serve @home with syrup.",
+                    Some("Cooking")
+                ),
+            ]
+        );
+        assert_eq!(recipe.groups.len(), 1);
+        assert_eq!(recipe.groups[0].ordinal, 1);
+    }
+
+    #[test]
+    fn test_parse_recipe_needs_title() {
+        let text = "Boil @water{1%cups}.";
+        assert!(matches!(
+            parse_recipe(text, None, author(), &test_catalog()).unwrap_err(),
+            Error::InvalidImport(_)
+        ));
+        let import = parse_recipe(text, Some("Water"), author(), &test_catalog()).unwrap();
+        assert_eq!(import.recipe.title, "Water");
+        assert_eq!(import.recipe.yield_units.name, "servings");
+        assert_eq!(import.unmapped[0].field, "servings");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut recipe = parse_recipe(PANCAKES, None, author(), &test_catalog())
+            .unwrap()
+            .recipe;
+        recipe.steps[0]
+            .instruction
+            .push_str(" Then -- rest #1 @ 20C.");
+        recipe.ingredients.push(RecipeIngredient {
+            preparation: "to taste".to_owned(),
+            ..recipe.ingredients[4].clone()
+        });
+        recipe.ingredients[4].ingredient.name = "pepper".to_owned();
+
+        let text = to_cooklang(&recipe);
+        assert!(text.contains("servings: 4\n"));
+        assert!(text.contains("Whisk @flour{250%grams} with @eggs{2} and @whole milk{3/2%cups}"));
+        assert!(text.contains("\n@pepper{1}\n"));
+        assert!(text.contains("-\\- rest \\#1 \\@ 20C."));
+
+        let parsed = parse_recipe(&text, None, author(), &test_catalog())
+            .unwrap()
+            .recipe;
+        assert_eq!(parsed.title, recipe.title);
+        assert_eq!(parsed.yield_quantity, recipe.yield_quantity);
+        assert_eq!(parsed.yield_units.name, recipe.yield_units.name);
+        assert_eq!(parsed.prep_time, recipe.prep_time);
+        assert_eq!(parsed.cook_time, recipe.cook_time);
+        assert_eq!(parsed.steps, recipe.steps);
+        assert_eq!(parsed.groups, recipe.groups);
+        let key = |x: &RecipeIngredient| {
+            format!(
+                "{} {} {} {} {:?}",
+                x.quantity, x.units.name, x.ingredient.name, x.preparation, x.group
+            )
+        };
+        let mut expected: Vec<String> = recipe.ingredients.iter().map(key).collect();
+        let mut actual: Vec<String> = parsed.ingredients.iter().map(key).collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+}
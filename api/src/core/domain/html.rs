@@ -0,0 +1,265 @@
+use std::fmt::Write;
+
+use super::{ingredient_line, schema_org, Recipe};
+
+const STYLE: &str = "\
+body{font-family:Georgia,serif;line-height:1.5;max-width:42rem;margin:2rem auto;padding:0 1rem;color:#222}\
+h1{margin-bottom:.25rem}\
+.description{font-style:italic}\
+img{max-width:100%;height:auto}\
+dl{display:flex;flex-wrap:wrap;gap:0 1.5rem}\
+dt{font-weight:bold}\
+dd{margin:0}\
+.tags{color:#666}\
+@media print{body{margin:0;max-width:none}img{max-height:10cm}a{color:inherit;text-decoration:none}}";
+
+/// Escapes text for use in HTML content and quoted attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A duration as people write it, such as "1 hr 30 min".
+pub fn describe_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{} min", minutes),
+        (hours, 0) => format!("{} hr", hours),
+        (hours, minutes) => format!("{} hr {} min", hours, minutes),
+    }
+}
+
+/// The recipe as a standalone, printable HTML page. The page embeds the
+/// recipe as schema.org JSON-LD too, so other apps can read it back.
+/// `image_url` is where the recipe's image is served from, if it has one.
+pub fn render_recipe(recipe: &Recipe, image_url: Option<&str>) -> String {
+    // Keep the JSON from closing the script element early.
+    let json_ld = schema_org::to_json_ld(recipe, image_url)
+        .to_string()
+        .replace("</", "<\\/");
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n\
+         <script type=\"application/ld+json\">{json_ld}</script>\n</head>\n<body>\n\
+         <article class=\"recipe\">\n<h1>{title}</h1>\n\
+         <p class=\"author\">By {author}</p>\n",
+        title = escape(&recipe.title),
+        style = STYLE,
+        json_ld = json_ld,
+        author = escape(&recipe.author.name),
+    );
+    if let Some(description) = &recipe.description {
+        let _ = writeln!(html, "<p class=\"description\">{}</p>", escape(description));
+    }
+    if let Some(image_url) = image_url {
+        let _ = writeln!(
+            html,
+            "<img src=\"{}\" alt=\"{}\">",
+            escape(image_url),
+            escape(&recipe.title)
+        );
+    }
+
+    html.push_str("<dl class=\"details\">\n");
+    let times = [
+        ("Prep", recipe.prep_time),
+        ("Cook", recipe.cook_time),
+        ("Inactive", recipe.inactive_time),
+        ("Total", schema_org::total_time(recipe)),
+    ];
+    for (label, duration) in times {
+        if let Some(duration) = duration {
+            let _ = writeln!(
+                html,
+                "<div><dt>{}</dt><dd><time datetime=\"{}\">{}</time></dd></div>",
+                label,
+                schema_org::format_duration(duration),
+                describe_duration(duration)
+            );
+        }
+    }
+    let _ = writeln!(
+        html,
+        "<div><dt>Yield</dt><dd>{} {}</dd></div>\n</dl>",
+        recipe.yield_quantity,
+        escape(&recipe.yield_units.name)
+    );
+
+    html.push_str("<section class=\"ingredients\">\n<h2>Ingredients</h2>\n");
+    let mut groups: Vec<Option<&str>> = vec![None];
+    let mut named: Vec<_> = recipe.groups.iter().collect();
+    named.sort_by_key(|x| x.ordinal);
+    groups.extend(named.iter().map(|x| Some(x.name.as_str())));
+    for group in groups {
+        let ingredients: Vec<_> = recipe
+            .ingredients
+            .iter()
+            .filter(|x| x.group.as_deref() == group)
+            .collect();
+        if ingredients.is_empty() {
+            continue;
+        }
+        if let Some(group) = group {
+            let _ = writeln!(html, "<h3>{}</h3>", escape(group));
+        }
+        html.push_str("<ul>\n");
+        for ingredient in ingredients {
+            let _ = writeln!(
+                html,
+                "<li>{}</li>",
+                escape(&ingredient_line::format(ingredient))
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</section>\n");
+
+    html.push_str("<section class=\"instructions\">\n<h2>Instructions</h2>\n");
+    for (group, steps) in schema_org::step_sections(recipe) {
+        if let Some(group) = group {
+            let _ = writeln!(html, "<h3>{}</h3>", escape(group));
+        }
+        let _ = writeln!(html, "<ol start=\"{}\">", steps[0].ordinal);
+        for step in steps {
+            let _ = writeln!(html, "<li>{}</li>", escape(&step.instruction));
+        }
+        html.push_str("</ol>\n");
+    }
+    html.push_str("</section>\n");
+
+    if !recipe.tags.is_empty() {
+        let tags: Vec<String> = recipe.tags.iter().map(|x| escape(&x.name)).collect();
+        let _ = writeln!(html, "<p class=\"tags\">{}</p>", tags.join(" · "));
+    }
+    html.push_str("</article>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::{
+        recipe::{Ingredient, RecipeGroup, RecipeIngredient, Step, Unit},
+        unit::UnitCatalog,
+        Tag, User,
+    };
+
+    fn recipe() -> Recipe {
+        let ingredient =
+            |name: &str, quantity: i32, units: &str, group: Option<&str>| RecipeIngredient {
+                id: None,
+                recipe_id: None,
+                ingredient: Ingredient {
+                    id: None,
+                    name: name.to_owned(),
+                },
+                quantity: quantity.into(),
+                units: Unit {
+                    id: None,
+                    name: units.to_owned(),
+                },
+                preparation: "".to_owned(),
+                group: group.map(str::to_owned),
+                sub_recipe_id: None,
+            };
+        let step = |ordinal, instruction: &str, group: Option<&str>| Step {
+            id: None,
+            recipe_id: None,
+            ordinal,
+            instruction: instruction.to_owned(),
+            group: group.map(str::to_owned),
+            image: None,
+        };
+        Recipe {
+            id: Some(1),
+            title: "Mac & Cheese".to_owned(),
+            description: Some("<Creamy>".to_owned()),
+            author: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            prep_time: Some(chrono::Duration::minutes(10)),
+            cook_time: Some(chrono::Duration::minutes(80)),
+            inactive_time: None,
+            yield_quantity: 4.into(),
+            yield_units: Unit {
+                id: None,
+                name: "servings".to_owned(),
+            },
+            ingredients: vec![
+                ingredient("cheddar", 200, "grams", Some("Sauce")),
+                ingredient("macaroni", 250, "grams", None),
+            ],
+            steps: vec![
+                step(2, "Make the sauce.", Some("Sauce")),
+                step(1, "Boil the macaroni.", None),
+            ],
+            groups: vec![RecipeGroup {
+                id: None,
+                name: "Sauce".to_owned(),
+                ordinal: 1,
+            }],
+            tags: vec![Tag {
+                id: None,
+                name: "comfort".to_owned(),
+            }],
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: Default::default(),
+            draft: false,
+        }
+    }
+
+    #[test]
+    fn test_describe_duration() {
+        assert_eq!(describe_duration(chrono::Duration::minutes(45)), "45 min");
+        assert_eq!(describe_duration(chrono::Duration::minutes(120)), "2 hr");
+        assert_eq!(
+            describe_duration(chrono::Duration::minutes(90)),
+            "1 hr 30 min"
+        );
+    }
+
+    #[test]
+    fn test_render_recipe() {
+        let html = render_recipe(&recipe(), Some("/media/recipe/1/a.png"));
+        assert!(html.contains("<title>Mac &amp; Cheese</title>"));
+        assert!(html.contains("<p class=\"description\">&lt;Creamy&gt;</p>"));
+        assert!(html.contains("<img src=\"/media/recipe/1/a.png\""));
+        assert!(html.contains("<time datetime=\"PT1H30M\">1 hr 30 min</time>"));
+        assert!(html.contains("<dd>4 servings</dd>"));
+        let body = &html[html.find("<body>").unwrap()..];
+        let macaroni = body.find("250 grams macaroni").unwrap();
+        let sauce = body.find("<h3>Sauce</h3>").unwrap();
+        assert!(macaroni < sauce && sauce < body.find("200 grams cheddar").unwrap());
+        assert!(body.find("Boil the macaroni.").unwrap() < body.find("Make the sauce.").unwrap());
+        assert!(html.contains("<ol start=\"2\">"));
+
+        // The embedded JSON-LD imports back to the same recipe.
+        let import = schema_org::parse_recipe(
+            &schema_org::Document::Html(html),
+            recipe().author,
+            &UnitCatalog::new(vec![]),
+        )
+        .unwrap();
+        assert_eq!(import.recipe.title, "Mac & Cheese");
+        assert_eq!(import.recipe.steps.len(), 2);
+    }
+}
@@ -0,0 +1,438 @@
+use super::{
+    recipe::{Ingredient, RecipeIngredient, Unit},
+    unit::UnitCatalog,
+    Quantity,
+};
+
+/// Unit given to ingredients that are counted rather than measured, or whose
+/// unit is not in the catalog.
+pub const DEFAULT_UNITS: &str = "each";
+
+/// Aliases whose case matters, as in "1 T sugar" against "1 t salt".
+const CASE_SENSITIVE_ALIASES: [(&str, &str); 2] = [("T", "tablespoons"), ("t", "teaspoons")];
+
+/// Ways of writing units beyond their catalog names and abbreviations,
+/// mapped to catalog names. Plurals are handled separately.
+const UNIT_ALIASES: [(&str, &str); 28] = [
+    ("tbs", "tablespoons"),
+    ("tbl", "tablespoons"),
+    ("tblsp", "tablespoons"),
+    ("tsps", "teaspoons"),
+    ("gr", "grams"),
+    ("gram", "grams"),
+    ("kilo", "kilograms"),
+    ("kilogram", "kilograms"),
+    ("lbs", "pounds"),
+    ("pound", "pounds"),
+    ("ounce", "ounces"),
+    ("fl. oz", "fluid ounces"),
+    ("floz", "fluid ounces"),
+    ("fluid ounce", "fluid ounces"),
+    ("cup", "cups"),
+    ("pint", "pints"),
+    ("quart", "quarts"),
+    ("gallon", "gallons"),
+    ("millilitre", "milliliters"),
+    ("milliliter", "milliliters"),
+    ("litre", "liters"),
+    ("liter", "liters"),
+    ("cc", "milliliters"),
+    ("inch", "inches"),
+    ("centimetre", "centimeters"),
+    ("millimetre", "millimeters"),
+    ("metre", "meters"),
+    ("dozens", "dozen"),
+];
+
+/// Words that join the two ends of a range, as in "2 to 3".
+const RANGE_WORDS: [&str; 3] = ["-", "to", "or"];
+
+/// Words that open an ingredient line but say nothing about it.
+const BULLETS: [&str; 4] = ["-", "*", "•", "·"];
+
+/// An ingredient read from a line of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedIngredient {
+    pub ingredient: RecipeIngredient,
+    /// The upper end of a range such as "2-3", whose lower end is the
+    /// ingredient's quantity.
+    pub quantity_max: Option<Quantity>,
+    /// What could not be read from the line, if anything.
+    pub warnings: Vec<String>,
+}
+
+/// A quantity and unit read from the start of some text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Amount {
+    pub quantity: Quantity,
+    pub quantity_max: Option<Quantity>,
+    /// The catalog name of the unit, if one followed the quantity.
+    pub units: Option<String>,
+    /// Whatever follows the quantity and unit.
+    pub rest: String,
+}
+
+/// Splits text into words, also splitting numbers from units written
+/// against them ("200g") and the ends of ranges ("2-3").
+fn split_words(text: &str) -> Vec<String> {
+    let is_number = |c: char| c.is_numeric() || c == '.' || c == '/';
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        let word = word.replace(['–', '—'], "-");
+        let parts: Vec<&str> = word.split('-').collect();
+        if parts.len() == 2
+            && parts
+                .iter()
+                .all(|x| x.chars().next().is_some_and(char::is_numeric))
+        {
+            words.extend(split_words(parts[0]));
+            words.push("-".to_owned());
+            words.extend(split_words(parts[1]));
+            continue;
+        }
+        match word.find(|c: char| !is_number(c)) {
+            Some(i) if i > 0 && word[i..].starts_with(char::is_alphabetic) => {
+                words.push(word[..i].to_owned());
+                words.push(word[i..].to_owned());
+            }
+            _ => words.push(word),
+        }
+    }
+    words
+}
+
+/// Reads a quantity from the start of `words`, trying a mixed number such as
+/// "1 1/2" before a single term. Returns how many words it used.
+fn leading_quantity(words: &[String]) -> Option<(Quantity, usize)> {
+    if words.len() >= 2 {
+        if let Ok(quantity) = format!("{} {}", words[0], words[1]).parse::<Quantity>() {
+            return Some((quantity, 2));
+        }
+    }
+    let first = words.first()?;
+    if (first.eq_ignore_ascii_case("a") || first.eq_ignore_ascii_case("an")) && words.len() > 1 {
+        return Some((1.into(), 1));
+    }
+    first.parse::<Quantity>().ok().map(|x| (x, 1))
+}
+
+/// Reads a quantity or a range of them from the start of `words`. Returns
+/// the low end, the high end of a range and how many words were used.
+fn leading_range(words: &[String]) -> Option<(Quantity, Option<Quantity>, usize)> {
+    let (low, used) = leading_quantity(words)?;
+    if let Some(joiner) = words.get(used) {
+        if RANGE_WORDS.contains(&joiner.to_lowercase().as_str()) {
+            if let Some((high, high_used)) = words
+                .get(used + 1..)
+                .and_then(leading_quantity)
+                .filter(|(high, _)| high.to_f64() > low.to_f64())
+            {
+                return Some((low, Some(high), used + 1 + high_used));
+            }
+        }
+    }
+    Some((low, None, used))
+}
+
+/// The catalog name of the unit a word stands for, if any.
+fn unit_named(word: &str, catalog: &UnitCatalog) -> Option<String> {
+    let word = word.trim_end_matches([',', '.']);
+    if let Some((_, name)) = CASE_SENSITIVE_ALIASES.iter().find(|(x, _)| *x == word) {
+        return catalog.find(name).ok().map(|x| x.name.clone());
+    }
+    let lower = word.to_lowercase();
+    let singulars = [
+        Some(lower.as_str()),
+        lower.strip_suffix("es"),
+        lower.strip_suffix('s'),
+    ];
+    singulars.iter().flatten().find_map(|word| {
+        UNIT_ALIASES
+            .iter()
+            .find(|(x, _)| x == word)
+            .and_then(|(_, name)| catalog.find(name).ok())
+            .or_else(|| catalog.lookup(word))
+            .map(|x| x.name.clone())
+    })
+}
+
+/// Finds the unit at the start of `words`, trying two-word units such as
+/// "fluid ounces" first. Returns the unit's name and how many words it used.
+fn leading_unit(words: &[String], catalog: &UnitCatalog) -> Option<(String, usize)> {
+    if words.len() >= 2 {
+        if let Some(name) = unit_named(&format!("{} {}", words[0], words[1]), catalog) {
+            return Some((name, 2));
+        }
+    }
+    unit_named(words.first()?, catalog).map(|name| (name, 1))
+}
+
+/// Reads the quantity, or range, and unit at the start of `text`, as in
+/// "2-3 tbsp" or "1½ cups". Returns `None` if it does not start with one.
+pub fn parse_amount(text: &str, catalog: &UnitCatalog) -> Option<Amount> {
+    let words = split_words(text);
+    let (quantity, quantity_max, used) = leading_range(&words)?;
+    let (units, used) = match leading_unit(&words[used..], catalog) {
+        Some((units, unit_words)) => (Some(units), used + unit_words),
+        None => (None, used),
+    };
+    Some(Amount {
+        quantity,
+        quantity_max,
+        units,
+        rest: words[used..].join(" "),
+    })
+}
+
+/// Takes parenthesised asides such as "(about 2 cups)" out of `text`,
+/// returning what is left and the asides.
+fn take_asides(text: &str) -> (String, Vec<String>) {
+    let mut remaining = String::with_capacity(text.len());
+    let mut asides = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('(') {
+        let end = match rest[start..].find(')') {
+            Some(x) => start + x,
+            None => break,
+        };
+        remaining.push_str(&rest[..start]);
+        let aside = rest[start + 1..end].trim();
+        if !aside.is_empty() {
+            asides.push(aside.to_owned());
+        }
+        rest = &rest[end + 1..];
+    }
+    remaining.push_str(rest);
+    (
+        remaining.split_whitespace().collect::<Vec<_>>().join(" "),
+        asides,
+    )
+}
+
+/// Reads an ingredient line such as "2 1/2 cups all-purpose flour, sifted"
+/// into quantity, unit, ingredient name and preparation. Parenthesised
+/// asides join the preparation. Lines without a quantity are kept whole as
+/// the name of 1 each, and a warning says so.
+pub fn parse(line: &str, catalog: &UnitCatalog) -> ParsedIngredient {
+    let mut line = line.trim();
+    if let Some(bullet) = BULLETS.iter().find(|x| line.starts_with(*x)) {
+        line = line[bullet.len()..].trim_start();
+    }
+    let mut warnings = vec![];
+    let amount = parse_amount(line, catalog).unwrap_or_else(|| {
+        if !line.is_empty() {
+            warnings.push(format!("no quantity in `{}`, read as 1 each", line));
+        }
+        Amount {
+            quantity: 1.into(),
+            quantity_max: None,
+            units: None,
+            rest: line.to_owned(),
+        }
+    });
+    let rest = amount.rest.strip_prefix("of ").unwrap_or(&amount.rest);
+    let (rest, asides) = take_asides(rest);
+    let (name, preparation) = match rest.split_once(',') {
+        Some((name, preparation)) => (name.trim(), preparation.trim()),
+        None => (rest.trim(), ""),
+    };
+    let preparation = std::iter::once(preparation.to_owned())
+        .chain(asides)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let name = if name.is_empty() {
+        warnings.push(format!("no ingredient name in `{}`", line));
+        line
+    } else {
+        name
+    };
+    ParsedIngredient {
+        ingredient: RecipeIngredient {
+            id: None,
+            recipe_id: None,
+            ingredient: Ingredient {
+                id: None,
+                name: name.to_owned(),
+            },
+            quantity: amount.quantity,
+            units: Unit {
+                id: None,
+                name: amount.units.unwrap_or_else(|| DEFAULT_UNITS.to_owned()),
+            },
+            preparation,
+            group: None,
+            sub_recipe_id: None,
+        },
+        quantity_max: amount.quantity_max,
+        warnings,
+    }
+}
+
+/// Writes an ingredient as a line `parse` reads back, such as "2 cups flour,
+/// sifted". Counted ingredients leave out the unit, as in "3 eggs".
+pub fn format(ingredient: &RecipeIngredient) -> String {
+    let mut line = ingredient.quantity.to_string();
+    if ingredient.units.name != DEFAULT_UNITS {
+        line.push(' ');
+        line.push_str(&ingredient.units.name);
+    }
+    line.push(' ');
+    line.push_str(&ingredient.ingredient.name);
+    if !ingredient.preparation.is_empty() {
+        line.push_str(", ");
+        line.push_str(&ingredient.preparation);
+    }
+    line
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::unit::test_catalog;
+
+    fn parsed(line: &str) -> (String, Option<String>, String, String, String, usize) {
+        let parsed = parse(line, &test_catalog());
+        (
+            parsed.ingredient.quantity.to_string(),
+            parsed.quantity_max.map(|x| x.to_string()),
+            parsed.ingredient.units.name,
+            parsed.ingredient.ingredient.name,
+            parsed.ingredient.preparation,
+            parsed.warnings.len(),
+        )
+    }
+
+    fn expected(
+        quantity: &str,
+        quantity_max: Option<&str>,
+        units: &str,
+        name: &str,
+        preparation: &str,
+        warnings: usize,
+    ) -> (String, Option<String>, String, String, String, usize) {
+        (
+            quantity.to_owned(),
+            quantity_max.map(str::to_owned),
+            units.to_owned(),
+            name.to_owned(),
+            preparation.to_owned(),
+            warnings,
+        )
+    }
+
+    #[test]
+    fn test_parse_quantities_and_units() {
+        assert_eq!(
+            parsed("2 1/2 cups all-purpose flour, sifted"),
+            expected("2 1/2", None, "cups", "all-purpose flour", "sifted", 0)
+        );
+        assert_eq!(
+            parsed("1½ Tbsp. olive oil"),
+            expected("1 1/2", None, "tablespoons", "olive oil", "", 0)
+        );
+        assert_eq!(
+            parsed("1 ½ lbs potatoes"),
+            expected("1 1/2", None, "pounds", "potatoes", "", 0)
+        );
+        assert_eq!(
+            parsed("200g butter"),
+            expected("200", None, "grams", "butter", "", 0)
+        );
+        assert_eq!(
+            parsed("1 T sugar"),
+            expected("1", None, "tablespoons", "sugar", "", 0)
+        );
+        assert_eq!(
+            parsed("1 t salt"),
+            expected("1", None, "teaspoons", "salt", "", 0)
+        );
+        assert_eq!(
+            parsed("8 fl oz milk"),
+            expected("8", None, "fluid ounces", "milk", "", 0)
+        );
+        assert_eq!(
+            parsed("2 inch piece of ginger"),
+            expected("2", None, "inches", "piece of ginger", "", 0)
+        );
+        assert_eq!(
+            parsed("0.5 cup of water"),
+            expected("1/2", None, "cups", "water", "", 0)
+        );
+        assert_eq!(
+            parsed("- 3 eggs, beaten"),
+            expected("3", None, "each", "eggs", "beaten", 0)
+        );
+        assert_eq!(
+            parsed("an onion, diced"),
+            expected("1", None, "each", "onion", "diced", 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(
+            parsed("2-3 cloves garlic"),
+            expected("2", Some("3"), "each", "cloves garlic", "", 0)
+        );
+        assert_eq!(
+            parsed("1/2 – 1 tsp chili flakes"),
+            expected("1/2", Some("1"), "teaspoons", "chili flakes", "", 0)
+        );
+        assert_eq!(
+            parsed("2 to 3 cups stock"),
+            expected("2", Some("3"), "cups", "stock", "", 0)
+        );
+        // Not a range: the high end is lower.
+        assert_eq!(
+            parsed("3 or 2 eggs"),
+            expected("3", None, "each", "or 2 eggs", "", 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_asides_and_problems() {
+        assert_eq!(
+            parsed("1 (14 oz) can tomatoes, drained"),
+            expected("1", None, "each", "can tomatoes", "drained, 14 oz", 0)
+        );
+        assert_eq!(
+            parsed("salt to taste"),
+            expected("1", None, "each", "salt to taste", "", 1)
+        );
+        assert_eq!(
+            parsed("2 cups"),
+            expected("2", None, "cups", "2 cups", "", 1)
+        );
+        assert_eq!(parsed("").5, 1);
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        let catalog = test_catalog();
+        assert_eq!(
+            parse_amount("4-6 cups", &catalog),
+            Some(Amount {
+                quantity: 4.into(),
+                quantity_max: Some(6.into()),
+                units: Some("cups".to_owned()),
+                rest: "".to_owned(),
+            })
+        );
+        assert_eq!(parse_amount("a", &catalog), None);
+        assert_eq!(parse_amount("some cookies", &catalog), None);
+    }
+
+    #[test]
+    fn test_format_round_trips() {
+        let catalog = test_catalog();
+        for line in [
+            "2 1/2 cups all-purpose flour, sifted",
+            "3 eggs",
+            "1/2 fluid ounces milk",
+        ] {
+            assert_eq!(format(&parse(line, &catalog).ingredient), line);
+        }
+    }
+}
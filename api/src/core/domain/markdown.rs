@@ -0,0 +1,226 @@
+use std::fmt::Write;
+
+use super::{html, ingredient_line, schema_org, Recipe};
+
+/// Escapes the characters Markdown would read as formatting.
+pub fn escape(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        let starts_block = i == 0 && matches!(c, '#' | '>' | '-' | '+' | '=');
+        if starts_block || matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The recipe as Markdown, ready to paste into a wiki or chat.
+/// `image_url` is where the recipe's image is served from, if it has one.
+pub fn render_recipe(recipe: &Recipe, image_url: Option<&str>) -> String {
+    let mut markdown = String::new();
+    let _ = writeln!(markdown, "# {}\n", escape(&recipe.title));
+    let _ = writeln!(markdown, "*By {}*\n", escape(&recipe.author.name));
+    if let Some(description) = &recipe.description {
+        let paragraphs: Vec<String> = description.lines().map(escape).collect();
+        let _ = writeln!(markdown, "{}\n", paragraphs.join("\n"));
+    }
+    if let Some(image_url) = image_url {
+        let _ = writeln!(markdown, "![{}]({})\n", escape(&recipe.title), image_url);
+    }
+
+    let times = [
+        ("Prep", recipe.prep_time),
+        ("Cook", recipe.cook_time),
+        ("Inactive", recipe.inactive_time),
+        ("Total", schema_org::total_time(recipe)),
+    ];
+    let mut details: Vec<String> = times
+        .iter()
+        .filter_map(|(label, duration)| {
+            duration.map(|x| format!("**{}:** {}", label, html::describe_duration(x)))
+        })
+        .collect();
+    details.push(format!(
+        "**Yield:** {} {}",
+        recipe.yield_quantity,
+        escape(&recipe.yield_units.name)
+    ));
+    let _ = writeln!(markdown, "{}\n", details.join(" · "));
+
+    markdown.push_str("## Ingredients\n\n");
+    let mut groups: Vec<Option<&str>> = vec![None];
+    let mut named: Vec<_> = recipe.groups.iter().collect();
+    named.sort_by_key(|x| x.ordinal);
+    groups.extend(named.iter().map(|x| Some(x.name.as_str())));
+    for group in groups {
+        let ingredients: Vec<_> = recipe
+            .ingredients
+            .iter()
+            .filter(|x| x.group.as_deref() == group)
+            .collect();
+        if ingredients.is_empty() {
+            continue;
+        }
+        if let Some(group) = group {
+            let _ = writeln!(markdown, "### {}\n", escape(group));
+        }
+        for ingredient in ingredients {
+            let _ = writeln!(
+                markdown,
+                "- {}",
+                escape(&ingredient_line::format(ingredient))
+            );
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push_str("## Instructions\n\n");
+    for (group, steps) in schema_org::step_sections(recipe) {
+        if let Some(group) = group {
+            let _ = writeln!(markdown, "### {}\n", escape(group));
+        }
+        for step in steps {
+            let _ = writeln!(markdown, "{}. {}", step.ordinal, escape(&step.instruction));
+        }
+        markdown.push('\n');
+    }
+
+    if !recipe.tags.is_empty() {
+        let tags: Vec<String> = recipe.tags.iter().map(|x| escape(&x.name)).collect();
+        let _ = writeln!(markdown, "*Tags: {}*\n", tags.join(", "));
+    }
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::{
+        recipe::{Ingredient, RecipeGroup, RecipeIngredient, Step, Unit},
+        Tag, User,
+    };
+
+    fn recipe() -> Recipe {
+        let ingredient =
+            |name: &str, quantity: i32, units: &str, group: Option<&str>| RecipeIngredient {
+                id: None,
+                recipe_id: None,
+                ingredient: Ingredient {
+                    id: None,
+                    name: name.to_owned(),
+                },
+                quantity: quantity.into(),
+                units: Unit {
+                    id: None,
+                    name: units.to_owned(),
+                },
+                preparation: "".to_owned(),
+                group: group.map(str::to_owned),
+                sub_recipe_id: None,
+            };
+        let step = |ordinal, instruction: &str, group: Option<&str>| Step {
+            id: None,
+            recipe_id: None,
+            ordinal,
+            instruction: instruction.to_owned(),
+            group: group.map(str::to_owned),
+            image: None,
+        };
+        Recipe {
+            id: Some(1),
+            title: "Mac & Cheese".to_owned(),
+            description: Some("Extra *creamy*\n\n# 1 comfort food".to_owned()),
+            author: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            prep_time: Some(chrono::Duration::minutes(10)),
+            cook_time: Some(chrono::Duration::minutes(80)),
+            inactive_time: None,
+            yield_quantity: 4.into(),
+            yield_units: Unit {
+                id: None,
+                name: "servings".to_owned(),
+            },
+            ingredients: vec![
+                ingredient("cheddar", 200, "grams", Some("Sauce")),
+                ingredient("macaroni", 250, "grams", None),
+                ingredient("eggs", 2, "each", None),
+            ],
+            steps: vec![
+                step(3, "Stir in the sauce.", None),
+                step(2, "Make the sauce.", Some("Sauce")),
+                step(1, "Boil the macaroni.", None),
+            ],
+            groups: vec![RecipeGroup {
+                id: None,
+                name: "Sauce".to_owned(),
+                ordinal: 1,
+            }],
+            tags: vec![Tag {
+                id: None,
+                name: "comfort".to_owned(),
+            }],
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: Default::default(),
+            draft: false,
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a *b* [c]"), "a \\*b\\* \\[c\\]");
+        assert_eq!(escape("# not a heading"), "\\# not a heading");
+        assert_eq!(escape("line\nbreak"), "line break");
+    }
+
+    #[test]
+    fn test_render_recipe() {
+        assert_eq!(
+            render_recipe(&recipe(), Some("/media/recipe/1/a.png")),
+            "\
+# Mac & Cheese
+
+*By Matt*
+
+Extra \\*creamy\\*
+
+\\# 1 comfort food
+
+![Mac & Cheese](/media/recipe/1/a.png)
+
+**Prep:** 10 min · **Cook:** 1 hr 20 min · **Total:** 1 hr 30 min · **Yield:** 4 servings
+
+## Ingredients
+
+- 250 grams macaroni
+- 2 eggs
+
+### Sauce
+
+- 200 grams cheddar
+
+## Instructions
+
+1. Boil the macaroni.
+
+### Sauce
+
+2. Make the sauce.
+
+3. Stir in the sauce.
+
+*Tags: comfort*
+"
+        );
+    }
+}
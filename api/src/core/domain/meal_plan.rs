@@ -0,0 +1,325 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use thiserror::Error;
+
+use super::{
+    recipe::{self, ScaleBy},
+    shopping_list, Quantity, Recipe, User,
+};
+
+/// The longest date range, in days, that a plan can be read for at once.
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+/// The yield units a planned servings count scales a recipe to.
+const SERVINGS_UNITS: &str = "servings";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("meal plan entry with id `{0}` not found")]
+    EntryNotFound(i32),
+    #[error("meal plan entry with id `{0}` belongs to another user")]
+    NotOwner(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("invalid meal plan: {0}")]
+    InvalidMealPlan(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            recipe::Error::Unexpected => Self::Unexpected,
+            e => Self::InvalidMealPlan(e.to_string()),
+        }
+    }
+}
+
+impl From<shopping_list::Error> for Error {
+    fn from(value: shopping_list::Error) -> Self {
+        match value {
+            shopping_list::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            shopping_list::Error::Unexpected => Self::Unexpected,
+            e => Self::InvalidMealPlan(e.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "meal_slot", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl MealSlot {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Breakfast => "Breakfast",
+            Self::Lunch => "Lunch",
+            Self::Dinner => "Dinner",
+            Self::Snack => "Snack",
+        }
+    }
+
+    /// When the meal goes on the calendar.
+    fn start_time(&self) -> NaiveTime {
+        let hour = match self {
+            Self::Breakfast => 8,
+            Self::Lunch => 12,
+            Self::Snack => 15,
+            Self::Dinner => 18,
+        };
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default()
+    }
+}
+
+/// A recipe planned for a meal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MealPlanEntry {
+    pub id: Option<i32>,
+    pub owner: User,
+    pub date: NaiveDate,
+    pub slot: MealSlot,
+    pub recipe_id: i32,
+    pub recipe_title: String,
+    pub servings: Quantity,
+}
+
+impl<'r> FromRow<'r, PgRow> for MealPlanEntry {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(MealPlanEntry {
+            id: row.try_get("id")?,
+            owner: User {
+                id: row.try_get("owner_id")?,
+                name: row.try_get("owner_name")?,
+            },
+            date: row.try_get("date")?,
+            slot: row.try_get("slot")?,
+            recipe_id: row.try_get("recipe_id")?,
+            recipe_title: row.try_get("recipe_title")?,
+            servings: row.try_get("servings")?,
+        })
+    }
+}
+
+impl MealPlanEntry {
+    pub fn validate(&self) -> Result<(), Error> {
+        if !self.servings.is_positive() {
+            return Err(Error::InvalidMealPlan(
+                "servings must be greater than zero".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The planned meals of a user between two dates, inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MealPlanQuery {
+    pub owner_id: i32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl MealPlanQuery {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.to < self.from {
+            return Err(Error::InvalidMealPlan(
+                "`to` must not be before `from`".to_owned(),
+            ));
+        }
+        if (self.to - self.from).num_days() >= MAX_RANGE_DAYS {
+            return Err(Error::InvalidMealPlan(format!(
+                "at most {} days can be read at once",
+                MAX_RANGE_DAYS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The recipes planned in `entries` with their servings added up, in the
+/// order they are first planned.
+pub fn planned_recipes(entries: &[MealPlanEntry]) -> Result<Vec<(i32, Quantity)>, Error> {
+    let mut planned: Vec<(i32, Quantity)> = Vec::new();
+    for entry in entries {
+        match planned.iter_mut().find(|(id, _)| *id == entry.recipe_id) {
+            Some((_, servings)) => {
+                *servings = servings
+                    .checked_add(entry.servings)
+                    .ok_or_else(|| Error::InvalidMealPlan("too many servings".to_owned()))?;
+            }
+            None => planned.push((entry.recipe_id, entry.servings)),
+        }
+    }
+    Ok(planned)
+}
+
+/// How far to scale a recipe to make `servings`. Recipes that don't yield
+/// servings, e.g. a loaf of bread, are made once as written.
+pub fn servings_scale(recipe: &Recipe, servings: Quantity) -> ScaleBy {
+    if recipe.yield_units.name == SERVINGS_UNITS {
+        ScaleBy::Yield(servings)
+    } else {
+        ScaleBy::Factor(1.into())
+    }
+}
+
+/// Escapes an iCalendar TEXT value.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line so that no line is longer than 75 octets, as
+/// iCalendar requires, and ends it with CRLF.
+fn push_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// The entries as an iCalendar document with one event per meal. Times are
+/// left floating so that meals show at the same hour in any time zone.
+pub fn to_icalendar(entries: &[MealPlanEntry], stamp: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//Stockpot//Meal Plan//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, "X-WR-CALNAME:Meal Plan");
+    for entry in entries {
+        let start = entry.date.and_time(entry.slot.start_time());
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(
+            &mut ics,
+            &format!("UID:meal-plan-{}@stockpot", entry.id.unwrap_or_default()),
+        );
+        push_line(
+            &mut ics,
+            &format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        );
+        push_line(
+            &mut ics,
+            &format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")),
+        );
+        push_line(&mut ics, "DURATION:PT1H");
+        let summary = format!(
+            "{}: {} ({} servings)",
+            entry.slot.name(),
+            entry.recipe_title,
+            entry.servings
+        );
+        push_line(&mut ics, &format!("SUMMARY:{}", escape_text(&summary)));
+        push_line(&mut ics, "END:VEVENT");
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn entry(id: i32, day: u32, slot: MealSlot, recipe_id: i32, title: &str) -> MealPlanEntry {
+        MealPlanEntry {
+            id: Some(id),
+            owner: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            date: NaiveDate::from_ymd_opt(2026, 10, day).unwrap(),
+            slot,
+            recipe_id,
+            recipe_title: title.to_owned(),
+            servings: 2.into(),
+        }
+    }
+
+    #[test]
+    fn test_planned_recipes() {
+        let entries = [
+            entry(1, 19, MealSlot::Dinner, 5, "Soup"),
+            entry(2, 20, MealSlot::Lunch, 6, "Salad"),
+            entry(3, 21, MealSlot::Lunch, 5, "Soup"),
+        ];
+        assert_eq!(
+            planned_recipes(&entries).unwrap(),
+            vec![(5, 4.into()), (6, 2.into())]
+        );
+    }
+
+    #[test]
+    fn test_query_validate() {
+        let query = |from: u32, to: u32| MealPlanQuery {
+            owner_id: 1,
+            from: NaiveDate::from_ymd_opt(2026, 10, from).unwrap(),
+            to: NaiveDate::from_ymd_opt(2026, 10, to).unwrap(),
+        };
+        assert!(query(19, 19).validate().is_ok());
+        assert!(matches!(
+            query(20, 19).validate(),
+            Err(Error::InvalidMealPlan(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_icalendar() {
+        let stamp = DateTime::parse_from_rfc3339("2026-10-18T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let ics = to_icalendar(
+            &[entry(
+                7,
+                19,
+                MealSlot::Dinner,
+                5,
+                "Soup, with bread; and a very long title that needs folding",
+            )],
+            stamp,
+        );
+        assert_eq!(
+            ics,
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//Stockpot//Meal Plan//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             X-WR-CALNAME:Meal Plan\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:meal-plan-7@stockpot\r\n\
+             DTSTAMP:20261018T093000Z\r\n\
+             DTSTART:20261019T180000\r\n\
+             DURATION:PT1H\r\n\
+             SUMMARY:Dinner: Soup\\, with bread\\; and a very long title that needs foldin\r\n \
+             g (2 servings)\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+}
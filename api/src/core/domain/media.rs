@@ -0,0 +1,294 @@
+use std::io::Cursor;
+
+use image::{codecs::jpeg::JpegEncoder, ImageReader, Limits};
+use rand::{distributions::Alphanumeric, Rng};
+use thiserror::Error;
+
+use super::recipe;
+
+/// Largest image upload accepted, in bytes.
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+/// Largest width or height of an uploaded image, in pixels.
+pub const MAX_IMAGE_DIMENSION: u32 = 8192;
+/// Thumbnails fit within a square of this many pixels.
+pub const THUMBNAIL_SIZE: u32 = 320;
+const THUMBNAIL_QUALITY: u8 = 80;
+const KEY_SUFFIX_LENGTH: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("step with id `{0}` not found")]
+    StepNotFound(i32),
+    #[error("media `{0}` not found")]
+    MediaNotFound(String),
+    #[error("unsupported content type `{0}`, expected image/jpeg, image/png or image/webp")]
+    UnsupportedContentType(String),
+    #[error("upload is larger than the {0} byte limit")]
+    TooLarge(usize),
+    #[error("invalid image: {0}")]
+    InvalidImage(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            _ => Self::Unexpected,
+        }
+    }
+}
+
+/// The image formats accepted for upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn from_content_type(content_type: &str) -> Result<ImageFormat, Error> {
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "image/jpeg" | "image/jpg" => Ok(Self::Jpeg),
+            "image/png" => Ok(Self::Png),
+            "image/webp" => Ok(Self::Webp),
+            _ => Err(Error::UnsupportedContentType(content_type.to_owned())),
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+
+    fn codec(&self) -> image::ImageFormat {
+        match self {
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Png => image::ImageFormat::Png,
+            Self::Webp => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// A stored file and its content type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Media {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// An uploaded image that has been checked to be within the size limits and
+/// to actually contain an image of the format it claims.
+#[derive(Debug, Clone)]
+pub struct ImageUpload {
+    format: ImageFormat,
+    bytes: Vec<u8>,
+    image: image::DynamicImage,
+}
+
+impl ImageUpload {
+    pub fn new(content_type: &str, bytes: Vec<u8>) -> Result<ImageUpload, Error> {
+        let format = ImageFormat::from_content_type(content_type)?;
+        if bytes.len() > MAX_IMAGE_BYTES {
+            return Err(Error::TooLarge(MAX_IMAGE_BYTES));
+        }
+        match image::guess_format(&bytes) {
+            Ok(guessed) if guessed == format.codec() => {}
+            _ => {
+                return Err(Error::InvalidImage(format!(
+                    "content is not {}",
+                    format.content_type()
+                )))
+            }
+        }
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+        limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+        let mut reader = ImageReader::with_format(Cursor::new(&bytes), format.codec());
+        reader.limits(limits);
+        let image = reader
+            .decode()
+            .map_err(|e| Error::InvalidImage(e.to_string()))?;
+        Ok(ImageUpload {
+            format,
+            bytes,
+            image,
+        })
+    }
+
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    pub fn into_media(self) -> Media {
+        Media {
+            content_type: self.format.content_type().to_owned(),
+            bytes: self.bytes,
+        }
+    }
+
+    /// A JPEG copy of the image shrunk to fit within `THUMBNAIL_SIZE`,
+    /// keeping its aspect ratio. Smaller images are not enlarged.
+    pub fn thumbnail(&self) -> Result<Media, Error> {
+        let thumbnail =
+            if self.image.width() > THUMBNAIL_SIZE || self.image.height() > THUMBNAIL_SIZE {
+                self.image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            } else {
+                self.image.clone()
+            };
+        let mut bytes = Vec::new();
+        JpegEncoder::new_with_quality(&mut bytes, THUMBNAIL_QUALITY)
+            .encode_image(&thumbnail.to_rgb8())
+            .map_err(|_| Error::Unexpected)?;
+        Ok(Media {
+            content_type: ImageFormat::Jpeg.content_type().to_owned(),
+            bytes,
+        })
+    }
+}
+
+/// A fresh, unguessable key for an image stored under `prefix`.
+pub fn image_key(prefix: &str, format: ImageFormat) -> String {
+    let suffix: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(KEY_SUFFIX_LENGTH)
+        .map(|x| char::from(x).to_ascii_lowercase())
+        .collect();
+    format!("{}/{}.{}", prefix, suffix, format.extension())
+}
+
+/// The key of the thumbnail stored alongside the image at `key`.
+pub fn thumbnail_key(key: &str) -> String {
+    let stem = key.rsplit_once('.').map_or(key, |(stem, _)| stem);
+    format!("{}.thumb.{}", stem, ImageFormat::Jpeg.extension())
+}
+
+/// Checks that a key is made of simple path segments, so it can be used as a
+/// file path or object name without escaping its store.
+pub fn validate_key(key: &str) -> Result<(), Error> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with('.')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::MediaNotFound(key.to_owned()))
+    }
+}
+
+/// The content type to serve a stored key with, judged by its extension.
+pub fn content_type_for_key(key: &str) -> &'static str {
+    key.rsplit_once('.')
+        .and_then(|(_, extension)| ImageFormat::from_extension(extension))
+        .map_or("application/octet-stream", |x| x.content_type())
+}
+
+#[cfg(test)]
+mod test {
+
+    use image::{ImageBuffer, Rgb};
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = ImageBuffer::from_pixel(width, height, Rgb([200u8, 100, 50]));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_image_upload_validates_content() {
+        assert!(ImageUpload::new("image/png", png(4, 4)).is_ok());
+        assert!(matches!(
+            ImageUpload::new("image/gif", png(4, 4)).unwrap_err(),
+            Error::UnsupportedContentType(_)
+        ));
+        assert!(matches!(
+            ImageUpload::new("image/jpeg", png(4, 4)).unwrap_err(),
+            Error::InvalidImage(_)
+        ));
+        let mut truncated = png(4, 4);
+        truncated.truncate(truncated.len() / 2);
+        assert!(matches!(
+            ImageUpload::new("image/png", truncated).unwrap_err(),
+            Error::InvalidImage(_)
+        ));
+        assert!(matches!(
+            ImageUpload::new("image/png", vec![0; MAX_IMAGE_BYTES + 1]).unwrap_err(),
+            Error::TooLarge(_)
+        ));
+    }
+
+    #[test]
+    fn test_thumbnail_fits_within_size() {
+        let thumbnail = ImageUpload::new("image/png", png(1000, 500))
+            .unwrap()
+            .thumbnail()
+            .unwrap();
+        assert_eq!(thumbnail.content_type, "image/jpeg");
+        let image = image::load_from_memory(&thumbnail.bytes).unwrap();
+        assert_eq!(
+            (image.width(), image.height()),
+            (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2)
+        );
+
+        let thumbnail = ImageUpload::new("image/png", png(10, 20))
+            .unwrap()
+            .thumbnail()
+            .unwrap();
+        let image = image::load_from_memory(&thumbnail.bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (10, 20));
+    }
+
+    #[test]
+    fn test_keys() {
+        let key = image_key("recipe/4", ImageFormat::Png);
+        assert!(key.starts_with("recipe/4/"));
+        assert!(key.ends_with(".png"));
+        assert!(validate_key(&key).is_ok());
+        assert_eq!(thumbnail_key("recipe/4/abc.png"), "recipe/4/abc.thumb.jpg");
+        assert_eq!(content_type_for_key("recipe/4/abc.thumb.jpg"), "image/jpeg");
+        for key in [
+            "",
+            "../etc/passwd",
+            "recipe//a.png",
+            "recipe/.hidden",
+            "a b.png",
+        ] {
+            assert!(validate_key(key).is_err());
+        }
+    }
+}
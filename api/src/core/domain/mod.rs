@@ -6,3 +6,22 @@ pub use self::auth::AuthUserCredentials;
 pub use self::auth::UserCredentials;
 pub mod recipe;
 pub use self::recipe::Recipe;
+pub mod tag;
+pub use self::tag::Tag;
+pub mod quantity;
+pub use self::quantity::Quantity;
+pub mod archive;
+pub mod collection;
+pub mod cook_log;
+pub mod cooklang;
+pub mod html;
+pub mod ingredient_line;
+pub mod markdown;
+pub mod meal_plan;
+pub mod media;
+pub mod pantry;
+pub mod review;
+pub mod revision;
+pub mod schema_org;
+pub mod shopping_list;
+pub mod unit;
@@ -0,0 +1,452 @@
+use chrono::{Duration, NaiveDate};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use thiserror::Error;
+
+use super::{
+    recipe::{self, Ingredient, RecipeIngredient, Unit},
+    unit::{self, UnitCatalog, UnitDefinition},
+    Quantity, User,
+};
+
+/// How many days ahead items count as expiring soon, unless asked otherwise.
+pub const DEFAULT_EXPIRING_DAYS: i64 = 7;
+
+/// The furthest ahead, in days, that expiring items can be looked for.
+pub const MAX_EXPIRING_DAYS: i64 = 366;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("pantry item with id `{0}` not found")]
+    ItemNotFound(i32),
+    #[error("pantry item with id `{0}` belongs to another user")]
+    NotOwner(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("unknown unit `{0}`")]
+    UnknownUnit(String),
+    #[error("cannot convert `{0}` to `{1}`")]
+    IncompatibleUnits(String, String),
+    #[error("not enough in stock: {0}")]
+    InsufficientStock(String),
+    #[error("cannot cook recipe: {0}")]
+    CannotCook(String),
+    #[error("invalid pantry item: {0}")]
+    InvalidPantryItem(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            recipe::Error::UnknownUnit(name) => Self::UnknownUnit(name),
+            recipe::Error::Unexpected => Self::Unexpected,
+            e => Self::CannotCook(e.to_string()),
+        }
+    }
+}
+
+impl From<unit::Error> for Error {
+    fn from(value: unit::Error) -> Self {
+        match value {
+            unit::Error::UnknownUnit(name) => Self::UnknownUnit(name),
+            unit::Error::IncompatibleUnits(from, to) => Self::IncompatibleUnits(from, to),
+            unit::Error::Unexpected => Self::Unexpected,
+        }
+    }
+}
+
+/// Moves an amount between units, keeping it exact when they are the same
+/// and otherwise to a millionth of the target units. It is not rounded the
+/// way the units are written, as e.g. 4 grams would then be no kilograms.
+fn convert(
+    quantity: Quantity,
+    from: &str,
+    to: &str,
+    catalog: &UnitCatalog,
+) -> Result<Quantity, Error> {
+    if from == to {
+        return Ok(quantity);
+    }
+    let converted = catalog.convert(quantity.to_f64(), from, to)?;
+    Quantity::new((converted * 1_000_000.0).round() as i64, 1_000_000)
+        .map_err(|_| Error::Unexpected)
+}
+
+/// Something in stock. Several items may hold the same ingredient, e.g.
+/// two bags of flour kept in different places or bought on different days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PantryItem {
+    pub id: Option<i32>,
+    pub owner: User,
+    pub ingredient: Ingredient,
+    pub quantity: Quantity,
+    pub units: Unit,
+    pub location: String,
+    pub expires_on: Option<NaiveDate>,
+}
+
+impl<'r> FromRow<'r, PgRow> for PantryItem {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(PantryItem {
+            id: row.try_get("id")?,
+            owner: User {
+                id: row.try_get("owner_id")?,
+                name: row.try_get("owner_name")?,
+            },
+            ingredient: Ingredient {
+                id: row.try_get("ingredient_id")?,
+                name: row.try_get("ingredient_name")?,
+            },
+            quantity: row.try_get("quantity")?,
+            units: Unit {
+                id: row.try_get("units_id")?,
+                name: row.try_get("units_name")?,
+            },
+            location: row.try_get("location")?,
+            expires_on: row.try_get("expires_on")?,
+        })
+    }
+}
+
+/// Whether an amount is taken out of stock or put into it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StockChange {
+    Add(Quantity),
+    Remove(Quantity),
+}
+
+impl PantryItem {
+    /// Trims the item's names, failing if it has no ingredient or a
+    /// negative quantity.
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.ingredient.name = self.ingredient.name.trim().to_owned();
+        self.location = self.location.trim().to_owned();
+        if self.ingredient.name.is_empty() {
+            return Err(Error::InvalidPantryItem(
+                "name must not be empty".to_owned(),
+            ));
+        }
+        if self.quantity.numerator() < 0 {
+            return Err(Error::InvalidPantryItem(
+                "quantity must not be negative".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn is_same_ingredient(&self, ingredient: &Ingredient) -> bool {
+        match (self.ingredient.id, ingredient.id) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.ingredient.name == ingredient.name,
+        }
+    }
+
+    /// Whether the item's units measure the same thing as `units`.
+    fn can_measure(&self, units: &UnitDefinition, catalog: &UnitCatalog) -> bool {
+        catalog
+            .find(&self.units.name)
+            .is_ok_and(|x| x.dimension == units.dimension)
+    }
+
+    /// Adds to or removes from the item an amount given in `units`, or in
+    /// the item's own units if left out. Removing more than is in stock
+    /// fails and leaves the item alone.
+    pub fn adjust(
+        &mut self,
+        change: StockChange,
+        units: Option<&str>,
+        catalog: &UnitCatalog,
+    ) -> Result<(), Error> {
+        let units = units.unwrap_or(&self.units.name);
+        let quantity = match change {
+            StockChange::Add(amount) => {
+                let amount = convert(amount, units, &self.units.name, catalog)?;
+                self.quantity.checked_add(amount)
+            }
+            StockChange::Remove(amount) => {
+                let amount = convert(amount, units, &self.units.name, catalog)?;
+                self.quantity.checked_sub(amount)
+            }
+        }
+        .ok_or_else(|| Error::InvalidPantryItem("quantity is too large".to_owned()))?;
+        if quantity.numerator() < 0 {
+            return Err(Error::InsufficientStock(format!(
+                "only {} {} of {} left",
+                self.quantity, self.units.name, self.ingredient.name
+            )));
+        }
+        self.quantity = quantity;
+        Ok(())
+    }
+
+    /// Takes up to `needed`, given in `units`, out of the item, returning
+    /// how much of it the item could not cover.
+    fn take(
+        &mut self,
+        needed: Quantity,
+        units: &str,
+        catalog: &UnitCatalog,
+    ) -> Result<Quantity, Error> {
+        let wanted = convert(needed, units, &self.units.name, catalog)?;
+        match self.quantity.checked_sub(wanted) {
+            Some(left) if left.numerator() >= 0 => {
+                self.quantity = left;
+                Ok(0.into())
+            }
+            _ => {
+                let available = convert(self.quantity, &self.units.name, units, catalog)?;
+                self.quantity = 0.into();
+                Ok(needed
+                    .checked_sub(available)
+                    .filter(|x| x.is_positive())
+                    .unwrap_or_else(|| 0.into()))
+            }
+        }
+    }
+}
+
+/// Which of a user's items to read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PantryQuery {
+    pub owner_id: i32,
+    pub location: Option<String>,
+    /// Only items still in stock that expire on or before this day, soonest
+    /// first.
+    pub expires_by: Option<NaiveDate>,
+}
+
+impl PantryQuery {
+    /// The items in stock that expire within `days` of `today`, including
+    /// those already past their date.
+    pub fn expiring(owner_id: i32, today: NaiveDate, days: i64) -> Result<Self, Error> {
+        if !(0..=MAX_EXPIRING_DAYS).contains(&days) {
+            return Err(Error::InvalidPantryItem(format!(
+                "days must be between 0 and {}",
+                MAX_EXPIRING_DAYS
+            )));
+        }
+        Ok(Self {
+            owner_id,
+            location: None,
+            expires_by: Some(today + Duration::days(days)),
+        })
+    }
+}
+
+/// An amount of an ingredient that a recipe needed but the pantry lacked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortfall {
+    pub ingredient: Ingredient,
+    pub quantity: Quantity,
+    pub units: Unit,
+}
+
+/// What cooking a recipe took from the pantry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CookReport {
+    pub recipe_id: i32,
+    pub title: String,
+    pub scale: Quantity,
+    /// The items drawn from, with what is left of them.
+    pub used: Vec<PantryItem>,
+    pub shortfalls: Vec<Shortfall>,
+}
+
+/// Takes a recipe's ingredients out of the pantry, drawing first on the
+/// items that expire soonest, and returns what could not be covered. Items
+/// that run out are left in the pantry with nothing in them.
+pub fn cook(
+    items: &mut [PantryItem],
+    ingredients: Vec<RecipeIngredient>,
+    catalog: &UnitCatalog,
+) -> Result<Vec<Shortfall>, Error> {
+    items.sort_by_key(|x| (x.expires_on.is_none(), x.expires_on, x.id));
+    let mut shortfalls: Vec<Shortfall> = Vec::new();
+    for ingredient in ingredients {
+        let units = catalog.find(&ingredient.units.name)?;
+        let mut needed = ingredient.quantity;
+        for item in items.iter_mut().filter(|x| {
+            x.is_same_ingredient(&ingredient.ingredient) && x.can_measure(units, catalog)
+        }) {
+            if !needed.is_positive() {
+                break;
+            }
+            needed = item.take(needed, &units.name, catalog)?;
+        }
+        if !needed.is_positive() {
+            continue;
+        }
+        let existing = shortfalls.iter_mut().find(|x| {
+            x.ingredient.name == ingredient.ingredient.name && x.units.name == units.name
+        });
+        match existing {
+            Some(shortfall) => {
+                shortfall.quantity = shortfall
+                    .quantity
+                    .checked_add(needed)
+                    .ok_or_else(|| Error::CannotCook("quantity is too large".to_owned()))?;
+            }
+            None => shortfalls.push(Shortfall {
+                ingredient: ingredient.ingredient,
+                quantity: needed,
+                units: ingredient.units,
+            }),
+        }
+    }
+    Ok(shortfalls)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::unit::test_catalog;
+
+    fn item(
+        id: i32,
+        name: &str,
+        quantity: Quantity,
+        units: &str,
+        expires_on: Option<u32>,
+    ) -> PantryItem {
+        PantryItem {
+            id: Some(id),
+            owner: User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            ingredient: Ingredient {
+                id: None,
+                name: name.to_owned(),
+            },
+            quantity,
+            units: Unit {
+                id: None,
+                name: units.to_owned(),
+            },
+            location: "Cupboard".to_owned(),
+            expires_on: expires_on.map(|day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap()),
+        }
+    }
+
+    fn ingredient(name: &str, quantity: Quantity, units: &str) -> RecipeIngredient {
+        RecipeIngredient {
+            id: None,
+            recipe_id: None,
+            ingredient: Ingredient {
+                id: None,
+                name: name.to_owned(),
+            },
+            quantity,
+            units: Unit {
+                id: None,
+                name: units.to_owned(),
+            },
+            preparation: String::new(),
+            group: None,
+            sub_recipe_id: None,
+        }
+    }
+
+    #[test]
+    fn test_adjust() {
+        let catalog = test_catalog();
+        let mut flour = item(1, "flour", 1.into(), "kilograms", None);
+        flour
+            .adjust(StockChange::Add(500.into()), Some("grams"), &catalog)
+            .unwrap();
+        assert_eq!(flour.quantity, Quantity::new(3, 2).unwrap());
+        flour
+            .adjust(StockChange::Remove(1.into()), None, &catalog)
+            .unwrap();
+        assert_eq!(flour.quantity, Quantity::new(1, 2).unwrap());
+        assert!(matches!(
+            flour.adjust(StockChange::Remove(1.into()), None, &catalog),
+            Err(Error::InsufficientStock(_))
+        ));
+        assert_eq!(flour.quantity, Quantity::new(1, 2).unwrap());
+        assert_eq!(
+            flour.adjust(StockChange::Add(1.into()), Some("milliliters"), &catalog),
+            Err(Error::IncompatibleUnits(
+                "milliliters".to_owned(),
+                "kilograms".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_small_amounts_of_larger_units() {
+        let catalog = test_catalog();
+        let mut flour = item(1, "flour", 1.into(), "kilograms", None);
+        flour
+            .adjust(StockChange::Remove(5.into()), Some("grams"), &catalog)
+            .unwrap();
+        assert_eq!(flour.quantity, Quantity::new(199, 200).unwrap());
+        let mut items = vec![flour];
+        let shortfalls = cook(
+            &mut items,
+            vec![ingredient("flour", 996.into(), "grams")],
+            &catalog,
+        )
+        .unwrap();
+        assert_eq!(items[0].quantity, 0.into());
+        assert_eq!(shortfalls[0].quantity, 1.into());
+        assert_eq!(shortfalls[0].units.name, "grams");
+    }
+
+    #[test]
+    fn test_cook_uses_soonest_expiring_first() {
+        let catalog = test_catalog();
+        let mut items = vec![
+            item(1, "flour", 1.into(), "kilograms", None),
+            item(2, "flour", 200.into(), "grams", Some(20)),
+            item(3, "milk", 100.into(), "milliliters", Some(19)),
+        ];
+        let shortfalls = cook(
+            &mut items,
+            vec![
+                ingredient("flour", 500.into(), "grams"),
+                ingredient("milk", 250.into(), "milliliters"),
+                ingredient("eggs", 2.into(), "grams"),
+                ingredient("milk", 50.into(), "milliliters"),
+            ],
+            &catalog,
+        )
+        .unwrap();
+        let left: Vec<(Option<i32>, Quantity)> = items.iter().map(|x| (x.id, x.quantity)).collect();
+        assert_eq!(
+            left,
+            vec![
+                (Some(3), 0.into()),
+                (Some(2), 0.into()),
+                (Some(1), Quantity::new(7, 10).unwrap()),
+            ]
+        );
+        let shortfalls: Vec<(String, Quantity)> = shortfalls
+            .into_iter()
+            .map(|x| (x.ingredient.name, x.quantity))
+            .collect();
+        assert_eq!(
+            shortfalls,
+            vec![
+                ("milk".to_owned(), 200.into()),
+                ("eggs".to_owned(), 2.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expiring() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(
+            PantryQuery::expiring(1, today, 7).unwrap().expires_by,
+            NaiveDate::from_ymd_opt(2026, 10, 25)
+        );
+        assert!(matches!(
+            PantryQuery::expiring(1, today, -1),
+            Err(Error::InvalidPantryItem(_))
+        ));
+    }
+}
@@ -0,0 +1,404 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("invalid quantity `{0}`")]
+    InvalidQuantity(String),
+}
+
+/// Denominators that read naturally in a recipe, e.g. "1/3 cup" or
+/// "1 1/2 tsp". Anything else is shown as a decimal where that is exact.
+const FRIENDLY_DENOMINATORS: [i64; 6] = [1, 2, 3, 4, 8, 16];
+
+/// How far, relative to the original value, an approximated quantity may
+/// drift in order to land on a friendly fraction.
+const APPROXIMATION_TOLERANCE: f64 = 0.05;
+
+/// The most digits accepted or shown after a decimal point.
+const MAX_DECIMAL_PLACES: u32 = 9;
+
+const VULGAR_FRACTIONS: [(char, i64, i64); 15] = [
+    ('½', 1, 2),
+    ('⅓', 1, 3),
+    ('⅔', 2, 3),
+    ('¼', 1, 4),
+    ('¾', 3, 4),
+    ('⅕', 1, 5),
+    ('⅖', 2, 5),
+    ('⅗', 3, 5),
+    ('⅘', 4, 5),
+    ('⅙', 1, 6),
+    ('⅚', 5, 6),
+    ('⅛', 1, 8),
+    ('⅜', 3, 8),
+    ('⅝', 5, 8),
+    ('⅞', 7, 8),
+];
+
+/// An exact rational quantity, always kept in lowest terms with a positive
+/// denominator so that equal amounts compare and hash equal.
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "t_quantity")]
+pub struct Quantity {
+    numerator: i64,
+    denominator: i64,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Quantity {
+    pub fn new(numerator: i64, denominator: i64) -> Result<Self, Error> {
+        Self::reduce(numerator as i128, denominator as i128)
+            .ok_or_else(|| Error::InvalidQuantity(format!("{}/{}", numerator, denominator)))
+    }
+
+    /// Brings a fraction into lowest terms, failing if the denominator is
+    /// zero or the result does not fit.
+    fn reduce(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Some(Self {
+            numerator: i64::try_from(numerator / divisor).ok()?,
+            denominator: i64::try_from(denominator / divisor).ok()?,
+        })
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * other.denominator as i128
+                + other.numerator as i128 * self.denominator as i128,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * other.denominator as i128
+                - other.numerator as i128 * self.denominator as i128,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * other.numerator as i128,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * other.denominator as i128,
+            self.denominator as i128 * other.numerator as i128,
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.numerator > 0
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// Rounds a computed value, such as the result of a unit conversion, to
+    /// the simplest friendly fraction that is close enough, otherwise to the
+    /// nearest thousandth.
+    pub fn approximate(value: f64) -> Self {
+        FRIENDLY_DENOMINATORS
+            .iter()
+            .map(|&d| Self::new((value * d as f64).round() as i64, d).unwrap())
+            .find(|x| (x.to_f64() - value).abs() <= value.abs() * APPROXIMATION_TOLERANCE)
+            .unwrap_or_else(|| Self::new((value * 1000.0).round() as i64, 1000).unwrap())
+    }
+
+    /// The number of decimal places needed to write the quantity out exactly,
+    /// if there are few enough of them to be readable.
+    fn decimal_places(&self) -> Option<u32> {
+        (0..=MAX_DECIMAL_PLACES).find(|&places| 10_i64.pow(places) % self.denominator == 0)
+    }
+
+    fn parse_whole(s: &str) -> Option<i64> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+
+    fn parse_decimal(s: &str) -> Option<Self> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && fraction.is_empty() || fraction.len() > MAX_DECIMAL_PLACES as usize {
+            return None;
+        }
+        let denominator = 10_i64.pow(fraction.len() as u32);
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            Self::parse_whole(whole)?
+        };
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            Self::parse_whole(fraction)?
+        };
+        Self::new(
+            whole.checked_mul(denominator)?.checked_add(fraction)?,
+            denominator,
+        )
+        .ok()
+    }
+
+    fn parse_fraction(s: &str) -> Option<Self> {
+        if let Some((numerator, denominator)) = s.split_once('/') {
+            return Self::new(
+                Self::parse_whole(numerator)?,
+                Self::parse_whole(denominator)?,
+            )
+            .ok();
+        }
+        let mut chars = s.chars();
+        let last = chars.next_back()?;
+        let (_, numerator, denominator) = VULGAR_FRACTIONS.iter().find(|(c, _, _)| *c == last)?;
+        let whole = chars.as_str();
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            Self::parse_whole(whole)?
+        };
+        Self::new(
+            whole.checked_mul(*denominator)?.checked_add(*numerator)?,
+            *denominator,
+        )
+        .ok()
+    }
+
+    fn parse_term(s: &str) -> Option<Self> {
+        Self::parse_decimal(s).or_else(|| Self::parse_fraction(s))
+    }
+}
+
+impl From<i64> for Quantity {
+    fn from(value: i64) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+}
+
+impl From<i32> for Quantity {
+    fn from(value: i32) -> Self {
+        Self::from(value as i64)
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = Error;
+
+    /// Accepts whole numbers ("2"), decimals ("0.75"), fractions ("1/2",
+    /// "½") and mixed numbers ("1 1/2", "1½").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidQuantity(s.to_owned());
+        let terms: Vec<&str> = s.split_whitespace().collect();
+        match terms[..] {
+            [term] => Self::parse_term(term).ok_or_else(invalid),
+            [whole, fraction] => {
+                let whole = Self::parse_whole(whole).ok_or_else(invalid)?;
+                let fraction = Self::parse_fraction(fraction).ok_or_else(invalid)?;
+                if fraction.numerator >= fraction.denominator {
+                    return Err(invalid());
+                }
+                let numerator = whole
+                    .checked_mul(fraction.denominator)
+                    .and_then(|x| x.checked_add(fraction.numerator))
+                    .ok_or_else(invalid)?;
+                Self::new(numerator, fraction.denominator)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    /// Renders the quantity the way it would be written in a recipe: as a
+    /// mixed number when the denominator is a friendly one, as a decimal when
+    /// that is exact, and as a plain fraction otherwise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.numerator < 0 {
+            write!(f, "-")?;
+        }
+        let numerator = self.numerator.abs();
+        let whole = numerator / self.denominator;
+        let remainder = numerator % self.denominator;
+        if remainder == 0 {
+            write!(f, "{}", whole)
+        } else if let (false, Some(places)) = (
+            FRIENDLY_DENOMINATORS.contains(&self.denominator),
+            self.decimal_places(),
+        ) {
+            let fraction = remainder * (10_i64.pow(places) / self.denominator);
+            write!(f, "{}.{:0width$}", whole, fraction, width = places as usize)
+        } else if whole == 0 {
+            write!(f, "{}/{}", remainder, self.denominator)
+        } else {
+            write!(f, "{} {}/{}", whole, remainder, self.denominator)
+        }
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+struct QuantityVisitor;
+
+impl<'de> de::Visitor<'de> for QuantityVisitor {
+    type Value = Quantity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a non-negative number or a string such as \"1 1/2\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        i64::try_from(v)
+            .map(Quantity::from)
+            .map_err(|_| E::custom(Error::InvalidQuantity(v.to_string())))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(QuantityVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn q(numerator: i64, denominator: i64) -> Quantity {
+        Quantity::new(numerator, denominator).unwrap()
+    }
+
+    #[test]
+    fn test_new_reduces() {
+        assert_eq!(q(6, 4), q(3, 2));
+        assert_eq!(q(3, -6), q(-1, 2));
+        assert_eq!(q(0, 5), Quantity::from(0));
+        assert!(Quantity::new(1, 0).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(q(1, 2).checked_add(q(1, 3)), Some(q(5, 6)));
+        assert_eq!(q(1, 2).checked_sub(q(3, 4)), Some(q(-1, 4)));
+        assert_eq!(q(3, 4).checked_mul(q(2, 3)), Some(q(1, 2)));
+        assert_eq!(q(3, 4).checked_div(q(3, 2)), Some(q(1, 2)));
+        assert_eq!(q(1, 2).checked_div(q(0, 1)), None);
+        assert_eq!(q(i64::MAX, 1).checked_mul(q(2, 1)), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("2".parse(), Ok(q(2, 1)));
+        assert_eq!("0.75".parse(), Ok(q(3, 4)));
+        assert_eq!(".5".parse(), Ok(q(1, 2)));
+        assert_eq!("1/2".parse(), Ok(q(1, 2)));
+        assert_eq!(" 1 1/2 ".parse(), Ok(q(3, 2)));
+        assert_eq!("½".parse(), Ok(q(1, 2)));
+        assert_eq!("1½".parse(), Ok(q(3, 2)));
+        assert_eq!("2 ⅓".parse(), Ok(q(7, 3)));
+        for invalid in [
+            "",
+            "a",
+            "-1",
+            "1/0",
+            "1 3/2",
+            "1.2.3",
+            "1 1 1/2",
+            "1/2/3",
+            "-1½",
+            "+1",
+            "3074457345618258602 2/3",
+            "3074457345618258602⅔",
+        ] {
+            assert_eq!(
+                invalid.parse::<Quantity>(),
+                Err(Error::InvalidQuantity(invalid.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(q(2, 1).to_string(), "2");
+        assert_eq!(q(1, 2).to_string(), "1/2");
+        assert_eq!(q(3, 2).to_string(), "1 1/2");
+        assert_eq!(q(7, 3).to_string(), "2 1/3");
+        assert_eq!(q(1, 10).to_string(), "0.1");
+        assert_eq!(q(203, 500).to_string(), "0.406");
+        assert_eq!(q(1, 7).to_string(), "1/7");
+        assert_eq!(q(-3, 2).to_string(), "-1 1/2");
+        for quantity in [q(1, 10), q(203, 500), q(1, 7), q(17, 16), q(5, 1)] {
+            assert_eq!(quantity.to_string().parse(), Ok(quantity));
+        }
+    }
+
+    #[test]
+    fn test_approximate() {
+        assert_eq!(Quantity::approximate(2.2046226), q(9, 4));
+        assert_eq!(Quantity::approximate(3.5273962), q(7, 2));
+        assert_eq!(Quantity::approximate(0.4057691), q(203, 500));
+        assert_eq!(Quantity::approximate(0.3333333), q(1, 3));
+        assert_eq!(Quantity::approximate(0.0), q(0, 1));
+    }
+
+    #[test]
+    fn test_serde() {
+        let parse = |json| serde_json::from_str::<Quantity>(json);
+        assert_eq!(parse("2").unwrap(), q(2, 1));
+        assert_eq!(parse("1.5").unwrap(), q(3, 2));
+        assert_eq!(parse("\"1 1/2\"").unwrap(), q(3, 2));
+        assert!(parse("-1").is_err());
+        assert!(parse("\"lots\"").is_err());
+        assert_eq!(serde_json::to_string(&q(3, 2)).unwrap(), "\"1 1/2\"");
+    }
+}
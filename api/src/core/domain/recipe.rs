@@ -1,4 +1,6 @@
-use serde::Serialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 use sqlx::{
     postgres::{PgHasArrayType, PgRow, PgTypeInfo},
     prelude::FromRow,
@@ -6,7 +8,7 @@ use sqlx::{
 };
 use thiserror::Error;
 
-use super::User;
+use super::{cook_log::CookStats, review::ReviewStats, unit, Quantity, Tag, User};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -14,6 +16,25 @@ pub enum Error {
     Unexpected,
     #[error("recipe with id `{0}` not found")]
     RecipeNotFound(i32),
+    #[error("invalid recipe query: {0}")]
+    InvalidQuery(String),
+    #[error("unknown unit `{0}`")]
+    UnknownUnit(String),
+    #[error("cannot scale recipe: {0}")]
+    InvalidScale(String),
+    #[error("invalid recipe: {0}")]
+    InvalidRecipe(String),
+    #[error("cannot import recipe: {0}")]
+    InvalidImport(String),
+}
+
+impl From<unit::Error> for Error {
+    fn from(value: unit::Error) -> Self {
+        match value {
+            unit::Error::UnknownUnit(name) => Self::UnknownUnit(name),
+            unit::Error::IncompatibleUnits(_, _) | unit::Error::Unexpected => Self::Unexpected,
+        }
+    }
 }
 
 #[derive(FromRow, Serialize, sqlx::Type, Debug, Clone, PartialEq)]
@@ -36,9 +57,13 @@ pub struct RecipeIngredient {
     pub id: Option<i32>,
     pub recipe_id: Option<i32>,
     pub ingredient: Ingredient,
-    pub quantity: i32,
+    pub quantity: Quantity,
     pub units: Unit,
     pub preparation: String,
+    pub group: Option<String>,
+    /// Another recipe this ingredient stands for, e.g. a stock or a dough,
+    /// measured in that recipe's yield units.
+    pub sub_recipe_id: Option<i32>,
 }
 
 impl PgHasArrayType for RecipeIngredient {
@@ -56,12 +81,16 @@ where
     i32: ::sqlx::types::Type<::sqlx::Postgres>,
     Ingredient: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
     Ingredient: ::sqlx::types::Type<::sqlx::Postgres>,
-    i32: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
-    i32: ::sqlx::types::Type<::sqlx::Postgres>,
+    Quantity: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    Quantity: ::sqlx::types::Type<::sqlx::Postgres>,
     Unit: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
     Unit: ::sqlx::types::Type<::sqlx::Postgres>,
     String: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
     String: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<String>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    Option<String>: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<i32>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    Option<i32>: ::sqlx::types::Type<::sqlx::Postgres>,
 {
     fn encode_by_ref(
         &self,
@@ -74,17 +103,23 @@ where
         encoder.encode(&self.quantity);
         encoder.encode(&self.units);
         encoder.encode(&self.preparation);
+        encoder.encode(&self.group);
+        encoder.encode(self.sub_recipe_id);
         encoder.finish();
         ::sqlx::encode::IsNull::No
     }
     fn size_hint(&self) -> ::std::primitive::usize {
-        6usize * (4 + 4)
+        8usize * (4 + 4)
             + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
             + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.recipe_id)
             + <Ingredient as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.ingredient)
-            + <i32 as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.quantity)
+            + <Quantity as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.quantity)
             + <Unit as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.units)
             + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.preparation)
+            + <Option<String> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.group)
+            + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(
+                &self.sub_recipe_id,
+            )
     }
 }
 #[automatically_derived]
@@ -96,12 +131,16 @@ where
     Option<i32>: ::sqlx::types::Type<::sqlx::Postgres>,
     Ingredient: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
     Ingredient: ::sqlx::types::Type<::sqlx::Postgres>,
-    i32: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
-    i32: ::sqlx::types::Type<::sqlx::Postgres>,
+    Quantity: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
+    Quantity: ::sqlx::types::Type<::sqlx::Postgres>,
     Unit: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
     Unit: ::sqlx::types::Type<::sqlx::Postgres>,
     String: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
     String: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<String>: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
+    Option<String>: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<i32>: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
+    Option<i32>: ::sqlx::types::Type<::sqlx::Postgres>,
 {
     fn decode(
         value: ::sqlx::postgres::PgValueRef<'r>,
@@ -115,9 +154,11 @@ where
         let id = decoder.try_decode::<Option<i32>>()?;
         let recipe_id = decoder.try_decode::<Option<i32>>()?;
         let ingredient = decoder.try_decode::<Ingredient>()?;
-        let quantity = decoder.try_decode::<i32>()?;
+        let quantity = decoder.try_decode::<Quantity>()?;
         let units = decoder.try_decode::<Unit>()?;
         let preparation = decoder.try_decode::<String>()?;
+        let group = decoder.try_decode::<Option<String>>()?;
+        let sub_recipe_id = decoder.try_decode::<Option<i32>>()?;
         ::std::result::Result::Ok(RecipeIngredient {
             id,
             recipe_id,
@@ -125,6 +166,8 @@ where
             quantity,
             units,
             preparation,
+            group,
+            sub_recipe_id,
         })
     }
 }
@@ -142,6 +185,9 @@ pub struct Step {
     pub recipe_id: Option<i32>,
     pub ordinal: i32,
     pub instruction: String,
+    pub group: Option<String>,
+    /// Media key of the step's photo, if it has one.
+    pub image: Option<String>,
 }
 
 impl PgHasArrayType for Step {
@@ -161,6 +207,8 @@ where
     i32: ::sqlx::types::Type<::sqlx::Postgres>,
     String: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
     String: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<String>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    Option<String>: ::sqlx::types::Type<::sqlx::Postgres>,
 {
     fn encode_by_ref(
         &self,
@@ -171,15 +219,19 @@ where
         encoder.encode(&self.recipe_id);
         encoder.encode(&self.ordinal);
         encoder.encode(&self.instruction);
+        encoder.encode(&self.group);
+        encoder.encode(&self.image);
         encoder.finish();
         ::sqlx::encode::IsNull::No
     }
     fn size_hint(&self) -> ::std::primitive::usize {
-        4usize * (4 + 4)
+        6usize * (4 + 4)
             + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
             + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.recipe_id)
             + <i32 as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.ordinal)
             + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.instruction)
+            + <Option<String> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.group)
+            + <Option<String> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.image)
     }
 }
 #[automatically_derived]
@@ -193,6 +245,8 @@ where
     i32: ::sqlx::types::Type<::sqlx::Postgres>,
     String: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
     String: ::sqlx::types::Type<::sqlx::Postgres>,
+    Option<String>: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
+    Option<String>: ::sqlx::types::Type<::sqlx::Postgres>,
 {
     fn decode(
         value: ::sqlx::postgres::PgValueRef<'r>,
@@ -207,11 +261,15 @@ where
         let recipe_id = decoder.try_decode::<Option<i32>>()?;
         let ordinal = decoder.try_decode::<i32>()?;
         let instruction = decoder.try_decode::<String>()?;
+        let group = decoder.try_decode::<Option<String>>()?;
+        let image = decoder.try_decode::<Option<String>>()?;
         ::std::result::Result::Ok(Step {
             id,
             recipe_id,
             ordinal,
             instruction,
+            group,
+            image,
         })
     }
 }
@@ -222,6 +280,179 @@ impl ::sqlx::Type<::sqlx::Postgres> for Step {
     }
 }
 
+/// A named section of a recipe, such as "For the dough". Ingredients and
+/// steps belong to a group by naming it in their `group`.
+#[derive(FromRow, Serialize, Debug, Clone, PartialEq)]
+#[sqlx(type_name = "t_recipe_group")]
+pub struct RecipeGroup {
+    pub id: Option<i32>,
+    pub name: String,
+    pub ordinal: i32,
+}
+
+impl PgHasArrayType for RecipeGroup {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_t_recipe_group")
+    }
+}
+
+#[automatically_derived]
+impl ::sqlx::encode::Encode<'_, ::sqlx::Postgres> for RecipeGroup
+where
+    Option<i32>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    Option<i32>: ::sqlx::types::Type<::sqlx::Postgres>,
+    String: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    String: ::sqlx::types::Type<::sqlx::Postgres>,
+    i32: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+    i32: ::sqlx::types::Type<::sqlx::Postgres>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut ::sqlx::postgres::PgArgumentBuffer,
+    ) -> ::sqlx::encode::IsNull {
+        let mut encoder = ::sqlx::postgres::types::PgRecordEncoder::new(buf);
+        encoder.encode(self.id);
+        encoder.encode(&self.name);
+        encoder.encode(self.ordinal);
+        encoder.finish();
+        ::sqlx::encode::IsNull::No
+    }
+    fn size_hint(&self) -> ::std::primitive::usize {
+        3usize * (4 + 4)
+            + <Option<i32> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
+            + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.name)
+            + <i32 as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.ordinal)
+    }
+}
+#[automatically_derived]
+impl<'r> ::sqlx::decode::Decode<'r, ::sqlx::Postgres> for RecipeGroup
+where
+    Option<i32>: for<'q> ::sqlx::decode::Decode<'q, ::sqlx::Postgres>,
+    Option<i32>: ::sqlx::types::Type<::sqlx::Postgres>,
+    String: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
+    String: ::sqlx::types::Type<::sqlx::Postgres>,
+    i32: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
+    i32: ::sqlx::types::Type<::sqlx::Postgres>,
+{
+    fn decode(
+        value: ::sqlx::postgres::PgValueRef<'r>,
+    ) -> ::std::result::Result<
+        Self,
+        ::std::boxed::Box<
+            dyn ::std::error::Error + 'static + ::std::marker::Send + ::std::marker::Sync,
+        >,
+    > {
+        let mut decoder = ::sqlx::postgres::types::PgRecordDecoder::new(value)?;
+        let id = decoder.try_decode::<Option<i32>>()?;
+        let name = decoder.try_decode::<String>()?;
+        let ordinal = decoder.try_decode::<i32>()?;
+        ::std::result::Result::Ok(RecipeGroup { id, name, ordinal })
+    }
+}
+#[automatically_derived]
+impl ::sqlx::Type<::sqlx::Postgres> for RecipeGroup {
+    fn type_info() -> ::sqlx::postgres::PgTypeInfo {
+        ::sqlx::postgres::PgTypeInfo::with_name("t_recipe_group")
+    }
+}
+
+/// A recipe that another was forked from, directly or through other forks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeAncestor {
+    pub id: i32,
+    pub title: String,
+    pub author: User,
+    pub visibility: Visibility,
+    pub draft: bool,
+}
+
+impl PgHasArrayType for RecipeAncestor {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_t_recipe_ancestor")
+    }
+}
+
+// Decoded by hand, as the derived impl cannot prove the nested `User`
+// decodes for every lifetime.
+impl<'r> ::sqlx::decode::Decode<'r, ::sqlx::Postgres> for RecipeAncestor {
+    fn decode(
+        value: ::sqlx::postgres::PgValueRef<'r>,
+    ) -> ::std::result::Result<
+        Self,
+        ::std::boxed::Box<
+            dyn ::std::error::Error + 'static + ::std::marker::Send + ::std::marker::Sync,
+        >,
+    > {
+        let mut decoder = ::sqlx::postgres::types::PgRecordDecoder::new(value)?;
+        let id = decoder.try_decode::<i32>()?;
+        let title = decoder.try_decode::<String>()?;
+        let author = decoder.try_decode::<User>()?;
+        let visibility = decoder.try_decode::<Visibility>()?;
+        let draft = decoder.try_decode::<bool>()?;
+        ::std::result::Result::Ok(RecipeAncestor {
+            id,
+            title,
+            author,
+            visibility,
+            draft,
+        })
+    }
+}
+
+impl ::sqlx::Type<::sqlx::Postgres> for RecipeAncestor {
+    fn type_info() -> ::sqlx::postgres::PgTypeInfo {
+        ::sqlx::postgres::PgTypeInfo::with_name("t_recipe_ancestor")
+    }
+}
+
+/// Who can read a recipe besides its author.
+#[derive(Serialize, Deserialize, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "recipe_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only the author.
+    Private,
+    /// Anyone with its id, but it is left out of listings and searches.
+    Unlisted,
+    #[default]
+    Public,
+}
+
+/// Whether the user with `viewer_id` may read a recipe by the user with
+/// `author_id` by its id. Authors see all their recipes; anyone else only
+/// published ones that are not private.
+pub fn is_visible(
+    author_id: Option<i32>,
+    visibility: Visibility,
+    draft: bool,
+    viewer_id: Option<i32>,
+) -> bool {
+    if viewer_id.is_some() && viewer_id == author_id {
+        return true;
+    }
+    !draft && visibility != Visibility::Private
+}
+
+/// SQL keeping the recipes `r` that anyone may read by their id, as in
+/// `is_visible`.
+pub const SQL_VISIBLE: &str = "(r.visibility <> 'private' AND NOT r.draft)";
+
+/// SQL keeping the recipes `r` that anyone may find in listings and
+/// searches: published public ones.
+pub const SQL_LISTED: &str = "(r.visibility = 'public' AND NOT r.draft)";
+
+/// SQL keeping the recipes `r` that the viewer whose id is `viewer`, e.g.
+/// `$2`, may read by their id.
+pub fn visible_to_sql(viewer: &str) -> String {
+    format!("(r.author = {} OR {})", viewer, SQL_VISIBLE)
+}
+
+/// SQL keeping the recipes `r` listed to the viewer whose id is `viewer`:
+/// their own and everyone's published public recipes.
+pub fn listed_to_sql(viewer: &str) -> String {
+    format!("(r.author = {} OR {})", viewer, SQL_LISTED)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Recipe {
     pub id: Option<i32>,
@@ -231,10 +462,27 @@ pub struct Recipe {
     pub prep_time: Option<chrono::Duration>,
     pub cook_time: Option<chrono::Duration>,
     pub inactive_time: Option<chrono::Duration>,
-    pub yield_quantity: i32,
+    pub yield_quantity: Quantity,
     pub yield_units: Unit,
     pub ingredients: Vec<RecipeIngredient>,
     pub steps: Vec<Step>,
+    pub groups: Vec<RecipeGroup>,
+    pub tags: Vec<Tag>,
+    /// Media key of the recipe's hero image, if it has one.
+    pub image: Option<String>,
+    /// Read along with the recipe; ignored when it is saved.
+    pub cook_stats: CookStats,
+    /// Read along with the recipe; ignored when it is saved.
+    pub review_stats: ReviewStats,
+    /// The recipe this one was copied from. Only set when it is created.
+    pub forked_from: Option<i32>,
+    /// The recipe this one was forked from and that recipe's own ancestors,
+    /// nearest first. Read along with the recipe; ignored when it is saved.
+    /// Contains hidden recipes until `without_hidden_lineage` is called.
+    pub lineage: Vec<RecipeAncestor>,
+    pub visibility: Visibility,
+    /// Drafts are only visible to their author, whatever their visibility.
+    pub draft: bool,
 }
 
 impl<'r> FromRow<'r, PgRow> for Recipe {
@@ -249,10 +497,19 @@ impl<'r> FromRow<'r, PgRow> for Recipe {
         let cook_time = cook_time_seconds.map(|i| chrono::Duration::seconds(i));
         let inactive_time_seconds: Option<i64> = row.try_get("inactive_time")?;
         let inactive_time = inactive_time_seconds.map(|i| chrono::Duration::seconds(i));
-        let yield_quantity: i32 = row.try_get("yield_quantity")?;
+        let yield_quantity: Quantity = row.try_get("yield_quantity")?;
         let yield_units: Unit = row.try_get("yield_units")?;
         let ingredients: Vec<RecipeIngredient> = row.try_get("ingredients")?;
         let steps: Vec<Step> = row.try_get("steps")?;
+        let groups: Vec<RecipeGroup> = row.try_get("groups")?;
+        let tags: Vec<Tag> = row.try_get("tags")?;
+        let image: Option<String> = row.try_get("image")?;
+        let cook_stats = CookStats::from_row(row)?;
+        let review_stats = ReviewStats::from_row(row)?;
+        let forked_from: Option<i32> = row.try_get("forked_from")?;
+        let lineage: Vec<RecipeAncestor> = row.try_get("lineage")?;
+        let visibility: Visibility = row.try_get("visibility")?;
+        let draft: bool = row.try_get("draft")?;
         Ok(Recipe {
             id,
             title,
@@ -265,6 +522,509 @@ impl<'r> FromRow<'r, PgRow> for Recipe {
             yield_units,
             ingredients,
             steps,
+            groups,
+            tags,
+            image,
+            cook_stats,
+            review_stats,
+            forked_from,
+            lineage,
+            visibility,
+            draft,
         })
     }
 }
+
+impl RecipeIngredient {
+    /// How many batches of `sub_recipe` this ingredient calls for, converting
+    /// its quantity into the sub-recipe's yield units where they differ.
+    pub fn sub_recipe_factor(
+        &self,
+        sub_recipe: &Recipe,
+        catalog: &unit::UnitCatalog,
+    ) -> Result<Quantity, Error> {
+        if !sub_recipe.yield_quantity.is_positive() {
+            return Err(Error::InvalidRecipe(format!(
+                "sub-recipe `{}` has no yield",
+                sub_recipe.title
+            )));
+        }
+        let quantity = if self.units.name == sub_recipe.yield_units.name {
+            self.quantity
+        } else {
+            let converted = catalog
+                .convert(
+                    self.quantity.to_f64(),
+                    &self.units.name,
+                    &sub_recipe.yield_units.name,
+                )
+                .map_err(|e| match e {
+                    unit::Error::IncompatibleUnits(_, _) => Error::InvalidRecipe(format!(
+                        "`{}` cannot measure sub-recipe `{}`, which yields `{}`",
+                        self.units.name, sub_recipe.title, sub_recipe.yield_units.name
+                    )),
+                    e => e.into(),
+                })?;
+            Quantity::approximate(converted)
+        };
+        quantity
+            .checked_div(sub_recipe.yield_quantity)
+            .ok_or_else(|| Error::InvalidScale("sub-recipe quantity is too large".to_owned()))
+    }
+}
+
+/// How far to scale a recipe: either to a target yield, expressed in the
+/// recipe's own yield units, or by a plain multiplier.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScaleBy {
+    Yield(Quantity),
+    Factor(Quantity),
+}
+
+impl Recipe {
+    /// The multiplier that takes this recipe to the requested scale.
+    pub fn scale_factor(&self, scale: ScaleBy) -> Result<Quantity, Error> {
+        let factor = match scale {
+            ScaleBy::Factor(factor) => factor,
+            ScaleBy::Yield(target) => {
+                if !self.yield_quantity.is_positive() {
+                    return Err(Error::InvalidScale(
+                        "recipe has no yield to scale from".to_owned(),
+                    ));
+                }
+                target
+                    .checked_div(self.yield_quantity)
+                    .ok_or_else(|| Error::InvalidScale("yield is too large".to_owned()))?
+            }
+        };
+        if !factor.is_positive() {
+            return Err(Error::InvalidScale(
+                "scale must be greater than zero".to_owned(),
+            ));
+        }
+        Ok(factor)
+    }
+
+    /// Ids of the recipes used directly as ingredients of this one.
+    pub fn sub_recipe_ids(&self) -> Vec<i32> {
+        self.ingredients
+            .iter()
+            .filter_map(|x| x.sub_recipe_id)
+            .collect()
+    }
+
+    /// Fails if following sub-recipes from this recipe, through the given
+    /// recipes, ever leads back to a recipe already on the way.
+    pub fn check_sub_recipe_cycles(&self, sub_recipes: &HashMap<i32, Recipe>) -> Result<(), Error> {
+        fn visit(
+            recipe: &Recipe,
+            path: &mut Vec<i32>,
+            sub_recipes: &HashMap<i32, Recipe>,
+        ) -> Result<(), Error> {
+            for id in recipe.sub_recipe_ids() {
+                if path.contains(&id) {
+                    return Err(Error::InvalidRecipe(format!(
+                        "sub-recipe `{}` would make the recipe include itself",
+                        id
+                    )));
+                }
+                if let Some(sub_recipe) = sub_recipes.get(&id) {
+                    path.push(id);
+                    visit(sub_recipe, path, sub_recipes)?;
+                    path.pop();
+                }
+            }
+            Ok(())
+        }
+        visit(self, &mut self.id.into_iter().collect(), sub_recipes)
+    }
+
+    /// The recipe's ingredients with every sub-recipe replaced, recursively,
+    /// by its own ingredients scaled to the amount called for. Each is paired
+    /// with whether it came from a sub-recipe.
+    fn expanded_ingredients(
+        &self,
+        sub_recipes: &HashMap<i32, Recipe>,
+        catalog: &unit::UnitCatalog,
+    ) -> Result<Vec<(RecipeIngredient, bool)>, Error> {
+        let mut expanded = Vec::with_capacity(self.ingredients.len());
+        for ingredient in &self.ingredients {
+            let sub_recipe = match ingredient.sub_recipe_id.and_then(|id| sub_recipes.get(&id)) {
+                Some(sub_recipe) => sub_recipe,
+                None => {
+                    expanded.push((ingredient.clone(), false));
+                    continue;
+                }
+            };
+            let factor = ingredient.sub_recipe_factor(sub_recipe, catalog)?;
+            for (x, _) in sub_recipe.expanded_ingredients(sub_recipes, catalog)? {
+                let quantity = x.quantity.checked_mul(factor).ok_or_else(|| {
+                    Error::InvalidScale("sub-recipe quantity is too large".to_owned())
+                })?;
+                expanded.push((
+                    RecipeIngredient {
+                        quantity,
+                        group: ingredient.group.clone(),
+                        ..x
+                    },
+                    true,
+                ));
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Replaces sub-recipe ingredients with the ingredients of the recipes
+    /// they refer to, scaled to the amount called for, and merges any that
+    /// then appear twice. Call `check_sub_recipe_cycles` first.
+    pub fn expand_sub_recipes(
+        self,
+        sub_recipes: &HashMap<i32, Recipe>,
+        catalog: &unit::UnitCatalog,
+    ) -> Result<Recipe, Error> {
+        let mut merged: Vec<(RecipeIngredient, bool)> = Vec::new();
+        for (ingredient, from_sub_recipe) in self.expanded_ingredients(sub_recipes, catalog)? {
+            let existing = merged.iter_mut().find(|(x, _)| {
+                x.ingredient.name == ingredient.ingredient.name
+                    && x.units.name == ingredient.units.name
+                    && x.preparation == ingredient.preparation
+                    && x.group == ingredient.group
+                    && x.sub_recipe_id == ingredient.sub_recipe_id
+            });
+            match existing {
+                Some((x, scaled)) => {
+                    x.quantity = x.quantity.checked_add(ingredient.quantity).ok_or_else(|| {
+                        Error::InvalidScale("sub-recipe quantity is too large".to_owned())
+                    })?;
+                    *scaled |= from_sub_recipe;
+                }
+                None => merged.push((ingredient, from_sub_recipe)),
+            }
+        }
+        let ingredients = merged
+            .into_iter()
+            .map(|(ingredient, scaled)| {
+                if !scaled {
+                    return Ok(ingredient);
+                }
+                Ok(RecipeIngredient {
+                    quantity: catalog.round(ingredient.quantity, &ingredient.units.name)?,
+                    ..ingredient
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Recipe {
+            ingredients,
+            ..self
+        })
+    }
+
+    /// Multiplies the yield and every ingredient quantity by `factor`,
+    /// keeping them exact.
+    pub fn scale(self, factor: Quantity) -> Result<Recipe, Error> {
+        let multiply = |quantity: Quantity| {
+            quantity
+                .checked_mul(factor)
+                .ok_or_else(|| Error::InvalidScale("scaled quantity is too large".to_owned()))
+        };
+        let yield_quantity = multiply(self.yield_quantity)?;
+        let ingredients = self
+            .ingredients
+            .into_iter()
+            .map(|ingredient| {
+                Ok(RecipeIngredient {
+                    quantity: multiply(ingredient.quantity)?,
+                    ..ingredient
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Recipe {
+            yield_quantity,
+            ingredients,
+            ..self
+        })
+    }
+
+    /// Whether `viewer`, or an anonymous user when `None`, may read the
+    /// recipe by its id.
+    pub fn is_visible_to(&self, viewer: Option<&User>) -> bool {
+        is_visible(
+            self.author.id,
+            self.visibility,
+            self.draft,
+            viewer.and_then(|x| x.id),
+        )
+    }
+
+    /// Leaves out the ancestors the user with `viewer_id` may not read,
+    /// along with `forked_from` when the recipe's parent is one of them.
+    pub fn without_hidden_lineage(mut self, viewer_id: Option<i32>) -> Recipe {
+        let visible =
+            |x: &RecipeAncestor| is_visible(x.author.id, x.visibility, x.draft, viewer_id);
+        if self
+            .lineage
+            .first()
+            .is_some_and(|x| Some(x.id) == self.forked_from && !visible(x))
+        {
+            self.forked_from = None;
+        }
+        self.lineage.retain(visible);
+        self
+    }
+
+    /// A copy of the recipe for `author` to adapt, titled `title`. Images
+    /// are left out, since they are removed along with the original.
+    pub fn fork(&self, title: String, author: User) -> Recipe {
+        Recipe {
+            id: None,
+            title,
+            author,
+            ingredients: self
+                .ingredients
+                .iter()
+                .map(|x| RecipeIngredient {
+                    id: None,
+                    recipe_id: None,
+                    ..x.clone()
+                })
+                .collect(),
+            steps: self
+                .steps
+                .iter()
+                .map(|x| Step {
+                    id: None,
+                    recipe_id: None,
+                    image: None,
+                    ..x.clone()
+                })
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|x| RecipeGroup {
+                    id: None,
+                    ..x.clone()
+                })
+                .collect(),
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: self.id,
+            lineage: vec![],
+            ..self.clone()
+        }
+    }
+}
+
+/// The first free title of the form "Title (n)", counting from 2.
+pub fn numbered_title(title: &str, taken: impl Fn(&str) -> bool) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|x| !taken(x))
+        .unwrap_or_default()
+}
+
+/// A field of an imported document that could not be carried over to the
+/// recipe, and why.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnmappedField {
+    pub field: String,
+    pub reason: String,
+}
+
+/// A recipe read from another format along with whatever was left behind.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipeImport {
+    pub recipe: Recipe,
+    pub unmapped: Vec<UnmappedField>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RecipeSort {
+    Title,
+    Newest,
+    TotalTime,
+    /// Best reviewed first; unreviewed recipes come last.
+    Rating,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipeQuery {
+    /// The user listing recipes. Their own recipes are listed whatever their
+    /// visibility; everyone else's only once public and published.
+    pub viewer_id: Option<i32>,
+    pub author_id: Option<i32>,
+    pub title: Option<String>,
+    pub max_total_time: Option<chrono::Duration>,
+    pub yield_units: Option<String>,
+    /// Only recipes carrying every one of these tags are returned.
+    pub tags: Vec<String>,
+    pub sort: Option<RecipeSort>,
+    pub page: i64,
+    pub limit: i64,
+}
+
+impl Default for RecipeQuery {
+    fn default() -> Self {
+        Self {
+            viewer_id: None,
+            author_id: None,
+            title: None,
+            max_total_time: None,
+            yield_units: None,
+            tags: vec![],
+            sort: None,
+            page: 1,
+            limit: 20,
+        }
+    }
+}
+
+/// How many results come before `page`. Saturates rather than overflowing
+/// for absurdly large page numbers, which then just come back empty.
+fn page_offset(page: i64, limit: i64) -> i64 {
+    (page - 1).saturating_mul(limit)
+}
+
+/// The page after `page`, unless it is the last one.
+fn next_page(page: i64, limit: i64, total_count: i64) -> Option<i64> {
+    if page.saturating_mul(limit) < total_count {
+        Some(page + 1)
+    } else {
+        None
+    }
+}
+
+impl RecipeQuery {
+    pub fn offset(&self) -> i64 {
+        page_offset(self.page, self.limit)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipePage {
+    pub recipes: Vec<Recipe>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+}
+
+impl RecipePage {
+    pub fn next_page(&self) -> Option<i64> {
+        next_page(self.page, self.limit, self.total_count)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipeSearchQuery {
+    /// See `RecipeQuery::viewer_id`.
+    pub viewer_id: Option<i32>,
+    pub text: String,
+    pub page: i64,
+    pub limit: i64,
+}
+
+impl RecipeSearchQuery {
+    pub fn offset(&self) -> i64 {
+        page_offset(self.page, self.limit)
+    }
+}
+
+/// A fragment of a matched field with the matching terms wrapped in `<b>` tags.
+#[derive(FromRow, Serialize, sqlx::Type, Debug, Clone, PartialEq)]
+#[sqlx(type_name = "t_search_highlight")]
+pub struct SearchHighlight {
+    pub field: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipeSearchResult {
+    pub recipe: Recipe,
+    pub rank: f32,
+    pub highlights: Vec<SearchHighlight>,
+}
+
+impl PgHasArrayType for SearchHighlight {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_t_search_highlight")
+    }
+}
+
+impl<'r> FromRow<'r, PgRow> for RecipeSearchResult {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(RecipeSearchResult {
+            recipe: Recipe::from_row(row)?,
+            rank: row.try_get("rank")?,
+            highlights: row.try_get("highlights")?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecipeSearchPage {
+    pub results: Vec<RecipeSearchResult>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+}
+
+impl RecipeSearchPage {
+    pub fn next_page(&self) -> Option<i64> {
+        next_page(self.page, self.limit, self.total_count)
+    }
+}
+
+/// Ingredients assumed to be on hand in any kitchen, ignored when matching
+/// recipes against the ingredients a user has.
+pub const STAPLE_INGREDIENTS: [&str; 5] = ["salt", "water", "pepper", "black pepper", "oil"];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IngredientMatchQuery {
+    /// See `RecipeQuery::viewer_id`.
+    pub viewer_id: Option<i32>,
+    pub ingredients: Vec<String>,
+    pub ignored: Vec<String>,
+    pub max_missing: Option<i64>,
+    pub page: i64,
+    pub limit: i64,
+}
+
+impl IngredientMatchQuery {
+    pub fn offset(&self) -> i64 {
+        page_offset(self.page, self.limit)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IngredientMatch {
+    pub recipe: Recipe,
+    pub covered: i64,
+    pub required: i64,
+    pub missing: Vec<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for IngredientMatch {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(IngredientMatch {
+            recipe: Recipe::from_row(row)?,
+            covered: row.try_get("covered")?,
+            required: row.try_get("required")?,
+            missing: row.try_get("missing")?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IngredientMatchPage {
+    pub results: Vec<IngredientMatch>,
+    pub page: i64,
+    pub limit: i64,
+    pub total_count: i64,
+}
+
+impl IngredientMatchPage {
+    pub fn next_page(&self) -> Option<i64> {
+        next_page(self.page, self.limit, self.total_count)
+    }
+}
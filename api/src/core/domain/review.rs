@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use thiserror::Error;
+
+use super::{cook_log::RATINGS, recipe, User};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("review with id `{0}` not found")]
+    ReviewNotFound(i32),
+    #[error("review with id `{0}` belongs to another user")]
+    NotReviewer(i32),
+    #[error("only the author of the recipe can reply to review `{0}`")]
+    NotAuthor(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("authors cannot review their own recipe `{0}`")]
+    OwnRecipe(i32),
+    #[error("recipe with id `{0}` has already been reviewed by this user")]
+    AlreadyReviewed(i32),
+    #[error("invalid review: {0}")]
+    InvalidReview(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            _ => Self::Unexpected,
+        }
+    }
+}
+
+/// A user's rating of someone else's recipe, with the author's reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub id: Option<i32>,
+    pub recipe_id: i32,
+    pub reviewer: User,
+    pub rating: i16,
+    pub body: String,
+    pub reply: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub replied_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> FromRow<'r, PgRow> for Review {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(Review {
+            id: row.try_get("id")?,
+            recipe_id: row.try_get("recipe_id")?,
+            reviewer: User {
+                id: row.try_get("reviewer_id")?,
+                name: row.try_get("reviewer_name")?,
+            },
+            rating: row.try_get("rating")?,
+            body: row.try_get("body")?,
+            reply: row.try_get("reply")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            replied_at: row.try_get("replied_at")?,
+        })
+    }
+}
+
+impl Review {
+    /// Trims the review's text, failing if its rating is out of range.
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.body = self.body.trim().to_owned();
+        if !RATINGS.contains(&self.rating) {
+            return Err(Error::InvalidReview(format!(
+                "rating must be between {} and {}",
+                RATINGS.start(),
+                RATINGS.end()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Trims a reply to a review; a blank reply removes it.
+pub fn normalize_reply(reply: Option<String>) -> Option<String> {
+    reply.map(|x| x.trim().to_owned()).filter(|x| !x.is_empty())
+}
+
+/// How a recipe has been reviewed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewStats {
+    pub review_count: i64,
+    /// To two decimal places, if there are any reviews.
+    pub average_rating: Option<f64>,
+}
+
+impl<'r> FromRow<'r, PgRow> for ReviewStats {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(ReviewStats {
+            review_count: row.try_get("review_count")?,
+            average_rating: row.try_get("review_rating")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn review(rating: i16) -> Review {
+        Review {
+            id: None,
+            recipe_id: 5,
+            reviewer: User {
+                id: Some(2),
+                name: "Sam".to_owned(),
+            },
+            rating,
+            body: "  Great weeknight dinner.\n".to_owned(),
+            reply: None,
+            created_at: None,
+            updated_at: None,
+            replied_at: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut valid = review(4);
+        assert!(valid.validate().is_ok());
+        assert_eq!(valid.body, "Great weeknight dinner.");
+        for rating in [0, 6] {
+            assert!(matches!(
+                review(rating).validate(),
+                Err(Error::InvalidReview(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_normalize_reply() {
+        assert_eq!(
+            normalize_reply(Some(" Thanks! ".to_owned())),
+            Some("Thanks!".to_owned())
+        );
+        assert_eq!(normalize_reply(Some("  ".to_owned())), None);
+        assert_eq!(normalize_reply(None), None);
+    }
+}
@@ -0,0 +1,460 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::{postgres::PgRow, FromRow, Row};
+use thiserror::Error;
+
+use super::{
+    recipe::{self, Ingredient, RecipeGroup, RecipeIngredient, Step, Unit},
+    Quantity, Recipe, Tag,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("revision `{1}` of recipe with id `{0}` not found")]
+    RevisionNotFound(i32, i32),
+    #[error("recipe with id `{0}` belongs to another user")]
+    NotAuthor(i32),
+    #[error("cannot revert recipe: {0}")]
+    CannotRevert(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            recipe::Error::UnknownUnit(_) | recipe::Error::InvalidRecipe(_) => {
+                Self::CannotRevert(value.to_string())
+            }
+            _ => Self::Unexpected,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotIngredient {
+    pub ingredient: String,
+    pub quantity: Quantity,
+    pub units: String,
+    pub preparation: String,
+    pub group: Option<String>,
+    pub sub_recipe_id: Option<i32>,
+}
+
+impl SnapshotIngredient {
+    /// Ingredients are told apart across revisions by name within a group.
+    fn key(&self) -> (Option<&str>, &str) {
+        (self.group.as_deref(), &self.ingredient)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotStep {
+    pub ordinal: i32,
+    pub instruction: String,
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotGroup {
+    pub name: String,
+    pub ordinal: i32,
+}
+
+/// The contents of a recipe as it was saved. Images are left out, since
+/// replacing one removes the file it replaced.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecipeSnapshot {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub prep_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub cook_time: Option<chrono::Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub inactive_time: Option<chrono::Duration>,
+    pub yield_quantity: Quantity,
+    pub yield_units: String,
+    pub ingredients: Vec<SnapshotIngredient>,
+    pub steps: Vec<SnapshotStep>,
+    #[serde(default)]
+    pub groups: Vec<SnapshotGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Fields compared by `diff`, in the order changes are listed.
+const FIELDS: [&str; 9] = [
+    "title",
+    "description",
+    "prep_time",
+    "cook_time",
+    "inactive_time",
+    "yield_quantity",
+    "yield_units",
+    "groups",
+    "tags",
+];
+
+impl RecipeSnapshot {
+    /// A snapshot of the recipe, with its lists in a stable order so equal
+    /// recipes give equal snapshots.
+    pub fn from_recipe(recipe: &Recipe) -> RecipeSnapshot {
+        let mut ingredients: Vec<SnapshotIngredient> = recipe
+            .ingredients
+            .iter()
+            .map(|x| SnapshotIngredient {
+                ingredient: x.ingredient.name.clone(),
+                quantity: x.quantity,
+                units: x.units.name.clone(),
+                preparation: x.preparation.clone(),
+                group: x.group.clone(),
+                sub_recipe_id: x.sub_recipe_id,
+            })
+            .collect();
+        ingredients.sort_by(|a, b| a.key().cmp(&b.key()));
+        let mut steps: Vec<SnapshotStep> = recipe
+            .steps
+            .iter()
+            .map(|x| SnapshotStep {
+                ordinal: x.ordinal,
+                instruction: x.instruction.clone(),
+                group: x.group.clone(),
+            })
+            .collect();
+        steps.sort_by_key(|x| x.ordinal);
+        let mut groups: Vec<SnapshotGroup> = recipe
+            .groups
+            .iter()
+            .map(|x| SnapshotGroup {
+                name: x.name.clone(),
+                ordinal: x.ordinal,
+            })
+            .collect();
+        groups.sort_by_key(|x| x.ordinal);
+        let mut tags: Vec<String> = recipe.tags.iter().map(|x| x.name.clone()).collect();
+        tags.sort();
+        RecipeSnapshot {
+            title: recipe.title.clone(),
+            description: recipe.description.clone(),
+            prep_time: recipe.prep_time,
+            cook_time: recipe.cook_time,
+            inactive_time: recipe.inactive_time,
+            yield_quantity: recipe.yield_quantity,
+            yield_units: recipe.yield_units.name.clone(),
+            ingredients,
+            steps,
+            groups,
+            tags,
+        }
+    }
+
+    /// `recipe` with its contents replaced by the snapshot's. Steps keep the
+    /// id and image of the current step with the same ordinal, since steps
+    /// are unique by ordinal and are updated in place.
+    pub fn apply(&self, recipe: Recipe) -> Recipe {
+        let steps = self
+            .steps
+            .iter()
+            .map(|x| {
+                let current = recipe.steps.iter().find(|y| y.ordinal == x.ordinal);
+                Step {
+                    id: current.and_then(|y| y.id),
+                    recipe_id: recipe.id,
+                    ordinal: x.ordinal,
+                    instruction: x.instruction.clone(),
+                    group: x.group.clone(),
+                    image: current.and_then(|y| y.image.clone()),
+                }
+            })
+            .collect();
+        Recipe {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            prep_time: self.prep_time,
+            cook_time: self.cook_time,
+            inactive_time: self.inactive_time,
+            yield_quantity: self.yield_quantity,
+            yield_units: Unit {
+                id: None,
+                name: self.yield_units.clone(),
+            },
+            ingredients: self
+                .ingredients
+                .iter()
+                .map(|x| RecipeIngredient {
+                    id: None,
+                    recipe_id: recipe.id,
+                    ingredient: Ingredient {
+                        id: None,
+                        name: x.ingredient.clone(),
+                    },
+                    quantity: x.quantity,
+                    units: Unit {
+                        id: None,
+                        name: x.units.clone(),
+                    },
+                    preparation: x.preparation.clone(),
+                    group: x.group.clone(),
+                    sub_recipe_id: x.sub_recipe_id,
+                })
+                .collect(),
+            steps,
+            groups: self
+                .groups
+                .iter()
+                .map(|x| RecipeGroup {
+                    id: None,
+                    name: x.name.clone(),
+                    ordinal: x.ordinal,
+                })
+                .collect(),
+            tags: self
+                .tags
+                .iter()
+                .map(|name| Tag {
+                    id: None,
+                    name: name.clone(),
+                })
+                .collect(),
+            ..recipe
+        }
+    }
+}
+
+/// A saved version of a recipe. Revisions are numbered from 1 per recipe,
+/// one for every time the recipe was saved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeRevision {
+    pub recipe_id: i32,
+    pub revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub snapshot: RecipeSnapshot,
+}
+
+impl FromRow<'_, PgRow> for RecipeRevision {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let snapshot: String = row.try_get("snapshot")?;
+        Ok(Self {
+            recipe_id: row.try_get("recipe_id")?,
+            revision: row.try_get("revision")?,
+            created_at: row.try_get("created_at")?,
+            snapshot: serde_json::from_str(&snapshot).map_err(|e| sqlx::Error::ColumnDecode {
+                index: "snapshot".to_owned(),
+                source: Box::new(e),
+            })?,
+        })
+    }
+}
+
+/// A revision without its contents, for listing a recipe's history.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct RevisionSummary {
+    pub revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// A changed recipe field, with its values as they appear in a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListDiff<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    pub changed: Vec<Change<T>>,
+}
+
+impl<T: Clone + PartialEq> ListDiff<T> {
+    /// Pairs up the items of both lists by `key`.
+    fn new<K: PartialEq>(from: &[T], to: &[T], key: impl Fn(&T) -> K) -> ListDiff<T> {
+        let find = |list: &[T], item: &T| list.iter().find(|x| key(x) == key(item)).cloned();
+        ListDiff {
+            added: to
+                .iter()
+                .filter(|x| find(from, x).is_none())
+                .cloned()
+                .collect(),
+            removed: from
+                .iter()
+                .filter(|x| find(to, x).is_none())
+                .cloned()
+                .collect(),
+            changed: from
+                .iter()
+                .filter_map(|x| {
+                    find(to, x).filter(|y| y != x).map(|y| Change {
+                        from: x.clone(),
+                        to: y,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub fields: Vec<FieldChange>,
+    pub ingredients: ListDiff<SnapshotIngredient>,
+    /// Steps are paired up by ordinal.
+    pub steps: ListDiff<SnapshotStep>,
+}
+
+/// What changed going from one revision of a recipe to another.
+pub fn diff(from: &RecipeRevision, to: &RecipeRevision) -> Result<RevisionDiff, Error> {
+    let to_value = |x: &RecipeSnapshot| {
+        serde_json::to_value(x).map_err(|e| {
+            log::error!("Failed to write recipe snapshot due to: {}", e);
+            Error::Unexpected
+        })
+    };
+    let (from_value, to_value) = (to_value(&from.snapshot)?, to_value(&to.snapshot)?);
+    let fields = FIELDS
+        .iter()
+        .filter(|x| from_value[**x] != to_value[**x])
+        .map(|x| FieldChange {
+            field: x.to_string(),
+            from: from_value[*x].clone(),
+            to: to_value[*x].clone(),
+        })
+        .collect();
+    Ok(RevisionDiff {
+        from: from.revision,
+        to: to.revision,
+        fields,
+        ingredients: ListDiff::new(&from.snapshot.ingredients, &to.snapshot.ingredients, |x| {
+            (x.group.clone(), x.ingredient.clone())
+        }),
+        steps: ListDiff::new(&from.snapshot.steps, &to.snapshot.steps, |x| x.ordinal),
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn ingredient(name: &str, quantity: i32) -> SnapshotIngredient {
+        SnapshotIngredient {
+            ingredient: name.to_owned(),
+            quantity: quantity.into(),
+            units: "grams".to_owned(),
+            preparation: String::new(),
+            group: None,
+            sub_recipe_id: None,
+        }
+    }
+
+    fn step(ordinal: i32, instruction: &str) -> SnapshotStep {
+        SnapshotStep {
+            ordinal,
+            instruction: instruction.to_owned(),
+            group: None,
+        }
+    }
+
+    fn revision(revision: i32, snapshot: RecipeSnapshot) -> RecipeRevision {
+        RecipeRevision {
+            recipe_id: 1,
+            revision,
+            created_at: Utc::now(),
+            snapshot,
+        }
+    }
+
+    fn snapshot() -> RecipeSnapshot {
+        RecipeSnapshot {
+            title: "Soup".to_owned(),
+            description: None,
+            prep_time: None,
+            cook_time: Some(chrono::Duration::minutes(30)),
+            inactive_time: None,
+            yield_quantity: 4.into(),
+            yield_units: "servings".to_owned(),
+            ingredients: vec![ingredient("leek", 200), ingredient("potato", 300)],
+            steps: vec![step(1, "Chop"), step(2, "Simmer")],
+            groups: vec![],
+            tags: vec!["soup".to_owned()],
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = revision(1, snapshot());
+        let to = revision(
+            3,
+            RecipeSnapshot {
+                title: "Leek Soup".to_owned(),
+                cook_time: Some(chrono::Duration::minutes(45)),
+                ingredients: vec![ingredient("leek", 400), ingredient("cream", 100)],
+                steps: vec![step(1, "Chop"), step(2, "Simmer gently"), step(3, "Blend")],
+                ..snapshot()
+            },
+        );
+        let changes = diff(&from, &to).unwrap();
+        assert_eq!((changes.from, changes.to), (1, 3));
+        assert_eq!(
+            changes.fields,
+            vec![
+                FieldChange {
+                    field: "title".to_owned(),
+                    from: "Soup".into(),
+                    to: "Leek Soup".into(),
+                },
+                FieldChange {
+                    field: "cook_time".to_owned(),
+                    from: 1800.into(),
+                    to: 2700.into(),
+                },
+            ]
+        );
+        assert_eq!(changes.ingredients.added, vec![ingredient("cream", 100)]);
+        assert_eq!(changes.ingredients.removed, vec![ingredient("potato", 300)]);
+        assert_eq!(
+            changes.ingredients.changed,
+            vec![Change {
+                from: ingredient("leek", 200),
+                to: ingredient("leek", 400),
+            }]
+        );
+        assert_eq!(changes.steps.added, vec![step(3, "Blend")]);
+        assert!(changes.steps.removed.is_empty());
+        assert_eq!(
+            changes.steps.changed,
+            vec![Change {
+                from: step(2, "Simmer"),
+                to: step(2, "Simmer gently"),
+            }]
+        );
+        assert!(diff(&from, &from).unwrap().fields.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RecipeSnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+}
@@ -0,0 +1,889 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    ingredient_line,
+    recipe::{Error, RecipeGroup, RecipeImport, RecipeIngredient, Step, Unit, UnmappedField},
+    unit::UnitCatalog,
+    Quantity, Recipe, Tag, User,
+};
+
+/// Unit given to yields that count people served.
+const SERVING_UNITS: &str = "servings";
+const SERVING_WORDS: [&str; 6] = [
+    "serving", "servings", "people", "person", "portion", "portions",
+];
+const YIELD_PREFIXES: [&str; 5] = ["serves", "makes", "yields", "yield", "servings"];
+
+/// A document to import a recipe from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Document {
+    /// A schema.org JSON-LD document.
+    JsonLd(String),
+    /// A web page embedding schema.org JSON-LD in `<script>` tags.
+    Html(String),
+}
+
+/// Reads the schema.org `Recipe` in a document into a recipe by `author`,
+/// noting every field that could not be carried over.
+pub fn parse_recipe(
+    document: &Document,
+    author: User,
+    catalog: &UnitCatalog,
+) -> Result<RecipeImport, Error> {
+    let blocks = match document {
+        Document::JsonLd(json) => vec![serde_json::from_str::<Value>(json)
+            .map_err(|e| Error::InvalidImport(format!("invalid JSON-LD: {}", e)))?],
+        Document::Html(html) => json_ld_blocks(html)
+            .iter()
+            .filter_map(|x| serde_json::from_str::<Value>(x).ok())
+            .collect(),
+    };
+    let node = blocks
+        .iter()
+        .find_map(find_recipe)
+        .ok_or_else(|| Error::InvalidImport("no schema.org Recipe found".to_owned()))?;
+    RecipeReader::new(catalog).read(node, author)
+}
+
+/// The contents of every `<script type="application/ld+json">` in a page.
+fn json_ld_blocks(html: &str) -> Vec<&str> {
+    // ASCII lowercasing keeps byte offsets, so they index into `html` too.
+    let lower = html.to_ascii_lowercase();
+    let mut blocks = Vec::new();
+    let mut position = 0;
+    while let Some(start) = lower[position..].find("<script").map(|x| x + position) {
+        let open_end = match lower[start..].find('>') {
+            Some(x) => start + x + 1,
+            None => break,
+        };
+        let close = match lower[open_end..].find("</script") {
+            Some(x) => open_end + x,
+            None => break,
+        };
+        if lower[start..open_end].contains("application/ld+json") {
+            blocks.push(&html[open_end..close]);
+        }
+        position = close;
+    }
+    blocks
+}
+
+fn is_recipe_type(value: Option<&Value>) -> bool {
+    let is_recipe = |x: &Value| {
+        x.as_str()
+            .is_some_and(|x| x == "Recipe" || x.ends_with("/Recipe") || x.ends_with(":Recipe"))
+    };
+    match value {
+        Some(Value::Array(types)) => types.iter().any(is_recipe),
+        Some(x) => is_recipe(x),
+        None => false,
+    }
+}
+
+/// Finds the first `Recipe` node, looking through arrays, `@graph` and the
+/// `mainEntity` of pages.
+fn find_recipe(value: &Value) -> Option<&Map<String, Value>> {
+    match value {
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        Value::Object(node) if is_recipe_type(node.get("@type")) => Some(node),
+        Value::Object(node) => ["@graph", "mainEntity"]
+            .iter()
+            .filter_map(|x| node.get(*x))
+            .find_map(find_recipe),
+        _ => None,
+    }
+}
+
+/// Parses an ISO 8601 duration such as `PT1H30M` or `P1DT2H`. Years and
+/// months are rejected, as they have no fixed length.
+pub fn parse_duration(text: &str) -> Option<chrono::Duration> {
+    let text = text.trim().to_ascii_uppercase();
+    let designators = text.strip_prefix('P')?;
+    let mut in_time = false;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut any = false;
+    for c in designators.chars() {
+        let scale = match (c, in_time) {
+            ('T', false) if number.is_empty() => {
+                in_time = true;
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() || c == '.' || c == ',' => {
+                number.push(if c == ',' { '.' } else { c });
+                continue;
+            }
+            ('W', false) => 7.0 * 86400.0,
+            ('D', false) => 86400.0,
+            ('H', true) => 3600.0,
+            ('M', true) => 60.0,
+            ('S', true) => 1.0,
+            _ => return None,
+        };
+        let value: f64 = number.parse().ok()?;
+        seconds += value * scale;
+        number.clear();
+        any = true;
+    }
+    if !any || !number.is_empty() {
+        return None;
+    }
+    chrono::Duration::try_seconds(seconds.round() as i64)
+}
+
+/// Formats a duration as ISO 8601, such as `PT1H30M`.
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let mut text = "PT".to_owned();
+    if hours > 0 {
+        text.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{}M", minutes));
+    }
+    if seconds > 0 || hours == 0 && minutes == 0 {
+        text.push_str(&format!("{}S", seconds));
+    }
+    text
+}
+
+/// The recipe's steps in order, each with the group it belongs to, gathered
+/// into runs of consecutive steps in the same group.
+pub fn step_sections(recipe: &Recipe) -> Vec<(Option<&str>, Vec<&Step>)> {
+    let mut steps: Vec<&Step> = recipe.steps.iter().collect();
+    steps.sort_by_key(|x| x.ordinal);
+    let mut sections: Vec<(Option<&str>, Vec<&Step>)> = vec![];
+    for step in steps {
+        match sections.last_mut() {
+            Some((group, steps)) if *group == step.group.as_deref() => steps.push(step),
+            _ => sections.push((step.group.as_deref(), vec![step])),
+        }
+    }
+    sections
+}
+
+/// The recipe's total time: prep, cook and inactive time together, if it
+/// has any of them.
+pub fn total_time(recipe: &Recipe) -> Option<chrono::Duration> {
+    [recipe.prep_time, recipe.cook_time, recipe.inactive_time]
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(|total, x| total + x)
+}
+
+/// The recipe as a schema.org `Recipe` JSON-LD document. `image_url` is
+/// where the recipe's image is served from, if it has one.
+pub fn to_json_ld(recipe: &Recipe, image_url: Option<&str>) -> Value {
+    let mut node = Map::new();
+    node.insert("@context".to_owned(), json!("https://schema.org"));
+    node.insert("@type".to_owned(), json!("Recipe"));
+    node.insert("name".to_owned(), json!(recipe.title));
+    if let Some(description) = &recipe.description {
+        node.insert("description".to_owned(), json!(description));
+    }
+    node.insert(
+        "author".to_owned(),
+        json!({"@type": "Person", "name": recipe.author.name}),
+    );
+    if let Some(image_url) = image_url {
+        node.insert("image".to_owned(), json!(image_url));
+    }
+    for (field, duration) in [
+        ("prepTime", recipe.prep_time),
+        ("cookTime", recipe.cook_time),
+        ("totalTime", total_time(recipe)),
+    ] {
+        if let Some(duration) = duration {
+            node.insert(field.to_owned(), json!(format_duration(duration)));
+        }
+    }
+    node.insert(
+        "recipeYield".to_owned(),
+        json!(format!(
+            "{} {}",
+            recipe.yield_quantity, recipe.yield_units.name
+        )),
+    );
+    node.insert(
+        "recipeIngredient".to_owned(),
+        json!(recipe
+            .ingredients
+            .iter()
+            .map(ingredient_line::format)
+            .collect::<Vec<_>>()),
+    );
+    let how_to_steps = |steps: &[&Step]| -> Vec<Value> {
+        steps
+            .iter()
+            .map(|x| json!({"@type": "HowToStep", "text": x.instruction}))
+            .collect()
+    };
+    let instructions: Vec<Value> = step_sections(recipe)
+        .into_iter()
+        .flat_map(|(group, steps)| match group {
+            Some(group) => vec![json!({
+                "@type": "HowToSection",
+                "name": group,
+                "itemListElement": how_to_steps(&steps),
+            })],
+            None => how_to_steps(&steps),
+        })
+        .collect();
+    node.insert("recipeInstructions".to_owned(), json!(instructions));
+    if !recipe.tags.is_empty() {
+        node.insert(
+            "keywords".to_owned(),
+            json!(recipe
+                .tags
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")),
+        );
+    }
+    Value::Object(node)
+}
+
+/// Decodes the HTML character references sites leave in their JSON-LD.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|x| u32::from_str_radix(x, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|x| x.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Removes markup, turning line breaks and block elements into newlines.
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            Some(x) => start + x,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+        let name: String = rest[start + 1..end]
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if matches!(name.as_str(), "br" | "p" | "li" | "div") {
+            stripped.push('\n');
+        }
+        rest = &rest[end + 1..];
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Plain text on a single line.
+fn clean_text(text: &str) -> String {
+    collapse_whitespace(&decode_entities(&strip_tags(text)))
+}
+
+/// Plain text split into its non-blank lines.
+fn clean_lines(text: &str) -> Vec<String> {
+    decode_entities(&strip_tags(text))
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// Every string in a value that may be a single string or a list of them.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(x) => vec![x.clone()],
+        Value::Number(x) => vec![x.to_string()],
+        Value::Array(items) => items.iter().flat_map(strings).collect(),
+        _ => vec![],
+    }
+}
+
+fn named_unit(name: &str) -> Unit {
+    Unit {
+        id: None,
+        name: name.to_owned(),
+    }
+}
+
+struct RecipeReader<'a> {
+    catalog: &'a UnitCatalog,
+    unmapped: Vec<UnmappedField>,
+}
+
+impl<'a> RecipeReader<'a> {
+    fn new(catalog: &'a UnitCatalog) -> Self {
+        Self {
+            catalog,
+            unmapped: vec![],
+        }
+    }
+
+    fn unmapped(&mut self, field: impl Into<String>, reason: impl Into<String>) {
+        self.unmapped.push(UnmappedField {
+            field: field.into(),
+            reason: reason.into(),
+        });
+    }
+
+    fn read(mut self, node: &Map<String, Value>, author: User) -> Result<RecipeImport, Error> {
+        let title = node
+            .get("name")
+            .and_then(|x| strings(x).into_iter().next())
+            .map(|x| clean_text(&x))
+            .filter(|x| !x.is_empty())
+            .ok_or_else(|| Error::InvalidImport("recipe has no name".to_owned()))?;
+        let description = node
+            .get("description")
+            .and_then(|x| strings(x).into_iter().next())
+            .map(|x| clean_text(&x))
+            .filter(|x| !x.is_empty());
+        let prep_time = self.duration(node, "prepTime");
+        let cook_time = self.duration(node, "cookTime");
+        let inactive_time = self.inactive_time(node, prep_time, cook_time);
+        let (yield_quantity, yield_units) = self.recipe_yield(node.get("recipeYield"));
+        let ingredients = self.ingredients(node);
+        let mut groups = vec![];
+        let mut steps = vec![];
+        if let Some(instructions) = node.get("recipeInstructions") {
+            self.instructions(instructions, None, &mut groups, &mut steps);
+        }
+        let tags = ["keywords", "recipeCategory", "recipeCuisine"]
+            .iter()
+            .filter_map(|x| node.get(*x))
+            .flat_map(strings)
+            .flat_map(|x| {
+                x.split(',')
+                    .map(clean_text)
+                    .filter(|x| !x.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .map(|name| Tag { id: None, name })
+            .collect();
+
+        for (key, _) in node.iter() {
+            let reason = match key.as_str() {
+                k if k.starts_with('@') => continue,
+                "name" | "description" | "prepTime" | "cookTime" | "totalTime" | "recipeYield"
+                | "recipeIngredient" | "ingredients" | "recipeInstructions" | "keywords"
+                | "recipeCategory" | "recipeCuisine" => continue,
+                "image" => "images are not imported; upload one to POST /recipe/:id/image",
+                "author" => "recipes are imported for the current user",
+                _ => "not supported",
+            };
+            self.unmapped(key.as_str(), reason);
+        }
+
+        Ok(RecipeImport {
+            recipe: Recipe {
+                id: None,
+                title,
+                description,
+                author,
+                prep_time,
+                cook_time,
+                inactive_time,
+                yield_quantity,
+                yield_units: named_unit(&yield_units),
+                ingredients,
+                steps,
+                groups,
+                tags,
+                image: None,
+                cook_stats: Default::default(),
+                review_stats: Default::default(),
+                forked_from: None,
+                lineage: vec![],
+                visibility: Default::default(),
+                draft: false,
+            },
+            unmapped: self.unmapped,
+        })
+    }
+
+    fn duration(&mut self, node: &Map<String, Value>, field: &str) -> Option<chrono::Duration> {
+        let text = node.get(field)?.as_str().unwrap_or_default();
+        let duration = parse_duration(text);
+        if duration.is_none() {
+            self.unmapped(field, format!("`{}` is not an ISO 8601 duration", text));
+        }
+        duration
+    }
+
+    /// Whatever part of `totalTime` is not prep or cook time, such as
+    /// resting or chilling.
+    fn inactive_time(
+        &mut self,
+        node: &Map<String, Value>,
+        prep_time: Option<chrono::Duration>,
+        cook_time: Option<chrono::Duration>,
+    ) -> Option<chrono::Duration> {
+        let total_time = self.duration(node, "totalTime")?;
+        if prep_time.is_none() && cook_time.is_none() {
+            self.unmapped(
+                "totalTime",
+                "cannot tell prep time from cook time without either of them",
+            );
+            return None;
+        }
+        let active = prep_time.unwrap_or_else(chrono::Duration::zero)
+            + cook_time.unwrap_or_else(chrono::Duration::zero);
+        Some(total_time - active).filter(|x| *x > chrono::Duration::zero())
+    }
+
+    fn recipe_yield(&mut self, value: Option<&Value>) -> (Quantity, String) {
+        let texts = value.map(strings).unwrap_or_default();
+        for text in &texts {
+            if let Some(parsed) = self.parse_yield(text) {
+                return parsed;
+            }
+        }
+        let reason = match texts.first() {
+            Some(text) => format!("cannot read a yield from `{}`, imported as 1 serving", text),
+            None => "missing, imported as 1 serving".to_owned(),
+        };
+        self.unmapped("recipeYield", reason);
+        (1.into(), SERVING_UNITS.to_owned())
+    }
+
+    fn parse_yield(&mut self, text: &str) -> Option<(Quantity, String)> {
+        let text = clean_text(text);
+        let mut words: Vec<String> = text
+            .to_lowercase()
+            .split_whitespace()
+            .map(|x| x.trim_end_matches(':').to_owned())
+            .collect();
+        if words
+            .first()
+            .is_some_and(|x| YIELD_PREFIXES.contains(&x.as_str()))
+        {
+            words.remove(0);
+        }
+        let amount = ingredient_line::parse_amount(&words.join(" "), self.catalog)?;
+        let serving_word = amount
+            .rest
+            .split_whitespace()
+            .next()
+            .is_none_or(|x| SERVING_WORDS.contains(&x));
+        let units = match amount.units {
+            Some(units) => units,
+            None if serving_word => SERVING_UNITS.to_owned(),
+            None => {
+                self.unmapped(
+                    "recipeYield",
+                    format!(
+                        "unknown unit in `{}`, imported as {}",
+                        text,
+                        ingredient_line::DEFAULT_UNITS
+                    ),
+                );
+                ingredient_line::DEFAULT_UNITS.to_owned()
+            }
+        };
+        if amount.quantity_max.is_some() {
+            self.unmapped(
+                "recipeYield",
+                format!("`{}` is a range, imported as {}", text, amount.quantity),
+            );
+        }
+        Some((amount.quantity, units))
+    }
+
+    fn ingredients(&mut self, node: &Map<String, Value>) -> Vec<RecipeIngredient> {
+        let (field, value) = match ["recipeIngredient", "ingredients"]
+            .iter()
+            .find_map(|x| node.get(*x).map(|value| (*x, value)))
+        {
+            Some(found) => found,
+            None => return vec![],
+        };
+        strings(value)
+            .iter()
+            .map(|x| clean_text(x))
+            .filter(|x| !x.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                let parsed = ingredient_line::parse(&line, self.catalog);
+                let field = format!("{}[{}]", field, i);
+                for warning in parsed.warnings {
+                    self.unmapped(field.as_str(), warning);
+                }
+                if let Some(quantity_max) = parsed.quantity_max {
+                    self.unmapped(
+                        field.as_str(),
+                        format!(
+                            "`{}` gives a range up to {}, imported as {}",
+                            line, quantity_max, parsed.ingredient.quantity
+                        ),
+                    );
+                }
+                parsed.ingredient
+            })
+            .collect()
+    }
+
+    fn instructions(
+        &mut self,
+        value: &Value,
+        group: Option<&str>,
+        groups: &mut Vec<RecipeGroup>,
+        steps: &mut Vec<Step>,
+    ) {
+        let push = |steps: &mut Vec<Step>, instruction: String| {
+            steps.push(Step {
+                id: None,
+                recipe_id: None,
+                ordinal: steps.len() as i32 + 1,
+                instruction,
+                group: group.map(str::to_owned),
+                image: None,
+            })
+        };
+        match value {
+            Value::String(text) => {
+                for line in clean_lines(text) {
+                    push(steps, line);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.instructions(item, group, groups, steps);
+                }
+            }
+            Value::Object(node) => {
+                let text = ["text", "name"]
+                    .iter()
+                    .filter_map(|x| node.get(*x).and_then(Value::as_str))
+                    .map(clean_text)
+                    .find(|x| !x.is_empty());
+                let is_section = node
+                    .get("@type")
+                    .and_then(Value::as_str)
+                    .is_some_and(|x| x.ends_with("HowToSection"));
+                match (node.get("itemListElement"), text) {
+                    (Some(items), Some(name)) if is_section => {
+                        if !groups.iter().any(|x| x.name == name) {
+                            groups.push(RecipeGroup {
+                                id: None,
+                                name: name.clone(),
+                                ordinal: groups.len() as i32 + 1,
+                            });
+                        }
+                        self.instructions(items, Some(&name), groups, steps);
+                    }
+                    (Some(items), None) => self.instructions(items, group, groups, steps),
+                    (_, Some(text)) => push(steps, text),
+                    (None, None) => {
+                        self.unmapped("recipeInstructions", "a step has no text");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::unit::test_catalog;
+
+    fn author() -> User {
+        User {
+            id: Some(1),
+            name: "Matt".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let minutes = |x| Some(chrono::Duration::minutes(x));
+        assert_eq!(parse_duration("PT1H30M"), minutes(90));
+        assert_eq!(parse_duration("pt45m"), minutes(45));
+        assert_eq!(parse_duration("P1DT2H"), minutes(26 * 60));
+        assert_eq!(parse_duration("PT0.5H"), minutes(30));
+        assert_eq!(parse_duration("PT90S"), Some(chrono::Duration::seconds(90)));
+        for text in [
+            "",
+            "P",
+            "PT",
+            "1H",
+            "P1M",
+            "P1Y",
+            "PT1H30",
+            "PT1X",
+            "PT99999999999999999S",
+        ] {
+            assert_eq!(parse_duration(text), None, "{}", text);
+        }
+
+        let json = r#"{"@type": "Recipe", "name": "Toast", "prepTime": "PT99999999999999999S"}"#;
+        let import = parse_recipe(
+            &Document::JsonLd(json.to_owned()),
+            author(),
+            &test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(import.recipe.prep_time, None);
+        assert_eq!(import.unmapped[0].field, "prepTime");
+    }
+
+    #[test]
+    fn test_parse_yield() {
+        let catalog = test_catalog();
+        let mut reader = RecipeReader::new(&catalog);
+        let parsed = |reader: &mut RecipeReader, x: &str| {
+            reader
+                .parse_yield(x)
+                .map(|(quantity, units)| (quantity.to_string(), units))
+        };
+        let expected = |quantity: &str, units: &str| Some((quantity.to_owned(), units.to_owned()));
+        assert_eq!(parsed(&mut reader, "4"), expected("4", "servings"));
+        assert_eq!(parsed(&mut reader, "6 people"), expected("6", "servings"));
+        assert_eq!(parsed(&mut reader, "Makes 2 cups"), expected("2", "cups"));
+        assert_eq!(parsed(&mut reader, "1 1/2 cups"), expected("1 1/2", "cups"));
+        assert!(reader.unmapped.is_empty());
+        assert_eq!(parsed(&mut reader, "24 cookies"), expected("24", "each"));
+        assert_eq!(reader.unmapped.len(), 1);
+        assert_eq!(parsed(&mut reader, "Serves 4-6"), expected("4", "servings"));
+        assert_eq!(reader.unmapped.len(), 2);
+        assert_eq!(parsed(&mut reader, "varies"), None);
+    }
+
+    #[test]
+    fn test_clean_text() {
+        assert_eq!(
+            clean_text("<p>Mac &amp; cheese&#39;s   <b>best</b>&nbsp;friend</p>"),
+            "Mac & cheese's best friend"
+        );
+        assert_eq!(
+            clean_lines("Preheat oven.<br/>Mix.\n\n Bake &#x26; cool."),
+            vec!["Preheat oven.", "Mix.", "Bake & cool."]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_ld() {
+        let json = r#"{
+            "@context": "https://schema.org",
+            "@graph": [
+                {"@type": "WebPage", "name": "Best Pancakes"},
+                {
+                    "@type": ["Recipe", "NewsArticle"],
+                    "name": "Pancakes",
+                    "description": "Fluffy &amp; light",
+                    "image": "https://example.com/pancakes.jpg",
+                    "author": {"@type": "Person", "name": "Someone"},
+                    "prepTime": "PT10M",
+                    "cookTime": "PT15M",
+                    "totalTime": "PT55M",
+                    "recipeYield": ["4", "4 servings"],
+                    "recipeIngredient": ["2 cups flour", "2 eggs", "salt to taste"],
+                    "recipeInstructions": [
+                        {"@type": "HowToStep", "text": "Mix everything."},
+                        {
+                            "@type": "HowToSection",
+                            "name": "To cook",
+                            "itemListElement": [
+                                {"@type": "HowToStep", "text": "Heat the pan."},
+                                {"@type": "HowToStep", "name": "Fry."}
+                            ]
+                        }
+                    ],
+                    "keywords": "breakfast, quick",
+                    "recipeCuisine": ["American"],
+                    "nutrition": {"calories": "200 kcal"}
+                }
+            ]
+        }"#;
+        let import = parse_recipe(
+            &Document::JsonLd(json.to_owned()),
+            author(),
+            &test_catalog(),
+        )
+        .unwrap();
+        let recipe = import.recipe;
+        assert_eq!(recipe.title, "Pancakes");
+        assert_eq!(recipe.description.as_deref(), Some("Fluffy & light"));
+        assert_eq!(recipe.prep_time, Some(chrono::Duration::minutes(10)));
+        assert_eq!(recipe.cook_time, Some(chrono::Duration::minutes(15)));
+        assert_eq!(recipe.inactive_time, Some(chrono::Duration::minutes(30)));
+        assert_eq!(recipe.yield_quantity, 4.into());
+        assert_eq!(recipe.yield_units.name, "servings");
+        assert_eq!(recipe.ingredients.len(), 3);
+        assert_eq!(recipe.ingredients[1].units.name, "each");
+        assert_eq!(
+            recipe
+                .steps
+                .iter()
+                .map(|x| (x.ordinal, x.instruction.as_str(), x.group.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "Mix everything.", None),
+                (2, "Heat the pan.", Some("To cook")),
+                (3, "Fry.", Some("To cook")),
+            ]
+        );
+        assert_eq!(recipe.groups.len(), 1);
+        assert_eq!(recipe.groups[0].name, "To cook");
+        assert_eq!(
+            recipe
+                .tags
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["breakfast", "quick", "American"]
+        );
+        assert_eq!(
+            import
+                .unmapped
+                .iter()
+                .map(|x| x.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["recipeIngredient[2]", "author", "image", "nutrition"]
+        );
+    }
+
+    #[test]
+    fn test_parse_html() {
+        let html = r#"<html><head>
+            <script type="text/javascript">var x = "{}";</script>
+            <SCRIPT type="application/ld+json">{ not json }</SCRIPT>
+            <script type="application/ld+json">
+                {"@type": "Recipe", "name": "Toast", "recipeInstructions": "Toast the bread.\nButter it."}
+            </script>
+        </head><body></body></html>"#;
+        let import =
+            parse_recipe(&Document::Html(html.to_owned()), author(), &test_catalog()).unwrap();
+        assert_eq!(import.recipe.title, "Toast");
+        assert_eq!(import.recipe.steps.len(), 2);
+        assert_eq!(import.recipe.yield_units.name, "servings");
+        assert_eq!(import.unmapped[0].field, "recipeYield");
+
+        assert!(matches!(
+            parse_recipe(
+                &Document::Html("<html></html>".to_owned()),
+                author(),
+                &test_catalog()
+            ),
+            Err(Error::InvalidImport(_))
+        ));
+        assert!(matches!(
+            parse_recipe(
+                &Document::JsonLd(r#"{"@type": "Recipe"}"#.to_owned()),
+                author(),
+                &test_catalog()
+            ),
+            Err(Error::InvalidImport(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::minutes(90)), "PT1H30M");
+        assert_eq!(format_duration(chrono::Duration::seconds(45)), "PT45S");
+        assert_eq!(format_duration(chrono::Duration::hours(26)), "PT26H");
+        assert_eq!(format_duration(chrono::Duration::zero()), "PT0S");
+    }
+
+    #[test]
+    fn test_json_ld_round_trips() {
+        let json = r#"{
+            "@type": "Recipe",
+            "name": "Pancakes",
+            "description": "Fluffy",
+            "prepTime": "PT10M",
+            "cookTime": "PT15M",
+            "totalTime": "PT55M",
+            "recipeYield": "4 servings",
+            "recipeIngredient": ["2 cups flour, sifted", "2 eggs"],
+            "recipeInstructions": [
+                {"@type": "HowToStep", "text": "Mix."},
+                {
+                    "@type": "HowToSection",
+                    "name": "To cook",
+                    "itemListElement": [{"@type": "HowToStep", "text": "Fry."}]
+                }
+            ],
+            "keywords": "breakfast"
+        }"#;
+        let catalog = test_catalog();
+        let recipe = parse_recipe(&Document::JsonLd(json.to_owned()), author(), &catalog)
+            .unwrap()
+            .recipe;
+        let exported = to_json_ld(&recipe, Some("/media/recipe/1/a.png"));
+        assert_eq!(exported["@type"], json!("Recipe"));
+        assert_eq!(exported["author"]["name"], json!("Matt"));
+        assert_eq!(exported["image"], json!("/media/recipe/1/a.png"));
+        assert_eq!(exported["totalTime"], json!("PT55M"));
+        assert_eq!(
+            exported["recipeIngredient"],
+            json!(["2 cups flour, sifted", "2 eggs"])
+        );
+        assert_eq!(
+            exported["recipeInstructions"][1]["itemListElement"][0]["text"],
+            json!("Fry.")
+        );
+
+        let reimported =
+            parse_recipe(&Document::JsonLd(exported.to_string()), author(), &catalog).unwrap();
+        assert_eq!(reimported.recipe, recipe);
+        assert_eq!(
+            reimported
+                .unmapped
+                .iter()
+                .map(|x| x.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["author", "image"]
+        );
+    }
+}
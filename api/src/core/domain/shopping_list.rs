@@ -0,0 +1,441 @@
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use thiserror::Error;
+
+use super::{
+    recipe::{self, Ingredient, RecipeIngredient, Unit},
+    unit::{self, UnitCatalog},
+    Quantity, User,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("shopping list with id `{0}` not found")]
+    ShoppingListNotFound(i32),
+    #[error("shopping list item with id `{0}` not found")]
+    ItemNotFound(i32),
+    #[error("shopping list with id `{0}` belongs to another user")]
+    NotOwner(i32),
+    #[error("recipe with id `{0}` not found")]
+    RecipeNotFound(i32),
+    #[error("unknown unit `{0}`")]
+    UnknownUnit(String),
+    #[error("cannot add recipe: {0}")]
+    CannotAddRecipe(String),
+    #[error("invalid shopping list: {0}")]
+    InvalidShoppingList(String),
+}
+
+impl From<recipe::Error> for Error {
+    fn from(value: recipe::Error) -> Self {
+        match value {
+            recipe::Error::RecipeNotFound(id) => Self::RecipeNotFound(id),
+            recipe::Error::UnknownUnit(name) => Self::UnknownUnit(name),
+            recipe::Error::Unexpected => Self::Unexpected,
+            e => Self::CannotAddRecipe(e.to_string()),
+        }
+    }
+}
+
+impl From<unit::Error> for Error {
+    fn from(value: unit::Error) -> Self {
+        match value {
+            unit::Error::UnknownUnit(name) => Self::UnknownUnit(name),
+            unit::Error::IncompatibleUnits(_, _) | unit::Error::Unexpected => Self::Unexpected,
+        }
+    }
+}
+
+/// Something to buy. Items added from recipes always have an amount;
+/// items added by hand may leave it out, e.g. "paper towels".
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingListItem {
+    pub id: Option<i32>,
+    pub ingredient: Ingredient,
+    pub quantity: Option<Quantity>,
+    pub units: Option<Unit>,
+    pub checked: bool,
+    pub ordinal: i32,
+}
+
+impl ShoppingListItem {
+    fn is_same_ingredient(&self, ingredient: &Ingredient) -> bool {
+        match (self.ingredient.id, ingredient.id) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.ingredient.name == ingredient.name,
+        }
+    }
+
+    /// Whether an amount in `units` can be summed into this item.
+    fn can_add(&self, units: &unit::UnitDefinition, catalog: &UnitCatalog) -> bool {
+        self.quantity.is_some()
+            && self
+                .units
+                .as_ref()
+                .and_then(|x| catalog.find(&x.name).ok())
+                .is_some_and(|x| x.dimension == units.dimension)
+    }
+
+    /// Sums an amount into the item, converting it to the item's units.
+    fn add(&mut self, quantity: Quantity, units: &str, catalog: &UnitCatalog) -> Result<(), Error> {
+        let (current, item_units) = match (self.quantity, &self.units) {
+            (Some(current), Some(item_units)) => (current, item_units),
+            _ => return Err(Error::Unexpected),
+        };
+        let amount = if units == item_units.name {
+            quantity
+        } else {
+            Quantity::approximate(catalog.convert(quantity.to_f64(), units, &item_units.name)?)
+        };
+        let total = current
+            .checked_add(amount)
+            .ok_or_else(|| Error::InvalidShoppingList("quantity is too large".to_owned()))?;
+        self.quantity = Some(catalog.round(total, &item_units.name)?);
+        Ok(())
+    }
+}
+
+impl<'r> FromRow<'r, PgRow> for ShoppingListItem {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        let units_id: Option<i32> = row.try_get("units_id")?;
+        let units_name: Option<String> = row.try_get("units_name")?;
+        Ok(ShoppingListItem {
+            id: row.try_get("id")?,
+            ingredient: Ingredient {
+                id: row.try_get("ingredient_id")?,
+                name: row.try_get("ingredient_name")?,
+            },
+            quantity: row.try_get("quantity")?,
+            units: units_name.map(|name| Unit { id: units_id, name }),
+            checked: row.try_get("checked")?,
+            ordinal: row.try_get("ordinal")?,
+        })
+    }
+}
+
+/// A recipe that was added to a shopping list, and how far it was scaled.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct ShoppingListRecipe {
+    pub id: Option<i32>,
+    pub recipe_id: i32,
+    pub title: String,
+    pub scale: Quantity,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingList {
+    pub id: Option<i32>,
+    pub name: String,
+    pub owner: User,
+    /// Kept in display order.
+    pub items: Vec<ShoppingListItem>,
+    pub recipes: Vec<ShoppingListRecipe>,
+}
+
+/// Changes to a shopping list item; fields left as `None` are kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemUpdate {
+    pub name: Option<String>,
+    pub quantity: Option<Quantity>,
+    pub units: Option<String>,
+    pub checked: Option<bool>,
+}
+
+/// Trims a shopping list or item name, failing if nothing is left.
+pub fn validate_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidShoppingList(
+            "name must not be empty".to_owned(),
+        ));
+    }
+    Ok(name.to_owned())
+}
+
+impl ShoppingList {
+    pub fn new(name: String, owner: User) -> Self {
+        Self {
+            id: None,
+            name,
+            owner,
+            items: vec![],
+            recipes: vec![],
+        }
+    }
+
+    fn renumber(&mut self) {
+        for (i, item) in self.items.iter_mut().enumerate() {
+            item.ordinal = i as i32 + 1;
+        }
+    }
+
+    /// Appends an item to the end of the list.
+    pub fn push_item(&mut self, item: ShoppingListItem) {
+        self.items.push(item);
+        self.renumber();
+    }
+
+    pub fn item_mut(&mut self, item_id: i32) -> Result<&mut ShoppingListItem, Error> {
+        self.items
+            .iter_mut()
+            .find(|x| x.id == Some(item_id))
+            .ok_or(Error::ItemNotFound(item_id))
+    }
+
+    pub fn remove_item(&mut self, item_id: i32) -> Result<ShoppingListItem, Error> {
+        let index = self
+            .items
+            .iter()
+            .position(|x| x.id == Some(item_id))
+            .ok_or(Error::ItemNotFound(item_id))?;
+        let item = self.items.remove(index);
+        self.renumber();
+        Ok(item)
+    }
+
+    /// Applies an update to an item. Renaming an item makes it refer to the
+    /// ingredient of the new name.
+    pub fn update_item(&mut self, item_id: i32, update: ItemUpdate) -> Result<(), Error> {
+        let item = self.item_mut(item_id)?;
+        if let Some(name) = update.name {
+            item.ingredient = Ingredient {
+                id: None,
+                name: validate_name(&name)?,
+            };
+        }
+        if let Some(quantity) = update.quantity {
+            item.quantity = Some(quantity);
+        }
+        if let Some(units) = update.units {
+            item.units = Some(Unit {
+                id: None,
+                name: units,
+            });
+        }
+        if let Some(checked) = update.checked {
+            item.checked = checked;
+        }
+        Ok(())
+    }
+
+    /// Puts the items in the order of `item_ids`, which must name every
+    /// item on the list exactly once.
+    pub fn reorder(&mut self, item_ids: &[i32]) -> Result<(), Error> {
+        let mut given = item_ids.to_vec();
+        given.sort_unstable();
+        let mut existing: Vec<i32> = self.items.iter().filter_map(|x| x.id).collect();
+        existing.sort_unstable();
+        if given != existing || existing.len() != self.items.len() {
+            return Err(Error::InvalidShoppingList(
+                "the new order must list every item exactly once".to_owned(),
+            ));
+        }
+        self.items.sort_by_key(|x| {
+            item_ids
+                .iter()
+                .position(|id| x.id == Some(*id))
+                .unwrap_or_default()
+        });
+        self.renumber();
+        Ok(())
+    }
+
+    /// Adds the ingredients of a recipe. Each is summed into an unchecked
+    /// item for the same ingredient whose units measure the same thing,
+    /// converting between units as needed; the rest become new items.
+    pub fn add_ingredients(
+        &mut self,
+        ingredients: Vec<RecipeIngredient>,
+        catalog: &UnitCatalog,
+    ) -> Result<(), Error> {
+        for ingredient in ingredients {
+            let units = catalog.find(&ingredient.units.name)?;
+            let existing = self.items.iter_mut().find(|x| {
+                !x.checked
+                    && x.is_same_ingredient(&ingredient.ingredient)
+                    && x.can_add(units, catalog)
+            });
+            match existing {
+                Some(item) => item.add(ingredient.quantity, &ingredient.units.name, catalog)?,
+                None => {
+                    let quantity = catalog.round(ingredient.quantity, &units.name)?;
+                    self.push_item(ShoppingListItem {
+                        id: None,
+                        ingredient: ingredient.ingredient,
+                        quantity: Some(quantity),
+                        units: Some(Unit {
+                            id: Some(units.id),
+                            name: units.name.clone(),
+                        }),
+                        checked: false,
+                        ordinal: 0,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::domain::unit::test_catalog;
+
+    fn ingredient(id: i32, name: &str, quantity: Quantity, units: &str) -> RecipeIngredient {
+        RecipeIngredient {
+            id: None,
+            recipe_id: None,
+            ingredient: Ingredient {
+                id: Some(id),
+                name: name.to_owned(),
+            },
+            quantity,
+            units: Unit {
+                id: None,
+                name: units.to_owned(),
+            },
+            preparation: "diced".to_owned(),
+            group: None,
+            sub_recipe_id: None,
+        }
+    }
+
+    fn list() -> ShoppingList {
+        ShoppingList::new(
+            "Groceries".to_owned(),
+            User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+        )
+    }
+
+    fn amounts(list: &ShoppingList) -> Vec<(String, Option<String>, Option<String>)> {
+        list.items
+            .iter()
+            .map(|x| {
+                (
+                    x.ingredient.name.clone(),
+                    x.quantity.map(|q| q.to_string()),
+                    x.units.as_ref().map(|u| u.name.clone()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_add_ingredients_sums_compatible_units() {
+        let catalog = test_catalog();
+        let mut list = list();
+        list.add_ingredients(
+            vec![
+                ingredient(1, "flour", 500.into(), "grams"),
+                ingredient(2, "milk", 1.into(), "cups"),
+                ingredient(3, "eggs", 2.into(), "each"),
+            ],
+            &catalog,
+        )
+        .unwrap();
+        list.add_ingredients(
+            vec![
+                ingredient(1, "flour", 1.into(), "kilograms"),
+                ingredient(2, "milk", 2.into(), "each"),
+                ingredient(3, "eggs", 1.into(), "each"),
+            ],
+            &catalog,
+        )
+        .unwrap();
+        assert_eq!(
+            amounts(&list),
+            vec![
+                (
+                    "flour".to_owned(),
+                    Some("1500".to_owned()),
+                    Some("grams".to_owned())
+                ),
+                (
+                    "milk".to_owned(),
+                    Some("1".to_owned()),
+                    Some("cups".to_owned())
+                ),
+                (
+                    "eggs".to_owned(),
+                    Some("3".to_owned()),
+                    Some("each".to_owned())
+                ),
+                (
+                    "milk".to_owned(),
+                    Some("2".to_owned()),
+                    Some("each".to_owned())
+                ),
+            ]
+        );
+        assert_eq!(
+            list.items.iter().map(|x| x.ordinal).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_add_ingredients_skips_checked_items() {
+        let catalog = test_catalog();
+        let mut list = list();
+        list.add_ingredients(vec![ingredient(1, "flour", 500.into(), "grams")], &catalog)
+            .unwrap();
+        list.items[0].checked = true;
+        list.add_ingredients(vec![ingredient(1, "flour", 200.into(), "grams")], &catalog)
+            .unwrap();
+        assert_eq!(
+            amounts(&list),
+            vec![
+                (
+                    "flour".to_owned(),
+                    Some("500".to_owned()),
+                    Some("grams".to_owned())
+                ),
+                (
+                    "flour".to_owned(),
+                    Some("200".to_owned()),
+                    Some("grams".to_owned())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reorder() {
+        let mut list = list();
+        for (id, name) in [(10, "a"), (11, "b"), (12, "c")] {
+            list.push_item(ShoppingListItem {
+                id: Some(id),
+                ingredient: Ingredient {
+                    id: None,
+                    name: name.to_owned(),
+                },
+                quantity: None,
+                units: None,
+                checked: false,
+                ordinal: 0,
+            });
+        }
+        list.reorder(&[12, 10, 11]).unwrap();
+        assert_eq!(
+            list.items
+                .iter()
+                .map(|x| (x.id, x.ordinal))
+                .collect::<Vec<_>>(),
+            vec![(Some(12), 1), (Some(10), 2), (Some(11), 3)]
+        );
+        assert!(matches!(
+            list.reorder(&[12, 10]),
+            Err(Error::InvalidShoppingList(_))
+        ));
+        assert!(matches!(
+            list.reorder(&[12, 10, 10]),
+            Err(Error::InvalidShoppingList(_))
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgHasArrayType, PgRow, PgTypeInfo},
+    prelude::FromRow,
+    Row,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("tag with id `{0}` not found")]
+    TagNotFound(i32),
+    #[error("invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("tag with id `{0}` is used on another user's recipes")]
+    NotOwner(i32),
+}
+
+#[derive(FromRow, Serialize, sqlx::Type, Debug, Clone, PartialEq)]
+#[sqlx(type_name = "t_tag")]
+pub struct Tag {
+    pub id: Option<i32>,
+    pub name: String,
+}
+
+impl PgHasArrayType for Tag {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_t_tag")
+    }
+}
+
+impl Tag {
+    /// Case-folds a tag name and collapses its whitespace so that e.g.
+    /// " Italian  Food" and "italian food" name the same tag.
+    pub fn normalize_name(name: &str) -> String {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Normalizes every name, dropping blanks and duplicates while keeping
+    /// the order they were first given in.
+    pub fn normalize_names(names: Vec<String>) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(names.len());
+        for name in names.iter().map(|x| Self::normalize_name(x)) {
+            if !name.is_empty() && !normalized.contains(&name) {
+                normalized.push(name);
+            }
+        }
+        normalized
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagUsage {
+    pub tag: Tag,
+    pub recipe_count: i64,
+}
+
+impl<'r> FromRow<'r, PgRow> for TagUsage {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(TagUsage {
+            tag: Tag {
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+            },
+            recipe_count: row.try_get("recipe_count")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_names() {
+        assert_eq!(
+            Tag::normalize_names(vec![
+                " Italian  Food".to_owned(),
+                "italian food".to_owned(),
+                "  ".to_owned(),
+                "Vegan".to_owned(),
+            ]),
+            vec!["italian food".to_owned(), "vegan".to_owned()]
+        );
+    }
+}
@@ -0,0 +1,472 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use thiserror::Error;
+
+use super::{recipe, Quantity, Recipe};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("unexpected error occurred")]
+    Unexpected,
+    #[error("unknown unit `{0}`")]
+    UnknownUnit(String),
+    #[error("cannot convert `{0}` to `{1}`")]
+    IncompatibleUnits(String, String),
+}
+
+#[derive(Serialize, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "unit_dimension", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Count,
+    Length,
+    Temperature,
+}
+
+#[derive(Serialize, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "measurement_system", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementSystem {
+    Metric,
+    Imperial,
+}
+
+/// A unit in the catalog along with how to convert it to the base unit of its
+/// dimension, e.g. grams for mass or milliliters for volume.
+#[derive(FromRow, Serialize, Debug, Clone, PartialEq)]
+pub struct UnitDefinition {
+    pub id: i32,
+    pub name: String,
+    pub abbreviation: Option<String>,
+    pub dimension: Dimension,
+    pub system: Option<MeasurementSystem>,
+    pub to_base_factor: f64,
+    pub to_base_offset: f64,
+}
+
+impl UnitDefinition {
+    pub fn to_base(&self, quantity: f64) -> f64 {
+        (quantity + self.to_base_offset) * self.to_base_factor
+    }
+
+    pub fn from_base(&self, quantity: f64) -> f64 {
+        quantity / self.to_base_factor - self.to_base_offset
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitCatalog {
+    pub units: Vec<UnitDefinition>,
+}
+
+impl UnitCatalog {
+    pub fn new(units: Vec<UnitDefinition>) -> Self {
+        Self { units }
+    }
+
+    pub fn find(&self, name: &str) -> Result<&UnitDefinition, Error> {
+        self.units
+            .iter()
+            .find(|unit| unit.name == name)
+            .ok_or_else(|| Error::UnknownUnit(name.to_owned()))
+    }
+
+    /// Finds the unit a word written in a recipe refers to, matching its
+    /// name, its name without a plural "s" or its abbreviation, ignoring case
+    /// and a trailing period ("Cups", "cup", "tbsp.").
+    pub fn lookup(&self, word: &str) -> Option<&UnitDefinition> {
+        let word = word.trim().trim_end_matches('.').to_lowercase();
+        if word.is_empty() {
+            return None;
+        }
+        self.units.iter().find(|unit| {
+            let name = unit.name.to_lowercase();
+            name == word
+                || name.strip_suffix('s') == Some(word.as_str())
+                || unit
+                    .abbreviation
+                    .as_ref()
+                    .is_some_and(|x| x.to_lowercase() == word)
+        })
+    }
+
+    /// Converts a quantity between two units of the same dimension.
+    pub fn convert(&self, quantity: f64, from: &str, to: &str) -> Result<f64, Error> {
+        let from_unit = self.find(from)?;
+        let to_unit = self.find(to)?;
+        if from_unit.dimension != to_unit.dimension {
+            return Err(Error::IncompatibleUnits(from.to_owned(), to.to_owned()));
+        }
+        Ok(to_unit.from_base(from_unit.to_base(quantity)))
+    }
+
+    /// Expresses a quantity in the given system, picking the largest unit of
+    /// the same dimension that keeps the quantity at or above one. Quantities in
+    /// units that already belong to the system, or to no system at all, are
+    /// returned unchanged.
+    pub fn convert_to_system(
+        &self,
+        quantity: f64,
+        unit: &str,
+        system: MeasurementSystem,
+    ) -> Result<(f64, &UnitDefinition), Error> {
+        let from_unit = self.find(unit)?;
+        if from_unit.system.is_none() || from_unit.system == Some(system) {
+            return Ok((quantity, from_unit));
+        }
+        let base = from_unit.to_base(quantity);
+        let target = self
+            .best_fit(base, from_unit.dimension, system)
+            .unwrap_or(from_unit);
+        if target.id == from_unit.id {
+            return Ok((quantity, from_unit));
+        }
+        Ok((target.from_base(base), target))
+    }
+
+    /// The largest unit of the dimension and system that keeps an amount,
+    /// given in the dimension's base unit, at or above one; failing that,
+    /// the smallest such unit.
+    fn best_fit(
+        &self,
+        base: f64,
+        dimension: Dimension,
+        system: MeasurementSystem,
+    ) -> Option<&UnitDefinition> {
+        let mut candidates: Vec<&UnitDefinition> = self
+            .units
+            .iter()
+            .filter(|x| x.dimension == dimension && x.system == Some(system))
+            .collect();
+        candidates.sort_by(|a, b| a.to_base_factor.total_cmp(&b.to_base_factor));
+        candidates
+            .iter()
+            .rev()
+            .find(|x| x.from_base(base).abs() >= 1.0)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    /// Rounds a computed quantity to a precision that suits its unit: the
+    /// nearest friendly fraction for imperial and count units, and whole
+    /// numbers, tenths or hundredths (depending on size) for metric ones.
+    pub fn round(&self, quantity: Quantity, unit: &str) -> Result<Quantity, Error> {
+        let unit = self.find(unit)?;
+        let value = quantity.to_f64();
+        let quantity = match unit.system {
+            Some(MeasurementSystem::Metric) => {
+                let denominator = match value.abs() {
+                    x if x >= 10.0 => 1,
+                    x if x >= 1.0 => 10,
+                    _ => 100,
+                };
+                Quantity::new((value * denominator as f64).round() as i64, denominator)
+                    .map_err(|_| Error::Unexpected)?
+            }
+            _ => Quantity::approximate(value),
+        };
+        Ok(quantity)
+    }
+
+    /// Moves a quantity to the unit of the same dimension and system that
+    /// reads best, e.g. 48 teaspoons to 1 cup or 1500 grams to 1.5
+    /// kilograms. Units that belong to no system are left alone.
+    pub fn promote<'a>(
+        &'a self,
+        quantity: Quantity,
+        unit: &'a str,
+    ) -> Result<(Quantity, &'a UnitDefinition), Error> {
+        let from_unit = self.find(unit)?;
+        let system = match from_unit.system {
+            Some(system) => system,
+            None => return Ok((quantity, from_unit)),
+        };
+        let base = from_unit.to_base(quantity.to_f64());
+        match self.best_fit(base, from_unit.dimension, system) {
+            Some(target) if target.id != from_unit.id => {
+                Ok((Quantity::approximate(target.from_base(base)), target))
+            }
+            _ => Ok((quantity, from_unit)),
+        }
+    }
+
+    /// Tidies up a recipe whose quantities were computed, e.g. by scaling:
+    /// optionally promotes each ingredient to a better-fitting unit, then
+    /// rounds the yield and every ingredient quantity to suit its unit.
+    pub fn round_recipe(&self, recipe: Recipe, promote_units: bool) -> Result<Recipe, Error> {
+        let yield_quantity = self.round(recipe.yield_quantity, &recipe.yield_units.name)?;
+        let ingredients = recipe
+            .ingredients
+            .into_iter()
+            .map(|ingredient| {
+                let (quantity, units) = if promote_units {
+                    let (quantity, target) =
+                        self.promote(ingredient.quantity, &ingredient.units.name)?;
+                    let units = recipe::Unit {
+                        id: Some(target.id),
+                        name: target.name.clone(),
+                    };
+                    (quantity, units)
+                } else {
+                    (ingredient.quantity, ingredient.units)
+                };
+                Ok(recipe::RecipeIngredient {
+                    quantity: self.round(quantity, &units.name)?,
+                    units,
+                    ..ingredient
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Recipe {
+            yield_quantity,
+            ingredients,
+            ..recipe
+        })
+    }
+
+    /// Expresses a quantity in the given system as with `convert_to_system`,
+    /// leaving it exact if it is already in a suitable unit and otherwise
+    /// rounding it to a cook-friendly amount.
+    fn convert_quantity_to_system(
+        &self,
+        quantity: Quantity,
+        units: &recipe::Unit,
+        system: MeasurementSystem,
+    ) -> Result<(Quantity, recipe::Unit), Error> {
+        let (converted, target) = self.convert_to_system(quantity.to_f64(), &units.name, system)?;
+        if target.name == units.name {
+            return Ok((quantity, units.clone()));
+        }
+        Ok((
+            Quantity::approximate(converted),
+            recipe::Unit {
+                id: Some(target.id),
+                name: target.name.clone(),
+            },
+        ))
+    }
+
+    /// Returns a copy of the recipe with its ingredient quantities and yield
+    /// expressed in the given system.
+    pub fn convert_recipe(
+        &self,
+        recipe: Recipe,
+        system: MeasurementSystem,
+    ) -> Result<Recipe, Error> {
+        let (yield_quantity, yield_units) =
+            self.convert_quantity_to_system(recipe.yield_quantity, &recipe.yield_units, system)?;
+        let ingredients = recipe
+            .ingredients
+            .into_iter()
+            .map(|ingredient| {
+                let (quantity, units) = self.convert_quantity_to_system(
+                    ingredient.quantity,
+                    &ingredient.units,
+                    system,
+                )?;
+                Ok(recipe::RecipeIngredient {
+                    quantity,
+                    units,
+                    ..ingredient
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Recipe {
+            yield_quantity,
+            yield_units,
+            ingredients,
+            ..recipe
+        })
+    }
+}
+
+/// The units the migrations seed, numbered in the order they are listed,
+/// for tests that need a catalog.
+#[cfg(test)]
+pub(crate) fn test_catalog() -> UnitCatalog {
+    use Dimension::*;
+    use MeasurementSystem::*;
+    let unit = |id, name: &str, abbreviation: Option<&str>, dimension, system, to_base_factor| {
+        UnitDefinition {
+            id,
+            name: name.to_owned(),
+            abbreviation: abbreviation.map(|x| x.to_owned()),
+            dimension,
+            system,
+            to_base_factor,
+            to_base_offset: 0.0,
+        }
+    };
+    UnitCatalog::new(vec![
+        unit(1, "grams", Some("g"), Mass, Some(Metric), 1.0),
+        unit(2, "milligrams", Some("mg"), Mass, Some(Metric), 0.001),
+        unit(3, "kilograms", Some("kg"), Mass, Some(Metric), 1000.0),
+        unit(4, "ounces", Some("oz"), Mass, Some(Imperial), 28.349523125),
+        unit(5, "pounds", Some("lb"), Mass, Some(Imperial), 453.59237),
+        unit(6, "milliliters", Some("ml"), Volume, Some(Metric), 1.0),
+        unit(7, "liters", Some("l"), Volume, Some(Metric), 1000.0),
+        unit(
+            8,
+            "teaspoons",
+            Some("tsp"),
+            Volume,
+            Some(Imperial),
+            4.92892159375,
+        ),
+        unit(
+            9,
+            "tablespoons",
+            Some("tbsp"),
+            Volume,
+            Some(Imperial),
+            14.78676478125,
+        ),
+        unit(
+            10,
+            "fluid ounces",
+            Some("fl oz"),
+            Volume,
+            Some(Imperial),
+            29.5735295625,
+        ),
+        unit(11, "cups", Some("c"), Volume, Some(Imperial), 236.5882365),
+        unit(12, "pints", Some("pt"), Volume, Some(Imperial), 473.176473),
+        unit(13, "quarts", Some("qt"), Volume, Some(Imperial), 946.352946),
+        unit(
+            14,
+            "gallons",
+            Some("gal"),
+            Volume,
+            Some(Imperial),
+            3785.411784,
+        ),
+        unit(15, "each", Some("ea"), Count, None, 1.0),
+        unit(16, "dozen", Some("doz"), Count, None, 12.0),
+        unit(17, "millimeters", Some("mm"), Length, Some(Metric), 1.0),
+        unit(18, "centimeters", Some("cm"), Length, Some(Metric), 10.0),
+        unit(19, "meters", Some("m"), Length, Some(Metric), 1000.0),
+        unit(20, "inches", Some("in"), Length, Some(Imperial), 25.4),
+        unit(21, "celsius", Some("°C"), Temperature, Some(Metric), 1.0),
+        UnitDefinition {
+            to_base_offset: -32.0,
+            ..unit(
+                22,
+                "fahrenheit",
+                Some("°F"),
+                Temperature,
+                Some(Imperial),
+                5.0 / 9.0,
+            )
+        },
+        unit(23, "servings", None, Count, None, 1.0),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn unit(
+        id: i32,
+        name: &str,
+        dimension: Dimension,
+        system: Option<MeasurementSystem>,
+        to_base_factor: f64,
+        to_base_offset: f64,
+    ) -> UnitDefinition {
+        UnitDefinition {
+            id,
+            name: name.to_owned(),
+            abbreviation: None,
+            dimension,
+            system,
+            to_base_factor,
+            to_base_offset,
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let catalog = UnitCatalog::new(vec![UnitDefinition {
+            abbreviation: Some("tsp".to_owned()),
+            ..unit(6, "teaspoons", Dimension::Volume, None, 4.92892159375, 0.0)
+        }]);
+        for word in ["teaspoons", "Teaspoon", "tsp", "TSP."] {
+            assert_eq!(catalog.lookup(word).map(|x| x.id), Some(6), "{}", word);
+        }
+        assert!(catalog.lookup("tablespoon").is_none());
+        assert!(catalog.lookup("").is_none());
+    }
+
+    #[test]
+    fn test_convert() {
+        let catalog = test_catalog();
+        assert!((catalog.convert(2.0, "pounds", "grams").unwrap() - 907.18474).abs() < 1e-9);
+        assert!((catalog.convert(212.0, "fahrenheit", "celsius").unwrap() - 100.0).abs() < 1e-9);
+        assert!((catalog.convert(180.0, "celsius", "fahrenheit").unwrap() - 356.0).abs() < 1e-9);
+        assert_eq!(
+            catalog.convert(1.0, "cups", "grams").unwrap_err(),
+            Error::IncompatibleUnits("cups".to_owned(), "grams".to_owned())
+        );
+        assert_eq!(
+            catalog.convert(1.0, "handfuls", "grams").unwrap_err(),
+            Error::UnknownUnit("handfuls".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_convert_to_system_picks_largest_unit_above_one() {
+        let catalog = test_catalog();
+        let (quantity, unit) = catalog
+            .convert_to_system(1000.0, "grams", MeasurementSystem::Imperial)
+            .unwrap();
+        assert_eq!(unit.name, "pounds");
+        assert!((quantity - 2.2046226).abs() < 1e-6);
+
+        let (quantity, unit) = catalog
+            .convert_to_system(100.0, "grams", MeasurementSystem::Imperial)
+            .unwrap();
+        assert_eq!(unit.name, "ounces");
+        assert!((quantity - 3.5273962).abs() < 1e-6);
+
+        let (quantity, unit) = catalog
+            .convert_to_system(2.0, "milliliters", MeasurementSystem::Imperial)
+            .unwrap();
+        assert_eq!(unit.name, "teaspoons");
+        assert!((quantity - 0.4057691).abs() < 1e-6);
+
+        let (quantity, unit) = catalog
+            .convert_to_system(3.0, "each", MeasurementSystem::Imperial)
+            .unwrap();
+        assert_eq!(unit.name, "each");
+        assert_eq!(quantity, 3.0);
+    }
+
+    #[test]
+    fn test_round() {
+        let catalog = test_catalog();
+        let q = |n, d| Quantity::new(n, d).unwrap();
+        assert_eq!(catalog.round(q(2501, 10), "grams"), Ok(q(250, 1)));
+        assert_eq!(catalog.round(q(7, 3), "grams"), Ok(q(23, 10)));
+        assert_eq!(catalog.round(q(1, 3), "milliliters"), Ok(q(33, 100)));
+        assert_eq!(catalog.round(q(7, 9), "cups"), Ok(q(3, 4)));
+        assert_eq!(catalog.round(q(5, 2), "each"), Ok(q(5, 2)));
+    }
+
+    #[test]
+    fn test_promote() {
+        let catalog = test_catalog();
+        let q = |n, d| Quantity::new(n, d).unwrap();
+        let (quantity, unit) = catalog.promote(q(48, 1), "teaspoons").unwrap();
+        assert_eq!((quantity, unit.name.as_str()), (q(1, 1), "cups"));
+        let (quantity, unit) = catalog.promote(q(1500, 1), "grams").unwrap();
+        assert_eq!((quantity, unit.name.as_str()), (q(3, 2), "kilograms"));
+        let (quantity, unit) = catalog.promote(q(2, 1), "teaspoons").unwrap();
+        assert_eq!((quantity, unit.name.as_str()), (q(2, 1), "teaspoons"));
+        let (quantity, unit) = catalog.promote(q(36, 1), "each").unwrap();
+        assert_eq!((quantity, unit.name.as_str()), (q(36, 1), "each"));
+    }
+}
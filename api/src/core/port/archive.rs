@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[async_trait]
+pub trait ArchiveService {
+    /// Archives every recipe `author` owns along with their images.
+    async fn export_library(
+        &self,
+        author: domain::User,
+    ) -> Result<domain::archive::Archive, domain::archive::Error>;
+    /// Recreates the archived recipes for `author`, settling clashes with
+    /// the titles of their existing recipes as `on_conflict` says.
+    async fn import_library(
+        &self,
+        archive: domain::archive::Archive,
+        author: domain::User,
+        on_conflict: domain::archive::OnConflict,
+    ) -> Result<domain::archive::ImportReport, domain::archive::Error>;
+}
@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+/// Collections and favorites come with all of their recipes, including ones
+/// the reader may not see; leaving those out is up to the caller.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait CollectionRepository {
+    async fn get_collections(
+        &self,
+        owner_id: i32,
+    ) -> Result<Vec<domain::collection::Collection>, domain::collection::Error>;
+    async fn get_collection(
+        &self,
+        id: i32,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn create_collection(
+        &self,
+        collection: domain::collection::Collection,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    /// Saves the collection's name, sharing and recipes in their order.
+    async fn update_collection(
+        &self,
+        collection: domain::collection::Collection,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn delete_collection(
+        &self,
+        id: i32,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    /// The user's favorite recipes, most recently added first.
+    async fn get_favorites(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<domain::collection::CollectionRecipe>, domain::collection::Error>;
+    async fn add_favorite(
+        &self,
+        user_id: i32,
+        recipe_id: i32,
+    ) -> Result<(), domain::collection::Error>;
+    async fn remove_favorite(
+        &self,
+        user_id: i32,
+        recipe_id: i32,
+    ) -> Result<(), domain::collection::Error>;
+    /// Which of `recipe_ids` the user has favorited.
+    async fn get_favorited(
+        &self,
+        user_id: i32,
+        recipe_ids: Vec<i32>,
+    ) -> Result<Vec<i32>, domain::collection::Error>;
+}
+
+/// Collections can only be changed by their owner; anyone else fails with
+/// `NotOwner`, as does reading a collection that is not shared. Favorites
+/// are private to each user. Recipes the reader may not see are left out of
+/// both.
+#[async_trait]
+pub trait CollectionService {
+    async fn get_collections(
+        &self,
+        owner: domain::User,
+    ) -> Result<Vec<domain::collection::Collection>, domain::collection::Error>;
+    /// Reads a collection as `viewer`, or anonymously.
+    async fn get_collection(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn create_collection(
+        &self,
+        name: String,
+        shared: bool,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    /// Renames a collection and sets whether it is shared.
+    async fn update_collection(
+        &self,
+        id: i32,
+        name: String,
+        shared: bool,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn delete_collection(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    /// Adds a recipe to the end of a collection.
+    async fn add_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn remove_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn reorder_recipes(
+        &self,
+        id: i32,
+        recipe_ids: Vec<i32>,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error>;
+    async fn get_favorites(
+        &self,
+        user: domain::User,
+    ) -> Result<Vec<domain::collection::CollectionRecipe>, domain::collection::Error>;
+    async fn add_favorite(
+        &self,
+        recipe_id: i32,
+        user: domain::User,
+    ) -> Result<(), domain::collection::Error>;
+    async fn remove_favorite(
+        &self,
+        recipe_id: i32,
+        user: domain::User,
+    ) -> Result<(), domain::collection::Error>;
+    /// Which of `recipe_ids` the user has favorited.
+    async fn get_favorited(
+        &self,
+        recipe_ids: Vec<i32>,
+        user: domain::User,
+    ) -> Result<Vec<i32>, domain::collection::Error>;
+}
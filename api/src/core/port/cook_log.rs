@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait CookLogRepository {
+    /// Leaves out entries of recipes the user with `viewer_id` may not read
+    /// and, from a user's history, those of recipes not listed to them.
+    async fn get_entries(
+        &self,
+        query: domain::cook_log::CookLogQuery,
+        viewer_id: Option<i32>,
+    ) -> Result<Vec<domain::cook_log::CookLogEntry>, domain::cook_log::Error>;
+    async fn get_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    async fn create_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    async fn update_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    async fn delete_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+}
+
+/// Anyone can read the history of a recipe or a user, except for entries of
+/// recipes hidden from them or, in a user's history, unlisted ones. Only the
+/// owner of an entry may change it; anyone else fails with `NotOwner`.
+#[async_trait]
+pub trait CookLogService {
+    /// The entries of a recipe or user, most recent first.
+    async fn get_history(
+        &self,
+        query: domain::cook_log::CookLogQuery,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::cook_log::CookLogEntry>, domain::cook_log::Error>;
+    async fn get_entry(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    /// Records that the entry's owner made a recipe.
+    async fn create_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    async fn update_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+    async fn delete_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error>;
+}
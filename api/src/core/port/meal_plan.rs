@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait MealPlanRepository {
+    async fn get_entries(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+    ) -> Result<Vec<domain::meal_plan::MealPlanEntry>, domain::meal_plan::Error>;
+    async fn get_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    async fn create_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    async fn update_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    async fn delete_entry(
+        &self,
+        id: i32,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+}
+
+/// Meal plans are private; entries belonging to anyone but `owner` fail
+/// with `NotOwner`.
+#[async_trait]
+pub trait MealPlanService {
+    async fn get_meal_plan(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+    ) -> Result<Vec<domain::meal_plan::MealPlanEntry>, domain::meal_plan::Error>;
+    async fn get_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    /// Plans a meal for the entry's owner.
+    async fn create_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    async fn update_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    async fn delete_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error>;
+    /// Creates a shopping list holding the ingredients of every recipe
+    /// planned in the date range, scaled to the servings planned.
+    async fn generate_shopping_list(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+        name: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::meal_plan::Error>;
+}
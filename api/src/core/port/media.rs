@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+/// Somewhere to keep uploaded files, addressed by key.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait MediaStore {
+    async fn put(&self, key: &str, media: domain::media::Media)
+        -> Result<(), domain::media::Error>;
+    async fn get(&self, key: &str) -> Result<domain::media::Media, domain::media::Error>;
+    async fn delete(&self, key: &str) -> Result<(), domain::media::Error>;
+}
+
+/// Records which stored images belong to which recipes and steps. Setters
+/// return the key they replaced so the old file can be removed.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait MediaRepository {
+    async fn set_recipe_image(
+        &self,
+        recipe_id: i32,
+        key: Option<String>,
+    ) -> Result<Option<String>, domain::media::Error>;
+    async fn set_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+        key: Option<String>,
+    ) -> Result<Option<String>, domain::media::Error>;
+}
+
+#[async_trait]
+pub trait MediaService {
+    async fn get_media(&self, key: &str) -> Result<domain::media::Media, domain::media::Error>;
+    async fn set_recipe_image(
+        &self,
+        recipe_id: i32,
+        image: domain::media::ImageUpload,
+    ) -> Result<String, domain::media::Error>;
+    async fn remove_recipe_image(&self, recipe_id: i32) -> Result<(), domain::media::Error>;
+    async fn set_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+        image: domain::media::ImageUpload,
+    ) -> Result<String, domain::media::Error>;
+    async fn remove_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+    ) -> Result<(), domain::media::Error>;
+    async fn delete_recipe_media(
+        &self,
+        recipe: &domain::Recipe,
+    ) -> Result<(), domain::media::Error>;
+    /// Removes the images of `previous` that `updated`, the same recipe
+    /// after an update, no longer has, such as those of removed steps.
+    async fn delete_unused_media(
+        &self,
+        previous: &domain::Recipe,
+        updated: &domain::Recipe,
+    ) -> Result<(), domain::media::Error>;
+}
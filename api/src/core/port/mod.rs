@@ -7,3 +7,36 @@ pub mod auth;
 pub use self::recipe::RecipeRepository;
 pub use self::recipe::RecipeService;
 pub mod recipe;
+pub use self::tag::TagRepository;
+pub use self::tag::TagService;
+pub mod tag;
+pub use self::unit::UnitRepository;
+pub use self::unit::UnitService;
+pub mod unit;
+pub use self::media::MediaRepository;
+pub use self::media::MediaService;
+pub use self::media::MediaStore;
+pub mod media;
+pub use self::archive::ArchiveService;
+pub mod archive;
+pub use self::shopping_list::ShoppingListRepository;
+pub use self::shopping_list::ShoppingListService;
+pub mod shopping_list;
+pub use self::meal_plan::MealPlanRepository;
+pub use self::meal_plan::MealPlanService;
+pub mod meal_plan;
+pub use self::pantry::PantryRepository;
+pub use self::pantry::PantryService;
+pub mod pantry;
+pub use self::cook_log::CookLogRepository;
+pub use self::cook_log::CookLogService;
+pub mod cook_log;
+pub use self::review::ReviewRepository;
+pub use self::review::ReviewService;
+pub mod review;
+pub use self::collection::CollectionRepository;
+pub use self::collection::CollectionService;
+pub mod collection;
+pub use self::revision::RevisionRepository;
+pub use self::revision::RevisionService;
+pub mod revision;
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PantryRepository {
+    async fn get_items(
+        &self,
+        query: domain::pantry::PantryQuery,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error>;
+    async fn get_item(&self, id: i32) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    async fn create_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    async fn update_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    /// Updates several items at once, either all of them or none.
+    async fn update_items(
+        &self,
+        items: Vec<domain::pantry::PantryItem>,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error>;
+    async fn delete_item(
+        &self,
+        id: i32,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+}
+
+/// Pantries are private; items belonging to anyone but `owner` fail with
+/// `NotOwner`.
+#[async_trait]
+pub trait PantryService {
+    async fn get_pantry(
+        &self,
+        query: domain::pantry::PantryQuery,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error>;
+    async fn get_item(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    /// Stocks an item for the item's owner.
+    async fn create_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    async fn update_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    /// Adds to or removes from an item's stock an amount in `units`, or in
+    /// the item's own units if left out.
+    async fn adjust_item(
+        &self,
+        id: i32,
+        change: domain::pantry::StockChange,
+        units: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    async fn delete_item(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error>;
+    /// Takes the ingredients of a recipe, scaled and with its sub-recipes
+    /// expanded, out of the owner's pantry. With `dry_run` the pantry is
+    /// left as it was and the report only says what cooking would take.
+    async fn cook_recipe(
+        &self,
+        recipe_id: i32,
+        scale: Option<domain::recipe::ScaleBy>,
+        dry_run: bool,
+        owner: domain::User,
+    ) -> Result<domain::pantry::CookReport, domain::pantry::Error>;
+}
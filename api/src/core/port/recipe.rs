@@ -2,10 +2,25 @@ use async_trait::async_trait;
 
 use crate::core::domain;
 
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
 #[async_trait]
 pub trait RecipeRepository {
-    async fn get_recipes(&self) -> Result<Vec<domain::Recipe>, domain::recipe::Error>;
+    async fn get_recipes(
+        &self,
+        query: domain::recipe::RecipeQuery,
+    ) -> Result<domain::recipe::RecipePage, domain::recipe::Error>;
     async fn get_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error>;
+    async fn search_recipes(
+        &self,
+        query: domain::recipe::RecipeSearchQuery,
+    ) -> Result<domain::recipe::RecipeSearchPage, domain::recipe::Error>;
+    async fn get_recipes_by_ingredients(
+        &self,
+        query: domain::recipe::IngredientMatchQuery,
+    ) -> Result<domain::recipe::IngredientMatchPage, domain::recipe::Error>;
     async fn create_recipe(
         &self,
         recipe: domain::Recipe,
@@ -19,12 +34,72 @@ pub trait RecipeRepository {
 
 #[async_trait]
 pub trait RecipeService {
-    async fn get_recipes(&self) -> Result<Vec<domain::Recipe>, domain::recipe::Error>;
+    async fn get_recipes(
+        &self,
+        query: domain::recipe::RecipeQuery,
+    ) -> Result<domain::recipe::RecipePage, domain::recipe::Error>;
     async fn get_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error>;
+    /// The recipe with `id` if `viewer`, or an anonymous user when `None`,
+    /// may read it. Recipes hidden from them are reported as not found.
+    async fn get_recipe(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::Recipe, domain::recipe::Error>;
+    async fn scale_recipe(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+        scale: domain::recipe::ScaleBy,
+        promote_units: bool,
+    ) -> Result<domain::Recipe, domain::recipe::Error>;
+    /// Inlines the recipe's sub-recipes, leaving the ones hidden from
+    /// `viewer` as plain ingredients.
+    async fn expand_recipe(
+        &self,
+        recipe: domain::Recipe,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::Recipe, domain::recipe::Error>;
+    async fn search_recipes(
+        &self,
+        query: domain::recipe::RecipeSearchQuery,
+    ) -> Result<domain::recipe::RecipeSearchPage, domain::recipe::Error>;
+    async fn get_recipes_by_ingredients(
+        &self,
+        query: domain::recipe::IngredientMatchQuery,
+    ) -> Result<domain::recipe::IngredientMatchPage, domain::recipe::Error>;
     async fn create_recipe(
         &self,
         recipe: domain::Recipe,
     ) -> Result<domain::Recipe, domain::recipe::Error>;
+    /// Reads free-text ingredient lines, such as pasted ingredient lists,
+    /// into ingredients.
+    async fn parse_ingredient_lines(
+        &self,
+        lines: Vec<String>,
+    ) -> Result<Vec<domain::ingredient_line::ParsedIngredient>, domain::recipe::Error>;
+    /// Creates a recipe for `author` from a schema.org document, reporting
+    /// the fields that could not be carried over.
+    async fn import_recipe(
+        &self,
+        document: domain::schema_org::Document,
+        author: domain::User,
+    ) -> Result<domain::recipe::RecipeImport, domain::recipe::Error>;
+    /// Creates a recipe for `author` from a Cooklang document. `title` names
+    /// the recipe when the document has no title of its own.
+    async fn import_cooklang(
+        &self,
+        text: String,
+        title: Option<String>,
+        author: domain::User,
+    ) -> Result<domain::recipe::RecipeImport, domain::recipe::Error>;
+    /// Copies a recipe into a new one authored by `author`, numbering the
+    /// title if `author` already has a recipe by that name.
+    async fn fork_recipe(
+        &self,
+        id: i32,
+        author: domain::User,
+    ) -> Result<domain::Recipe, domain::recipe::Error>;
     async fn update_recipe(
         &self,
         recipe: domain::Recipe,
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ReviewRepository {
+    async fn get_reviews(
+        &self,
+        recipe_id: i32,
+    ) -> Result<Vec<domain::review::Review>, domain::review::Error>;
+    async fn get_review(&self, id: i32) -> Result<domain::review::Review, domain::review::Error>;
+    async fn create_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    /// Saves the rating and body of a review.
+    async fn update_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    /// Saves the reply to a review.
+    async fn update_reply(
+        &self,
+        id: i32,
+        reply: Option<String>,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    async fn delete_review(&self, id: i32)
+        -> Result<domain::review::Review, domain::review::Error>;
+}
+
+/// Anyone who can read a recipe can read its reviews. Any user but the
+/// recipe's author can review it once, and only the reviewer may change their
+/// review; only the author may reply to one.
+#[async_trait]
+pub trait ReviewService {
+    /// The reviews of a recipe, most recent first.
+    async fn get_reviews(
+        &self,
+        recipe_id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::review::Review>, domain::review::Error>;
+    async fn get_review(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    async fn create_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    async fn update_review(
+        &self,
+        review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    async fn delete_review(
+        &self,
+        id: i32,
+        reviewer: domain::User,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+    /// Sets or, when blank, removes the author's reply to a review.
+    async fn reply_to_review(
+        &self,
+        id: i32,
+        reply: Option<String>,
+        author: domain::User,
+    ) -> Result<domain::review::Review, domain::review::Error>;
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+/// Revisions are recorded by the recipe repository each time a recipe is
+/// saved, so they are only ever read here.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait RevisionRepository {
+    async fn get_revisions(
+        &self,
+        recipe_id: i32,
+    ) -> Result<Vec<domain::revision::RevisionSummary>, domain::revision::Error>;
+    async fn get_revision(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+    ) -> Result<domain::revision::RecipeRevision, domain::revision::Error>;
+}
+
+/// Anyone who can read a recipe can read its history; only its author may
+/// revert it.
+#[async_trait]
+pub trait RevisionService {
+    /// The revisions of a recipe, newest first.
+    async fn get_revisions(
+        &self,
+        recipe_id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::revision::RevisionSummary>, domain::revision::Error>;
+    async fn get_revision(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::revision::RecipeRevision, domain::revision::Error>;
+    async fn diff_revisions(
+        &self,
+        recipe_id: i32,
+        from: i32,
+        to: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::revision::RevisionDiff, domain::revision::Error>;
+    /// Saves the recipe with the contents it had at `revision`, which
+    /// records a new revision rather than discarding the later ones.
+    async fn revert_recipe(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+        author: domain::User,
+    ) -> Result<domain::Recipe, domain::revision::Error>;
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+/// A change made to a shopping list while it is locked for modification.
+pub type ShoppingListChange = Box<
+    dyn FnOnce(&mut domain::shopping_list::ShoppingList) -> Result<(), domain::shopping_list::Error>
+        + Send,
+>;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ShoppingListRepository {
+    async fn get_shopping_lists(
+        &self,
+        owner_id: i32,
+    ) -> Result<Vec<domain::shopping_list::ShoppingList>, domain::shopping_list::Error>;
+    async fn get_shopping_list(
+        &self,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn create_shopping_list(
+        &self,
+        shopping_list: domain::shopping_list::ShoppingList,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    /// Loads the list, applies `modify` to it and saves its name, items and
+    /// recipes, removing the items that are no longer on it. Changes to the
+    /// same list are applied one at a time, and nothing is saved if `modify`
+    /// fails.
+    async fn modify_shopping_list(
+        &self,
+        id: i32,
+        modify: ShoppingListChange,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn delete_shopping_list(
+        &self,
+        id: i32,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+}
+
+/// Shopping lists are private, so every method takes the user acting on
+/// the list and fails with `NotOwner` for anyone else's.
+#[async_trait]
+pub trait ShoppingListService {
+    async fn get_shopping_lists(
+        &self,
+        owner: domain::User,
+    ) -> Result<Vec<domain::shopping_list::ShoppingList>, domain::shopping_list::Error>;
+    async fn get_shopping_list(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn create_shopping_list(
+        &self,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    /// Creates a list holding the ingredients of each recipe, scaled as
+    /// given. Nothing is saved unless every recipe could be added.
+    async fn create_shopping_list_from_recipes(
+        &self,
+        name: String,
+        recipes: Vec<(i32, Option<domain::recipe::ScaleBy>)>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn rename_shopping_list(
+        &self,
+        id: i32,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn delete_shopping_list(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    /// Adds a recipe's ingredients, with sub-recipes expanded, to the list.
+    /// Without a `scale` the recipe is added as written.
+    async fn add_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        scale: Option<domain::recipe::ScaleBy>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    /// Adds an item by hand to the end of the list.
+    async fn add_item(
+        &self,
+        id: i32,
+        name: String,
+        quantity: Option<domain::Quantity>,
+        units: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn update_item(
+        &self,
+        id: i32,
+        item_id: i32,
+        update: domain::shopping_list::ItemUpdate,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn remove_item(
+        &self,
+        id: i32,
+        item_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+    async fn reorder_items(
+        &self,
+        id: i32,
+        item_ids: Vec<i32>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error>;
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait TagRepository {
+    /// Counts only the recipes listed to the user with `viewer_id`, leaving
+    /// out tags used on nothing else.
+    async fn get_tags(
+        &self,
+        viewer_id: Option<i32>,
+    ) -> Result<Vec<domain::tag::TagUsage>, domain::tag::Error>;
+    /// Fails with `NotOwner` when the tag, or the one it would be merged
+    /// into, is on a recipe not written by the user with `owner_id`.
+    async fn rename_tag(
+        &self,
+        id: i32,
+        name: String,
+        owner_id: i32,
+    ) -> Result<domain::tag::TagUsage, domain::tag::Error>;
+}
+
+/// Tags are shared by everyone, so a user may only rename one that is used
+/// on nothing but their own recipes.
+#[async_trait]
+pub trait TagService {
+    /// The tags on recipes listed to `viewer`, along with unused ones.
+    async fn get_tags(
+        &self,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::tag::TagUsage>, domain::tag::Error>;
+    /// Renames the tag with the given id. When another tag already has the
+    /// new name the two are merged, leaving only the existing tag.
+    async fn rename_tag(
+        &self,
+        id: i32,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::tag::TagUsage, domain::tag::Error>;
+}
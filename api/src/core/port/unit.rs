@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+
+use crate::core::domain;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait UnitRepository {
+    async fn get_units(&self) -> Result<Vec<domain::unit::UnitDefinition>, domain::unit::Error>;
+}
+
+#[async_trait]
+pub trait UnitService {
+    async fn get_units(&self) -> Result<domain::unit::UnitCatalog, domain::unit::Error>;
+    async fn convert_recipe(
+        &self,
+        recipe: domain::Recipe,
+        system: domain::unit::MeasurementSystem,
+    ) -> Result<domain::Recipe, domain::unit::Error>;
+}
@@ -0,0 +1,276 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+
+/// Page size used when walking through a user's recipes.
+const PAGE_LIMIT: i64 = 100;
+
+pub struct DefaultArchiveService {
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    media_service: Arc<dyn port::MediaService + Send + Sync>,
+}
+
+impl DefaultArchiveService {
+    pub fn new(
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+        media_service: Arc<dyn port::MediaService + Send + Sync>,
+    ) -> DefaultArchiveService {
+        DefaultArchiveService {
+            recipe_service,
+            media_service,
+        }
+    }
+
+    /// The ids of every recipe `author` owns, keyed by title.
+    async fn recipe_ids(
+        &self,
+        author: &domain::User,
+    ) -> Result<HashMap<String, i32>, domain::archive::Error> {
+        let mut ids = HashMap::new();
+        let mut page = 1;
+        loop {
+            let recipes = self
+                .recipe_service
+                .get_recipes(domain::recipe::RecipeQuery {
+                    viewer_id: author.id,
+                    author_id: author.id,
+                    page,
+                    limit: PAGE_LIMIT,
+                    ..Default::default()
+                })
+                .await?;
+            for recipe in &recipes.recipes {
+                if let Some(id) = recipe.id {
+                    ids.insert(recipe.title.clone(), id);
+                }
+            }
+            match recipes.next_page() {
+                Some(next) => page = next,
+                None => return Ok(ids),
+            }
+        }
+    }
+
+    /// Reads an archived image back into an upload.
+    async fn upload(
+        archive: &domain::archive::Archive,
+        path: &str,
+    ) -> Result<domain::media::ImageUpload, String> {
+        let media = archive
+            .media
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("image `{}` is missing from the archive", path))?;
+        tokio::task::spawn_blocking(move || {
+            domain::media::ImageUpload::new(&media.content_type, media.bytes)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("image `{}`: {}", path, e))
+    }
+
+    /// Gives an imported recipe the archived recipe's images, noting the
+    /// ones that could not be restored.
+    async fn restore_images(
+        &self,
+        archive: &domain::archive::Archive,
+        archived: &domain::archive::ArchivedRecipe,
+        recipe: &domain::Recipe,
+        warnings: &mut Vec<String>,
+    ) {
+        let id = recipe.id.unwrap_or_default();
+        match &archived.image {
+            Some(path) => {
+                let result = match Self::upload(archive, path).await {
+                    Ok(upload) => self
+                        .media_service
+                        .set_recipe_image(id, upload)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    warnings.push(format!("recipe image not restored: {}", e));
+                }
+            }
+            None if recipe.image.is_some() => {
+                if let Err(e) = self.media_service.remove_recipe_image(id).await {
+                    warnings.push(format!("previous recipe image not removed: {}", e));
+                }
+            }
+            None => {}
+        }
+        for archived_step in &archived.steps {
+            let step = match recipe
+                .steps
+                .iter()
+                .find(|x| x.ordinal == archived_step.ordinal)
+            {
+                Some(step) => step,
+                None => continue,
+            };
+            let (path, step_id) = match (&archived_step.image, step.id) {
+                (Some(path), Some(step_id)) => (path, step_id),
+                (None, Some(step_id)) if step.image.is_some() => {
+                    if let Err(e) = self.media_service.remove_step_image(id, step_id).await {
+                        warnings.push(format!(
+                            "previous image of step {} not removed: {}",
+                            archived_step.ordinal, e
+                        ));
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let result = match Self::upload(archive, path).await {
+                Ok(upload) => self
+                    .media_service
+                    .set_step_image(id, step_id, upload)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warnings.push(format!(
+                    "image of step {} not restored: {}",
+                    archived_step.ordinal, e
+                ));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl port::ArchiveService for DefaultArchiveService {
+    async fn export_library(
+        &self,
+        author: domain::User,
+    ) -> Result<domain::archive::Archive, domain::archive::Error> {
+        let mut ids: Vec<i32> = self.recipe_ids(&author).await?.into_values().collect();
+        ids.sort_unstable();
+        let mut recipes = Vec::with_capacity(ids.len());
+        let mut media = Vec::new();
+        for id in ids {
+            let mut recipe = self.recipe_service.get_recipe_by_id(id).await?;
+            // Images that have gone missing from the store are left out
+            // rather than failing the whole export.
+            let images = std::iter::once(&mut recipe.image)
+                .chain(recipe.steps.iter_mut().map(|x| &mut x.image));
+            for image in images {
+                if let Some(key) = image.clone() {
+                    match self.media_service.get_media(&key).await {
+                        Ok(stored) => media.push((key, stored)),
+                        Err(e) => {
+                            log::warn!("Leaving media `{}` out of archive due to: {}", key, e);
+                            *image = None;
+                        }
+                    }
+                }
+            }
+            recipes.push(recipe);
+        }
+        let mut archive = domain::archive::Archive::new(&author, &recipes);
+        for (key, stored) in media {
+            archive.add_media(&key, stored);
+        }
+        Ok(archive)
+    }
+
+    async fn import_library(
+        &self,
+        archive: domain::archive::Archive,
+        author: domain::User,
+        on_conflict: domain::archive::OnConflict,
+    ) -> Result<domain::archive::ImportReport, domain::archive::Error> {
+        let mut titles = self.recipe_ids(&author).await?;
+        let mut imported_ids: HashMap<i32, i32> = HashMap::new();
+        let mut report = domain::archive::ImportReport::default();
+        for archived in domain::archive::import_order(&archive.manifest.recipes) {
+            let mut warnings: Vec<String> = archived
+                .ingredients
+                .iter()
+                .filter(|x| {
+                    x.sub_recipe
+                        .is_some_and(|id| !imported_ids.contains_key(&id))
+                })
+                .map(|x| {
+                    format!(
+                        "`{}` refers to a recipe that was not imported, \
+                         so it was kept as a plain ingredient",
+                        x.ingredient
+                    )
+                })
+                .collect();
+            let mut recipe = archived.to_recipe(author.clone(), &imported_ids);
+            let existing = titles.get(&archived.title).copied();
+            let (result, outcome) = match (existing, on_conflict) {
+                (None, _) => (
+                    self.recipe_service.create_recipe(recipe).await,
+                    domain::archive::ImportOutcome::Created,
+                ),
+                (Some(id), domain::archive::OnConflict::Skip) => {
+                    imported_ids.insert(archived.id, id);
+                    report.recipes.push(domain::archive::ImportedRecipe {
+                        title: archived.title.clone(),
+                        outcome: domain::archive::ImportOutcome::Skipped,
+                        recipe_id: Some(id),
+                        warnings: vec![],
+                    });
+                    continue;
+                }
+                (Some(_), domain::archive::OnConflict::Rename) => {
+                    recipe.title =
+                        domain::recipe::numbered_title(&recipe.title, |x| titles.contains_key(x));
+                    let title = recipe.title.clone();
+                    (
+                        self.recipe_service.create_recipe(recipe).await,
+                        domain::archive::ImportOutcome::Renamed(title),
+                    )
+                }
+                (Some(id), domain::archive::OnConflict::Overwrite) => {
+                    recipe.id = Some(id);
+                    // Steps are unique by ordinal, so the existing ones are
+                    // updated in place rather than replaced.
+                    let result = match self.recipe_service.get_recipe_by_id(id).await {
+                        Ok(existing) => {
+                            for step in recipe.steps.iter_mut() {
+                                step.id = existing
+                                    .steps
+                                    .iter()
+                                    .find(|x| x.ordinal == step.ordinal)
+                                    .and_then(|x| x.id);
+                            }
+                            self.recipe_service.update_recipe(recipe).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    (result, domain::archive::ImportOutcome::Overwritten)
+                }
+            };
+            let recipe = match result {
+                Ok(recipe) => recipe,
+                Err(e) => {
+                    report.recipes.push(domain::archive::ImportedRecipe {
+                        title: archived.title.clone(),
+                        outcome: domain::archive::ImportOutcome::Failed(e.to_string()),
+                        recipe_id: None,
+                        warnings,
+                    });
+                    continue;
+                }
+            };
+            let id = recipe.id.unwrap_or_default();
+            titles.insert(recipe.title.clone(), id);
+            imported_ids.insert(archived.id, id);
+            self.restore_images(&archive, archived, &recipe, &mut warnings)
+                .await;
+            report.recipes.push(domain::archive::ImportedRecipe {
+                title: archived.title.clone(),
+                outcome,
+                recipe_id: Some(id),
+                warnings,
+            });
+        }
+        Ok(report)
+    }
+}
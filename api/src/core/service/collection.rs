@@ -0,0 +1,351 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultCollectionService {
+    collection_repository: Box<dyn port::CollectionRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+}
+
+impl DefaultCollectionService {
+    pub fn new(
+        collection_repository: Box<dyn port::CollectionRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    ) -> DefaultCollectionService {
+        DefaultCollectionService {
+            collection_repository,
+            recipe_service,
+        }
+    }
+
+    /// Loads a collection, making sure it belongs to `owner`.
+    async fn owned_collection(
+        &self,
+        id: i32,
+        owner: &domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let collection = self.collection_repository.get_collection(id).await?;
+        if collection.owner.id != owner.id {
+            return Err(domain::collection::Error::NotOwner(id));
+        }
+        Ok(collection)
+    }
+}
+
+#[async_trait]
+impl port::CollectionService for DefaultCollectionService {
+    async fn get_collections(
+        &self,
+        owner: domain::User,
+    ) -> Result<Vec<domain::collection::Collection>, domain::collection::Error> {
+        let owner_id = owner.id.ok_or(domain::collection::Error::Unexpected)?;
+        Ok(self
+            .collection_repository
+            .get_collections(owner_id)
+            .await?
+            .into_iter()
+            .map(|x| x.without_hidden_recipes(Some(owner_id)))
+            .collect())
+    }
+
+    async fn get_collection(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let collection = self.collection_repository.get_collection(id).await?;
+        let viewer_id = viewer.and_then(|x| x.id);
+        if !collection.shared && viewer_id != collection.owner.id {
+            return Err(domain::collection::Error::NotOwner(id));
+        }
+        Ok(collection.without_hidden_recipes(viewer_id))
+    }
+
+    async fn create_collection(
+        &self,
+        name: String,
+        shared: bool,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let name = domain::collection::validate_name(&name)?;
+        self.collection_repository
+            .create_collection(domain::collection::Collection::new(name, shared, owner))
+            .await
+    }
+
+    async fn update_collection(
+        &self,
+        id: i32,
+        name: String,
+        shared: bool,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let name = domain::collection::validate_name(&name)?;
+        let owner_id = owner.id;
+        let mut collection = self.owned_collection(id, &owner).await?;
+        collection.name = name;
+        collection.shared = shared;
+        Ok(self
+            .collection_repository
+            .update_collection(collection)
+            .await?
+            .without_hidden_recipes(owner_id))
+    }
+
+    async fn delete_collection(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        self.owned_collection(id, &owner).await?;
+        Ok(self
+            .collection_repository
+            .delete_collection(id)
+            .await?
+            .without_hidden_recipes(owner.id))
+    }
+
+    async fn add_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let owner_id = owner.id;
+        let mut collection = self.owned_collection(id, &owner).await?;
+        let recipe = self
+            .recipe_service
+            .get_recipe(recipe_id, Some(owner))
+            .await?;
+        collection.add_recipe(domain::collection::CollectionRecipe {
+            recipe_id,
+            title: recipe.title,
+            author_id: recipe
+                .author
+                .id
+                .ok_or(domain::collection::Error::Unexpected)?,
+            visibility: recipe.visibility,
+            draft: recipe.draft,
+        });
+        Ok(self
+            .collection_repository
+            .update_collection(collection)
+            .await?
+            .without_hidden_recipes(owner_id))
+    }
+
+    async fn remove_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let owner_id = owner.id;
+        let mut collection = self.owned_collection(id, &owner).await?;
+        collection.remove_recipe(recipe_id)?;
+        Ok(self
+            .collection_repository
+            .update_collection(collection)
+            .await?
+            .without_hidden_recipes(owner_id))
+    }
+
+    async fn reorder_recipes(
+        &self,
+        id: i32,
+        recipe_ids: Vec<i32>,
+        owner: domain::User,
+    ) -> Result<domain::collection::Collection, domain::collection::Error> {
+        let owner_id = owner.id;
+        let mut collection = self.owned_collection(id, &owner).await?;
+        // Recipes the owner can no longer read keep their place at the end.
+        let mut recipe_ids = recipe_ids;
+        recipe_ids.extend(
+            collection
+                .recipes
+                .iter()
+                .filter(|x| !x.is_visible_to(owner_id))
+                .map(|x| x.recipe_id),
+        );
+        collection.reorder(&recipe_ids)?;
+        Ok(self
+            .collection_repository
+            .update_collection(collection)
+            .await?
+            .without_hidden_recipes(owner_id))
+    }
+
+    async fn get_favorites(
+        &self,
+        user: domain::User,
+    ) -> Result<Vec<domain::collection::CollectionRecipe>, domain::collection::Error> {
+        let user_id = user.id.ok_or(domain::collection::Error::Unexpected)?;
+        Ok(self
+            .collection_repository
+            .get_favorites(user_id)
+            .await?
+            .into_iter()
+            .filter(|x| x.is_visible_to(Some(user_id)))
+            .collect())
+    }
+
+    async fn add_favorite(
+        &self,
+        recipe_id: i32,
+        user: domain::User,
+    ) -> Result<(), domain::collection::Error> {
+        let user_id = user.id.ok_or(domain::collection::Error::Unexpected)?;
+        self.recipe_service
+            .get_recipe(recipe_id, Some(user))
+            .await?;
+        self.collection_repository
+            .add_favorite(user_id, recipe_id)
+            .await
+    }
+
+    async fn remove_favorite(
+        &self,
+        recipe_id: i32,
+        user: domain::User,
+    ) -> Result<(), domain::collection::Error> {
+        let user_id = user.id.ok_or(domain::collection::Error::Unexpected)?;
+        self.collection_repository
+            .remove_favorite(user_id, recipe_id)
+            .await
+    }
+
+    async fn get_favorited(
+        &self,
+        recipe_ids: Vec<i32>,
+        user: domain::User,
+    ) -> Result<Vec<i32>, domain::collection::Error> {
+        let user_id = user.id.ok_or(domain::collection::Error::Unexpected)?;
+        self.collection_repository
+            .get_favorited(user_id, recipe_ids)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            collection::{CollectionService, MockCollectionRepository},
+            recipe::MockRecipeRepository,
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn service(repository: MockCollectionRepository) -> DefaultCollectionService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(MockRecipeRepository::new()),
+            unit_service,
+        ));
+        DefaultCollectionService::new(Box::new(repository), recipe_service)
+    }
+
+    fn user(id: i32) -> domain::User {
+        domain::User {
+            id: Some(id),
+            name: format!("user {}", id),
+        }
+    }
+
+    fn collection(shared: bool) -> domain::collection::Collection {
+        domain::collection::Collection {
+            id: Some(3),
+            ..domain::collection::Collection::new("Desserts".to_owned(), shared, user(1))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_shared_collections_can_be_read_by_others() {
+        let mut mock = MockCollectionRepository::new();
+        mock.expect_get_collection()
+            .with(eq(3))
+            .returning(|_| Ok(collection(false)));
+        mock.expect_get_collection()
+            .with(eq(4))
+            .returning(|_| Ok(collection(true)));
+        let service = service(mock);
+        assert!(service.get_collection(3, Some(user(1))).await.is_ok());
+        for viewer in [Some(user(2)), None] {
+            assert_eq!(
+                service.get_collection(3, viewer).await.unwrap_err(),
+                domain::collection::Error::NotOwner(3)
+            );
+        }
+        assert!(service.get_collection(4, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reorder_keeps_recipes_hidden_from_owner() {
+        let mut mock = MockCollectionRepository::new();
+        mock.expect_get_collection().with(eq(3)).returning(|_| {
+            let mut collection = collection(true);
+            for (recipe_id, author_id, draft) in [(4, 1, true), (7, 2, true), (9, 2, false)] {
+                collection.add_recipe(domain::collection::CollectionRecipe {
+                    recipe_id,
+                    title: format!("recipe {}", recipe_id),
+                    author_id,
+                    visibility: Default::default(),
+                    draft,
+                });
+            }
+            Ok(collection)
+        });
+        mock.expect_update_collection()
+            .withf(|x| x.recipes.iter().map(|x| x.recipe_id).eq([9, 4, 7]))
+            .times(1)
+            .returning(Ok);
+        let collection = service(mock)
+            .reorder_recipes(3, vec![9, 4], user(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            collection
+                .recipes
+                .iter()
+                .map(|x| x.recipe_id)
+                .collect::<Vec<_>>(),
+            vec![9, 4]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_collections_cannot_be_changed_by_others() {
+        let mut mock = MockCollectionRepository::new();
+        mock.expect_get_collection()
+            .with(eq(3))
+            .returning(|_| Ok(collection(true)));
+        mock.expect_update_collection().never();
+        mock.expect_delete_collection().never();
+        let service = service(mock);
+        assert_eq!(
+            service
+                .update_collection(3, "Mine now".to_owned(), true, user(2))
+                .await
+                .unwrap_err(),
+            domain::collection::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service
+                .reorder_recipes(3, vec![], user(2))
+                .await
+                .unwrap_err(),
+            domain::collection::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service.delete_collection(3, user(2)).await.unwrap_err(),
+            domain::collection::Error::NotOwner(3)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultCookLogService {
+    cook_log_repository: Box<dyn port::CookLogRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+}
+
+impl DefaultCookLogService {
+    pub fn new(
+        cook_log_repository: Box<dyn port::CookLogRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    ) -> DefaultCookLogService {
+        DefaultCookLogService {
+            cook_log_repository,
+            recipe_service,
+        }
+    }
+
+    /// Loads an entry, making sure it belongs to `owner`.
+    async fn owned_entry(
+        &self,
+        id: i32,
+        owner: &domain::User,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let entry = self.cook_log_repository.get_entry(id).await?;
+        if entry.owner.id != owner.id {
+            return Err(domain::cook_log::Error::NotOwner(id));
+        }
+        Ok(entry)
+    }
+
+    /// Validates an entry and fills in the title of its recipe.
+    async fn prepare(
+        &self,
+        mut entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        entry.validate()?;
+        entry.recipe_title = self
+            .recipe_service
+            .get_recipe(entry.recipe_id, Some(entry.owner.clone()))
+            .await?
+            .title;
+        Ok(entry)
+    }
+}
+
+#[async_trait]
+impl port::CookLogService for DefaultCookLogService {
+    async fn get_history(
+        &self,
+        query: domain::cook_log::CookLogQuery,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::cook_log::CookLogEntry>, domain::cook_log::Error> {
+        let viewer_id = viewer.as_ref().and_then(|x| x.id);
+        if let domain::cook_log::CookLogQuery::Recipe(recipe_id) = query {
+            self.recipe_service.get_recipe(recipe_id, viewer).await?;
+        }
+        self.cook_log_repository.get_entries(query, viewer_id).await
+    }
+
+    async fn get_entry(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let entry = self.cook_log_repository.get_entry(id).await?;
+        match self
+            .recipe_service
+            .get_recipe(entry.recipe_id, viewer)
+            .await
+        {
+            Ok(_) => Ok(entry),
+            Err(domain::recipe::Error::RecipeNotFound(_)) => {
+                Err(domain::cook_log::Error::EntryNotFound(id))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn create_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let entry = self.prepare(entry).await?;
+        self.cook_log_repository.create_entry(entry).await
+    }
+
+    async fn update_entry(
+        &self,
+        entry: domain::cook_log::CookLogEntry,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        let id = entry.id.ok_or(domain::cook_log::Error::Unexpected)?;
+        self.owned_entry(id, &entry.owner).await?;
+        let entry = self.prepare(entry).await?;
+        self.cook_log_repository.update_entry(entry).await
+    }
+
+    async fn delete_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::cook_log::CookLogEntry, domain::cook_log::Error> {
+        self.owned_entry(id, &owner).await?;
+        self.cook_log_repository.delete_entry(id).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            cook_log::{CookLogService, MockCookLogRepository},
+            recipe::MockRecipeRepository,
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn service(
+        repository: MockCookLogRepository,
+        recipe_repository: MockRecipeRepository,
+    ) -> DefaultCookLogService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(recipe_repository),
+            unit_service,
+        ));
+        DefaultCookLogService::new(Box::new(repository), recipe_service)
+    }
+
+    fn entry(owner_id: i32, rating: Option<i16>) -> domain::cook_log::CookLogEntry {
+        domain::cook_log::CookLogEntry {
+            id: Some(3),
+            owner: domain::User {
+                id: Some(owner_id),
+                name: format!("user {}", owner_id),
+            },
+            recipe_id: 5,
+            recipe_title: "Soup".to_owned(),
+            cooked_on: chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            servings: 2.into(),
+            notes: String::new(),
+            modifications: String::new(),
+            rating,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_users_entry_is_refused() {
+        let mut mock = MockCookLogRepository::new();
+        mock.expect_get_entry()
+            .with(eq(3))
+            .returning(|_| Ok(entry(1, None)));
+        mock.expect_update_entry().never();
+        mock.expect_delete_entry().never();
+        let service = service(mock, MockRecipeRepository::new());
+        assert_eq!(
+            service.update_entry(entry(2, None)).await.unwrap_err(),
+            domain::cook_log::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service
+                .delete_entry(3, entry(2, None).owner)
+                .await
+                .unwrap_err(),
+            domain::cook_log::Error::NotOwner(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_rating_is_refused() {
+        let mut mock = MockCookLogRepository::new();
+        mock.expect_create_entry().never();
+        let mut recipe_mock = MockRecipeRepository::new();
+        recipe_mock.expect_get_recipe_by_id().never();
+        assert!(matches!(
+            service(mock, recipe_mock)
+                .create_entry(entry(1, Some(6)))
+                .await
+                .unwrap_err(),
+            domain::cook_log::Error::InvalidCookLog(_)
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultMealPlanService {
+    meal_plan_repository: Box<dyn port::MealPlanRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    shopping_list_service: Arc<dyn port::ShoppingListService + Send + Sync>,
+}
+
+impl DefaultMealPlanService {
+    pub fn new(
+        meal_plan_repository: Box<dyn port::MealPlanRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+        shopping_list_service: Arc<dyn port::ShoppingListService + Send + Sync>,
+    ) -> DefaultMealPlanService {
+        DefaultMealPlanService {
+            meal_plan_repository,
+            recipe_service,
+            shopping_list_service,
+        }
+    }
+
+    /// Loads an entry, making sure it belongs to `owner`.
+    async fn owned_entry(
+        &self,
+        id: i32,
+        owner: &domain::User,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let entry = self.meal_plan_repository.get_entry(id).await?;
+        if entry.owner.id != owner.id {
+            return Err(domain::meal_plan::Error::NotOwner(id));
+        }
+        Ok(entry)
+    }
+
+    /// Validates an entry and fills in the title of its recipe.
+    async fn prepare(
+        &self,
+        mut entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        entry.validate()?;
+        entry.recipe_title = self
+            .recipe_service
+            .get_recipe(entry.recipe_id, Some(entry.owner.clone()))
+            .await?
+            .title;
+        Ok(entry)
+    }
+}
+
+#[async_trait]
+impl port::MealPlanService for DefaultMealPlanService {
+    async fn get_meal_plan(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+    ) -> Result<Vec<domain::meal_plan::MealPlanEntry>, domain::meal_plan::Error> {
+        query.validate()?;
+        self.meal_plan_repository.get_entries(query).await
+    }
+
+    async fn get_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        self.owned_entry(id, &owner).await
+    }
+
+    async fn create_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let entry = self.prepare(entry).await?;
+        self.meal_plan_repository.create_entry(entry).await
+    }
+
+    async fn update_entry(
+        &self,
+        entry: domain::meal_plan::MealPlanEntry,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        let id = entry.id.ok_or(domain::meal_plan::Error::Unexpected)?;
+        self.owned_entry(id, &entry.owner).await?;
+        let entry = self.prepare(entry).await?;
+        self.meal_plan_repository.update_entry(entry).await
+    }
+
+    async fn delete_entry(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::meal_plan::MealPlanEntry, domain::meal_plan::Error> {
+        self.owned_entry(id, &owner).await?;
+        self.meal_plan_repository.delete_entry(id).await
+    }
+
+    async fn generate_shopping_list(
+        &self,
+        query: domain::meal_plan::MealPlanQuery,
+        name: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::meal_plan::Error> {
+        let name = name.unwrap_or_else(|| format!("Meal plan {} to {}", query.from, query.to));
+        let entries = self.get_meal_plan(query).await?;
+        let mut recipes = Vec::new();
+        for (recipe_id, servings) in domain::meal_plan::planned_recipes(&entries)? {
+            let recipe = self
+                .recipe_service
+                .get_recipe(recipe_id, Some(owner.clone()))
+                .await?;
+            let scale = domain::meal_plan::servings_scale(&recipe, servings);
+            recipes.push((recipe_id, Some(scale)));
+        }
+        Ok(self
+            .shopping_list_service
+            .create_shopping_list_from_recipes(name, recipes, owner)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            meal_plan::{MealPlanService, MockMealPlanRepository},
+            recipe::MockRecipeRepository,
+            shopping_list::MockShoppingListRepository,
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultShoppingListService, DefaultUnitService},
+    };
+
+    fn service(repository: MockMealPlanRepository) -> DefaultMealPlanService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(MockRecipeRepository::new()),
+            unit_service.clone(),
+        ));
+        let shopping_list_service = Arc::new(DefaultShoppingListService::new(
+            Box::new(MockShoppingListRepository::new()),
+            recipe_service.clone(),
+            unit_service,
+        ));
+        DefaultMealPlanService::new(Box::new(repository), recipe_service, shopping_list_service)
+    }
+
+    fn entry(owner_id: i32) -> domain::meal_plan::MealPlanEntry {
+        domain::meal_plan::MealPlanEntry {
+            id: Some(3),
+            owner: domain::User {
+                id: Some(owner_id),
+                name: format!("user {}", owner_id),
+            },
+            date: chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            slot: domain::meal_plan::MealSlot::Dinner,
+            recipe_id: 5,
+            recipe_title: "Soup".to_owned(),
+            servings: 2.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_users_entry_is_refused() {
+        let mut mock = MockMealPlanRepository::new();
+        mock.expect_get_entry()
+            .with(eq(3))
+            .returning(|_| Ok(entry(1)));
+        mock.expect_update_entry().never();
+        mock.expect_delete_entry().never();
+        let service = service(mock);
+        assert_eq!(
+            service.update_entry(entry(2)).await.unwrap_err(),
+            domain::meal_plan::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service.delete_entry(3, entry(2).owner).await.unwrap_err(),
+            domain::meal_plan::Error::NotOwner(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_meal_plan_rejects_backwards_range() {
+        let mut mock = MockMealPlanRepository::new();
+        mock.expect_get_entries().never();
+        let query = domain::meal_plan::MealPlanQuery {
+            owner_id: 1,
+            from: chrono::NaiveDate::from_ymd_opt(2026, 10, 20).unwrap(),
+            to: chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+        };
+        assert!(matches!(
+            service(mock).get_meal_plan(query).await.unwrap_err(),
+            domain::meal_plan::Error::InvalidMealPlan(_)
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct DefaultMediaService {
+    media_repository: Box<dyn port::MediaRepository + Send + Sync>,
+    media_store: Box<dyn port::MediaStore + Send + Sync>,
+}
+
+impl DefaultMediaService {
+    pub fn new(
+        media_repository: Box<dyn port::MediaRepository + Send + Sync>,
+        media_store: Box<dyn port::MediaStore + Send + Sync>,
+    ) -> DefaultMediaService {
+        DefaultMediaService {
+            media_repository,
+            media_store,
+        }
+    }
+
+    /// Stores the image and its thumbnail under a new key beneath `prefix`.
+    async fn put_image(
+        &self,
+        prefix: &str,
+        image: domain::media::ImageUpload,
+    ) -> Result<String, domain::media::Error> {
+        let key = domain::media::image_key(prefix, image.format());
+        let (image, thumbnail) = tokio::task::spawn_blocking(move || {
+            let thumbnail = image.thumbnail();
+            (image, thumbnail)
+        })
+        .await
+        .map_err(|_| domain::media::Error::Unexpected)?;
+        let thumbnail = thumbnail?;
+        self.media_store.put(&key, image.into_media()).await?;
+        self.media_store
+            .put(&domain::media::thumbnail_key(&key), thumbnail)
+            .await?;
+        Ok(key)
+    }
+
+    /// Removes an image and its thumbnail. Failures are only logged, as the
+    /// image is already unreferenced by the time it is removed.
+    async fn remove_image(&self, key: &str) {
+        for key in [key.to_owned(), domain::media::thumbnail_key(key)] {
+            if let Err(e) = self.media_store.delete(&key).await {
+                log::warn!("Failed to remove media `{}` due to: {}", key, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl port::MediaService for DefaultMediaService {
+    async fn get_media(&self, key: &str) -> Result<domain::media::Media, domain::media::Error> {
+        domain::media::validate_key(key)?;
+        self.media_store.get(key).await
+    }
+
+    async fn set_recipe_image(
+        &self,
+        recipe_id: i32,
+        image: domain::media::ImageUpload,
+    ) -> Result<String, domain::media::Error> {
+        let key = self
+            .put_image(&format!("recipe/{}", recipe_id), image)
+            .await?;
+        match self
+            .media_repository
+            .set_recipe_image(recipe_id, Some(key.clone()))
+            .await
+        {
+            Ok(Some(previous)) => self.remove_image(&previous).await,
+            Ok(None) => {}
+            Err(e) => {
+                self.remove_image(&key).await;
+                return Err(e);
+            }
+        }
+        Ok(key)
+    }
+
+    async fn remove_recipe_image(&self, recipe_id: i32) -> Result<(), domain::media::Error> {
+        if let Some(previous) = self
+            .media_repository
+            .set_recipe_image(recipe_id, None)
+            .await?
+        {
+            self.remove_image(&previous).await;
+        }
+        Ok(())
+    }
+
+    async fn set_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+        image: domain::media::ImageUpload,
+    ) -> Result<String, domain::media::Error> {
+        let key = self
+            .put_image(&format!("recipe/{}/step/{}", recipe_id, step_id), image)
+            .await?;
+        match self
+            .media_repository
+            .set_step_image(recipe_id, step_id, Some(key.clone()))
+            .await
+        {
+            Ok(Some(previous)) => self.remove_image(&previous).await,
+            Ok(None) => {}
+            Err(e) => {
+                self.remove_image(&key).await;
+                return Err(e);
+            }
+        }
+        Ok(key)
+    }
+
+    async fn remove_step_image(
+        &self,
+        recipe_id: i32,
+        step_id: i32,
+    ) -> Result<(), domain::media::Error> {
+        if let Some(previous) = self
+            .media_repository
+            .set_step_image(recipe_id, step_id, None)
+            .await?
+        {
+            self.remove_image(&previous).await;
+        }
+        Ok(())
+    }
+
+    async fn delete_recipe_media(
+        &self,
+        recipe: &domain::Recipe,
+    ) -> Result<(), domain::media::Error> {
+        for key in recipe_media(recipe) {
+            self.remove_image(key).await;
+        }
+        Ok(())
+    }
+
+    async fn delete_unused_media(
+        &self,
+        previous: &domain::Recipe,
+        updated: &domain::Recipe,
+    ) -> Result<(), domain::media::Error> {
+        let kept: HashSet<&String> = recipe_media(updated).collect();
+        for key in recipe_media(previous).filter(|x| !kept.contains(x)) {
+            self.remove_image(key).await;
+        }
+        Ok(())
+    }
+}
+
+/// The keys of the images of a recipe and its steps.
+fn recipe_media(recipe: &domain::Recipe) -> impl Iterator<Item = &String> {
+    recipe
+        .image
+        .iter()
+        .chain(recipe.steps.iter().filter_map(|x| x.image.as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::Cursor;
+
+    use super::*;
+
+    use crate::core::port::{
+        media::{MockMediaRepository, MockMediaStore},
+        MediaService,
+    };
+
+    fn upload() -> domain::media::ImageUpload {
+        let image = image::ImageBuffer::from_pixel(4, 4, image::Rgb([0u8, 0, 0]));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        domain::media::ImageUpload::new("image/png", bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_recipe_image_replaces_previous() {
+        let mut store = MockMediaStore::new();
+        store
+            .expect_put()
+            .withf(|key, _| key.starts_with("recipe/3/"))
+            .times(2)
+            .returning(|_, _| Ok(()));
+        store
+            .expect_delete()
+            .withf(|key| key == "recipe/3/old.png" || key == "recipe/3/old.thumb.jpg")
+            .times(2)
+            .returning(|_| Ok(()));
+        let mut repository = MockMediaRepository::new();
+        repository
+            .expect_set_recipe_image()
+            .withf(|id, key| *id == 3 && key.is_some())
+            .returning(|_, _| Ok(Some("recipe/3/old.png".to_owned())));
+        let media_service = DefaultMediaService::new(Box::new(repository), Box::new(store));
+
+        let key = media_service.set_recipe_image(3, upload()).await.unwrap();
+        assert!(key.starts_with("recipe/3/") && key.ends_with(".png"));
+    }
+
+    #[tokio::test]
+    async fn test_set_step_image_cleans_up_when_step_is_missing() {
+        let mut store = MockMediaStore::new();
+        store.expect_put().times(2).returning(|_, _| Ok(()));
+        store.expect_delete().times(2).returning(|_| Ok(()));
+        let mut repository = MockMediaRepository::new();
+        repository
+            .expect_set_step_image()
+            .returning(|_, step_id, _| Err(domain::media::Error::StepNotFound(step_id)));
+        let media_service = DefaultMediaService::new(Box::new(repository), Box::new(store));
+
+        assert!(matches!(
+            media_service
+                .set_step_image(3, 9, upload())
+                .await
+                .unwrap_err(),
+            domain::media::Error::StepNotFound(9)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_media_rejects_unsafe_keys() {
+        let mut store = MockMediaStore::new();
+        store.expect_get().never();
+        let media_service =
+            DefaultMediaService::new(Box::new(MockMediaRepository::new()), Box::new(store));
+        assert!(matches!(
+            media_service.get_media("../secret").await.unwrap_err(),
+            domain::media::Error::MediaNotFound(_)
+        ));
+    }
+}
@@ -4,3 +4,25 @@ mod auth;
 pub use self::auth::DefaultAuthUserService;
 mod recipe;
 pub use self::recipe::DefaultRecipeService;
+mod tag;
+pub use self::tag::DefaultTagService;
+mod unit;
+pub use self::unit::DefaultUnitService;
+mod media;
+pub use self::media::DefaultMediaService;
+mod archive;
+pub use self::archive::DefaultArchiveService;
+mod shopping_list;
+pub use self::shopping_list::DefaultShoppingListService;
+mod meal_plan;
+pub use self::meal_plan::DefaultMealPlanService;
+mod pantry;
+pub use self::pantry::DefaultPantryService;
+mod cook_log;
+pub use self::cook_log::DefaultCookLogService;
+mod review;
+pub use self::review::DefaultReviewService;
+mod collection;
+pub use self::collection::DefaultCollectionService;
+mod revision;
+pub use self::revision::DefaultRevisionService;
@@ -0,0 +1,238 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultPantryService {
+    pantry_repository: Box<dyn port::PantryRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    unit_service: Arc<dyn port::UnitService + Send + Sync>,
+}
+
+impl DefaultPantryService {
+    pub fn new(
+        pantry_repository: Box<dyn port::PantryRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+        unit_service: Arc<dyn port::UnitService + Send + Sync>,
+    ) -> DefaultPantryService {
+        DefaultPantryService {
+            pantry_repository,
+            recipe_service,
+            unit_service,
+        }
+    }
+
+    /// Loads an item, making sure it belongs to `owner`.
+    async fn owned_item(
+        &self,
+        id: i32,
+        owner: &domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let item = self.pantry_repository.get_item(id).await?;
+        if item.owner.id != owner.id {
+            return Err(domain::pantry::Error::NotOwner(id));
+        }
+        Ok(item)
+    }
+
+    /// Validates an item and puts its units in their catalog spelling.
+    async fn prepare(
+        &self,
+        mut item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        item.validate()?;
+        let catalog = self.unit_service.get_units().await?;
+        let units = catalog.find(&item.units.name)?;
+        item.units = domain::recipe::Unit {
+            id: Some(units.id),
+            name: units.name.clone(),
+        };
+        item.ingredient.id = None;
+        Ok(item)
+    }
+}
+
+#[async_trait]
+impl port::PantryService for DefaultPantryService {
+    async fn get_pantry(
+        &self,
+        query: domain::pantry::PantryQuery,
+    ) -> Result<Vec<domain::pantry::PantryItem>, domain::pantry::Error> {
+        self.pantry_repository.get_items(query).await
+    }
+
+    async fn get_item(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        self.owned_item(id, &owner).await
+    }
+
+    async fn create_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let item = self.prepare(item).await?;
+        self.pantry_repository.create_item(item).await
+    }
+
+    async fn update_item(
+        &self,
+        item: domain::pantry::PantryItem,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let id = item.id.ok_or(domain::pantry::Error::Unexpected)?;
+        self.owned_item(id, &item.owner).await?;
+        let item = self.prepare(item).await?;
+        self.pantry_repository.update_item(item).await
+    }
+
+    async fn adjust_item(
+        &self,
+        id: i32,
+        change: domain::pantry::StockChange,
+        units: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        let mut item = self.owned_item(id, &owner).await?;
+        let catalog = self.unit_service.get_units().await?;
+        let units = match units {
+            Some(units) => Some(catalog.find(&units)?.name.clone()),
+            None => None,
+        };
+        item.adjust(change, units.as_deref(), &catalog)?;
+        self.pantry_repository.update_item(item).await
+    }
+
+    async fn delete_item(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::pantry::PantryItem, domain::pantry::Error> {
+        self.owned_item(id, &owner).await?;
+        self.pantry_repository.delete_item(id).await
+    }
+
+    async fn cook_recipe(
+        &self,
+        recipe_id: i32,
+        scale: Option<domain::recipe::ScaleBy>,
+        dry_run: bool,
+        owner: domain::User,
+    ) -> Result<domain::pantry::CookReport, domain::pantry::Error> {
+        let owner_id = owner.id.ok_or(domain::pantry::Error::Unexpected)?;
+        let recipe = self
+            .recipe_service
+            .get_recipe(recipe_id, Some(owner.clone()))
+            .await?;
+        let factor = match scale {
+            Some(scale) => recipe.scale_factor(scale)?,
+            None => 1.into(),
+        };
+        let recipe = self
+            .recipe_service
+            .expand_recipe(recipe.scale(factor)?, Some(owner.clone()))
+            .await?;
+        let catalog = self.unit_service.get_units().await?;
+        let before = self
+            .pantry_repository
+            .get_items(domain::pantry::PantryQuery {
+                owner_id,
+                location: None,
+                expires_by: None,
+            })
+            .await?;
+        let mut items = before.clone();
+        let shortfalls = domain::pantry::cook(&mut items, recipe.ingredients, &catalog)?;
+        let mut used: Vec<domain::pantry::PantryItem> =
+            items.into_iter().filter(|x| !before.contains(x)).collect();
+        if !dry_run {
+            used = self.pantry_repository.update_items(used).await?;
+        }
+        Ok(domain::pantry::CookReport {
+            recipe_id,
+            title: recipe.title,
+            scale: factor,
+            used,
+            shortfalls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            pantry::{MockPantryRepository, PantryService},
+            recipe::MockRecipeRepository,
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn service(repository: MockPantryRepository) -> DefaultPantryService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(MockRecipeRepository::new()),
+            unit_service.clone(),
+        ));
+        DefaultPantryService::new(Box::new(repository), recipe_service, unit_service)
+    }
+
+    fn item(owner_id: i32) -> domain::pantry::PantryItem {
+        domain::pantry::PantryItem {
+            id: Some(3),
+            owner: domain::User {
+                id: Some(owner_id),
+                name: format!("user {}", owner_id),
+            },
+            ingredient: domain::recipe::Ingredient {
+                id: Some(4),
+                name: "flour".to_owned(),
+            },
+            quantity: 1.into(),
+            units: domain::recipe::Unit {
+                id: Some(5),
+                name: "kilograms".to_owned(),
+            },
+            location: "Cupboard".to_owned(),
+            expires_on: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_users_item_is_refused() {
+        let mut mock = MockPantryRepository::new();
+        mock.expect_get_item()
+            .with(eq(3))
+            .returning(|_| Ok(item(1)));
+        mock.expect_update_item().never();
+        mock.expect_delete_item().never();
+        let service = service(mock);
+        assert_eq!(
+            service.update_item(item(2)).await.unwrap_err(),
+            domain::pantry::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service
+                .adjust_item(
+                    3,
+                    domain::pantry::StockChange::Add(1.into()),
+                    None,
+                    item(2).owner
+                )
+                .await
+                .unwrap_err(),
+            domain::pantry::Error::NotOwner(3)
+        );
+        assert_eq!(
+            service.delete_item(3, item(2).owner).await.unwrap_err(),
+            domain::pantry::Error::NotOwner(3)
+        );
+    }
+}
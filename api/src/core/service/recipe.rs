@@ -1,39 +1,967 @@
 use crate::core::{domain, port};
 use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+const MAX_PAGE_LIMIT: i64 = 100;
+const MAX_INGREDIENT_LINES: usize = 200;
+
+fn validate_paging(page: i64, limit: i64) -> Result<(), domain::recipe::Error> {
+    if page < 1 {
+        return Err(domain::recipe::Error::InvalidQuery(format!(
+            "page must be at least 1, got {}",
+            page
+        )));
+    }
+    if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+        return Err(domain::recipe::Error::InvalidQuery(format!(
+            "limit must be between 1 and {}, got {}",
+            MAX_PAGE_LIMIT, limit
+        )));
+    }
+    Ok(())
+}
+
+fn normalize_tags(recipe: domain::Recipe) -> domain::Recipe {
+    let names = recipe.tags.into_iter().map(|tag| tag.name).collect();
+    domain::Recipe {
+        tags: domain::Tag::normalize_names(names)
+            .into_iter()
+            .map(|name| domain::Tag { id: None, name })
+            .collect(),
+        ..recipe
+    }
+}
+
+/// Ensures group names are present and distinct, and that every ingredient
+/// and step names one of the recipe's groups, if any.
+fn validate_groups(recipe: &domain::Recipe) -> Result<(), domain::recipe::Error> {
+    let mut names: Vec<&str> = Vec::with_capacity(recipe.groups.len());
+    for group in &recipe.groups {
+        if group.name.trim().is_empty() {
+            return Err(domain::recipe::Error::InvalidRecipe(
+                "group names must not be blank".to_owned(),
+            ));
+        }
+        if names.contains(&group.name.as_str()) {
+            return Err(domain::recipe::Error::InvalidRecipe(format!(
+                "duplicate group `{}`",
+                group.name
+            )));
+        }
+        names.push(&group.name);
+    }
+    let referenced = recipe
+        .ingredients
+        .iter()
+        .map(|x| &x.group)
+        .chain(recipe.steps.iter().map(|x| &x.group));
+    for group in referenced.flatten() {
+        if !names.contains(&group.as_str()) {
+            return Err(domain::recipe::Error::InvalidRecipe(format!(
+                "unknown group `{}`",
+                group
+            )));
+        }
+    }
+    Ok(())
+}
 
 pub struct DefaultRecipeService {
     recipe_repository: Box<dyn port::RecipeRepository + Send + Sync>,
+    unit_service: Arc<dyn port::UnitService + Send + Sync>,
 }
 
 impl DefaultRecipeService {
     pub fn new(
         recipe_repository: Box<dyn port::RecipeRepository + Send + Sync>,
+        unit_service: Arc<dyn port::UnitService + Send + Sync>,
     ) -> DefaultRecipeService {
-        DefaultRecipeService { recipe_repository }
+        DefaultRecipeService {
+            recipe_repository,
+            unit_service,
+        }
+    }
+
+    /// Ensures every unit used by the recipe is in the unit catalog.
+    async fn validate_units(&self, recipe: &domain::Recipe) -> Result<(), domain::recipe::Error> {
+        let catalog = self.unit_service.get_units().await?;
+        catalog.find(&recipe.yield_units.name)?;
+        for ingredient in &recipe.ingredients {
+            catalog.find(&ingredient.units.name)?;
+        }
+        Ok(())
+    }
+
+    /// Loads every recipe reachable through the recipe's sub-recipes, keyed
+    /// by id. The recipe itself is never loaded, so cycles back to it stay
+    /// visible to `check_sub_recipe_cycles`.
+    async fn load_sub_recipes(
+        &self,
+        recipe: &domain::Recipe,
+    ) -> Result<HashMap<i32, domain::Recipe>, domain::recipe::Error> {
+        let mut sub_recipes = HashMap::new();
+        let mut pending = recipe.sub_recipe_ids();
+        while let Some(id) = pending.pop() {
+            if Some(id) == recipe.id || sub_recipes.contains_key(&id) {
+                continue;
+            }
+            let sub_recipe = match self.recipe_repository.get_recipe_by_id(id).await {
+                Err(domain::recipe::Error::RecipeNotFound(_)) => {
+                    return Err(domain::recipe::Error::InvalidRecipe(format!(
+                        "unknown sub-recipe `{}`",
+                        id
+                    )))
+                }
+                result => result?,
+            };
+            pending.extend(sub_recipe.sub_recipe_ids());
+            sub_recipes.insert(id, sub_recipe);
+        }
+        Ok(sub_recipes)
+    }
+
+    /// Ensures every sub-recipe exists, can be measured in the units the
+    /// recipe calls for, and never leads back to the recipe.
+    async fn validate_sub_recipes(
+        &self,
+        recipe: &domain::Recipe,
+    ) -> Result<(), domain::recipe::Error> {
+        if recipe.sub_recipe_ids().is_empty() {
+            return Ok(());
+        }
+        let sub_recipes = self.load_sub_recipes(recipe).await?;
+        // Only the recipe's own sub-recipes were chosen by its author; the
+        // ones those pull in are their authors' business.
+        for id in recipe.sub_recipe_ids() {
+            if sub_recipes
+                .get(&id)
+                .is_some_and(|x| !x.is_visible_to(Some(&recipe.author)))
+            {
+                return Err(domain::recipe::Error::InvalidRecipe(format!(
+                    "unknown sub-recipe `{}`",
+                    id
+                )));
+            }
+        }
+        recipe.check_sub_recipe_cycles(&sub_recipes)?;
+        let catalog = self.unit_service.get_units().await?;
+        for ingredient in &recipe.ingredients {
+            if let Some(sub_recipe) = ingredient.sub_recipe_id.and_then(|id| sub_recipes.get(&id)) {
+                ingredient.sub_recipe_factor(sub_recipe, &catalog)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Titles of `author`'s recipes that contain `title`.
+    async fn titles_like(
+        &self,
+        title: &str,
+        author: &domain::User,
+    ) -> Result<Vec<String>, domain::recipe::Error> {
+        let mut titles = vec![];
+        let mut page = 1;
+        loop {
+            let recipes = self
+                .recipe_repository
+                .get_recipes(domain::recipe::RecipeQuery {
+                    viewer_id: author.id,
+                    author_id: author.id,
+                    title: Some(title.to_owned()),
+                    page,
+                    limit: MAX_PAGE_LIMIT,
+                    ..Default::default()
+                })
+                .await?;
+            titles.extend(recipes.recipes.iter().map(|x| x.title.clone()));
+            match recipes.next_page() {
+                Some(next) => page = next,
+                None => return Ok(titles),
+            }
+        }
     }
 }
 
 #[async_trait]
 impl port::RecipeService for DefaultRecipeService {
-    async fn get_recipes(&self) -> Result<Vec<domain::Recipe>, domain::recipe::Error> {
-        Ok(self.recipe_repository.get_recipes().await?)
+    async fn get_recipes(
+        &self,
+        query: domain::recipe::RecipeQuery,
+    ) -> Result<domain::recipe::RecipePage, domain::recipe::Error> {
+        validate_paging(query.page, query.limit)?;
+        let query = domain::recipe::RecipeQuery {
+            tags: domain::Tag::normalize_names(query.tags),
+            ..query
+        };
+        let viewer_id = query.viewer_id;
+        let mut page = self.recipe_repository.get_recipes(query).await?;
+        page.recipes = page
+            .recipes
+            .into_iter()
+            .map(|x| x.without_hidden_lineage(viewer_id))
+            .collect();
+        Ok(page)
+    }
+    async fn search_recipes(
+        &self,
+        query: domain::recipe::RecipeSearchQuery,
+    ) -> Result<domain::recipe::RecipeSearchPage, domain::recipe::Error> {
+        if query.text.trim().is_empty() {
+            return Err(domain::recipe::Error::InvalidQuery(
+                "search text must not be empty".to_owned(),
+            ));
+        }
+        validate_paging(query.page, query.limit)?;
+        let viewer_id = query.viewer_id;
+        let mut page = self.recipe_repository.search_recipes(query).await?;
+        page.results = page
+            .results
+            .into_iter()
+            .map(|x| domain::recipe::RecipeSearchResult {
+                recipe: x.recipe.without_hidden_lineage(viewer_id),
+                ..x
+            })
+            .collect();
+        Ok(page)
+    }
+    async fn get_recipes_by_ingredients(
+        &self,
+        query: domain::recipe::IngredientMatchQuery,
+    ) -> Result<domain::recipe::IngredientMatchPage, domain::recipe::Error> {
+        let normalize = |names: Vec<String>| -> Vec<String> {
+            names
+                .into_iter()
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        };
+        let query = domain::recipe::IngredientMatchQuery {
+            ingredients: normalize(query.ingredients),
+            ignored: normalize(query.ignored),
+            ..query
+        };
+        if query.ingredients.is_empty() {
+            return Err(domain::recipe::Error::InvalidQuery(
+                "at least one ingredient is required".to_owned(),
+            ));
+        }
+        if let Some(max_missing) = query.max_missing {
+            if max_missing < 0 {
+                return Err(domain::recipe::Error::InvalidQuery(format!(
+                    "max missing must not be negative, got {}",
+                    max_missing
+                )));
+            }
+        }
+        validate_paging(query.page, query.limit)?;
+        let viewer_id = query.viewer_id;
+        let mut page = self
+            .recipe_repository
+            .get_recipes_by_ingredients(query)
+            .await?;
+        page.results = page
+            .results
+            .into_iter()
+            .map(|x| domain::recipe::IngredientMatch {
+                recipe: x.recipe.without_hidden_lineage(viewer_id),
+                ..x
+            })
+            .collect();
+        Ok(page)
     }
     async fn get_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error> {
         Ok(self.recipe_repository.get_recipe_by_id(id).await?)
     }
+    async fn get_recipe(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::Recipe, domain::recipe::Error> {
+        let recipe = self.recipe_repository.get_recipe_by_id(id).await?;
+        if !recipe.is_visible_to(viewer.as_ref()) {
+            return Err(domain::recipe::Error::RecipeNotFound(id));
+        }
+        Ok(recipe.without_hidden_lineage(viewer.and_then(|x| x.id)))
+    }
+    async fn scale_recipe(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+        scale: domain::recipe::ScaleBy,
+        promote_units: bool,
+    ) -> Result<domain::Recipe, domain::recipe::Error> {
+        let recipe = self.get_recipe(id, viewer).await?;
+        let factor = recipe.scale_factor(scale)?;
+        let recipe = recipe.scale(factor)?;
+        let catalog = self.unit_service.get_units().await?;
+        Ok(catalog.round_recipe(recipe, promote_units)?)
+    }
+    async fn expand_recipe(
+        &self,
+        recipe: domain::Recipe,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::Recipe, domain::recipe::Error> {
+        if recipe.sub_recipe_ids().is_empty() {
+            return Ok(recipe);
+        }
+        let mut sub_recipes = self.load_sub_recipes(&recipe).await?;
+        recipe.check_sub_recipe_cycles(&sub_recipes)?;
+        sub_recipes.retain(|_, x| x.is_visible_to(viewer.as_ref()));
+        let catalog = self.unit_service.get_units().await?;
+        recipe.expand_sub_recipes(&sub_recipes, &catalog)
+    }
     async fn create_recipe(
         &self,
         recipe: domain::Recipe,
     ) -> Result<domain::Recipe, domain::recipe::Error> {
-        Ok(self.recipe_repository.create_recipe(recipe).await?)
+        validate_groups(&recipe)?;
+        self.validate_units(&recipe).await?;
+        self.validate_sub_recipes(&recipe).await?;
+        let author_id = recipe.author.id;
+        Ok(self
+            .recipe_repository
+            .create_recipe(normalize_tags(recipe))
+            .await?
+            .without_hidden_lineage(author_id))
+    }
+    async fn parse_ingredient_lines(
+        &self,
+        lines: Vec<String>,
+    ) -> Result<Vec<domain::ingredient_line::ParsedIngredient>, domain::recipe::Error> {
+        if lines.len() > MAX_INGREDIENT_LINES {
+            return Err(domain::recipe::Error::InvalidQuery(format!(
+                "at most {} lines can be parsed at once, got {}",
+                MAX_INGREDIENT_LINES,
+                lines.len()
+            )));
+        }
+        let catalog = self.unit_service.get_units().await?;
+        Ok(lines
+            .iter()
+            .map(|line| domain::ingredient_line::parse(line, &catalog))
+            .collect())
+    }
+    async fn import_recipe(
+        &self,
+        document: domain::schema_org::Document,
+        author: domain::User,
+    ) -> Result<domain::recipe::RecipeImport, domain::recipe::Error> {
+        let catalog = self.unit_service.get_units().await?;
+        let import = domain::schema_org::parse_recipe(&document, author, &catalog)?;
+        let recipe = self.create_recipe(import.recipe).await?;
+        Ok(domain::recipe::RecipeImport { recipe, ..import })
+    }
+    async fn import_cooklang(
+        &self,
+        text: String,
+        title: Option<String>,
+        author: domain::User,
+    ) -> Result<domain::recipe::RecipeImport, domain::recipe::Error> {
+        let catalog = self.unit_service.get_units().await?;
+        let import = domain::cooklang::parse_recipe(&text, title.as_deref(), author, &catalog)?;
+        let recipe = self.create_recipe(import.recipe).await?;
+        Ok(domain::recipe::RecipeImport { recipe, ..import })
+    }
+    async fn fork_recipe(
+        &self,
+        id: i32,
+        author: domain::User,
+    ) -> Result<domain::Recipe, domain::recipe::Error> {
+        let recipe = self.get_recipe(id, Some(author.clone())).await?;
+        let taken = self.titles_like(&recipe.title, &author).await?;
+        let title = if taken.contains(&recipe.title) {
+            domain::recipe::numbered_title(&recipe.title, |x| taken.iter().any(|t| t == x))
+        } else {
+            recipe.title.clone()
+        };
+        self.create_recipe(recipe.fork(title, author)).await
     }
     async fn update_recipe(
         &self,
         recipe: domain::Recipe,
     ) -> Result<domain::Recipe, domain::recipe::Error> {
-        Ok(self.recipe_repository.update_recipe(recipe).await?)
+        validate_groups(&recipe)?;
+        self.validate_units(&recipe).await?;
+        self.validate_sub_recipes(&recipe).await?;
+        let author_id = recipe.author.id;
+        Ok(self
+            .recipe_repository
+            .update_recipe(normalize_tags(recipe))
+            .await?
+            .without_hidden_lineage(author_id))
     }
     async fn delete_recipe_by_id(&self, id: i32) -> Result<domain::Recipe, domain::recipe::Error> {
         Ok(self.recipe_repository.delete_recipe_by_id(id).await?)
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            recipe::{MockRecipeRepository, RecipeService},
+            unit::MockUnitRepository,
+        },
+        service::DefaultUnitService,
+    };
+
+    fn unit_service() -> Arc<DefaultUnitService> {
+        let mut mock = MockUnitRepository::new();
+        mock.expect_get_units().returning(|| {
+            Ok(vec![domain::unit::UnitDefinition {
+                id: 1,
+                name: "grams".to_owned(),
+                abbreviation: Some("g".to_owned()),
+                dimension: domain::unit::Dimension::Mass,
+                system: Some(domain::unit::MeasurementSystem::Metric),
+                to_base_factor: 1.0,
+                to_base_offset: 0.0,
+            }])
+        });
+        Arc::new(DefaultUnitService::new(Box::new(mock)))
+    }
+
+    fn recipe(units: &str) -> domain::Recipe {
+        domain::Recipe {
+            id: None,
+            title: "Buttered Carrots".to_owned(),
+            description: None,
+            author: domain::User {
+                id: Some(1),
+                name: "Matt".to_owned(),
+            },
+            prep_time: None,
+            cook_time: None,
+            inactive_time: None,
+            yield_quantity: 200.into(),
+            yield_units: domain::recipe::Unit {
+                id: None,
+                name: "grams".to_owned(),
+            },
+            ingredients: vec![domain::recipe::RecipeIngredient {
+                id: None,
+                recipe_id: None,
+                ingredient: domain::recipe::Ingredient {
+                    id: None,
+                    name: "carrots".to_owned(),
+                },
+                quantity: 200.into(),
+                units: domain::recipe::Unit {
+                    id: None,
+                    name: units.to_owned(),
+                },
+                preparation: "diced".to_owned(),
+                group: None,
+                sub_recipe_id: None,
+            }],
+            steps: vec![],
+            groups: vec![],
+            tags: vec![],
+            image: None,
+            cook_stats: Default::default(),
+            review_stats: Default::default(),
+            forked_from: None,
+            lineage: vec![],
+            visibility: Default::default(),
+            draft: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_recipe_rejects_unknown_units() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_create_recipe().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        assert!(matches!(
+            recipe_service
+                .create_recipe(recipe("handfuls"))
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::UnknownUnit(name) if name == "handfuls"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_recipe_with_known_units() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_create_recipe()
+            .with(eq(recipe("grams")))
+            .once()
+            .returning(|recipe| {
+                Ok(domain::Recipe {
+                    id: Some(1),
+                    ..recipe
+                })
+            });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        assert_eq!(
+            recipe_service
+                .create_recipe(recipe("grams"))
+                .await
+                .unwrap()
+                .id,
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_recipes_passes_query_to_repository() {
+        let query = domain::recipe::RecipeQuery {
+            title: Some("carrot".to_owned()),
+            sort: Some(domain::recipe::RecipeSort::Title),
+            ..Default::default()
+        };
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipes()
+            .with(eq(query.clone()))
+            .once()
+            .returning(|q| {
+                Ok(domain::recipe::RecipePage {
+                    recipes: vec![],
+                    page: q.page,
+                    limit: q.limit,
+                    total_count: 0,
+                })
+            });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        let page = recipe_service.get_recipes(query).await.unwrap();
+        assert_eq!(page.total_count, 0);
+        assert_eq!(page.next_page(), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_recipes_rejects_invalid_paging() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipes().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        assert!(matches!(
+            recipe_service
+                .get_recipes(domain::recipe::RecipeQuery {
+                    page: 0,
+                    ..Default::default()
+                })
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::InvalidQuery(_)
+        ));
+        assert!(matches!(
+            recipe_service
+                .get_recipes(domain::recipe::RecipeQuery {
+                    limit: MAX_PAGE_LIMIT + 1,
+                    ..Default::default()
+                })
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::InvalidQuery(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_recipes_by_ingredients_normalizes_names() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipes_by_ingredients()
+            .with(eq(domain::recipe::IngredientMatchQuery {
+                viewer_id: None,
+                ingredients: vec!["carrots".to_owned(), "butter".to_owned()],
+                ignored: vec!["salt".to_owned()],
+                max_missing: Some(1),
+                page: 1,
+                limit: 20,
+            }))
+            .once()
+            .returning(|q| {
+                Ok(domain::recipe::IngredientMatchPage {
+                    results: vec![],
+                    page: q.page,
+                    limit: q.limit,
+                    total_count: 0,
+                })
+            });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        recipe_service
+            .get_recipes_by_ingredients(domain::recipe::IngredientMatchQuery {
+                viewer_id: None,
+                ingredients: vec![" Carrots".to_owned(), "BUTTER ".to_owned(), "".to_owned()],
+                ignored: vec!["Salt".to_owned()],
+                max_missing: Some(1),
+                page: 1,
+                limit: 20,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_recipes_by_ingredients_requires_ingredients() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipes_by_ingredients().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        assert!(matches!(
+            recipe_service
+                .get_recipes_by_ingredients(domain::recipe::IngredientMatchQuery {
+                    viewer_id: None,
+                    ingredients: vec![" ".to_owned()],
+                    ignored: vec![],
+                    max_missing: None,
+                    page: 1,
+                    limit: 20,
+                })
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::InvalidQuery(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_recipes_rejects_blank_text() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_search_recipes().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        assert!(matches!(
+            recipe_service
+                .search_recipes(domain::recipe::RecipeSearchQuery {
+                    viewer_id: None,
+                    text: "  ".to_owned(),
+                    page: 1,
+                    limit: 20,
+                })
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::InvalidQuery(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scale_recipe() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(1))
+            .returning(|_| Ok(recipe("grams")));
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+
+        let scaled = recipe_service
+            .scale_recipe(1, None, domain::recipe::ScaleBy::Yield(500.into()), false)
+            .await
+            .unwrap();
+        assert_eq!(scaled.yield_quantity, 500.into());
+        assert_eq!(scaled.ingredients[0].quantity, 500.into());
+
+        let third = domain::Quantity::new(1, 3).unwrap();
+        let scaled = recipe_service
+            .scale_recipe(1, None, domain::recipe::ScaleBy::Factor(third), false)
+            .await
+            .unwrap();
+        assert_eq!(scaled.yield_quantity, 67.into());
+        assert_eq!(scaled.ingredients[0].quantity, 67.into());
+    }
+
+    #[tokio::test]
+    async fn test_get_recipe_hides_private_and_draft_recipes() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id().returning(|id| {
+            let (visibility, draft) = match id {
+                1 => (domain::recipe::Visibility::Private, false),
+                2 => (domain::recipe::Visibility::Unlisted, false),
+                _ => (domain::recipe::Visibility::Public, true),
+            };
+            Ok(domain::Recipe {
+                id: Some(id),
+                visibility,
+                draft,
+                ..recipe("grams")
+            })
+        });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        let author = recipe("grams").author;
+        let other = domain::User {
+            id: Some(2),
+            name: "Other".to_owned(),
+        };
+        for id in [1, 3] {
+            assert!(matches!(
+                recipe_service.get_recipe(id, Some(other.clone())).await,
+                Err(domain::recipe::Error::RecipeNotFound(x)) if x == id
+            ));
+            assert!(matches!(
+                recipe_service.get_recipe(id, None).await,
+                Err(domain::recipe::Error::RecipeNotFound(x)) if x == id
+            ));
+            assert!(recipe_service
+                .get_recipe(id, Some(author.clone()))
+                .await
+                .is_ok());
+        }
+        assert!(recipe_service.get_recipe(2, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_recipe_hides_hidden_ancestors() {
+        let ancestor = |id: i32, author_id: i32, visibility| domain::recipe::RecipeAncestor {
+            id,
+            title: format!("Recipe {}", id),
+            author: domain::User {
+                id: Some(author_id),
+                name: "Other".to_owned(),
+            },
+            visibility,
+            draft: false,
+        };
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(1))
+            .returning(move |_| {
+                Ok(domain::Recipe {
+                    id: Some(1),
+                    forked_from: Some(2),
+                    lineage: vec![
+                        ancestor(2, 2, domain::recipe::Visibility::Private),
+                        ancestor(3, 3, domain::recipe::Visibility::Unlisted),
+                    ],
+                    ..recipe("grams")
+                })
+            });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        let recipe = recipe_service.get_recipe(1, None).await.unwrap();
+        assert_eq!(recipe.forked_from, None);
+        assert_eq!(
+            recipe.lineage.iter().map(|x| x.id).collect::<Vec<_>>(),
+            vec![3]
+        );
+        let parent_author = domain::User {
+            id: Some(2),
+            name: "Other".to_owned(),
+        };
+        let recipe = recipe_service
+            .get_recipe(1, Some(parent_author))
+            .await
+            .unwrap();
+        assert_eq!(recipe.forked_from, Some(2));
+        assert_eq!(recipe.lineage.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fork_recipe() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id().with(eq(1)).returning(|_| {
+            Ok(domain::Recipe {
+                id: Some(1),
+                image: Some("carrots.jpg".to_owned()),
+                ..recipe("grams")
+            })
+        });
+        mock.expect_get_recipes().returning(|q| {
+            assert_eq!(q.author_id, Some(2));
+            Ok(domain::recipe::RecipePage {
+                recipes: ["Buttered Carrots", "Buttered Carrots (2)"]
+                    .iter()
+                    .map(|title| domain::Recipe {
+                        title: title.to_string(),
+                        ..recipe("grams")
+                    })
+                    .collect(),
+                page: q.page,
+                limit: q.limit,
+                total_count: 2,
+            })
+        });
+        mock.expect_create_recipe().once().returning(Ok);
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        let author = domain::User {
+            id: Some(2),
+            name: "Other".to_owned(),
+        };
+        let fork = recipe_service.fork_recipe(1, author.clone()).await.unwrap();
+        assert_eq!(fork.id, None);
+        assert_eq!(fork.title, "Buttered Carrots (3)");
+        assert_eq!(fork.author, author);
+        assert_eq!(fork.forked_from, Some(1));
+        assert_eq!(fork.image, None);
+        assert_eq!(fork.ingredients, recipe("grams").ingredients);
+    }
+
+    #[test]
+    fn test_numbered_title() {
+        let taken = ["Soup", "Soup (2)"];
+        assert_eq!(
+            domain::recipe::numbered_title("Soup", |x| taken.contains(&x)),
+            "Soup (3)"
+        );
+        assert_eq!(
+            domain::recipe::numbered_title("Stew", |x| taken.contains(&x)),
+            "Stew (2)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scale_recipe_rejects_non_positive_scale() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .returning(|_| Ok(recipe("grams")));
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        for scale in [
+            domain::recipe::ScaleBy::Factor(0.into()),
+            domain::recipe::ScaleBy::Yield(0.into()),
+        ] {
+            assert!(matches!(
+                recipe_service
+                    .scale_recipe(1, None, scale, false)
+                    .await
+                    .unwrap_err(),
+                domain::recipe::Error::InvalidScale(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_recipe_rejects_invalid_groups() {
+        let group = |name: &str| domain::recipe::RecipeGroup {
+            id: None,
+            name: name.to_owned(),
+            ordinal: 1,
+        };
+        let mut unknown = recipe("grams");
+        unknown.ingredients[0].group = Some("For the glaze".to_owned());
+        let duplicate = domain::Recipe {
+            groups: vec![group("For the glaze"), group("For the glaze")],
+            ..recipe("grams")
+        };
+        let blank = domain::Recipe {
+            groups: vec![group(" ")],
+            ..recipe("grams")
+        };
+
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_create_recipe().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        for recipe in [unknown, duplicate, blank] {
+            assert!(matches!(
+                recipe_service.create_recipe(recipe).await.unwrap_err(),
+                domain::recipe::Error::InvalidRecipe(_)
+            ));
+        }
+    }
+
+    fn sub_recipe(id: i32, sub_recipe_id: i32, quantity: i64) -> domain::Recipe {
+        let mut recipe = recipe("grams");
+        recipe.id = Some(id);
+        recipe.ingredients.push(domain::recipe::RecipeIngredient {
+            quantity: quantity.into(),
+            sub_recipe_id: Some(sub_recipe_id),
+            ..recipe.ingredients[0].clone()
+        });
+        recipe
+    }
+
+    #[tokio::test]
+    async fn test_update_recipe_rejects_invalid_sub_recipes() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(2))
+            .returning(|_| Ok(sub_recipe(2, 1, 100)));
+        mock.expect_get_recipe_by_id()
+            .with(eq(3))
+            .returning(|id| Err(domain::recipe::Error::RecipeNotFound(id)));
+        mock.expect_update_recipe().never();
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+        for recipe in [
+            sub_recipe(1, 1, 100),
+            sub_recipe(1, 2, 100),
+            sub_recipe(1, 3, 100),
+        ] {
+            assert!(matches!(
+                recipe_service.update_recipe(recipe).await.unwrap_err(),
+                domain::recipe::Error::InvalidRecipe(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expand_recipe() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(2))
+            .returning(|_| Ok(sub_recipe(2, 3, 100)));
+        mock.expect_get_recipe_by_id().with(eq(3)).returning(|_| {
+            Ok(domain::Recipe {
+                id: Some(3),
+                ..recipe("grams")
+            })
+        });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+
+        // 100g of recipe 2 is half a batch: 100g of carrots plus 50g of
+        // recipe 3, which is itself a quarter batch of 50g carrots.
+        let expanded = recipe_service
+            .expand_recipe(sub_recipe(1, 2, 100), None)
+            .await
+            .unwrap();
+        assert_eq!(expanded.ingredients.len(), 1);
+        assert_eq!(expanded.ingredients[0].ingredient.name, "carrots");
+        assert_eq!(expanded.ingredients[0].quantity, 350.into());
+        assert_eq!(expanded.ingredients[0].sub_recipe_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_expand_recipe_skips_hidden_sub_recipes() {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(2))
+            .returning(|_| Ok(sub_recipe(2, 3, 100)));
+        mock.expect_get_recipe_by_id().with(eq(3)).returning(|_| {
+            Ok(domain::Recipe {
+                id: Some(3),
+                draft: true,
+                ..recipe("grams")
+            })
+        });
+        let recipe_service = DefaultRecipeService::new(Box::new(mock), unit_service());
+
+        // Recipe 3 is a draft, so only its author sees it inlined.
+        let expanded = recipe_service
+            .expand_recipe(sub_recipe(1, 2, 100), None)
+            .await
+            .unwrap();
+        let mut ingredients: Vec<_> = expanded
+            .ingredients
+            .iter()
+            .map(|x| (x.sub_recipe_id, x.quantity))
+            .collect();
+        ingredients.sort_by_key(|x| x.0);
+        assert_eq!(ingredients, vec![(None, 300.into()), (Some(3), 50.into())]);
+        let author = recipe("grams").author;
+        let expanded = recipe_service
+            .expand_recipe(sub_recipe(1, 2, 100), Some(author))
+            .await
+            .unwrap();
+        assert_eq!(expanded.ingredients.len(), 1);
+        assert_eq!(expanded.ingredients[0].quantity, 350.into());
+    }
+
+    #[tokio::test]
+    async fn test_parse_ingredient_lines() {
+        let recipe_service =
+            DefaultRecipeService::new(Box::new(MockRecipeRepository::new()), unit_service());
+        let parsed = recipe_service
+            .parse_ingredient_lines(vec!["200g carrots, diced".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(parsed[0].ingredient.quantity, 200.into());
+        assert_eq!(parsed[0].ingredient.units.name, "grams");
+        assert_eq!(parsed[0].ingredient.ingredient.name, "carrots");
+        assert_eq!(parsed[0].ingredient.preparation, "diced");
+
+        assert!(matches!(
+            recipe_service
+                .parse_ingredient_lines(vec!["1 carrot".to_owned(); MAX_INGREDIENT_LINES + 1])
+                .await
+                .unwrap_err(),
+            domain::recipe::Error::InvalidQuery(_)
+        ));
+    }
+}
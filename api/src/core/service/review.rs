@@ -0,0 +1,276 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultReviewService {
+    review_repository: Box<dyn port::ReviewRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+}
+
+impl DefaultReviewService {
+    pub fn new(
+        review_repository: Box<dyn port::ReviewRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    ) -> DefaultReviewService {
+        DefaultReviewService {
+            review_repository,
+            recipe_service,
+        }
+    }
+
+    /// Loads a review, making sure it was written by `reviewer`.
+    async fn owned_review(
+        &self,
+        id: i32,
+        reviewer: &domain::User,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let review = self.review_repository.get_review(id).await?;
+        if review.reviewer.id != reviewer.id {
+            return Err(domain::review::Error::NotReviewer(id));
+        }
+        Ok(review)
+    }
+}
+
+#[async_trait]
+impl port::ReviewService for DefaultReviewService {
+    async fn get_reviews(
+        &self,
+        recipe_id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::review::Review>, domain::review::Error> {
+        self.recipe_service.get_recipe(recipe_id, viewer).await?;
+        self.review_repository.get_reviews(recipe_id).await
+    }
+
+    async fn get_review(
+        &self,
+        id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let review = self.review_repository.get_review(id).await?;
+        match self
+            .recipe_service
+            .get_recipe(review.recipe_id, viewer)
+            .await
+        {
+            Ok(_) => Ok(review),
+            Err(domain::recipe::Error::RecipeNotFound(_)) => {
+                Err(domain::review::Error::ReviewNotFound(id))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn create_review(
+        &self,
+        mut review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        review.validate()?;
+        let recipe = self
+            .recipe_service
+            .get_recipe(review.recipe_id, Some(review.reviewer.clone()))
+            .await?;
+        if recipe.author.id == review.reviewer.id {
+            return Err(domain::review::Error::OwnRecipe(review.recipe_id));
+        }
+        if self
+            .review_repository
+            .get_reviews(review.recipe_id)
+            .await?
+            .iter()
+            .any(|x| x.reviewer.id == review.reviewer.id)
+        {
+            return Err(domain::review::Error::AlreadyReviewed(review.recipe_id));
+        }
+        self.review_repository.create_review(review).await
+    }
+
+    async fn update_review(
+        &self,
+        mut review: domain::review::Review,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let id = review.id.ok_or(domain::review::Error::Unexpected)?;
+        let existing = self.owned_review(id, &review.reviewer).await?;
+        review.validate()?;
+        review.recipe_id = existing.recipe_id;
+        self.review_repository.update_review(review).await
+    }
+
+    async fn delete_review(
+        &self,
+        id: i32,
+        reviewer: domain::User,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        self.owned_review(id, &reviewer).await?;
+        self.review_repository.delete_review(id).await
+    }
+
+    async fn reply_to_review(
+        &self,
+        id: i32,
+        reply: Option<String>,
+        author: domain::User,
+    ) -> Result<domain::review::Review, domain::review::Error> {
+        let review = self.review_repository.get_review(id).await?;
+        let recipe = self
+            .recipe_service
+            .get_recipe_by_id(review.recipe_id)
+            .await?;
+        if recipe.author.id != author.id {
+            return Err(domain::review::Error::NotAuthor(id));
+        }
+        self.review_repository
+            .update_reply(id, domain::review::normalize_reply(reply))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            recipe::MockRecipeRepository,
+            review::{MockReviewRepository, ReviewService},
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn service(
+        repository: MockReviewRepository,
+        recipe_repository: MockRecipeRepository,
+    ) -> DefaultReviewService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(recipe_repository),
+            unit_service,
+        ));
+        DefaultReviewService::new(Box::new(repository), recipe_service)
+    }
+
+    fn user(id: i32) -> domain::User {
+        domain::User {
+            id: Some(id),
+            name: format!("user {}", id),
+        }
+    }
+
+    fn review(reviewer_id: i32) -> domain::review::Review {
+        domain::review::Review {
+            id: Some(3),
+            recipe_id: 5,
+            reviewer: user(reviewer_id),
+            rating: 4,
+            body: String::new(),
+            reply: None,
+            created_at: None,
+            updated_at: None,
+            replied_at: None,
+        }
+    }
+
+    fn recipe_repository(author_id: i32, draft: bool) -> MockRecipeRepository {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(5))
+            .returning(move |_| {
+                Ok(domain::Recipe {
+                    id: Some(5),
+                    title: "Soup".to_owned(),
+                    description: None,
+                    author: user(author_id),
+                    prep_time: None,
+                    cook_time: None,
+                    inactive_time: None,
+                    yield_quantity: 1.into(),
+                    yield_units: domain::recipe::Unit {
+                        id: Some(1),
+                        name: "servings".to_owned(),
+                    },
+                    ingredients: vec![],
+                    steps: vec![],
+                    groups: vec![],
+                    tags: vec![],
+                    image: None,
+                    cook_stats: Default::default(),
+                    review_stats: Default::default(),
+                    forked_from: None,
+                    lineage: vec![],
+                    visibility: Default::default(),
+                    draft,
+                })
+            });
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_author_cannot_review_own_recipe() {
+        let mut mock = MockReviewRepository::new();
+        mock.expect_create_review().never();
+        assert_eq!(
+            service(mock, recipe_repository(1, false))
+                .create_review(review(1))
+                .await
+                .unwrap_err(),
+            domain::review::Error::OwnRecipe(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_author_can_reply() {
+        let mut mock = MockReviewRepository::new();
+        mock.expect_get_review()
+            .with(eq(3))
+            .returning(|_| Ok(review(2)));
+        mock.expect_update_reply()
+            .with(eq(3), eq(Some("Thanks!".to_owned())))
+            .times(1)
+            .returning(|_, reply| Ok(domain::review::Review { reply, ..review(2) }));
+        let service = service(mock, recipe_repository(1, false));
+        assert_eq!(
+            service
+                .reply_to_review(3, Some("Thanks!".to_owned()), user(2))
+                .await
+                .unwrap_err(),
+            domain::review::Error::NotAuthor(3)
+        );
+        assert_eq!(
+            service
+                .reply_to_review(3, Some(" Thanks! ".to_owned()), user(1))
+                .await
+                .unwrap()
+                .reply,
+            Some("Thanks!".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reviews_of_hidden_recipe_are_hidden() {
+        let mut mock = MockReviewRepository::new();
+        mock.expect_get_review()
+            .with(eq(3))
+            .returning(|_| Ok(review(2)));
+        mock.expect_get_reviews()
+            .with(eq(5))
+            .times(1)
+            .returning(|_| Ok(vec![review(2)]));
+        let service = service(mock, recipe_repository(1, true));
+        assert_eq!(
+            service.get_review(3, Some(user(2))).await.unwrap_err(),
+            domain::review::Error::ReviewNotFound(3)
+        );
+        assert_eq!(
+            service.get_reviews(5, None).await.unwrap_err(),
+            domain::review::Error::RecipeNotFound(5)
+        );
+        assert!(service.get_review(3, Some(user(1))).await.is_ok());
+        assert!(service.get_reviews(5, Some(user(1))).await.is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultRevisionService {
+    revision_repository: Box<dyn port::RevisionRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+}
+
+impl DefaultRevisionService {
+    pub fn new(
+        revision_repository: Box<dyn port::RevisionRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    ) -> DefaultRevisionService {
+        DefaultRevisionService {
+            revision_repository,
+            recipe_service,
+        }
+    }
+}
+
+#[async_trait]
+impl port::RevisionService for DefaultRevisionService {
+    async fn get_revisions(
+        &self,
+        recipe_id: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::revision::RevisionSummary>, domain::revision::Error> {
+        self.recipe_service.get_recipe(recipe_id, viewer).await?;
+        self.revision_repository.get_revisions(recipe_id).await
+    }
+
+    async fn get_revision(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::revision::RecipeRevision, domain::revision::Error> {
+        self.recipe_service.get_recipe(recipe_id, viewer).await?;
+        self.revision_repository
+            .get_revision(recipe_id, revision)
+            .await
+    }
+
+    async fn diff_revisions(
+        &self,
+        recipe_id: i32,
+        from: i32,
+        to: i32,
+        viewer: Option<domain::User>,
+    ) -> Result<domain::revision::RevisionDiff, domain::revision::Error> {
+        self.recipe_service.get_recipe(recipe_id, viewer).await?;
+        let from = self
+            .revision_repository
+            .get_revision(recipe_id, from)
+            .await?;
+        let to = self.revision_repository.get_revision(recipe_id, to).await?;
+        domain::revision::diff(&from, &to)
+    }
+
+    async fn revert_recipe(
+        &self,
+        recipe_id: i32,
+        revision: i32,
+        author: domain::User,
+    ) -> Result<domain::Recipe, domain::revision::Error> {
+        let recipe = self.recipe_service.get_recipe_by_id(recipe_id).await?;
+        if recipe.author.id != author.id {
+            return Err(domain::revision::Error::NotAuthor(recipe_id));
+        }
+        let revision = self
+            .revision_repository
+            .get_revision(recipe_id, revision)
+            .await?;
+        Ok(self
+            .recipe_service
+            .update_recipe(revision.snapshot.apply(recipe))
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            recipe::MockRecipeRepository,
+            revision::{MockRevisionRepository, RevisionService},
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn service(
+        repository: MockRevisionRepository,
+        recipe_repository: MockRecipeRepository,
+    ) -> DefaultRevisionService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(recipe_repository),
+            unit_service,
+        ));
+        DefaultRevisionService::new(Box::new(repository), recipe_service)
+    }
+
+    fn user(id: i32) -> domain::User {
+        domain::User {
+            id: Some(id),
+            name: format!("user {}", id),
+        }
+    }
+
+    fn recipe_repository(visibility: domain::recipe::Visibility) -> MockRecipeRepository {
+        let mut mock = MockRecipeRepository::new();
+        mock.expect_get_recipe_by_id()
+            .with(eq(5))
+            .returning(move |_| {
+                Ok(domain::Recipe {
+                    id: Some(5),
+                    title: "Soup".to_owned(),
+                    description: None,
+                    author: user(1),
+                    prep_time: None,
+                    cook_time: None,
+                    inactive_time: None,
+                    yield_quantity: 1.into(),
+                    yield_units: domain::recipe::Unit {
+                        id: Some(1),
+                        name: "servings".to_owned(),
+                    },
+                    ingredients: vec![],
+                    steps: vec![],
+                    groups: vec![],
+                    tags: vec![],
+                    image: None,
+                    cook_stats: Default::default(),
+                    review_stats: Default::default(),
+                    forked_from: None,
+                    lineage: vec![],
+                    visibility,
+                    draft: false,
+                })
+            });
+        mock.expect_update_recipe().never();
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_only_author_can_revert() {
+        let mut mock = MockRevisionRepository::new();
+        mock.expect_get_revision().never();
+        assert_eq!(
+            service(mock, recipe_repository(Default::default()))
+                .revert_recipe(5, 1, user(2))
+                .await
+                .unwrap_err(),
+            domain::revision::Error::NotAuthor(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_requires_both_revisions() {
+        let mut mock = MockRevisionRepository::new();
+        mock.expect_get_revision()
+            .with(eq(5), eq(1))
+            .returning(|_, _| Err(domain::revision::Error::RevisionNotFound(5, 1)));
+        assert_eq!(
+            service(mock, recipe_repository(Default::default()))
+                .diff_revisions(5, 1, 2, None)
+                .await
+                .unwrap_err(),
+            domain::revision::Error::RevisionNotFound(5, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_history_hidden_from_others() {
+        let mut mock = MockRevisionRepository::new();
+        mock.expect_get_revisions()
+            .with(eq(5))
+            .times(1)
+            .returning(|_| Ok(vec![]));
+        let service = service(mock, recipe_repository(domain::recipe::Visibility::Private));
+        assert_eq!(
+            service.get_revisions(5, Some(user(2))).await.unwrap_err(),
+            domain::revision::Error::RecipeNotFound(5)
+        );
+        assert_eq!(
+            service.get_revisions(5, None).await.unwrap_err(),
+            domain::revision::Error::RecipeNotFound(5)
+        );
+        assert!(service.get_revisions(5, Some(user(1))).await.is_ok());
+    }
+}
@@ -0,0 +1,386 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultShoppingListService {
+    shopping_list_repository: Box<dyn port::ShoppingListRepository + Send + Sync>,
+    recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+    unit_service: Arc<dyn port::UnitService + Send + Sync>,
+}
+
+impl DefaultShoppingListService {
+    pub fn new(
+        shopping_list_repository: Box<dyn port::ShoppingListRepository + Send + Sync>,
+        recipe_service: Arc<dyn port::RecipeService + Send + Sync>,
+        unit_service: Arc<dyn port::UnitService + Send + Sync>,
+    ) -> DefaultShoppingListService {
+        DefaultShoppingListService {
+            shopping_list_repository,
+            recipe_service,
+            unit_service,
+        }
+    }
+
+    /// Loads a list, making sure it belongs to `owner`.
+    async fn owned_list(
+        &self,
+        id: i32,
+        owner: &domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let shopping_list = self.shopping_list_repository.get_shopping_list(id).await?;
+        check_owner(&shopping_list, owner)?;
+        Ok(shopping_list)
+    }
+
+    /// Applies `modify` to a list belonging to `owner`, with the list locked
+    /// against other changes.
+    async fn modify_owned_list(
+        &self,
+        id: i32,
+        owner: domain::User,
+        modify: impl FnOnce(
+                &mut domain::shopping_list::ShoppingList,
+            ) -> Result<(), domain::shopping_list::Error>
+            + Send
+            + 'static,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.shopping_list_repository
+            .modify_shopping_list(
+                id,
+                Box::new(move |shopping_list| {
+                    check_owner(shopping_list, &owner)?;
+                    modify(shopping_list)
+                }),
+            )
+            .await
+    }
+
+    /// Loads a recipe scaled as asked, with sub-recipes expanded, and the
+    /// factor it was scaled by. Without a `scale` the recipe is as written.
+    async fn scaled_recipe(
+        &self,
+        recipe_id: i32,
+        scale: Option<domain::recipe::ScaleBy>,
+        owner: &domain::User,
+    ) -> Result<(domain::Recipe, domain::Quantity), domain::shopping_list::Error> {
+        let recipe = self
+            .recipe_service
+            .get_recipe(recipe_id, Some(owner.clone()))
+            .await?;
+        let factor = match scale {
+            Some(scale) => recipe.scale_factor(scale)?,
+            None => 1.into(),
+        };
+        let recipe = self
+            .recipe_service
+            .expand_recipe(recipe.scale(factor)?, Some(owner.clone()))
+            .await?;
+        Ok((recipe, factor))
+    }
+
+    /// The catalog name of `units`, failing for units not in the catalog.
+    async fn unit_name(&self, units: &str) -> Result<String, domain::shopping_list::Error> {
+        let catalog = self.unit_service.get_units().await?;
+        Ok(catalog.find(units)?.name.clone())
+    }
+}
+
+fn check_owner(
+    shopping_list: &domain::shopping_list::ShoppingList,
+    owner: &domain::User,
+) -> Result<(), domain::shopping_list::Error> {
+    if shopping_list.owner.id != owner.id {
+        return Err(domain::shopping_list::Error::NotOwner(
+            shopping_list.id.unwrap_or_default(),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds the ingredients of a recipe scaled by `factor` to the list.
+fn add_recipe_to(
+    shopping_list: &mut domain::shopping_list::ShoppingList,
+    recipe_id: i32,
+    recipe: domain::Recipe,
+    factor: domain::Quantity,
+    catalog: &domain::unit::UnitCatalog,
+) -> Result<(), domain::shopping_list::Error> {
+    shopping_list.add_ingredients(recipe.ingredients, catalog)?;
+    shopping_list
+        .recipes
+        .push(domain::shopping_list::ShoppingListRecipe {
+            id: None,
+            recipe_id,
+            title: recipe.title,
+            scale: factor,
+        });
+    Ok(())
+}
+
+#[async_trait]
+impl port::ShoppingListService for DefaultShoppingListService {
+    async fn get_shopping_lists(
+        &self,
+        owner: domain::User,
+    ) -> Result<Vec<domain::shopping_list::ShoppingList>, domain::shopping_list::Error> {
+        let owner_id = owner.id.ok_or(domain::shopping_list::Error::Unexpected)?;
+        self.shopping_list_repository
+            .get_shopping_lists(owner_id)
+            .await
+    }
+
+    async fn get_shopping_list(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.owned_list(id, &owner).await
+    }
+
+    async fn create_shopping_list(
+        &self,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let name = domain::shopping_list::validate_name(&name)?;
+        self.shopping_list_repository
+            .create_shopping_list(domain::shopping_list::ShoppingList::new(name, owner))
+            .await
+    }
+
+    async fn create_shopping_list_from_recipes(
+        &self,
+        name: String,
+        recipes: Vec<(i32, Option<domain::recipe::ScaleBy>)>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let name = domain::shopping_list::validate_name(&name)?;
+        let mut scaled = Vec::with_capacity(recipes.len());
+        for (recipe_id, scale) in recipes {
+            scaled.push((
+                recipe_id,
+                self.scaled_recipe(recipe_id, scale, &owner).await?,
+            ));
+        }
+        let catalog = self.unit_service.get_units().await?;
+        let mut shopping_list = domain::shopping_list::ShoppingList::new(name, owner);
+        for (recipe_id, (recipe, factor)) in scaled {
+            add_recipe_to(&mut shopping_list, recipe_id, recipe, factor, &catalog)?;
+        }
+        self.shopping_list_repository
+            .create_shopping_list(shopping_list)
+            .await
+    }
+
+    async fn rename_shopping_list(
+        &self,
+        id: i32,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let name = domain::shopping_list::validate_name(&name)?;
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            shopping_list.name = name;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_shopping_list(
+        &self,
+        id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.owned_list(id, &owner).await?;
+        self.shopping_list_repository.delete_shopping_list(id).await
+    }
+
+    async fn add_recipe(
+        &self,
+        id: i32,
+        recipe_id: i32,
+        scale: Option<domain::recipe::ScaleBy>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.owned_list(id, &owner).await?;
+        let (recipe, factor) = self.scaled_recipe(recipe_id, scale, &owner).await?;
+        let catalog = self.unit_service.get_units().await?;
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            add_recipe_to(shopping_list, recipe_id, recipe, factor, &catalog)
+        })
+        .await
+    }
+
+    async fn add_item(
+        &self,
+        id: i32,
+        name: String,
+        quantity: Option<domain::Quantity>,
+        units: Option<String>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        let name = domain::shopping_list::validate_name(&name)?;
+        let units = match units {
+            Some(units) => Some(domain::recipe::Unit {
+                id: None,
+                name: self.unit_name(&units).await?,
+            }),
+            None => None,
+        };
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            shopping_list.push_item(domain::shopping_list::ShoppingListItem {
+                id: None,
+                ingredient: domain::recipe::Ingredient { id: None, name },
+                quantity,
+                units,
+                checked: false,
+                ordinal: 0,
+            });
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_item(
+        &self,
+        id: i32,
+        item_id: i32,
+        mut update: domain::shopping_list::ItemUpdate,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        if let Some(units) = &update.units {
+            update.units = Some(self.unit_name(units).await?);
+        }
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            shopping_list.update_item(item_id, update)
+        })
+        .await
+    }
+
+    async fn remove_item(
+        &self,
+        id: i32,
+        item_id: i32,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            shopping_list.remove_item(item_id)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn reorder_items(
+        &self,
+        id: i32,
+        item_ids: Vec<i32>,
+        owner: domain::User,
+    ) -> Result<domain::shopping_list::ShoppingList, domain::shopping_list::Error> {
+        self.modify_owned_list(id, owner, move |shopping_list| {
+            shopping_list.reorder(&item_ids)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::{
+        port::{
+            recipe::MockRecipeRepository,
+            shopping_list::{MockShoppingListRepository, ShoppingListService},
+            unit::MockUnitRepository,
+        },
+        service::{DefaultRecipeService, DefaultUnitService},
+    };
+
+    fn user(id: i32) -> domain::User {
+        domain::User {
+            id: Some(id),
+            name: format!("user {}", id),
+        }
+    }
+
+    fn service(repository: MockShoppingListRepository) -> DefaultShoppingListService {
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(MockRecipeRepository::new()),
+            unit_service.clone(),
+        ));
+        DefaultShoppingListService::new(Box::new(repository), recipe_service, unit_service)
+    }
+
+    #[tokio::test]
+    async fn test_other_users_list_is_refused() {
+        let mut mock = MockShoppingListRepository::new();
+        let groceries = || domain::shopping_list::ShoppingList {
+            id: Some(7),
+            ..domain::shopping_list::ShoppingList::new("Groceries".to_owned(), user(1))
+        };
+        mock.expect_get_shopping_list()
+            .with(eq(7))
+            .returning(move |_| Ok(groceries()));
+        mock.expect_modify_shopping_list()
+            .returning(move |_, modify| {
+                let mut shopping_list = groceries();
+                modify(&mut shopping_list)?;
+                Ok(shopping_list)
+            });
+        mock.expect_delete_shopping_list().never();
+        let service = service(mock);
+        assert_eq!(
+            service
+                .rename_shopping_list(7, "Mine".to_owned(), user(2))
+                .await
+                .unwrap_err(),
+            domain::shopping_list::Error::NotOwner(7)
+        );
+        assert_eq!(
+            service.delete_shopping_list(7, user(2)).await.unwrap_err(),
+            domain::shopping_list::Error::NotOwner(7)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_shopping_list_rejects_blank_name() {
+        let mut mock = MockShoppingListRepository::new();
+        mock.expect_create_shopping_list().never();
+        assert!(matches!(
+            service(mock)
+                .create_shopping_list("  ".to_owned(), user(1))
+                .await
+                .unwrap_err(),
+            domain::shopping_list::Error::InvalidShoppingList(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_from_recipes_is_not_saved_on_failure() {
+        let mut mock = MockShoppingListRepository::new();
+        mock.expect_create_shopping_list().never();
+        let mut recipe_repository = MockRecipeRepository::new();
+        recipe_repository
+            .expect_get_recipe_by_id()
+            .with(eq(3))
+            .returning(|id| Err(domain::recipe::Error::RecipeNotFound(id)));
+        let unit_service = Arc::new(DefaultUnitService::new(Box::new(MockUnitRepository::new())));
+        let recipe_service = Arc::new(DefaultRecipeService::new(
+            Box::new(recipe_repository),
+            unit_service.clone(),
+        ));
+        let service = DefaultShoppingListService::new(Box::new(mock), recipe_service, unit_service);
+        assert_eq!(
+            service
+                .create_shopping_list_from_recipes("Week".to_owned(), vec![(3, None)], user(1))
+                .await
+                .unwrap_err(),
+            domain::shopping_list::Error::RecipeNotFound(3)
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+#[cfg(test)]
+use mockall::predicate::*;
+
+pub struct DefaultTagService {
+    tag_repository: Box<dyn port::TagRepository + Send + Sync>,
+}
+
+impl DefaultTagService {
+    pub fn new(tag_repository: Box<dyn port::TagRepository + Send + Sync>) -> DefaultTagService {
+        DefaultTagService { tag_repository }
+    }
+}
+
+#[async_trait]
+impl port::TagService for DefaultTagService {
+    async fn get_tags(
+        &self,
+        viewer: Option<domain::User>,
+    ) -> Result<Vec<domain::tag::TagUsage>, domain::tag::Error> {
+        Ok(self
+            .tag_repository
+            .get_tags(viewer.and_then(|x| x.id))
+            .await?)
+    }
+
+    async fn rename_tag(
+        &self,
+        id: i32,
+        name: String,
+        owner: domain::User,
+    ) -> Result<domain::tag::TagUsage, domain::tag::Error> {
+        let owner_id = owner.id.ok_or(domain::tag::Error::Unexpected)?;
+        let name = domain::Tag::normalize_name(&name);
+        if name.is_empty() {
+            return Err(domain::tag::Error::InvalidTag(
+                "tag name must not be empty".to_owned(),
+            ));
+        }
+        Ok(self.tag_repository.rename_tag(id, name, owner_id).await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use crate::core::port::tag::{MockTagRepository, TagService};
+
+    fn user() -> domain::User {
+        domain::User {
+            id: Some(1),
+            name: "Matt".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag_normalizes_name() {
+        let mut mock = MockTagRepository::new();
+        mock.expect_rename_tag()
+            .with(eq(3), eq("main course".to_owned()), eq(1))
+            .once()
+            .returning(|id, name, _| {
+                Ok(domain::tag::TagUsage {
+                    tag: domain::Tag { id: Some(id), name },
+                    recipe_count: 2,
+                })
+            });
+        let tag_service = DefaultTagService::new(Box::new(mock));
+        assert_eq!(
+            tag_service
+                .rename_tag(3, " Main  Course ".to_owned(), user())
+                .await
+                .unwrap(),
+            domain::tag::TagUsage {
+                tag: domain::Tag {
+                    id: Some(3),
+                    name: "main course".to_owned()
+                },
+                recipe_count: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag_rejects_blank_name() {
+        let mut mock = MockTagRepository::new();
+        mock.expect_rename_tag().never();
+        let tag_service = DefaultTagService::new(Box::new(mock));
+        assert!(matches!(
+            tag_service
+                .rename_tag(3, "  ".to_owned(), user())
+                .await
+                .unwrap_err(),
+            domain::tag::Error::InvalidTag(_)
+        ));
+    }
+}
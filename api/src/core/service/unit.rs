@@ -0,0 +1,29 @@
+use crate::core::{domain, port};
+use async_trait::async_trait;
+
+pub struct DefaultUnitService {
+    unit_repository: Box<dyn port::UnitRepository + Send + Sync>,
+}
+
+impl DefaultUnitService {
+    pub fn new(unit_repository: Box<dyn port::UnitRepository + Send + Sync>) -> DefaultUnitService {
+        DefaultUnitService { unit_repository }
+    }
+}
+
+#[async_trait]
+impl port::UnitService for DefaultUnitService {
+    async fn get_units(&self) -> Result<domain::unit::UnitCatalog, domain::unit::Error> {
+        Ok(domain::unit::UnitCatalog::new(
+            self.unit_repository.get_units().await?,
+        ))
+    }
+
+    async fn convert_recipe(
+        &self,
+        recipe: domain::Recipe,
+        system: domain::unit::MeasurementSystem,
+    ) -> Result<domain::Recipe, domain::unit::Error> {
+        self.get_units().await?.convert_recipe(recipe, system)
+    }
+}
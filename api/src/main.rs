@@ -1,13 +1,20 @@
-use std::sync::Arc;
+use std::{io::Write, path::PathBuf, sync::Arc};
 
-use anyhow::Ok;
+use anyhow::{Context, Ok};
 use clap::Parser;
 use dotenvy::dotenv;
 use log::info;
 use stockpot::{
-    adapters::{http, repositories},
-    commands::{RootCommand, SubCommand},
-    core::service,
+    adapters::{http, repositories, storage},
+    commands::{
+        cooklang, export::ExportFormat, import, server::MediaStoreKind, MediaOptions, RootCommand,
+        SubCommand,
+    },
+    core::{
+        domain,
+        port::{self, ArchiveService, AuthUserRepository, RecipeService, UserService},
+        service,
+    },
 };
 
 #[tokio::main]
@@ -20,23 +27,14 @@ async fn main() -> anyhow::Result<()> {
     match app.subcmd {
         SubCommand::Server(s) => {
             info!("Starting server at {}", s.addr);
-            let connect_options = sqlx::postgres::PgConnectOptions::new()
-                .host(&s.db_host)
-                .port(s.db_port)
-                .username(&s.db_username)
-                .password(&s.db_password)
-                .database(&s.db_database);
-            info!(
-                "Attempting db connection at postgres://{}:{}@{}:{}/{}",
-                s.db_username,
-                "*".repeat(s.db_password.len()),
-                s.db_host,
+            let pool = connect(
+                &s.db_host,
                 s.db_port,
-                s.db_database
-            );
-            let pool = sqlx::postgres::PgPoolOptions::new()
-                .connect_with(connect_options)
-                .await?;
+                &s.db_username,
+                &s.db_password,
+                &s.db_database,
+            )
+            .await?;
             let user_service = Arc::new(service::DefaultUserService::new(Box::new(
                 repositories::PostgresUserRepository::new(pool.clone()),
             )));
@@ -45,14 +43,293 @@ async fn main() -> anyhow::Result<()> {
                 user_service.clone(),
                 s.jwt_token_secret,
             ));
-            let recipe_service = Box::new(service::DefaultRecipeService::new(Box::new(
-                repositories::PostgresRecipeRepository::new(pool.clone()),
+            let unit_service = Arc::new(service::DefaultUnitService::new(Box::new(
+                repositories::PostgresUnitRepository::new(pool.clone()),
+            )));
+            let recipe_service = Arc::new(service::DefaultRecipeService::new(
+                Box::new(repositories::PostgresRecipeRepository::new(pool.clone())),
+                unit_service.clone(),
+            ));
+            let tag_service = Box::new(service::DefaultTagService::new(Box::new(
+                repositories::PostgresTagRepository::new(pool.clone()),
             )));
-            http::App::new(user_service.clone(), auth_user_service, recipe_service)
-                .serve(s.addr)
+            let media_store = media_store(MediaOptions {
+                media_store: s.media_store,
+                media_dir: s.media_dir,
+                s3_endpoint: s.s3_endpoint,
+                s3_bucket: s.s3_bucket,
+                s3_region: s.s3_region,
+                s3_access_key: s.s3_access_key,
+                s3_secret_key: s.s3_secret_key,
+            })?;
+            let media_service = Arc::new(service::DefaultMediaService::new(
+                Box::new(repositories::PostgresMediaRepository::new(pool.clone())),
+                media_store,
+            ));
+            let archive_service = Box::new(service::DefaultArchiveService::new(
+                recipe_service.clone(),
+                media_service.clone(),
+            ));
+            let shopping_list_service = Arc::new(service::DefaultShoppingListService::new(
+                Box::new(repositories::PostgresShoppingListRepository::new(
+                    pool.clone(),
+                )),
+                recipe_service.clone(),
+                unit_service.clone(),
+            ));
+            let meal_plan_service = Box::new(service::DefaultMealPlanService::new(
+                Box::new(repositories::PostgresMealPlanRepository::new(pool.clone())),
+                recipe_service.clone(),
+                shopping_list_service.clone(),
+            ));
+            let pantry_service = Box::new(service::DefaultPantryService::new(
+                Box::new(repositories::PostgresPantryRepository::new(pool.clone())),
+                recipe_service.clone(),
+                unit_service.clone(),
+            ));
+            let cook_log_service = Box::new(service::DefaultCookLogService::new(
+                Box::new(repositories::PostgresCookLogRepository::new(pool.clone())),
+                recipe_service.clone(),
+            ));
+            let review_service = Box::new(service::DefaultReviewService::new(
+                Box::new(repositories::PostgresReviewRepository::new(pool.clone())),
+                recipe_service.clone(),
+            ));
+            let collection_service = Box::new(service::DefaultCollectionService::new(
+                Box::new(repositories::PostgresCollectionRepository::new(
+                    pool.clone(),
+                )),
+                recipe_service.clone(),
+            ));
+            let revision_service = Box::new(service::DefaultRevisionService::new(
+                Box::new(repositories::PostgresRevisionRepository::new(pool.clone())),
+                recipe_service.clone(),
+            ));
+            http::App::new(
+                user_service.clone(),
+                auth_user_service,
+                recipe_service,
+                tag_service,
+                unit_service,
+                media_service,
+                archive_service,
+                shopping_list_service,
+                meal_plan_service,
+                pantry_service,
+                cook_log_service,
+                review_service,
+                collection_service,
+                revision_service,
+            )
+            .serve(s.addr)
+            .await?;
+        }
+        SubCommand::Cooklang(c) => {
+            let pool = connect(
+                &c.db.db_host,
+                c.db.db_port,
+                &c.db.db_username,
+                &c.db.db_password,
+                &c.db.db_database,
+            )
+            .await?;
+            let recipe_service = recipe_service(&pool);
+            match c.action {
+                cooklang::Action::Import {
+                    file,
+                    username,
+                    title,
+                } => {
+                    let text = std::fs::read_to_string(&file)
+                        .with_context(|| format!("cannot read {}", file.display()))?;
+                    let author = find_user(&pool, username).await?;
+                    let import = recipe_service.import_cooklang(text, title, author).await?;
+                    println!(
+                        "Imported recipe {} \"{}\"",
+                        import.recipe.id.unwrap_or_default(),
+                        import.recipe.title
+                    );
+                    for field in import.unmapped {
+                        println!("  {}: {}", field.field, field.reason);
+                    }
+                }
+                cooklang::Action::Export { id, output } => {
+                    let recipe = recipe_service.get_recipe_by_id(id).await?;
+                    write_output(output, domain::cooklang::to_cooklang(&recipe))?;
+                }
+            }
+        }
+        SubCommand::Export(e) => {
+            let pool = connect(
+                &e.db.db_host,
+                e.db.db_port,
+                &e.db.db_username,
+                &e.db.db_password,
+                &e.db.db_database,
+            )
+            .await?;
+            let recipe_service = recipe_service(&pool);
+            if let Some(username) = e.username {
+                let author = find_user(&pool, username).await?;
+                let media_service = media_service(&pool, e.media)?;
+                let archive = service::DefaultArchiveService::new(recipe_service, media_service)
+                    .export_library(author)
+                    .await?;
+                info!("Archived {} recipes", archive.manifest.recipes.len());
+                write_output(e.output, archive.to_bytes()?)?;
+                return Ok(());
+            }
+            let recipe = recipe_service
+                .get_recipe_by_id(e.id.context("a recipe id is required")?)
                 .await?;
+            // Media URLs are relative to the server, so exports made here
+            // leave the image out.
+            let text = match e.format {
+                ExportFormat::Markdown => domain::markdown::render_recipe(&recipe, None),
+                ExportFormat::Cooklang => domain::cooklang::to_cooklang(&recipe),
+                ExportFormat::Html => domain::html::render_recipe(&recipe, None),
+                ExportFormat::JsonLd => {
+                    format!("{:#}\n", domain::schema_org::to_json_ld(&recipe, None))
+                }
+            };
+            write_output(e.output, text)?;
+        }
+        SubCommand::Import(i) => {
+            let pool = connect(
+                &i.db.db_host,
+                i.db.db_port,
+                &i.db.db_username,
+                &i.db.db_password,
+                &i.db.db_database,
+            )
+            .await?;
+            let bytes = std::fs::read(&i.archive)
+                .with_context(|| format!("cannot read {}", i.archive.display()))?;
+            let archive = domain::archive::Archive::from_bytes(&bytes)?;
+            let author = find_user(&pool, i.username).await?;
+            let on_conflict = match i.on_conflict {
+                import::OnConflict::Skip => domain::archive::OnConflict::Skip,
+                import::OnConflict::Rename => domain::archive::OnConflict::Rename,
+                import::OnConflict::Overwrite => domain::archive::OnConflict::Overwrite,
+            };
+            let report = service::DefaultArchiveService::new(
+                recipe_service(&pool),
+                media_service(&pool, i.media)?,
+            )
+            .import_library(archive, author, on_conflict)
+            .await?;
+            for recipe in report.recipes {
+                let outcome = match recipe.outcome {
+                    domain::archive::ImportOutcome::Created => "created".to_owned(),
+                    domain::archive::ImportOutcome::Renamed(title) => {
+                        format!("renamed to \"{}\"", title)
+                    }
+                    domain::archive::ImportOutcome::Overwritten => "overwritten".to_owned(),
+                    domain::archive::ImportOutcome::Skipped => "skipped".to_owned(),
+                    domain::archive::ImportOutcome::Failed(e) => format!("failed: {}", e),
+                };
+                println!("\"{}\": {}", recipe.title, outcome);
+                for warning in recipe.warnings {
+                    println!("  {}", warning);
+                }
+            }
         }
     }
 
     Ok(())
 }
+
+/// Writes `contents` to the file at `path`, or to standard output without
+/// one.
+fn write_output(path: Option<PathBuf>, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    match path {
+        Some(path) => std::fs::write(&path, contents)
+            .with_context(|| format!("cannot write {}", path.display())),
+        None => {
+            std::io::stdout().write_all(contents.as_ref())?;
+            Ok(())
+        }
+    }
+}
+
+/// The user who signs in as `username`.
+async fn find_user(pool: &sqlx::PgPool, username: String) -> anyhow::Result<domain::User> {
+    let credentials = repositories::PostgresAuthUserRepository::new(pool.clone())
+        .get_auth_user_credentials(username)
+        .await?;
+    Ok(
+        service::DefaultUserService::new(Box::new(repositories::PostgresUserRepository::new(
+            pool.clone(),
+        )))
+        .get_user(credentials.user_id)
+        .await?,
+    )
+}
+
+fn recipe_service(pool: &sqlx::PgPool) -> Arc<service::DefaultRecipeService> {
+    Arc::new(service::DefaultRecipeService::new(
+        Box::new(repositories::PostgresRecipeRepository::new(pool.clone())),
+        Arc::new(service::DefaultUnitService::new(Box::new(
+            repositories::PostgresUnitRepository::new(pool.clone()),
+        ))),
+    ))
+}
+
+fn media_service(
+    pool: &sqlx::PgPool,
+    options: MediaOptions,
+) -> anyhow::Result<Arc<service::DefaultMediaService>> {
+    Ok(Arc::new(service::DefaultMediaService::new(
+        Box::new(repositories::PostgresMediaRepository::new(pool.clone())),
+        media_store(options)?,
+    )))
+}
+
+fn media_store(options: MediaOptions) -> anyhow::Result<Box<dyn port::MediaStore + Send + Sync>> {
+    Ok(match options.media_store {
+        MediaStoreKind::Local => {
+            info!("Storing media in {}", options.media_dir.display());
+            Box::new(storage::LocalMediaStore::new(options.media_dir))
+        }
+        MediaStoreKind::S3 => {
+            let config = storage::S3Config {
+                endpoint: options.s3_endpoint.context("S3_ENDPOINT is required")?,
+                bucket: options.s3_bucket.context("S3_BUCKET is required")?,
+                region: options.s3_region,
+                access_key: options.s3_access_key.context("S3_ACCESS_KEY is required")?,
+                secret_key: options.s3_secret_key.context("S3_SECRET_KEY is required")?,
+            };
+            info!(
+                "Storing media in bucket {} at {}",
+                config.bucket, config.endpoint
+            );
+            Box::new(storage::S3MediaStore::new(config)?)
+        }
+    })
+}
+
+async fn connect(
+    host: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+    database: &str,
+) -> anyhow::Result<sqlx::PgPool> {
+    let connect_options = sqlx::postgres::PgConnectOptions::new()
+        .host(host)
+        .port(port)
+        .username(username)
+        .password(password)
+        .database(database);
+    info!(
+        "Attempting db connection at postgres://{}:{}@{}:{}/{}",
+        username,
+        "*".repeat(password.len()),
+        host,
+        port,
+        database
+    );
+    Ok(sqlx::postgres::PgPoolOptions::new()
+        .connect_with(connect_options)
+        .await?)
+}
@@ -1,22 +1,48 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
 
 use axum::{
     body::{self, Body},
-    http::{request::Builder, Request, StatusCode},
+    http::{request::Builder, HeaderMap, Request, StatusCode},
+    routing::put,
+    Router,
 };
 use headers::HeaderMapExt;
 use serde_json::{json, Value};
 use sqlx::PgPool;
 use stockpot::{
     adapters::{
-        http::{self, recipe::GetRecipe},
-        repositories,
+        http::{
+            self,
+            recipe::{
+                GetIngredientMatchPage, GetRecipe, GetRecipeGroup, GetRecipePage,
+                GetRecipeSearchPage, GetSearchHighlight,
+            },
+            tag::GetTag,
+            unit::GetUnit,
+        },
+        repositories, storage,
     },
-    core::service,
+    core::{domain, port, service},
 };
 use tower::{Service, ServiceExt};
 
 fn create_app(pool: PgPool) -> http::App {
+    create_app_with_media_store(
+        pool,
+        Box::new(storage::LocalMediaStore::new(
+            std::env::temp_dir().join("stockpot-test-media"),
+        )),
+    )
+}
+
+fn create_app_with_media_store(
+    pool: PgPool,
+    media_store: Box<dyn port::MediaStore + Send + Sync>,
+) -> http::App {
     let user_service = Arc::new(service::DefaultUserService::new(Box::new(
         repositories::PostgresUserRepository::new(pool.clone()),
     )));
@@ -25,10 +51,75 @@ fn create_app(pool: PgPool) -> http::App {
         user_service.clone(),
         String::from("secret"),
     ));
-    let recipe_service = Box::new(service::DefaultRecipeService::new(Box::new(
-        repositories::PostgresRecipeRepository::new(pool),
+    let unit_service = Arc::new(service::DefaultUnitService::new(Box::new(
+        repositories::PostgresUnitRepository::new(pool.clone()),
+    )));
+    let recipe_service = Arc::new(service::DefaultRecipeService::new(
+        Box::new(repositories::PostgresRecipeRepository::new(pool.clone())),
+        unit_service.clone(),
+    ));
+    let tag_service = Box::new(service::DefaultTagService::new(Box::new(
+        repositories::PostgresTagRepository::new(pool.clone()),
     )));
-    http::App::new(user_service, auth_service, recipe_service)
+    let media_service = Arc::new(service::DefaultMediaService::new(
+        Box::new(repositories::PostgresMediaRepository::new(pool.clone())),
+        media_store,
+    ));
+    let archive_service = Box::new(service::DefaultArchiveService::new(
+        recipe_service.clone(),
+        media_service.clone(),
+    ));
+    let shopping_list_service = Arc::new(service::DefaultShoppingListService::new(
+        Box::new(repositories::PostgresShoppingListRepository::new(
+            pool.clone(),
+        )),
+        recipe_service.clone(),
+        unit_service.clone(),
+    ));
+    let meal_plan_service = Box::new(service::DefaultMealPlanService::new(
+        Box::new(repositories::PostgresMealPlanRepository::new(pool.clone())),
+        recipe_service.clone(),
+        shopping_list_service.clone(),
+    ));
+    let pantry_service = Box::new(service::DefaultPantryService::new(
+        Box::new(repositories::PostgresPantryRepository::new(pool.clone())),
+        recipe_service.clone(),
+        unit_service.clone(),
+    ));
+    let cook_log_service = Box::new(service::DefaultCookLogService::new(
+        Box::new(repositories::PostgresCookLogRepository::new(pool.clone())),
+        recipe_service.clone(),
+    ));
+    let review_service = Box::new(service::DefaultReviewService::new(
+        Box::new(repositories::PostgresReviewRepository::new(pool.clone())),
+        recipe_service.clone(),
+    ));
+    let collection_service = Box::new(service::DefaultCollectionService::new(
+        Box::new(repositories::PostgresCollectionRepository::new(
+            pool.clone(),
+        )),
+        recipe_service.clone(),
+    ));
+    let revision_service = Box::new(service::DefaultRevisionService::new(
+        Box::new(repositories::PostgresRevisionRepository::new(pool)),
+        recipe_service.clone(),
+    ));
+    http::App::new(
+        user_service,
+        auth_service,
+        recipe_service,
+        tag_service,
+        unit_service,
+        media_service,
+        archive_service,
+        shopping_list_service,
+        meal_plan_service,
+        pantry_service,
+        cook_log_service,
+        review_service,
+        collection_service,
+        revision_service,
+    )
 }
 
 #[sqlx::test]
@@ -1128,3 +1219,3679 @@ async fn test_delete_recipe(pool: PgPool) {
         StatusCode::NOT_FOUND
     );
 }
+
+async fn post_recipe(app: &mut axum::Router, recipe: Value) -> GetRecipe {
+    let request = get_authed_request_builder("/recipe", "POST")
+        .body(Body::from(serde_json::to_vec(&recipe).unwrap()))
+        .unwrap();
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .unwrap();
+    assert_eq!(result.status(), StatusCode::CREATED);
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn create_recipe_with_title(app: &mut axum::Router, title: &str, cook_time: i64) {
+    post_recipe(
+        app,
+        json!({
+                "title": title,
+                "description": null,
+                "prep_time": null,
+                "cook_time": cook_time,
+                "inactive_time": null,
+                "yield_quantity": 200,
+                "yield_units": "grams",
+                "ingredients": [
+                    {
+                        "ingredient": "carrots",
+                        "quantity": 200,
+                        "units": "grams",
+                        "preparation": "diced"
+                    }
+                ],
+                "steps": [
+                    {
+                        "ordinal": 1,
+                        "instruction": "Cook the carrots"
+                    }
+                ]
+        }),
+    )
+    .await;
+}
+
+async fn get_json(app: &mut axum::Router, uri: &str) -> (StatusCode, Value) {
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = result.status();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_get_recipes_paginated_and_sorted(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    create_recipe_with_title(&mut app, "Roasted Carrots", 1800).await;
+    create_recipe_with_title(&mut app, "Braised Carrots", 3600).await;
+    create_recipe_with_title(&mut app, "Carrot Salad", 0).await;
+
+    let (status, json) = get_json(&mut app, "/recipe?sort=title&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        page.recipes
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Braised Carrots", "Carrot Salad"]
+    );
+    assert_eq!(page.total_count, 3);
+    assert_eq!(page.next_page, Some(2));
+
+    let (status, json) = get_json(&mut app, "/recipe?sort=title&limit=2&page=2").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        page.recipes
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Roasted Carrots"]
+    );
+    assert_eq!(page.next_page, None);
+
+    let (status, json) = get_json(
+        &mut app,
+        &format!("/recipe?sort=title&limit=100&page={}", i64::MAX),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert!(page.recipes.is_empty());
+    assert_eq!(page.next_page, None);
+
+    let (status, json) = get_json(&mut app, "/recipe?sort=total_time").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        page.recipes
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Carrot Salad", "Roasted Carrots", "Braised Carrots"]
+    );
+
+    let (status, json) = get_json(&mut app, "/recipe?sort=newest").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        page.recipes
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Carrot Salad", "Braised Carrots", "Roasted Carrots"]
+    );
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_get_recipes_filtered(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    create_recipe_with_title(&mut app, "Roasted Carrots", 1800).await;
+    create_recipe_with_title(&mut app, "Braised Carrots", 3600).await;
+    create_recipe_with_title(&mut app, "Potato Salad", 0).await;
+
+    let (status, json) = get_json(
+        &mut app,
+        "/recipe?title=carrot&max_total_time=2000&author=1",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        page.recipes
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Roasted Carrots"]
+    );
+    assert_eq!(page.total_count, 1);
+
+    let (status, json) = get_json(&mut app, "/recipe?author=2").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 0);
+    assert!(page.recipes.is_empty());
+
+    let (status, json) = get_json(&mut app, "/recipe?yield_units=grams").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 3);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_get_recipes_invalid_params(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let (status, json) = get_json(&mut app, "/recipe?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        json,
+        json!({"error": "invalid recipe query: limit must be between 1 and 100, got 0"})
+    );
+
+    let (status, _) = get_json(&mut app, "/recipe?sort=popularity").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_search_recipes(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    post_recipe(
+        &mut app,
+        json!({
+            "title": "Buttered Carrots",
+            "description": "Buttery carrots in a butter sauce",
+            "prep_time": null,
+            "cook_time": null,
+            "inactive_time": null,
+            "yield_quantity": 200,
+            "yield_units": "grams",
+            "ingredients": [
+                {
+                    "ingredient": "carrots",
+                    "quantity": 200,
+                    "units": "grams",
+                    "preparation": "diced"
+                }
+            ],
+            "steps": [
+                {
+                    "ordinal": 1,
+                    "instruction": "Saute the carrots"
+                }
+            ]
+        }),
+    )
+    .await;
+    post_recipe(
+        &mut app,
+        json!({
+            "title": "Mashed Potatoes",
+            "description": null,
+            "prep_time": null,
+            "cook_time": null,
+            "inactive_time": null,
+            "yield_quantity": 400,
+            "yield_units": "grams",
+            "ingredients": [
+                {
+                    "ingredient": "potatoes",
+                    "quantity": 400,
+                    "units": "grams",
+                    "preparation": "peeled"
+                },
+                {
+                    "ingredient": "butter",
+                    "quantity": 50,
+                    "units": "grams",
+                    "preparation": "softened"
+                }
+            ],
+            "steps": [
+                {
+                    "ordinal": 1,
+                    "instruction": "Boil the potatoes"
+                },
+                {
+                    "ordinal": 2,
+                    "instruction": "Mash with the butter"
+                }
+            ]
+        }),
+    )
+    .await;
+
+    let (status, json) = get_json(&mut app, "/recipe/search?q=butter").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipeSearchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 2);
+    assert_eq!(
+        page.results
+            .iter()
+            .map(|r| r.recipe.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Buttered Carrots", "Mashed Potatoes"]
+    );
+    assert!(page.results[0].rank > page.results[1].rank);
+    assert_eq!(
+        page.results[1].highlights,
+        vec![
+            GetSearchHighlight {
+                field: "ingredient".to_owned(),
+                snippet: "<b>butter</b>".to_owned(),
+            },
+            GetSearchHighlight {
+                field: "step".to_owned(),
+                snippet: "Mash with the <b>butter</b>".to_owned(),
+            },
+        ]
+    );
+
+    let (status, json) = get_json(&mut app, "/recipe/search?q=boil").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipeSearchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 1);
+    assert_eq!(page.results[0].recipe.title, "Mashed Potatoes");
+
+    // Terms may be found in different fields.
+    let (status, json) = get_json(&mut app, "/recipe/search?q=potato%20butter").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipeSearchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 1);
+    assert_eq!(page.results[0].recipe.title, "Mashed Potatoes");
+    assert_eq!(
+        page.results[0]
+            .highlights
+            .iter()
+            .map(|x| x.field.as_str())
+            .collect::<Vec<_>>(),
+        vec!["title", "ingredient", "ingredient", "step", "step"]
+    );
+
+    let (status, json) = get_json(
+        &mut app,
+        &format!("/recipe/search?q=boil&page={}", i64::MAX),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipeSearchPage = serde_json::from_value(json).unwrap();
+    assert!(page.results.is_empty());
+    assert_eq!(page.next_page, None);
+
+    let (status, _) = get_json(&mut app, "/recipe/search?q=").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+fn recipe_with_ingredients(title: &str, ingredients: &[&str]) -> Value {
+    json!({
+        "title": title,
+        "description": null,
+        "prep_time": null,
+        "cook_time": null,
+        "inactive_time": null,
+        "yield_quantity": 100,
+        "yield_units": "grams",
+        "ingredients": ingredients
+            .iter()
+            .map(|name| json!({
+                "ingredient": name,
+                "quantity": 100,
+                "units": "grams",
+                "preparation": ""
+            }))
+            .collect::<Vec<_>>(),
+        "steps": [
+            {
+                "ordinal": 1,
+                "instruction": "Combine everything"
+            }
+        ]
+    })
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_get_recipes_by_ingredients(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    post_recipe(
+        &mut app,
+        recipe_with_ingredients("Buttered Carrots", &["carrots", "butter", "salt"]),
+    )
+    .await;
+    post_recipe(
+        &mut app,
+        recipe_with_ingredients("Carrot Cake", &["carrots", "flour", "sugar", "eggs"]),
+    )
+    .await;
+    post_recipe(
+        &mut app,
+        recipe_with_ingredients("Boiled Potatoes", &["potatoes", "water"]),
+    )
+    .await;
+
+    let (status, json) = get_json(
+        &mut app,
+        "/recipe/by-ingredients?ingredients=Carrots,butter",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetIngredientMatchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 2);
+    assert_eq!(page.results[0].recipe.title, "Buttered Carrots");
+    assert_eq!(page.results[0].covered, 2);
+    assert_eq!(page.results[0].required, 2);
+    assert!(page.results[0].missing.is_empty());
+    assert_eq!(page.results[1].recipe.title, "Carrot Cake");
+    assert_eq!(page.results[1].missing, vec!["eggs", "flour", "sugar"]);
+
+    let (status, json) = get_json(
+        &mut app,
+        "/recipe/by-ingredients?ingredients=carrots,butter&max_missing=0&ignore_staples=false",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetIngredientMatchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 0);
+
+    let (status, json) = get_json(
+        &mut app,
+        "/recipe/by-ingredients?ingredients=carrots,butter&max_missing=0",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetIngredientMatchPage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 1);
+    assert_eq!(page.results[0].recipe.title, "Buttered Carrots");
+
+    let (status, json) = get_json(
+        &mut app,
+        &format!(
+            "/recipe/by-ingredients?ingredients=carrots&page={}",
+            i64::MAX
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetIngredientMatchPage = serde_json::from_value(json).unwrap();
+    assert!(page.results.is_empty());
+    assert_eq!(page.next_page, None);
+}
+
+async fn send_authed_json(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = get_authed_request_builder(uri, method)
+        .body(match body {
+            Some(body) => Body::from(serde_json::to_vec(&body).unwrap()),
+            None => Body::empty(),
+        })
+        .unwrap();
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .unwrap();
+    let status = result.status();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_tags(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let mut carrots = recipe_with_ingredients("Buttered Carrots", &["carrots", "butter"]);
+    carrots["tags"] = json!([" Side  Dish", "VEGETARIAN", "vegetarian"]);
+    let created = post_recipe(&mut app, carrots).await;
+    assert_eq!(created.tags, vec!["side dish", "vegetarian"]);
+
+    let mut stew = recipe_with_ingredients("Beef Stew", &["beef", "carrots"]);
+    stew["tags"] = json!(["Main Course", "winter"]);
+    let stew = post_recipe(&mut app, stew).await;
+
+    let (status, json) = get_json(&mut app, "/recipe?tags=Vegetarian").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: GetRecipePage = serde_json::from_value(json).unwrap();
+    assert_eq!(page.total_count, 1);
+    assert_eq!(page.recipes[0].title, "Buttered Carrots");
+
+    let mut update = recipe_with_ingredients("Beef Stew", &["beef", "carrots"]);
+    update["id"] = json!(stew.id);
+    update["steps"][0]["id"] = json!(stew.steps.iter().next().unwrap().id);
+    update["tags"] = json!(["main course", "side dish"]);
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}", stew.id),
+        Some(update),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let updated: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(updated.tags, vec!["main course", "side dish"]);
+
+    let (status, json) = get_json(&mut app, "/tag").await;
+    assert_eq!(status, StatusCode::OK);
+    let tags: Vec<GetTag> = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        tags.iter()
+            .map(|t| (t.name.as_str(), t.recipe_count))
+            .collect::<Vec<_>>(),
+        vec![
+            ("main course", 1),
+            ("side dish", 2),
+            ("vegetarian", 1),
+            ("winter", 0)
+        ]
+    );
+
+    let vegetarian = tags.iter().find(|t| t.name == "vegetarian").unwrap();
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/tag/{}", vegetarian.id),
+        Some(json!({"name": "Side Dish"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let merged: GetTag = serde_json::from_value(json).unwrap();
+    assert_eq!(merged.name, "side dish");
+    assert_eq!(merged.recipe_count, 2);
+
+    let (status, json) = get_json(&mut app, "/tag").await;
+    assert_eq!(status, StatusCode::OK);
+    let tags: Vec<GetTag> = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+        vec!["main course", "side dish", "winter"]
+    );
+
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/tag/999",
+        Some(json!({"name": "dessert"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // Tags on someone else's recipes can be neither renamed nor merged into.
+    sqlx::query(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id FROM u WHERE recipe.id = $1
+        "#,
+    )
+    .bind(stew.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let tag_id = |name: &str| tags.iter().find(|t| t.name == name).unwrap().id;
+    for (id, name) in [
+        (tag_id("main course"), "entree"),
+        (tag_id("winter"), "main course"),
+    ] {
+        let (status, _) = send_authed_json(
+            &mut app,
+            "POST",
+            &format!("/tag/{}", id),
+            Some(json!({"name": name})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/tag/{}", tag_id("winter")),
+        Some(json!({"name": "cold weather"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], json!("cold weather"));
+    // Tags only count the recipes listed to the reader.
+    sqlx::query("UPDATE recipe SET visibility = 'private' WHERE id = $1")
+        .bind(stew.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, json) = get_json(&mut app, "/tag").await;
+    assert_eq!(status, StatusCode::OK);
+    let tags: Vec<GetTag> = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        tags.iter()
+            .map(|t| (t.name.as_str(), t.recipe_count))
+            .collect::<Vec<_>>(),
+        vec![("cold weather", 0), ("side dish", 1)]
+    );
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_units(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let (status, json) = get_json(&mut app, "/unit").await;
+    assert_eq!(status, StatusCode::OK);
+    let units: Vec<GetUnit> = serde_json::from_value(json).unwrap();
+    let cups = units.iter().find(|u| u.name == "cups").unwrap();
+    assert_eq!(cups.dimension, "volume");
+    assert_eq!(cups.system.as_deref(), Some("imperial"));
+
+    let mut recipe = recipe_with_ingredients("Carrot Soup", &["carrots"]);
+    recipe["yield_quantity"] = json!(1000);
+    recipe["yield_units"] = json!("milliliters");
+    recipe["ingredients"][0]["quantity"] = json!(1000);
+    recipe["ingredients"][0]["units"] = json!("grams");
+    let created = post_recipe(&mut app, recipe).await;
+
+    let (status, json) =
+        get_json(&mut app, &format!("/recipe/{}?units=imperial", created.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let converted: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(converted.yield_quantity.to_string(), "1 1/16");
+    assert_eq!(converted.yield_units, "quarts");
+    let ingredient = converted.ingredients.iter().next().unwrap();
+    assert_eq!(ingredient.quantity.to_string(), "2 1/4");
+    assert_eq!(ingredient.units, "pounds");
+
+    let (status, json) = get_json(&mut app, &format!("/recipe/{}?units=metric", created.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let unchanged: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(unchanged, created);
+
+    let (status, _) = get_json(&mut app, &format!("/recipe/{}?units=martian", created.id)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut recipe = recipe_with_ingredients("Carrot Handfuls", &["carrots"]);
+    recipe["ingredients"][0]["units"] = json!("handfuls");
+    let (status, json) = send_authed_json(&mut app, "POST", "/recipe", Some(recipe)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json, json!({"error": "unknown unit `handfuls`"}));
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_fractional_quantities(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let mut recipe = recipe_with_ingredients("Vinaigrette", &["olive oil", "vinegar", "mustard"]);
+    recipe["yield_quantity"] = json!("1 1/2");
+    recipe["ingredients"][0]["quantity"] = json!(0.75);
+    recipe["ingredients"][1]["quantity"] = json!("⅓");
+    recipe["ingredients"][2]["quantity"] = json!("0.1");
+    let created = post_recipe(&mut app, recipe).await;
+
+    let (status, json) = get_json(&mut app, &format!("/recipe/{}", created.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["yield_quantity"], json!("1 1/2"));
+    let mut quantities: Vec<(String, Value)> = json["ingredients"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| {
+            (
+                x["ingredient"].as_str().unwrap().to_owned(),
+                x["quantity"].clone(),
+            )
+        })
+        .collect();
+    quantities.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        quantities,
+        vec![
+            ("mustard".to_owned(), json!("0.1")),
+            ("olive oil".to_owned(), json!("3/4")),
+            ("vinegar".to_owned(), json!("1/3")),
+        ]
+    );
+
+    let mut recipe = recipe_with_ingredients("Mystery Dressing", &["olive oil"]);
+    recipe["ingredients"][0]["quantity"] = json!("a splash");
+    let (status, _) = send_authed_json(&mut app, "POST", "/recipe", Some(recipe)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_scale_recipe(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let mut recipe = recipe_with_ingredients("Pancakes", &["flour", "milk", "sugar"]);
+    recipe["yield_quantity"] = json!(4);
+    recipe["yield_units"] = json!("each");
+    recipe["ingredients"][0]["quantity"] = json!(200);
+    recipe["ingredients"][1]["quantity"] = json!("1/2");
+    recipe["ingredients"][1]["units"] = json!("cups");
+    recipe["ingredients"][2]["quantity"] = json!(16);
+    recipe["ingredients"][2]["units"] = json!("teaspoons");
+    let created = post_recipe(&mut app, recipe).await;
+
+    let amounts = |json: &Value| {
+        let mut amounts: Vec<(String, String, String)> = json["ingredients"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| {
+                (
+                    x["ingredient"].as_str().unwrap().to_owned(),
+                    x["quantity"].as_str().unwrap().to_owned(),
+                    x["units"].as_str().unwrap().to_owned(),
+                )
+            })
+            .collect();
+        amounts.sort();
+        amounts
+    };
+    let amount = |ingredient: &str, quantity: &str, units: &str| {
+        (ingredient.to_owned(), quantity.to_owned(), units.to_owned())
+    };
+
+    let (status, json) =
+        get_json(&mut app, &format!("/recipe/{}/scaled?yield=12", created.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["yield_quantity"], json!("12"));
+    assert_eq!(
+        amounts(&json),
+        vec![
+            amount("flour", "600", "grams"),
+            amount("milk", "1 1/2", "cups"),
+            amount("sugar", "48", "teaspoons"),
+        ]
+    );
+
+    let (status, json) = get_json(
+        &mut app,
+        &format!("/recipe/{}/scaled?yield=12&promote_units=true", created.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        amounts(&json),
+        vec![
+            amount("flour", "600", "grams"),
+            amount("milk", "1 1/2", "cups"),
+            amount("sugar", "1", "cups"),
+        ]
+    );
+
+    let (status, json) = get_json(
+        &mut app,
+        &format!("/recipe/{}/scaled?factor=0.5", created.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["yield_quantity"], json!("2"));
+    assert_eq!(
+        amounts(&json),
+        vec![
+            amount("flour", "100", "grams"),
+            amount("milk", "1/4", "cups"),
+            amount("sugar", "8", "teaspoons"),
+        ]
+    );
+
+    // Scaling is read-only.
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", created.id)).await;
+    assert_eq!(json["yield_quantity"], json!("4"));
+
+    for params in ["", "yield=12&factor=2", "factor=0", "factor=lots"] {
+        let (status, _) = get_json(
+            &mut app,
+            &format!("/recipe/{}/scaled?{}", created.id, params),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "params: {}", params);
+    }
+
+    let (status, _) = get_json(&mut app, "/recipe/9999/scaled?factor=2").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_groups(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let mut recipe = recipe_with_ingredients("Apple Pie", &["salt"]);
+    recipe["steps"] = json!([]);
+    recipe["groups"] = json!([
+        {
+            "name": "For the dough",
+            "ingredients": [
+                {"ingredient": "flour", "quantity": 300, "units": "grams", "preparation": ""},
+                {"ingredient": "butter", "quantity": 200, "units": "grams", "preparation": "cold"}
+            ],
+            "steps": [{"ordinal": 1, "instruction": "Rub the butter into the flour"}]
+        },
+        {
+            "name": "For the filling",
+            "ingredients": [
+                {"ingredient": "apples", "quantity": 1000, "units": "grams", "preparation": "sliced"}
+            ],
+            "steps": [{"ordinal": 2, "instruction": "Toss the apples with sugar"}]
+        }
+    ]);
+    let created = post_recipe(&mut app, recipe).await;
+
+    let names = |group: &GetRecipeGroup| {
+        let mut names: Vec<String> = group
+            .ingredients
+            .iter()
+            .map(|x| x.ingredient.clone())
+            .chain(group.steps.iter().map(|x| x.instruction.clone()))
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(
+        created
+            .ingredients
+            .iter()
+            .map(|x| x.ingredient.as_str())
+            .collect::<Vec<_>>(),
+        vec!["salt"]
+    );
+    assert!(created.steps.is_empty());
+    assert_eq!(
+        created
+            .groups
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["For the dough", "For the filling"]
+    );
+    assert_eq!(
+        names(&created.groups[0]),
+        vec!["Rub the butter into the flour", "butter", "flour"]
+    );
+    assert_eq!(
+        names(&created.groups[1]),
+        vec!["Toss the apples with sugar", "apples"]
+    );
+
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", created.id)).await;
+    let fetched: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(fetched, created);
+
+    // Swap the group order, move the butter into the filling as a new
+    // "For the crumble" group and drop the dough group, keeping item ids.
+    let item = |group: &GetRecipeGroup, name: &str| {
+        let ingredient = group
+            .ingredients
+            .iter()
+            .find(|x| x.ingredient == name)
+            .unwrap();
+        json!({
+            "id": ingredient.id,
+            "ingredient": ingredient.ingredient,
+            "quantity": ingredient.quantity,
+            "units": ingredient.units,
+            "preparation": ingredient.preparation
+        })
+    };
+    let step = |group: &GetRecipeGroup| {
+        let step = group.steps.iter().next().unwrap();
+        json!({"id": step.id, "ordinal": step.ordinal, "instruction": step.instruction})
+    };
+    let salt = created.ingredients.iter().next().unwrap();
+    let update = json!({
+        "id": created.id,
+        "title": "Apple Crumble",
+        "description": null,
+        "prep_time": null,
+        "cook_time": null,
+        "inactive_time": null,
+        "yield_quantity": 100,
+        "yield_units": "grams",
+        "ingredients": [{
+            "id": salt.id,
+            "ingredient": "salt",
+            "quantity": 100,
+            "units": "grams",
+            "preparation": ""
+        }],
+        "steps": [step(&created.groups[0])],
+        "groups": [
+            {
+                "name": "For the filling",
+                "ingredients": [item(&created.groups[1], "apples")],
+                "steps": [step(&created.groups[1])]
+            },
+            {
+                "name": "For the crumble",
+                "ingredients": [
+                    item(&created.groups[0], "flour"),
+                    item(&created.groups[0], "butter")
+                ]
+            }
+        ]
+    });
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}", created.id),
+        Some(update),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let updated: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        updated
+            .groups
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["For the filling", "For the crumble"]
+    );
+    assert_eq!(
+        names(&updated.groups[0]),
+        vec!["Toss the apples with sugar", "apples"]
+    );
+    assert_eq!(names(&updated.groups[1]), vec!["butter", "flour"]);
+    assert_eq!(
+        updated.steps.iter().next().unwrap().id,
+        created.groups[0].steps.iter().next().unwrap().id
+    );
+    assert_eq!(
+        updated.groups[1]
+            .ingredients
+            .iter()
+            .map(|x| x.id)
+            .collect::<HashSet<_>>(),
+        created.groups[0]
+            .ingredients
+            .iter()
+            .map(|x| x.id)
+            .collect::<HashSet<_>>()
+    );
+
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", created.id)).await;
+    let fetched: GetRecipe = serde_json::from_value(json).unwrap();
+    assert_eq!(fetched, updated);
+
+    let mut duplicate = recipe_with_ingredients("Twice Baked", &["potatoes"]);
+    duplicate["groups"] = json!([{"name": "Topping"}, {"name": "Topping"}]);
+    let (status, json) = send_authed_json(&mut app, "POST", "/recipe", Some(duplicate)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        json,
+        json!({"error": "invalid recipe: duplicate group `Topping`"})
+    );
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_sub_recipes(pool: PgPool) {
+    let mut app = create_app(pool).router();
+
+    let mut stock = recipe_with_ingredients("Vegetable Stock", &["onions", "water"]);
+    stock["yield_quantity"] = json!(2);
+    stock["yield_units"] = json!("liters");
+    stock["ingredients"][0]["quantity"] = json!(200);
+    stock["ingredients"][1]["quantity"] = json!(2);
+    stock["ingredients"][1]["units"] = json!("liters");
+    let stock = post_recipe(&mut app, stock).await;
+
+    let mut soup = recipe_with_ingredients("Onion Soup", &["onions", "stock"]);
+    soup["ingredients"][1]["quantity"] = json!(500);
+    soup["ingredients"][1]["units"] = json!("milliliters");
+    soup["ingredients"][1]["sub_recipe_id"] = json!(stock.id);
+    let soup = post_recipe(&mut app, soup).await;
+    let sub_recipe = soup
+        .ingredients
+        .iter()
+        .find(|x| x.ingredient == "stock")
+        .unwrap();
+    assert_eq!(sub_recipe.sub_recipe_id, Some(stock.id));
+
+    let (status, json) = get_json(&mut app, &format!("/recipe/{}?expand=true", soup.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut amounts: Vec<(String, String, String)> = json["ingredients"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| {
+            (
+                x["ingredient"].as_str().unwrap().to_owned(),
+                x["quantity"].as_str().unwrap().to_owned(),
+                x["units"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect();
+    amounts.sort();
+    assert_eq!(
+        amounts,
+        vec![
+            ("onions".to_owned(), "150".to_owned(), "grams".to_owned()),
+            ("water".to_owned(), "1/2".to_owned(), "liters".to_owned()),
+        ]
+    );
+
+    // The stock can't be made from the soup it goes into.
+    let mut update = recipe_with_ingredients("Vegetable Stock", &["onions", "soup"]);
+    update["id"] = json!(stock.id);
+    update["ingredients"][1]["sub_recipe_id"] = json!(soup.id);
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}", stock.id),
+        Some(update),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut unknown = recipe_with_ingredients("Mystery Soup", &["stock"]);
+    unknown["ingredients"][0]["sub_recipe_id"] = json!(stock.id + 100);
+    let (status, _) = send_authed_json(&mut app, "POST", "/recipe", Some(unknown)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut unmeasurable = recipe_with_ingredients("Heavy Soup", &["stock"]);
+    unmeasurable["ingredients"][0]["sub_recipe_id"] = json!(stock.id);
+    let (status, _) = send_authed_json(&mut app, "POST", "/recipe", Some(unmeasurable)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::ImageBuffer::from_pixel(width, height, image::Rgb([180u8, 90, 30]));
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+async fn send_image(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    content_type: &str,
+    image: &[u8],
+) -> (StatusCode, Value) {
+    let boundary = "stockpot-test-boundary";
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"image\"; filename=\"photo\"\r\n\
+         Content-Type: {}\r\n\r\n",
+        boundary, content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(image);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    let mut request = get_authed_request_builder(uri, method)
+        .body(Body::from(body))
+        .unwrap();
+    request.headers_mut().insert(
+        "Content-Type",
+        format!("multipart/form-data; boundary={}", boundary)
+            .parse()
+            .unwrap(),
+    );
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .unwrap();
+    let status = result.status();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn get_media(app: &mut axum::Router, uri: &str) -> (StatusCode, String, Vec<u8>) {
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = result.status();
+    let content_type = result
+        .headers()
+        .get("content-type")
+        .map(|x| x.to_str().unwrap().to_owned())
+        .unwrap_or_default();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_images(pool: PgPool) {
+    let media_dir = tempfile::tempdir().unwrap();
+    let mut app = create_app_with_media_store(
+        pool,
+        Box::new(storage::LocalMediaStore::new(media_dir.path())),
+    )
+    .router();
+    let recipe = post_recipe(&mut app, recipe_with_ingredients("Toast", &["bread"])).await;
+    let step = recipe.steps.iter().next().unwrap().id;
+    assert_eq!(recipe.image, None);
+
+    let image = png(640, 480);
+    let (status, json) = send_image(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}/image", recipe.id),
+        "image/png",
+        &image,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let url = json["url"].as_str().unwrap().to_owned();
+    let thumbnail_url = json["thumbnail_url"].as_str().unwrap().to_owned();
+    assert!(url.starts_with(&format!("/media/recipe/{}/", recipe.id)));
+
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", recipe.id)).await;
+    assert_eq!(json["image"]["url"], json!(url));
+    assert_eq!(json["image"]["thumbnail_url"], json!(thumbnail_url));
+    assert_eq!(
+        get_media(&mut app, &url).await,
+        (StatusCode::OK, "image/png".to_owned(), image.clone())
+    );
+    let (status, content_type, thumbnail) = get_media(&mut app, &thumbnail_url).await;
+    assert_eq!(
+        (status, content_type.as_str()),
+        (StatusCode::OK, "image/jpeg")
+    );
+    let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+    assert_eq!((thumbnail.width(), thumbnail.height()), (320, 240));
+
+    // Replacing the image removes the old one.
+    let (status, json) = send_image(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}/image", recipe.id),
+        "image/png",
+        &png(10, 10),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(json["url"], json!(url));
+    assert_eq!(get_media(&mut app, &url).await.0, StatusCode::NOT_FOUND);
+
+    let step_uri = format!("/recipe/{}/step/{}/image", recipe.id, step);
+    let (status, json) = send_image(&mut app, "POST", &step_uri, "image/png", &image).await;
+    assert_eq!(status, StatusCode::OK);
+    let step_url = json["url"].as_str().unwrap().to_owned();
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", recipe.id)).await;
+    assert_eq!(json["steps"][0]["image"]["url"], json!(step_url));
+
+    for (uri, content_type, bytes, expected) in [
+        (
+            step_uri.clone(),
+            "text/plain",
+            b"hello".to_vec(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+        (
+            step_uri.clone(),
+            "image/jpeg",
+            image.clone(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            step_uri.clone(),
+            "image/png",
+            vec![0; domain::media::MAX_IMAGE_BYTES + 1],
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        (
+            format!("/recipe/{}/step/{}/image", recipe.id, step + 100),
+            "image/png",
+            image.clone(),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let (status, _) = send_image(&mut app, "POST", &uri, content_type, &bytes).await;
+        assert_eq!(status, expected, "uploading {} to {}", content_type, uri);
+    }
+
+    let (status, _) = send_authed_json(&mut app, "DELETE", &step_uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", recipe.id)).await;
+    assert_eq!(json["steps"][0]["image"], Value::Null);
+    assert_eq!(
+        get_media(&mut app, &step_url).await.0,
+        StatusCode::NOT_FOUND
+    );
+
+    // Removing a step, by updating or reverting the recipe, removes its
+    // image but leaves the rest.
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", recipe.id)).await;
+    let url = json["image"]["url"].as_str().unwrap().to_owned();
+    let recipe_uri = format!("/recipe/{}", recipe.id);
+    let update = |steps: Value| {
+        json!({
+            "id": recipe.id,
+            "title": "Toast",
+            "description": null,
+            "prep_time": null,
+            "cook_time": null,
+            "inactive_time": null,
+            "yield_quantity": 100,
+            "yield_units": "grams",
+            "ingredients": [
+                {"ingredient": "bread", "quantity": 100, "units": "grams", "preparation": ""}
+            ],
+            "steps": steps
+        })
+    };
+    let second_step_image = |json: &Value| {
+        let step = json["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|x| x["ordinal"] == json!(2))
+            .unwrap();
+        format!("/recipe/{}/step/{}/image", recipe.id, step["id"])
+    };
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &recipe_uri,
+        Some(update(json!([
+            {"id": step, "ordinal": 1, "instruction": "Toast the bread"},
+            {"ordinal": 2, "instruction": "Butter it"}
+        ]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = send_image(
+        &mut app,
+        "POST",
+        &second_step_image(&json),
+        "image/png",
+        &image,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let step_url = json["url"].as_str().unwrap().to_owned();
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &recipe_uri,
+        Some(update(json!([
+            {"id": step, "ordinal": 1, "instruction": "Toast the bread"}
+        ]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        get_media(&mut app, &step_url).await.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(get_media(&mut app, &url).await.0, StatusCode::OK);
+
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/revisions/2/revert", recipe_uri),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = send_image(
+        &mut app,
+        "POST",
+        &second_step_image(&json),
+        "image/png",
+        &image,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let step_url = json["url"].as_str().unwrap().to_owned();
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/revisions/1/revert", recipe_uri),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        get_media(&mut app, &step_url).await.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(get_media(&mut app, &url).await.0, StatusCode::OK);
+
+    // Deleting the recipe removes its images.
+    let (status, _) =
+        send_authed_json(&mut app, "DELETE", &format!("/recipe/{}", recipe.id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(get_media(&mut app, &url).await.0, StatusCode::NOT_FOUND);
+}
+
+type FakeBucket = Arc<Mutex<HashMap<String, (String, Vec<u8>)>>>;
+
+/// Checks the parts of a request an S3 service would before trusting it.
+fn is_signed(headers: &HeaderMap, body: &[u8]) -> bool {
+    use sha2::Digest;
+    let authorization = headers
+        .get("authorization")
+        .and_then(|x| x.to_str().ok())
+        .unwrap_or_default();
+    let payload_hash = headers
+        .get("x-amz-content-sha256")
+        .and_then(|x| x.to_str().ok())
+        .unwrap_or_default();
+    authorization.starts_with("AWS4-HMAC-SHA256 Credential=minio/")
+        && authorization.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,")
+        && headers.contains_key("x-amz-date")
+        && payload_hash == hex::encode(sha2::Sha256::digest(body))
+}
+
+/// An in-memory stand-in for a MinIO server, holding a single bucket.
+async fn spawn_fake_s3() -> (String, FakeBucket) {
+    use axum::extract::{Path, State};
+
+    let bucket: FakeBucket = Arc::new(Mutex::new(HashMap::new()));
+    let router =
+        Router::new()
+            .route(
+                "/stockpot/*key",
+                put(
+                    |State(bucket): State<FakeBucket>,
+                     Path(key): Path<String>,
+                     headers: HeaderMap,
+                     body: axum::body::Bytes| async move {
+                        if !is_signed(&headers, &body) {
+                            return StatusCode::FORBIDDEN;
+                        }
+                        let content_type = headers
+                            .get("content-type")
+                            .map(|x| x.to_str().unwrap().to_owned())
+                            .unwrap_or_default();
+                        bucket
+                            .lock()
+                            .unwrap()
+                            .insert(key, (content_type, body.to_vec()));
+                        StatusCode::OK
+                    },
+                )
+                .get(
+                    |State(bucket): State<FakeBucket>,
+                     Path(key): Path<String>,
+                     headers: HeaderMap| async move {
+                        if !is_signed(&headers, b"") {
+                            return Err(StatusCode::FORBIDDEN);
+                        }
+                        match bucket.lock().unwrap().get(&key) {
+                            Some((content_type, bytes)) => {
+                                Ok(([("content-type", content_type.clone())], bytes.clone()))
+                            }
+                            None => Err(StatusCode::NOT_FOUND),
+                        }
+                    },
+                )
+                .delete(
+                    |State(bucket): State<FakeBucket>,
+                     Path(key): Path<String>,
+                     headers: HeaderMap| async move {
+                        if !is_signed(&headers, b"") {
+                            return StatusCode::FORBIDDEN;
+                        }
+                        bucket.lock().unwrap().remove(&key);
+                        StatusCode::NO_CONTENT
+                    },
+                ),
+            )
+            .with_state(bucket.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}", addr), bucket)
+}
+
+#[tokio::test]
+async fn test_s3_media_store() {
+    use port::MediaStore;
+
+    let (endpoint, bucket) = spawn_fake_s3().await;
+    let store = storage::S3MediaStore::new(storage::S3Config {
+        endpoint,
+        bucket: "stockpot".to_owned(),
+        region: "us-east-1".to_owned(),
+        access_key: "minio".to_owned(),
+        secret_key: "minio123".to_owned(),
+    })
+    .unwrap();
+    let media = domain::media::Media {
+        content_type: "image/png".to_owned(),
+        bytes: png(2, 2),
+    };
+
+    store.put("recipe/1/abc.png", media.clone()).await.unwrap();
+    assert!(bucket.lock().unwrap().contains_key("recipe/1/abc.png"));
+    assert_eq!(store.get("recipe/1/abc.png").await.unwrap(), media);
+
+    store.delete("recipe/1/abc.png").await.unwrap();
+    assert!(matches!(
+        store.get("recipe/1/abc.png").await.unwrap_err(),
+        domain::media::Error::MediaNotFound(_)
+    ));
+    // Deleting something already gone is not an error.
+    store.delete("recipe/1/abc.png").await.unwrap();
+}
+
+async fn send_import(
+    app: &mut axum::Router,
+    content_type: &str,
+    body: &str,
+) -> (StatusCode, Value) {
+    send_authed_text(app, "/recipe/import", content_type, body).await
+}
+
+async fn send_authed_text(
+    app: &mut axum::Router,
+    uri: &str,
+    content_type: &str,
+    body: &str,
+) -> (StatusCode, Value) {
+    send_authed_bytes(app, uri, content_type, body.as_bytes().to_vec()).await
+}
+
+async fn send_authed_bytes(
+    app: &mut axum::Router,
+    uri: &str,
+    content_type: &str,
+    body: Vec<u8>,
+) -> (StatusCode, Value) {
+    let mut request = get_authed_request_builder(uri, "POST")
+        .body(Body::from(body))
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("Content-Type", content_type.parse().unwrap());
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .unwrap();
+    let status = result.status();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_import_recipe(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let recipe = json!({
+        "@context": "https://schema.org",
+        "@type": "Recipe",
+        "name": "Garlic Butter Pasta",
+        "description": "Quick &amp; easy",
+        "image": ["https://example.com/pasta.jpg"],
+        "prepTime": "PT5M",
+        "cookTime": "PT15M",
+        "recipeYield": "Serves 4",
+        "recipeIngredient": ["200 g spaghetti", "2 tbsp butter, melted", "garlic to taste"],
+        "recipeInstructions": [
+            {"@type": "HowToStep", "text": "Boil the pasta."},
+            {"@type": "HowToStep", "text": "Toss with the butter."}
+        ],
+        "recipeCategory": "Dinner",
+        "aggregateRating": {"ratingValue": "4.8"}
+    });
+    let page = format!(
+        "<!DOCTYPE html><html><head><title>Pasta</title>\
+         <script type=\"application/ld+json\">{}</script></head><body></body></html>",
+        json!({"@graph": [{"@type": "WebSite"}, recipe]})
+    );
+
+    let (status, json) = send_import(&mut app, "text/html; charset=utf-8", &page).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let imported: GetRecipe = serde_json::from_value(json["recipe"].clone()).unwrap();
+    assert_eq!(imported.title, "Garlic Butter Pasta");
+    assert_eq!(imported.description.as_deref(), Some("Quick & easy"));
+    assert_eq!(imported.author.name, "Matt");
+    assert_eq!(imported.prep_time, Some(chrono::Duration::minutes(5)));
+    assert_eq!(imported.cook_time, Some(chrono::Duration::minutes(15)));
+    assert_eq!(imported.yield_quantity, 4.into());
+    assert_eq!(imported.yield_units, "servings");
+    let mut ingredients: Vec<(String, String, String, String)> = imported
+        .ingredients
+        .iter()
+        .map(|x| {
+            (
+                x.quantity.to_string(),
+                x.units.clone(),
+                x.ingredient.clone(),
+                x.preparation.clone(),
+            )
+        })
+        .collect();
+    ingredients.sort();
+    assert_eq!(
+        ingredients,
+        vec![
+            (
+                "1".to_owned(),
+                "each".to_owned(),
+                "garlic to taste".to_owned(),
+                "".to_owned()
+            ),
+            (
+                "2".to_owned(),
+                "tablespoons".to_owned(),
+                "butter".to_owned(),
+                "melted".to_owned()
+            ),
+            (
+                "200".to_owned(),
+                "grams".to_owned(),
+                "spaghetti".to_owned(),
+                "".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(imported.steps.len(), 2);
+    let unmapped: Vec<&str> = json["unmapped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        unmapped,
+        vec!["recipeIngredient[2]", "aggregateRating", "image"]
+    );
+
+    let (_, json) = get_json(&mut app, &format!("/recipe/{}", imported.id)).await;
+    assert_eq!(json["title"], json!("Garlic Butter Pasta"));
+
+    // Raw JSON-LD is accepted too, with or without a JSON content type.
+    for content_type in ["application/ld+json", "text/plain"] {
+        let mut recipe = recipe.clone();
+        recipe["name"] = json!(content_type);
+        let (status, json) = send_import(&mut app, content_type, &recipe.to_string()).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", content_type);
+        assert_eq!(json["recipe"]["title"], json!(content_type));
+    }
+
+    for (content_type, body) in [
+        ("text/html", "<html><body>No recipe here</body></html>"),
+        ("application/json", "{\"@type\": \"Recipe\""),
+        (
+            "application/json",
+            "{\"@type\": \"Person\", \"name\": \"Someone\"}",
+        ),
+    ] {
+        let (status, _) = send_import(&mut app, content_type, body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_parse_ingredient_lines(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let (status, json) = send_authed_json(
+        &mut app,
+        "POST",
+        "/ingredient/parse",
+        Some(json!({
+            "lines": [
+                "2 1/2 cups all-purpose flour, sifted",
+                "1½ tbsp. olive oil",
+                "2-3 lbs potatoes (about 6)",
+                "salt to taste"
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json,
+        json!({
+            "ingredients": [
+                {
+                    "line": "2 1/2 cups all-purpose flour, sifted",
+                    "ingredient": "all-purpose flour",
+                    "quantity": "2 1/2",
+                    "quantity_max": null,
+                    "units": "cups",
+                    "preparation": "sifted",
+                    "warnings": []
+                },
+                {
+                    "line": "1½ tbsp. olive oil",
+                    "ingredient": "olive oil",
+                    "quantity": "1 1/2",
+                    "quantity_max": null,
+                    "units": "tablespoons",
+                    "preparation": "",
+                    "warnings": []
+                },
+                {
+                    "line": "2-3 lbs potatoes (about 6)",
+                    "ingredient": "potatoes",
+                    "quantity": "2",
+                    "quantity_max": "3",
+                    "units": "pounds",
+                    "preparation": "about 6",
+                    "warnings": []
+                },
+                {
+                    "line": "salt to taste",
+                    "ingredient": "salt to taste",
+                    "quantity": "1",
+                    "quantity_max": null,
+                    "units": "each",
+                    "preparation": "",
+                    "warnings": ["no quantity in `salt to taste`, read as 1 each"]
+                }
+            ]
+        })
+    );
+
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/ingredient/parse",
+        Some(json!({ "lines": vec!["1 egg"; 201] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+async fn get_with_accept(
+    app: &mut axum::Router,
+    uri: &str,
+    accept: &str,
+) -> (StatusCode, String, String) {
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(
+            Request::builder()
+                .uri(uri)
+                .header("Accept", accept)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = result.status();
+    let content_type = result
+        .headers()
+        .get("content-type")
+        .map(|x| x.to_str().unwrap().to_owned())
+        .unwrap_or_default();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_export_recipe(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let mut request = recipe_with_ingredients("Bread & Butter", &["bread", "butter"]);
+    request["prep_time"] = json!(300);
+    request["cook_time"] = json!(5400);
+    let recipe = post_recipe(&mut app, request).await;
+    let uri = format!("/recipe/{}", recipe.id);
+
+    let (status, content_type, body) = get_with_accept(
+        &mut app,
+        &format!("{}?format=jsonld", uri),
+        "application/json",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/ld+json");
+    let json_ld: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json_ld["@context"], json!("https://schema.org"));
+    assert_eq!(json_ld["@type"], json!("Recipe"));
+    assert_eq!(json_ld["name"], json!("Bread & Butter"));
+    assert_eq!(json_ld["prepTime"], json!("PT5M"));
+    assert_eq!(json_ld["cookTime"], json!("PT1H30M"));
+    assert_eq!(json_ld["totalTime"], json!("PT1H35M"));
+    assert_eq!(json_ld["recipeYield"], json!("100 grams"));
+    let mut ingredients: Vec<&str> = json_ld["recipeIngredient"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x.as_str().unwrap())
+        .collect();
+    ingredients.sort();
+    assert_eq!(ingredients, vec!["100 grams bread", "100 grams butter"]);
+    assert_eq!(
+        json_ld["recipeInstructions"],
+        json!([{"@type": "HowToStep", "text": "Combine everything"}])
+    );
+
+    let (status, content_type, body) = get_with_accept(
+        &mut app,
+        &uri,
+        "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/html"));
+    assert!(body.starts_with("<!DOCTYPE html>"));
+    assert!(body.contains("<h1>Bread &amp; Butter</h1>"));
+    assert!(body.contains("<script type=\"application/ld+json\">"));
+
+    // An exported page imports back as a copy of the recipe.
+    let (status, json) = send_import(
+        &mut app,
+        "text/html",
+        &body.replace(
+            "\"name\":\"Bread & Butter\"",
+            "\"name\":\"Bread & Butter Again\"",
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let copy: GetRecipe = serde_json::from_value(json["recipe"].clone()).unwrap();
+    assert_eq!(copy.title, "Bread & Butter Again");
+    let amounts = |recipe: &GetRecipe| -> HashSet<(String, String, String)> {
+        recipe
+            .ingredients
+            .iter()
+            .map(|x| {
+                (
+                    x.ingredient.clone(),
+                    x.quantity.to_string(),
+                    x.units.clone(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(amounts(&copy), amounts(&recipe));
+    assert_eq!(copy.prep_time, recipe.prep_time);
+    assert_eq!(copy.cook_time, recipe.cook_time);
+
+    for accept in [
+        "application/json",
+        "*/*",
+        "text/html;q=0.5, application/json",
+    ] {
+        let (status, content_type, body) = get_with_accept(&mut app, &uri, accept).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(content_type, "application/json", "{}", accept);
+        let json: GetRecipe = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.id, recipe.id);
+    }
+
+    let (status, _, _) = get_with_accept(&mut app, &format!("{}?format=pdf", uri), "*/*").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_export_recipe_markdown(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let mut request = recipe_with_ingredients("Bread & Butter", &["bread"]);
+    request["prep_time"] = json!(300);
+    request["steps"] = json!([
+        {"ordinal": 2, "instruction": "Spread the *butter*"},
+        {"ordinal": 1, "instruction": "Slice the bread"}
+    ]);
+    let recipe = post_recipe(&mut app, request).await;
+    let uri = format!("/recipe/{}", recipe.id);
+
+    for (uri, accept) in [
+        (format!("{}?format=markdown", uri), "application/json"),
+        (uri.clone(), "text/markdown"),
+        (uri.clone(), "text/plain"),
+    ] {
+        let (status, content_type, body) = get_with_accept(&mut app, &uri, accept).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(content_type, "text/markdown; charset=utf-8");
+        assert!(body.starts_with("# Bread & Butter\n\n*By Matt*\n"));
+        assert!(body.contains("**Prep:** 5 min · **Total:** 5 min · **Yield:** 100 grams\n"));
+        assert!(body.contains("## Ingredients\n\n- 100 grams bread\n"));
+        assert!(
+            body.contains("## Instructions\n\n1. Slice the bread\n2. Spread the \\*butter\\*\n")
+        );
+    }
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_cooklang_round_trip(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let recipe = post_recipe(
+        &mut app,
+        json!({
+            "title": "Glazed Buns",
+            "description": "Soft and sweet",
+            "prep_time": 1200,
+            "cook_time": null,
+            "inactive_time": null,
+            "yield_quantity": 12,
+            "yield_units": "each",
+            "ingredients": [
+                {"ingredient": "flour", "quantity": "2 1/2", "units": "cups", "preparation": "sifted"},
+                {"ingredient": "eggs", "quantity": 2, "units": "each", "preparation": ""},
+                {"ingredient": "milk", "quantity": 1, "units": "cups", "preparation": ""}
+            ],
+            "steps": [
+                {"ordinal": 1, "instruction": "Whisk the flour with the eggs."},
+                {"ordinal": 2, "instruction": "Bake for 20 minutes at 180C -- until golden."}
+            ],
+            "groups": [{
+                "name": "Glaze",
+                "ingredients": [
+                    {"ingredient": "sugar", "quantity": "1/2", "units": "cups", "preparation": ""}
+                ],
+                "steps": [{"ordinal": 3, "instruction": "Drizzle the sugar over the buns."}]
+            }]
+        }),
+    )
+    .await;
+
+    let (status, content_type, text) = get_with_accept(
+        &mut app,
+        &format!("/recipe/{}?format=cooklang", recipe.id),
+        "application/json",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/plain"));
+    assert!(text.contains("yield: 12 each\n"));
+    assert!(text.contains("Whisk the @flour{5/2%cups}(sifted) with the @eggs{2}."));
+    assert!(text.contains("\n== Glaze ==\n"));
+    let (_, _, cooklang) =
+        get_with_accept(&mut app, &format!("/recipe/{}", recipe.id), "text/cooklang").await;
+    assert_eq!(cooklang, text);
+
+    let (status, json) = send_authed_text(
+        &mut app,
+        "/recipe/import/cooklang",
+        "text/plain",
+        &text.replace("title: Glazed Buns", "title: Glazed Buns Again"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["unmapped"], json!([]));
+    let copy: GetRecipe = serde_json::from_value(json["recipe"].clone()).unwrap();
+    assert_eq!(copy.title, "Glazed Buns Again");
+    assert_eq!(copy.description, recipe.description);
+    assert_eq!(copy.prep_time, recipe.prep_time);
+    assert_eq!(copy.yield_quantity, recipe.yield_quantity);
+    assert_eq!(copy.yield_units, recipe.yield_units);
+    let amounts =
+        |recipe: &GetRecipe| -> HashSet<(Option<String>, String, String, String, String)> {
+            let ungrouped = recipe.ingredients.iter().map(|x| (None, x));
+            let grouped = recipe
+                .groups
+                .iter()
+                .flat_map(|g| g.ingredients.iter().map(move |x| (Some(g.name.clone()), x)));
+            ungrouped
+                .chain(grouped)
+                .map(|(group, x)| {
+                    (
+                        group,
+                        x.ingredient.clone(),
+                        x.quantity.to_string(),
+                        x.units.clone(),
+                        x.preparation.clone(),
+                    )
+                })
+                .collect()
+        };
+    assert_eq!(amounts(&copy), amounts(&recipe));
+    let steps = |recipe: &GetRecipe| -> HashSet<(Option<String>, i32, String)> {
+        let ungrouped = recipe.steps.iter().map(|x| (None, x));
+        let grouped = recipe
+            .groups
+            .iter()
+            .flat_map(|g| g.steps.iter().map(move |x| (Some(g.name.clone()), x)));
+        ungrouped
+            .chain(grouped)
+            .map(|(group, x)| (group, x.ordinal, x.instruction.clone()))
+            .collect()
+    };
+    assert_eq!(steps(&copy), steps(&recipe));
+
+    let (status, json) = send_authed_text(
+        &mut app,
+        "/recipe/import/cooklang?title=Plain%20Water",
+        "text/plain",
+        "Boil @water{2%cups}.",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["recipe"]["title"], json!("Plain Water"));
+
+    let (status, _) = send_authed_text(
+        &mut app,
+        "/recipe/import/cooklang",
+        "text/plain",
+        "Boil @water{2%cups}.",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+async fn get_authed_bytes(app: &mut axum::Router, uri: &str) -> (StatusCode, String, Vec<u8>) {
+    let request = get_authed_request_builder(uri, "GET")
+        .body(Body::empty())
+        .unwrap();
+    let result = app
+        .as_service()
+        .ready()
+        .await
+        .unwrap()
+        .call(request)
+        .await
+        .unwrap();
+    let status = result.status();
+    let content_type = result
+        .headers()
+        .get("content-type")
+        .map(|x| x.to_str().unwrap().to_owned())
+        .unwrap_or_default();
+    let body = body::to_bytes(result.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_library_archive(pool: PgPool) {
+    let media_dir = tempfile::tempdir().unwrap();
+    let mut app = create_app_with_media_store(
+        pool,
+        Box::new(storage::LocalMediaStore::new(media_dir.path())),
+    )
+    .router();
+
+    let mut stock = recipe_with_ingredients("Vegetable Stock", &["onions", "water"]);
+    stock["yield_units"] = json!("liters");
+    let stock = post_recipe(&mut app, stock).await;
+    let mut soup = recipe_with_ingredients("Onion Soup", &["onions", "stock"]);
+    soup["ingredients"][1]["units"] = json!("milliliters");
+    soup["ingredients"][1]["sub_recipe_id"] = json!(stock.id);
+    let soup = post_recipe(&mut app, soup).await;
+    let image = png(64, 48);
+    let (status, _) = send_image(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}/image", soup.id),
+        "image/png",
+        &image,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, content_type, archive) = get_authed_bytes(&mut app, "/recipe/archive").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/zip");
+    let parsed = domain::archive::Archive::from_bytes(&archive).unwrap();
+    assert_eq!(parsed.manifest.version, domain::archive::ARCHIVE_VERSION);
+    assert_eq!(parsed.manifest.recipes.len(), 2);
+    assert_eq!(parsed.media.len(), 1);
+
+    let import = |on_conflict: &str| format!("/recipe/archive?on_conflict={}", on_conflict);
+    let outcomes = |json: &Value| -> Vec<(String, String)> {
+        json["recipes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| {
+                (
+                    x["title"].as_str().unwrap().to_owned(),
+                    x["outcome"].as_str().unwrap().to_owned(),
+                )
+            })
+            .collect()
+    };
+
+    let (status, json) = send_authed_bytes(
+        &mut app,
+        &import("skip"),
+        "application/zip",
+        archive.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        outcomes(&json),
+        vec![
+            ("Vegetable Stock".to_owned(), "skipped".to_owned()),
+            ("Onion Soup".to_owned(), "skipped".to_owned()),
+        ]
+    );
+
+    // Renamed copies point at each other rather than at the originals.
+    let (status, json) = send_authed_bytes(
+        &mut app,
+        &import("rename"),
+        "application/zip",
+        archive.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        outcomes(&json),
+        vec![
+            ("Vegetable Stock".to_owned(), "renamed".to_owned()),
+            ("Onion Soup".to_owned(), "renamed".to_owned()),
+        ]
+    );
+    assert_eq!(json["recipes"][1]["new_title"], json!("Onion Soup (2)"));
+    let stock_copy = json["recipes"][0]["recipe_id"].as_i64().unwrap();
+    let soup_copy = json["recipes"][1]["recipe_id"].as_i64().unwrap();
+    let (_, copy) = get_json(&mut app, &format!("/recipe/{}", soup_copy)).await;
+    assert_eq!(copy["title"], json!("Onion Soup (2)"));
+    let sub_recipe = copy["ingredients"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|x| x["ingredient"] == json!("stock"))
+        .unwrap();
+    assert_eq!(sub_recipe["sub_recipe_id"], json!(stock_copy));
+    assert_eq!(sub_recipe["units"], json!("milliliters"));
+    let url = copy["image"]["url"].as_str().unwrap().to_owned();
+    assert_eq!(
+        get_media(&mut app, &url).await,
+        (StatusCode::OK, "image/png".to_owned(), image)
+    );
+
+    let (status, json) =
+        send_authed_bytes(&mut app, &import("overwrite"), "application/zip", archive).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        outcomes(&json),
+        vec![
+            ("Vegetable Stock".to_owned(), "overwritten".to_owned()),
+            ("Onion Soup".to_owned(), "overwritten".to_owned()),
+        ]
+    );
+    assert_eq!(json["recipes"][0]["recipe_id"], json!(stock.id));
+    assert_eq!(json["recipes"][1]["recipe_id"], json!(soup.id));
+
+    let (status, _) = send_authed_bytes(
+        &mut app,
+        &import("skip"),
+        "application/zip",
+        b"not a zip".to_vec(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_shopping_list(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+
+    let mut pancakes = recipe_with_ingredients("Pancakes", &["flour", "milk"]);
+    pancakes["yield_quantity"] = json!(4);
+    pancakes["yield_units"] = json!("servings");
+    pancakes["ingredients"][1]["quantity"] = json!("1/2");
+    pancakes["ingredients"][1]["units"] = json!("liters");
+    post_recipe(&mut app, pancakes).await;
+    let mut bread = recipe_with_ingredients("Bread", &["flour", "milk"]);
+    bread["ingredients"][0]["quantity"] = json!(1);
+    bread["ingredients"][0]["units"] = json!("kilograms");
+    let bread = post_recipe(&mut app, bread).await;
+    let (_, pancakes) = get_json(&mut app, "/recipe?title=Pancakes").await;
+    let pancakes_id = pancakes["recipes"][0]["id"].clone();
+
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        "/shopping-list",
+        Some(json!({"name": "Weekly shop"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(list["items"], json!([]));
+    let uri = format!("/shopping-list/{}", list["id"]);
+
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/recipe", uri),
+        Some(json!({"recipe_id": pancakes_id, "yield": 8})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/recipe", uri),
+        Some(json!({"recipe_id": bread.id})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let items = |list: &Value| -> Vec<(String, Value, Value)> {
+        list["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| {
+                (
+                    x["name"].as_str().unwrap().to_owned(),
+                    x["quantity"].clone(),
+                    x["units"].clone(),
+                )
+            })
+            .collect()
+    };
+    // Flour is summed into grams, while milk by weight can't join milk by
+    // volume.
+    assert_eq!(
+        items(&list),
+        vec![
+            ("flour".to_owned(), json!("1200"), json!("grams")),
+            ("milk".to_owned(), json!("1"), json!("liters")),
+            ("milk".to_owned(), json!("100"), json!("grams")),
+        ]
+    );
+    assert_eq!(
+        list["recipes"],
+        json!([
+            {"recipe_id": pancakes_id, "title": "Pancakes", "scale": "2"},
+            {"recipe_id": bread.id, "title": "Bread", "scale": "1"},
+        ])
+    );
+
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/item", uri),
+        Some(json!({"name": " paper towels "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        items(&list)[3],
+        ("paper towels".to_owned(), Value::Null, Value::Null)
+    );
+    let ids: Vec<i64> = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["id"].as_i64().unwrap())
+        .collect();
+
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/item/{}", uri, ids[0]),
+        Some(json!({"checked": true})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["items"][0]["checked"], json!(true));
+
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/order", uri),
+        Some(json!({"item_ids": [ids[3], ids[0], ids[2], ids[1]]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let order: Vec<(i64, i64)> = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["id"].as_i64().unwrap(), x["ordinal"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(
+        order,
+        vec![(ids[3], 1), (ids[0], 2), (ids[2], 3), (ids[1], 4)]
+    );
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/order", uri),
+        Some(json!({"item_ids": [ids[3]]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, list) = send_authed_json(
+        &mut app,
+        "DELETE",
+        &format!("{}/item/{}", uri, ids[2]),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["items"].as_array().unwrap().len(), 3);
+
+    let (status, lists) = send_authed_json(&mut app, "GET", "/shopping-list", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(lists.as_array().unwrap().len(), 1);
+
+    // Lists are private to their owner.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        INSERT INTO shopping_list (owner, name) SELECT id, 'Theirs' FROM u RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (status, _) =
+        send_authed_json(&mut app, "GET", &format!("/shopping-list/{}", other), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_shopping_list_concurrent_changes(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let (_, list) = send_authed_json(
+        &mut app,
+        "POST",
+        "/shopping-list",
+        Some(json!({"name": "Weekly shop"})),
+    )
+    .await;
+    let uri = format!("/shopping-list/{}", list["id"]);
+
+    let names = [
+        "eggs", "milk", "bread", "butter", "jam", "tea", "rice", "oats",
+    ];
+    let requests: Vec<_> = names
+        .iter()
+        .map(|name| {
+            let mut app = app.clone();
+            let uri = format!("{}/item", uri);
+            let body = json!({ "name": name });
+            tokio::spawn(async move { send_authed_json(&mut app, "POST", &uri, Some(body)).await })
+        })
+        .collect();
+    for request in requests {
+        let (status, _) = request.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (_, list) = send_authed_json(&mut app, "GET", &uri, None).await;
+    let mut added: Vec<&str> = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["name"].as_str().unwrap())
+        .collect();
+    added.sort();
+    let mut expected = names.to_vec();
+    expected.sort();
+    assert_eq!(added, expected);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_meal_plan(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+
+    let mut soup = recipe_with_ingredients("Soup", &["onions"]);
+    soup["yield_quantity"] = json!(4);
+    soup["yield_units"] = json!("servings");
+    let soup = post_recipe(&mut app, soup).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+
+    let mut ids = Vec::new();
+    for (date, slot, recipe_id, servings) in [
+        ("2026-10-20", "lunch", soup.id, 4),
+        ("2026-10-19", "dinner", soup.id, 2),
+        ("2026-10-20", "breakfast", bread.id, 1),
+        ("2026-10-26", "dinner", soup.id, 8),
+    ] {
+        let (status, entry) = send_authed_json(
+            &mut app,
+            "POST",
+            "/meal-plan",
+            Some(json!({
+                "date": date,
+                "slot": slot,
+                "recipe_id": recipe_id,
+                "servings": servings,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(entry["id"].as_i64().unwrap());
+    }
+    let (status, entry) =
+        send_authed_json(&mut app, "GET", &format!("/meal-plan/{}", ids[1]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        entry,
+        json!({
+            "id": ids[1],
+            "date": "2026-10-19",
+            "slot": "dinner",
+            "recipe_id": soup.id,
+            "recipe_title": "Soup",
+            "servings": "2",
+        })
+    );
+
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/meal-plan",
+        Some(json!({"date": "2026-10-19", "slot": "lunch", "recipe_id": soup.id, "servings": 0})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/meal-plan",
+        Some(json!({"date": "2026-10-19", "slot": "lunch", "recipe_id": 9999, "servings": 1})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, entry) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/meal-plan/{}", ids[0]),
+        Some(json!({"date": "2026-10-20", "slot": "lunch", "recipe_id": soup.id, "servings": 6})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry["servings"], json!("6"));
+
+    // Entries come back in calendar order, leaving out those outside the
+    // range.
+    let (status, plan) = send_authed_json(
+        &mut app,
+        "GET",
+        "/meal-plan?from=2026-10-19&to=2026-10-25",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let plan: Vec<i64> = plan
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(plan, vec![ids[1], ids[2], ids[0]]);
+    let (status, _) = send_authed_json(
+        &mut app,
+        "GET",
+        "/meal-plan?from=2026-10-25&to=2026-10-19",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, content_type, ics) = get_authed_bytes(
+        &mut app,
+        "/meal-plan/calendar.ics?from=2026-10-19&to=2026-10-25",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/calendar; charset=utf-8");
+    let ics = String::from_utf8(ics).unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+    assert!(ics.contains(
+        "DTSTART:20261019T180000\r\nDURATION:PT1H\r\nSUMMARY:Dinner: Soup (2 servings)\r\n"
+    ));
+
+    // Soup is planned for 8 servings, twice its yield, while bread doesn't
+    // yield servings and is made once.
+    let (status, list) = send_authed_json(
+        &mut app,
+        "POST",
+        "/meal-plan/shopping-list",
+        Some(json!({"from": "2026-10-19", "to": "2026-10-25"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(list["name"], json!("Meal plan 2026-10-19 to 2026-10-25"));
+    let items: Vec<(Value, Value, Value)> = list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["name"].clone(), x["quantity"].clone(), x["units"].clone()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (json!("onions"), json!("200"), json!("grams")),
+            (json!("flour"), json!("100"), json!("grams")),
+        ]
+    );
+    let (status, lists) = send_authed_json(&mut app, "GET", "/shopping-list", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(lists.as_array().unwrap().len(), 1);
+
+    // Entries are private to their owner.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        INSERT INTO meal_plan_entry (owner, date, slot, recipe, servings)
+        SELECT id, '2026-10-19', 'lunch', $1, ROW(1, 1)::t_quantity FROM u RETURNING id
+        "#,
+    )
+    .bind(soup.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (status, _) =
+        send_authed_json(&mut app, "GET", &format!("/meal-plan/{}", other), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, plan) = send_authed_json(
+        &mut app,
+        "GET",
+        "/meal-plan?from=2026-10-19&to=2026-10-19",
+        None,
+    )
+    .await;
+    assert_eq!(plan.as_array().unwrap().len(), 1);
+
+    let uri = format!("/meal-plan/{}", ids[1]);
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_pantry(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let today = chrono::Utc::now().date_naive();
+    let day = |days: i64| (today + chrono::Duration::days(days)).to_string();
+
+    let mut ids = Vec::new();
+    for (name, quantity, units, location, expires_on) in [
+        ("flour", json!(1), "kilograms", "Cupboard", Value::Null),
+        ("flour", json!(200), "grams", "Cupboard", json!(day(2))),
+        ("milk", json!("1/2"), "liters", "Fridge", json!(day(-1))),
+        ("eggs", json!(6), "each", "Fridge", json!(day(30))),
+    ] {
+        let (status, item) = send_authed_json(
+            &mut app,
+            "POST",
+            "/pantry",
+            Some(json!({
+                "name": name,
+                "quantity": quantity,
+                "units": units,
+                "location": location,
+                "expires_on": expires_on,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(item["id"].as_i64().unwrap());
+    }
+    let (status, item) =
+        send_authed_json(&mut app, "GET", &format!("/pantry/{}", ids[2]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(item["name"], json!("milk"));
+    assert_eq!(item["quantity"], json!("1/2"));
+    assert_eq!(item["units"], json!("liters"));
+    assert_eq!(item["expires_on"], json!(day(-1)));
+
+    for request in [
+        json!({"name": "salt", "quantity": 1, "units": "handfuls"}),
+        json!({"name": " ", "quantity": 1, "units": "grams"}),
+    ] {
+        let (status, _) = send_authed_json(&mut app, "POST", "/pantry", Some(request)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let item_ids = |items: &Value| -> Vec<i64> {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["id"].as_i64().unwrap())
+            .collect()
+    };
+    let (status, items) = send_authed_json(&mut app, "GET", "/pantry", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(item_ids(&items), vec![ids[1], ids[0], ids[3], ids[2]]);
+    let (_, items) = send_authed_json(&mut app, "GET", "/pantry?location=Fridge", None).await;
+    assert_eq!(item_ids(&items), vec![ids[3], ids[2]]);
+
+    // Expired items count as expiring, soonest first.
+    let (status, items) = send_authed_json(&mut app, "GET", "/pantry/expiring", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(item_ids(&items), vec![ids[2], ids[1]]);
+    let (_, items) = send_authed_json(&mut app, "GET", "/pantry/expiring?days=30", None).await;
+    assert_eq!(item_ids(&items), vec![ids[2], ids[1], ids[3]]);
+    let (status, _) = send_authed_json(&mut app, "GET", "/pantry/expiring?days=-1", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let adjust = format!("/pantry/{}/adjust", ids[0]);
+    let (status, item) = send_authed_json(
+        &mut app,
+        "POST",
+        &adjust,
+        Some(json!({"add": 500, "units": "grams"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(item["quantity"], json!("1 1/2"));
+    for request in [
+        json!({"remove": 2}),
+        json!({"add": 1, "remove": 1}),
+        json!({"add": 1, "units": "liters"}),
+    ] {
+        let (status, _) = send_authed_json(&mut app, "POST", &adjust, Some(request)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let mut pancakes = recipe_with_ingredients("Pancakes", &["flour", "milk", "eggs"]);
+    pancakes["yield_quantity"] = json!(4);
+    pancakes["yield_units"] = json!("servings");
+    pancakes["ingredients"][0]["quantity"] = json!(250);
+    pancakes["ingredients"][1]["quantity"] = json!("1/2");
+    pancakes["ingredients"][1]["units"] = json!("liters");
+    pancakes["ingredients"][2]["quantity"] = json!(1);
+    pancakes["ingredients"][2]["units"] = json!("each");
+    let pancakes = post_recipe(&mut app, pancakes).await;
+
+    // A dry run reports what cooking would take without touching stock.
+    let cook = json!({"recipe_id": pancakes.id, "yield": 8, "dry_run": true});
+    let (status, report) = send_authed_json(&mut app, "POST", "/pantry/cook", Some(cook)).await;
+    assert_eq!(status, StatusCode::OK);
+    let amounts = |items: &Value| -> Vec<(Value, Value, Value)> {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| (x["name"].clone(), x["quantity"].clone(), x["units"].clone()))
+            .collect()
+    };
+    assert_eq!(report["scale"], json!("2"));
+    assert_eq!(
+        amounts(&report["used"]),
+        vec![
+            (json!("milk"), json!("0"), json!("liters")),
+            (json!("flour"), json!("0"), json!("grams")),
+            (json!("eggs"), json!("4"), json!("each")),
+            (json!("flour"), json!("1.2"), json!("kilograms")),
+        ]
+    );
+    assert_eq!(
+        amounts(&report["shortfalls"]),
+        vec![(json!("milk"), json!("1/2"), json!("liters"))]
+    );
+    let (_, item) = send_authed_json(&mut app, "GET", &format!("/pantry/{}", ids[0]), None).await;
+    assert_eq!(item["quantity"], json!("1 1/2"));
+
+    let cook = json!({"recipe_id": pancakes.id, "yield": 8});
+    let (status, report) = send_authed_json(&mut app, "POST", "/pantry/cook", Some(cook)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["used"].as_array().unwrap().len(), 4);
+    let (_, items) = send_authed_json(&mut app, "GET", "/pantry", None).await;
+    assert_eq!(
+        amounts(&items),
+        vec![
+            (json!("flour"), json!("0"), json!("grams")),
+            (json!("flour"), json!("1.2"), json!("kilograms")),
+            (json!("eggs"), json!("4"), json!("each")),
+            (json!("milk"), json!("0"), json!("liters")),
+        ]
+    );
+    // Items that ran out no longer show as expiring.
+    let (_, items) = send_authed_json(&mut app, "GET", "/pantry/expiring", None).await;
+    assert_eq!(items, json!([]));
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/pantry/cook",
+        Some(json!({"recipe_id": 9999})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Pantries are private to their owner.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        INSERT INTO pantry_item (owner, ingredient, quantity, units)
+        SELECT u.id, i.id, ROW(1, 1)::t_quantity, un.id
+        FROM u, ingredient i, unit un
+        WHERE i.name = 'flour' AND un.name = 'grams'
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (status, _) = send_authed_json(&mut app, "GET", &format!("/pantry/{}", other), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, items) = send_authed_json(&mut app, "GET", "/pantry", None).await;
+    assert_eq!(items.as_array().unwrap().len(), 4);
+
+    let uri = format!("/pantry/{}", ids[3]);
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_cook_log(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+
+    let (status, stats) = get_json(&mut app, &format!("/recipe/{}", soup.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        stats["cook_stats"],
+        json!({"times_cooked": 0, "last_cooked": null, "average_rating": null})
+    );
+
+    let mut ids = Vec::new();
+    for (recipe_id, cooked_on, rating) in [
+        (soup.id, "2026-10-01", json!(4)),
+        (soup.id, "2026-10-12", json!(5)),
+        (bread.id, "2026-10-05", Value::Null),
+        (soup.id, "2026-09-20", Value::Null),
+    ] {
+        let (status, entry) = send_authed_json(
+            &mut app,
+            "POST",
+            "/cook-log",
+            Some(json!({
+                "recipe_id": recipe_id,
+                "cooked_on": cooked_on,
+                "servings": 2,
+                "notes": " Lovely ",
+                "rating": rating,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(entry["id"].as_i64().unwrap());
+    }
+    let (status, entry) = get_json(&mut app, &format!("/cook-log/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry["recipe_title"], json!("Soup"));
+    assert_eq!(entry["cook"]["name"], json!("Matt"));
+    assert_eq!(entry["cooked_on"], json!("2026-10-01"));
+    assert_eq!(entry["servings"], json!("2"));
+    assert_eq!(entry["notes"], json!("Lovely"));
+    assert_eq!(entry["modifications"], json!(""));
+    assert_eq!(entry["rating"], json!(4));
+
+    for (request, expected) in [
+        (
+            json!({"recipe_id": soup.id, "cooked_on": "2026-10-01", "servings": 2, "rating": 6}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"recipe_id": soup.id, "cooked_on": "2026-10-01", "servings": 0}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"recipe_id": 9999, "cooked_on": "2026-10-01", "servings": 2}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let (status, _) = send_authed_json(&mut app, "POST", "/cook-log", Some(request)).await;
+        assert_eq!(status, expected);
+    }
+
+    let (_, recipe) = get_json(&mut app, &format!("/recipe/{}", soup.id)).await;
+    assert_eq!(
+        recipe["cook_stats"],
+        json!({"times_cooked": 3, "last_cooked": "2026-10-12", "average_rating": 4.5})
+    );
+
+    let entry_ids = |entries: &Value| -> Vec<i64> {
+        entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["id"].as_i64().unwrap())
+            .collect()
+    };
+    let (status, history) = get_json(&mut app, &format!("/recipe/{}/cook-log", soup.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry_ids(&history), vec![ids[1], ids[0], ids[3]]);
+    let (status, _) = get_json(&mut app, "/recipe/9999/cook-log").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let user_id = entry["cook"]["id"].as_i64().unwrap();
+    let (status, history) = get_json(&mut app, &format!("/user/{}/cook-log", user_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry_ids(&history), vec![ids[1], ids[2], ids[0], ids[3]]);
+
+    let uri = format!("/cook-log/{}", ids[3]);
+    let (status, entry) = send_authed_json(
+        &mut app,
+        "POST",
+        &uri,
+        Some(json!({
+            "recipe_id": soup.id,
+            "cooked_on": "2026-09-20",
+            "servings": "1 1/2",
+            "modifications": "Added chilli",
+            "rating": 2,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry["servings"], json!("1 1/2"));
+    assert_eq!(entry["modifications"], json!("Added chilli"));
+    let (_, recipe) = get_json(&mut app, &format!("/recipe/{}", soup.id)).await;
+    assert_eq!(recipe["cook_stats"]["average_rating"], json!(3.67));
+
+    // Only the cook can change their entries.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        INSERT INTO cook_log (owner, recipe, cooked_on, servings)
+        SELECT u.id, $1, '2026-10-15', ROW(1, 1)::t_quantity
+        FROM u
+        RETURNING id
+        "#,
+    )
+    .bind(soup.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let other_uri = format!("/cook-log/{}", other);
+    let (status, _) = send_authed_json(&mut app, "DELETE", &other_uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, history) = get_json(&mut app, &format!("/recipe/{}/cook-log", soup.id)).await;
+    assert_eq!(history[0]["cook"]["name"], json!("Other"));
+
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get_json(&mut app, &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_cook_log_hides_private_recipes(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let mut soup = recipe_with_ingredients("Secret Soup", &["leek"]);
+    soup["visibility"] = json!("private");
+    let soup = post_recipe(&mut app, soup).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+    let mut ids = Vec::new();
+    for recipe_id in [soup.id, bread.id] {
+        let (status, entry) = send_authed_json(
+            &mut app,
+            "POST",
+            "/cook-log",
+            Some(json!({"recipe_id": recipe_id, "cooked_on": "2026-10-01", "servings": 2})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(entry["id"].as_i64().unwrap());
+    }
+    let user_id = soup.author.id;
+    let titles = |entries: &Value| -> Vec<String> {
+        let mut titles: Vec<String> = entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["recipe_title"].as_str().unwrap().to_owned())
+            .collect();
+        titles.sort();
+        titles
+    };
+    let soup_history = format!("/recipe/{}/cook-log", soup.id);
+    let user_history = format!("/user/{}/cook-log", user_id);
+    let soup_entry = format!("/cook-log/{}", ids[0]);
+
+    let (status, history) = send_authed_json(&mut app, "GET", &user_history, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&history), vec!["Bread", "Secret Soup"]);
+    let (status, _) = send_authed_json(&mut app, "GET", &soup_entry, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = get_json(&mut app, &soup_history).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get_json(&mut app, &soup_entry).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, history) = get_json(&mut app, &user_history).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&history), vec!["Bread"]);
+
+    // Having cooked a private recipe gives no access once it is someone else's.
+    sqlx::query(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id FROM u WHERE recipe.id = $1
+        "#,
+    )
+    .bind(soup.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    for uri in [&soup_history, &soup_entry] {
+        let (status, _) = send_authed_json(&mut app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+    }
+    let (_, history) = send_authed_json(&mut app, "GET", &user_history, None).await;
+    assert_eq!(titles(&history), vec!["Bread"]);
+
+    // Unlisted recipes stay out of a user's history, but not the recipe's.
+    sqlx::query("UPDATE recipe SET visibility = 'unlisted' WHERE id = $1")
+        .bind(bread.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, history) = get_json(&mut app, &user_history).await;
+    assert_eq!(history, json!([]));
+    let (_, history) = get_json(&mut app, &format!("/recipe/{}/cook-log", bread.id)).await;
+    assert_eq!(titles(&history), vec!["Bread"]);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_reviews(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+    let stew = post_recipe(&mut app, recipe_with_ingredients("Stew", &["beef"])).await;
+
+    // Soup is someone else's, so Matt may review it.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id FROM u WHERE recipe.id = $1
+        RETURNING u.id
+        "#,
+    )
+    .bind(soup.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let reviews_uri = format!("/recipe/{}/reviews", soup.id);
+    let (status, review) = send_authed_json(
+        &mut app,
+        "POST",
+        &reviews_uri,
+        Some(json!({"rating": 3, "body": " Needs salt. "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(review["recipe_id"], json!(soup.id));
+    assert_eq!(review["reviewer"]["name"], json!("Matt"));
+    assert_eq!(review["rating"], json!(3));
+    assert_eq!(review["body"], json!("Needs salt."));
+    assert_eq!(review["reply"], Value::Null);
+    let uri = format!("/review/{}", review["id"]);
+
+    for (uri, request, expected) in [
+        (
+            reviews_uri.clone(),
+            json!({"rating": 4}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            format!("/recipe/{}/reviews", bread.id),
+            json!({"rating": 4}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            format!("/recipe/{}/reviews", stew.id),
+            json!({"rating": 0}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/recipe/9999/reviews".to_owned(),
+            json!({"rating": 4}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let (status, _) = send_authed_json(&mut app, "POST", &uri, Some(request)).await;
+        assert_eq!(status, expected);
+    }
+
+    let (status, review) =
+        send_authed_json(&mut app, "POST", &uri, Some(json!({"rating": 4}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(review["rating"], json!(4));
+    assert_eq!(review["body"], json!(""));
+    // Only the author of the recipe can reply.
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/reply", uri),
+        Some(json!({"reply": "Thanks!"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Others review Matt's bread, and Matt replies.
+    let bread_reviews: Vec<i32> = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Third') RETURNING id)
+        INSERT INTO review (recipe, reviewer, rating, body)
+        VALUES ($1, $2, 5, 'Perfect crust'), ($1, (SELECT id FROM u), 2, 'Too dense')
+        RETURNING id
+        "#,
+    )
+    .bind(bread.id)
+    .bind(other)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let bread_review = format!("/review/{}", bread_reviews[1]);
+    let reply = format!("{}/reply", bread_review);
+    let (status, review) = send_authed_json(
+        &mut app,
+        "POST",
+        &reply,
+        Some(json!({"reply": " Try a longer proof. "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(review["reply"], json!("Try a longer proof."));
+    assert!(review["replied_at"].is_string());
+    let (status, _) =
+        send_authed_json(&mut app, "POST", &bread_review, Some(json!({"rating": 5}))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_authed_json(&mut app, "DELETE", &bread_review, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, reviews) = get_json(&mut app, &format!("/recipe/{}/reviews", bread.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reviews.as_array().unwrap().len(), 2);
+    let (_, recipe) = get_json(&mut app, &format!("/recipe/{}", bread.id)).await;
+    assert_eq!(
+        recipe["review_stats"],
+        json!({"review_count": 2, "average_rating": 3.5})
+    );
+    let (_, recipe) = get_json(&mut app, &format!("/recipe/{}", stew.id)).await;
+    assert_eq!(
+        recipe["review_stats"],
+        json!({"review_count": 0, "average_rating": null})
+    );
+
+    let titles = |page: &Value| -> Vec<String> {
+        page["recipes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["title"].as_str().unwrap().to_owned())
+            .collect()
+    };
+    let (status, page) = get_json(&mut app, "/recipe?sort=rating").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&page), vec!["Soup", "Bread", "Stew"]);
+
+    let (status, _) = send_authed_json(&mut app, "POST", &reply, Some(json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, review) = get_json(&mut app, &bread_review).await;
+    assert_eq!(review["reply"], Value::Null);
+    assert_eq!(review["replied_at"], Value::Null);
+
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get_json(&mut app, &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, page) = get_json(&mut app, "/recipe?sort=rating").await;
+    assert_eq!(titles(&page), vec!["Bread", "Soup", "Stew"]);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_review_race(pool: PgPool) {
+    use port::ReviewRepository;
+
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let matt: i32 = sqlx::query_scalar("SELECT id FROM app_user WHERE name = 'Matt'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let repository = repositories::PostgresReviewRepository::new(pool.clone());
+    let review = domain::review::Review {
+        id: None,
+        recipe_id: soup.id,
+        reviewer: domain::User {
+            id: Some(matt),
+            name: "Matt".to_owned(),
+        },
+        rating: 4,
+        body: String::new(),
+        reply: None,
+        created_at: None,
+        updated_at: None,
+        replied_at: None,
+    };
+    // Both requests passed the service's check before either was saved.
+    repository.create_review(review.clone()).await.unwrap();
+    assert_eq!(
+        repository.create_review(review).await,
+        Err(domain::review::Error::AlreadyReviewed(soup.id))
+    );
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_favorites(pool: PgPool) {
+    let mut app = create_app(pool).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+
+    // Anonymous requests leave the flag out.
+    let (_, recipe) = get_json(&mut app, &format!("/recipe/{}", soup.id)).await;
+    assert!(recipe.get("favorited").is_none());
+    let (_, recipe) =
+        send_authed_json(&mut app, "GET", &format!("/recipe/{}", soup.id), None).await;
+    assert_eq!(recipe["favorited"], json!(false));
+
+    for id in [soup.id, bread.id, soup.id] {
+        let (status, _) =
+            send_authed_json(&mut app, "POST", &format!("/recipe/{}/favorite", id), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    let (status, _) = send_authed_json(&mut app, "POST", "/recipe/9999/favorite", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, favorites) = send_authed_json(&mut app, "GET", "/favorites", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        favorites,
+        json!([
+            {"recipe_id": bread.id, "title": "Bread"},
+            {"recipe_id": soup.id, "title": "Soup"},
+        ])
+    );
+    let (_, recipe) =
+        send_authed_json(&mut app, "GET", &format!("/recipe/{}", soup.id), None).await;
+    assert_eq!(recipe["favorited"], json!(true));
+
+    let (status, _) = send_authed_json(
+        &mut app,
+        "DELETE",
+        &format!("/recipe/{}/favorite", soup.id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, page) = send_authed_json(&mut app, "GET", "/recipe?sort=title", None).await;
+    let flags: Vec<(Value, Value)> = page["recipes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["title"].clone(), x["favorited"].clone()))
+        .collect();
+    assert_eq!(
+        flags,
+        vec![(json!("Bread"), json!(true)), (json!("Soup"), json!(false))]
+    );
+    let (_, page) = send_authed_json(&mut app, "GET", "/recipe/search?q=bread", None).await;
+    assert_eq!(page["results"][0]["recipe"]["favorited"], json!(true));
+    let (_, page) = send_authed_json(
+        &mut app,
+        "GET",
+        "/recipe/by-ingredients?ingredients=flour",
+        None,
+    )
+    .await;
+    assert_eq!(page["results"][0]["recipe"]["favorited"], json!(true));
+    let (_, page) = get_json(&mut app, "/recipe/search?q=bread").await;
+    assert!(page["results"][0]["recipe"].get("favorited").is_none());
+    let (status, _) = get_json(&mut app, "/favorites").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_collections(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let bread = post_recipe(&mut app, recipe_with_ingredients("Bread", &["flour"])).await;
+    let stew = post_recipe(&mut app, recipe_with_ingredients("Stew", &["beef"])).await;
+
+    let (status, collection) = send_authed_json(
+        &mut app,
+        "POST",
+        "/collection",
+        Some(json!({"name": " Winter "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(collection["name"], json!("Winter"));
+    assert_eq!(collection["shared"], json!(false));
+    assert_eq!(collection["owner"]["name"], json!("Matt"));
+    let uri = format!("/collection/{}", collection["id"]);
+    let (status, _) =
+        send_authed_json(&mut app, "POST", "/collection", Some(json!({"name": " "}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for id in [soup.id, bread.id, stew.id, soup.id] {
+        let (status, _) = send_authed_json(
+            &mut app,
+            "POST",
+            &format!("{}/recipe", uri),
+            Some(json!({"recipe_id": id})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/recipe", uri),
+        Some(json!({"recipe_id": 9999})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let recipe_ids = |collection: &Value| -> Vec<i64> {
+        collection["recipes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["recipe_id"].as_i64().unwrap())
+            .collect()
+    };
+    let (status, collection) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        recipe_ids(&collection),
+        vec![soup.id as i64, bread.id as i64, stew.id as i64]
+    );
+    assert_eq!(collection["recipes"][1]["title"], json!("Bread"));
+
+    let (status, collection) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/order", uri),
+        Some(json!({"recipe_ids": [stew.id, soup.id, bread.id]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        recipe_ids(&collection),
+        vec![stew.id as i64, soup.id as i64, bread.id as i64]
+    );
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/order", uri),
+        Some(json!({"recipe_ids": [stew.id]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, collection) = send_authed_json(
+        &mut app,
+        "DELETE",
+        &format!("{}/recipe/{}", uri, soup.id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        recipe_ids(&collection),
+        vec![stew.id as i64, bread.id as i64]
+    );
+    let (status, _) = send_authed_json(
+        &mut app,
+        "DELETE",
+        &format!("{}/recipe/{}", uri, soup.id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Private collections can't be read by others until they are shared.
+    let (status, _) = get_json(&mut app, &uri).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, collection) = send_authed_json(
+        &mut app,
+        "POST",
+        &uri,
+        Some(json!({"name": "Winter warmers", "shared": true})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(collection["name"], json!("Winter warmers"));
+    assert_eq!(
+        recipe_ids(&collection),
+        vec![stew.id as i64, bread.id as i64]
+    );
+    let (status, collection) = get_json(&mut app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(collection["shared"], json!(true));
+
+    // Shared collections are still read-only to everyone but the owner.
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        INSERT INTO collection (owner, name, shared) SELECT u.id, 'Theirs', true FROM u
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let other_uri = format!("/collection/{}", other);
+    let (status, _) = send_authed_json(&mut app, "GET", &other_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/recipe", other_uri),
+        Some(json!({"recipe_id": soup.id})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_authed_json(&mut app, "DELETE", &other_uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, collections) = send_authed_json(&mut app, "GET", "/collection", None).await;
+    assert_eq!(collections.as_array().unwrap().len(), 1);
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        "/collection",
+        Some(json!({"name": "Autumn"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, winter) = send_authed_json(&mut app, "GET", &uri, None).await;
+    let (_, collections) = send_authed_json(&mut app, "GET", "/collection", None).await;
+    assert_eq!(collections[0]["name"], json!("Autumn"));
+    assert_eq!(collections[0]["recipes"], json!([]));
+    assert_eq!(collections[1], winter);
+
+    let (status, _) = send_authed_json(&mut app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_collections_hide_private_recipes(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(&mut app, recipe_with_ingredients("Soup", &["leek"])).await;
+    let pie = post_recipe(&mut app, recipe_with_ingredients("Pie", &["apple"])).await;
+    let stew = post_recipe(&mut app, recipe_with_ingredients("Stew", &["beef"])).await;
+    let (_, collection) = send_authed_json(
+        &mut app,
+        "POST",
+        "/collection",
+        Some(json!({"name": "Winter", "shared": true})),
+    )
+    .await;
+    let uri = format!("/collection/{}", collection["id"]);
+    for id in [soup.id, pie.id, stew.id] {
+        let (status, _) = send_authed_json(
+            &mut app,
+            "POST",
+            &format!("{}/recipe", uri),
+            Some(json!({"recipe_id": id})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}/favorite", pie.id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let titles = |recipes: &Value| -> Vec<String> {
+        recipes
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["title"].as_str().unwrap().to_owned())
+            .collect()
+    };
+
+    // The owner's own private recipe is only hidden from others.
+    sqlx::query("UPDATE recipe SET visibility = 'private' WHERE id = $1")
+        .bind(soup.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, collection) = get_json(&mut app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&collection["recipes"]), vec!["Pie", "Stew"]);
+    let (_, collection) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(titles(&collection["recipes"]), vec!["Soup", "Pie", "Stew"]);
+
+    // Someone else's draft is hidden from the owner too, but stays in the
+    // collection.
+    sqlx::query(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id, draft = true FROM u WHERE recipe.id = $1
+        "#,
+    )
+    .bind(pie.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (_, collection) = send_authed_json(&mut app, "GET", &uri, None).await;
+    assert_eq!(titles(&collection["recipes"]), vec!["Soup", "Stew"]);
+    let (_, collections) = send_authed_json(&mut app, "GET", "/collection", None).await;
+    assert_eq!(titles(&collections[0]["recipes"]), vec!["Soup", "Stew"]);
+    let (_, favorites) = send_authed_json(&mut app, "GET", "/favorites", None).await;
+    assert_eq!(favorites, json!([]));
+    let (status, collection) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/order", uri),
+        Some(json!({"recipe_ids": [stew.id, soup.id]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&collection["recipes"]), vec!["Stew", "Soup"]);
+    sqlx::query("UPDATE recipe SET draft = false WHERE id = $1")
+        .bind(pie.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, collection) = get_json(&mut app, &uri).await;
+    assert_eq!(titles(&collection["recipes"]), vec!["Stew", "Pie"]);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_fork_recipe(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(
+        &mut app,
+        recipe_with_ingredients("Soup", &["leek", "potato"]),
+    )
+    .await;
+    let other: i32 = sqlx::query_scalar(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id FROM u WHERE recipe.id = $1 RETURNING u.id
+        "#,
+    )
+    .bind(soup.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let (status, fork) =
+        send_authed_json(&mut app, "POST", &format!("/recipe/{}/fork", soup.id), None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_ne!(fork["id"], json!(soup.id));
+    assert_eq!(fork["title"], json!("Soup"));
+    assert_eq!(fork["author"]["name"], json!("Matt"));
+    assert_eq!(fork["forked_from"], json!(soup.id));
+    assert_eq!(
+        fork["lineage"],
+        json!([{"id": soup.id, "title": "Soup", "author": {"id": other, "name": "Other"}}])
+    );
+    let fork: GetRecipe = serde_json::from_value(fork).unwrap();
+    let names = |recipe: &GetRecipe| {
+        let mut names: Vec<String> = recipe
+            .ingredients
+            .iter()
+            .map(|x| x.ingredient.clone())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&fork), vec!["leek", "potato"]);
+    assert_eq!(fork.steps.len(), 1);
+    assert!(fork
+        .steps
+        .iter()
+        .all(|x| !soup.steps.iter().any(|y| y.id == x.id)));
+
+    // The caller already has a "Soup", so a fork of the fork is numbered.
+    let (status, grandchild) =
+        send_authed_json(&mut app, "POST", &format!("/recipe/{}/fork", fork.id), None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(grandchild["title"], json!("Soup (2)"));
+    let lineage: Vec<Value> = grandchild["lineage"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["id"].clone())
+        .collect();
+    assert_eq!(lineage, vec![json!(fork.id), json!(soup.id)]);
+
+    // Removing the original cuts the lineage short but keeps the forks.
+    sqlx::query("DELETE FROM recipe WHERE id = $1")
+        .bind(soup.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, recipe) = get_json(&mut app, &format!("/recipe/{}", fork.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recipe["forked_from"], json!(null));
+    assert_eq!(recipe["lineage"], json!([]));
+    assert_eq!(
+        names(&serde_json::from_value(recipe).unwrap()),
+        vec!["leek", "potato"]
+    );
+
+    let (status, _) = send_authed_json(&mut app, "POST", "/recipe/9999/fork", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_revisions(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let soup = post_recipe(
+        &mut app,
+        recipe_with_ingredients("Soup", &["leek", "potato"]),
+    )
+    .await;
+    let uri = format!("/recipe/{}", soup.id);
+    let leek = soup
+        .ingredients
+        .iter()
+        .find(|x| x.ingredient == "leek")
+        .unwrap();
+    let step = soup.steps.iter().next().unwrap();
+    let update = |title: &str| {
+        json!({
+            "id": soup.id,
+            "title": title,
+            "description": null,
+            "prep_time": null,
+            "cook_time": 1800,
+            "inactive_time": null,
+            "yield_quantity": 100,
+            "yield_units": "grams",
+            "ingredients": [
+                {"id": leek.id, "ingredient": "leek", "quantity": 400, "units": "grams", "preparation": ""},
+                {"ingredient": "cream", "quantity": 100, "units": "grams", "preparation": ""}
+            ],
+            "steps": [
+                {"id": step.id, "ordinal": 1, "instruction": "Combine everything slowly"},
+                {"ordinal": 2, "instruction": "Blend"}
+            ]
+        })
+    };
+    let (status, _) = send_authed_json(&mut app, "POST", &uri, Some(update("Leek Soup"))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, revisions) = get_json(&mut app, &format!("{}/revisions", uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    let summary: Vec<(Value, Value)> = revisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["revision"].clone(), x["title"].clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(json!(2), json!("Leek Soup")), (json!(1), json!("Soup"))]
+    );
+    let (status, revision) = get_json(&mut app, &format!("{}/revisions/1", uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revision["title"], json!("Soup"));
+    assert_eq!(revision["ingredients"].as_array().unwrap().len(), 2);
+
+    let (status, diff) = get_json(&mut app, &format!("{}/revisions/diff?from=1&to=2", uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    let fields: Vec<&Value> = diff["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| &x["field"])
+        .collect();
+    assert_eq!(fields, vec!["title", "cook_time"]);
+    assert_eq!(
+        diff["ingredients"]["added"][0]["ingredient"],
+        json!("cream")
+    );
+    assert_eq!(
+        diff["ingredients"]["removed"][0]["ingredient"],
+        json!("potato")
+    );
+    assert_eq!(
+        diff["ingredients"]["changed"][0]["from"]["quantity"],
+        json!("100")
+    );
+    assert_eq!(
+        diff["ingredients"]["changed"][0]["to"]["quantity"],
+        json!("400")
+    );
+    assert_eq!(diff["steps"]["added"][0]["instruction"], json!("Blend"));
+    assert_eq!(
+        diff["steps"]["changed"][0]["to"]["instruction"],
+        json!("Combine everything slowly")
+    );
+
+    // Reverting saves the old contents as a new revision.
+    let (status, reverted) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("{}/revisions/1/revert", uri),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let reverted: GetRecipe = serde_json::from_value(reverted).unwrap();
+    assert_eq!(reverted.title, "Soup");
+    assert_eq!(reverted.cook_time, None);
+    let mut names: Vec<&str> = reverted
+        .ingredients
+        .iter()
+        .map(|x| x.ingredient.as_str())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["leek", "potato"]);
+    assert_eq!(reverted.steps.len(), 1);
+    assert_eq!(reverted.steps.iter().next().unwrap().id, step.id);
+    let (_, diff) = get_json(&mut app, &format!("{}/revisions/diff?from=1&to=3", uri)).await;
+    assert_eq!(diff["fields"], json!([]));
+    assert_eq!(diff["ingredients"]["changed"], json!([]));
+
+    // Recipes saved before revisions were kept gain one for their contents
+    // before the first update.
+    sqlx::query("DELETE FROM recipe_revision WHERE recipe = $1")
+        .bind(soup.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    send_authed_json(&mut app, "POST", &uri, Some(update("Leek Soup"))).await;
+    let (_, revisions) = get_json(&mut app, &format!("{}/revisions", uri)).await;
+    assert_eq!(revisions[1]["title"], json!("Soup"));
+    assert_eq!(revisions[0]["title"], json!("Leek Soup"));
+
+    let (status, _) = get_json(&mut app, &format!("{}/revisions/9", uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get_json(&mut app, "/recipe/9999/revisions").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("user"))]
+async fn test_recipe_visibility(pool: PgPool) {
+    let mut app = create_app(pool.clone()).router();
+    let mut created = HashMap::new();
+    for (title, visibility, draft) in [
+        ("Private Soup", "private", false),
+        ("Unlisted Stew", "unlisted", false),
+        ("Draft Pie", "public", true),
+        ("Public Salad", "public", false),
+    ] {
+        let mut recipe = recipe_with_ingredients(title, &["leek"]);
+        recipe["visibility"] = json!(visibility);
+        recipe["draft"] = json!(draft);
+        let recipe = post_recipe(&mut app, recipe).await;
+        assert_eq!(
+            serde_json::to_value(recipe.visibility).unwrap(),
+            json!(visibility)
+        );
+        assert_eq!(recipe.draft, draft);
+        created.insert(title, recipe.id);
+    }
+    let titles = |json: &Value, key: &str| {
+        let mut titles: Vec<String> = json[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| {
+                x.get("recipe").unwrap_or(x)["title"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect();
+        titles.sort();
+        titles
+    };
+
+    // Listings only show other people's published public recipes.
+    for uri in [
+        "/recipe",
+        "/recipe/search?q=leek",
+        "/recipe/by-ingredients?ingredients=leek",
+    ] {
+        let key = if uri == "/recipe" {
+            "recipes"
+        } else {
+            "results"
+        };
+        let (status, json) = get_json(&mut app, uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(titles(&json, key), vec!["Public Salad"]);
+        assert_eq!(json["total_count"], json!(1));
+
+        let (status, json) = send_authed_json(&mut app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            titles(&json, key),
+            vec!["Draft Pie", "Private Soup", "Public Salad", "Unlisted Stew"]
+        );
+    }
+
+    // Unlisted recipes can still be read by anyone with the link.
+    for (title, status) in [
+        ("Private Soup", StatusCode::NOT_FOUND),
+        ("Unlisted Stew", StatusCode::OK),
+        ("Draft Pie", StatusCode::NOT_FOUND),
+        ("Public Salad", StatusCode::OK),
+    ] {
+        let uri = format!("/recipe/{}", created[title]);
+        assert_eq!(get_json(&mut app, &uri).await.0, status, "{}", title);
+        let (authed, _) = send_authed_json(&mut app, "GET", &uri, None).await;
+        assert_eq!(authed, StatusCode::OK, "{}", title);
+    }
+
+    // Once the private recipe belongs to someone else, it is gone for us too.
+    let soup = created["Private Soup"];
+    sqlx::query(
+        r#"
+        WITH u AS (INSERT INTO app_user (name) VALUES ('Other') RETURNING id)
+        UPDATE recipe SET author = u.id FROM u WHERE recipe.id = $1
+        "#,
+    )
+    .bind(soup)
+    .execute(&pool)
+    .await
+    .unwrap();
+    for (method, uri) in [
+        ("GET", format!("/recipe/{}", soup)),
+        ("GET", format!("/recipe/{}/scaled?factor=2", soup)),
+        ("GET", format!("/recipe/{}/revisions", soup)),
+        ("POST", format!("/recipe/{}/fork", soup)),
+        ("DELETE", format!("/recipe/{}/image", soup)),
+        ("DELETE", format!("/recipe/{}", soup)),
+    ] {
+        let (status, _) = send_authed_json(&mut app, method, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, uri);
+    }
+    let mut update = recipe_with_ingredients("Mine Now", &["leek"]);
+    update["id"] = json!(soup);
+    let (status, _) =
+        send_authed_json(&mut app, "POST", &format!("/recipe/{}", soup), Some(update)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, json) = send_authed_json(&mut app, "GET", "/recipe", None).await;
+    assert_eq!(json["total_count"], json!(3));
+
+    // Updates that leave the visibility out keep it; publishing the draft
+    // lists it.
+    let pie = created["Draft Pie"];
+    let (_, recipe) = send_authed_json(&mut app, "GET", &format!("/recipe/{}", pie), None).await;
+    let recipe: GetRecipe = serde_json::from_value(recipe).unwrap();
+    let mut update = json!({
+        "id": pie,
+        "title": "Draft Pie",
+        "description": null,
+        "prep_time": null,
+        "cook_time": null,
+        "inactive_time": null,
+        "yield_quantity": 100,
+        "yield_units": "grams",
+        "ingredients": recipe
+            .ingredients
+            .iter()
+            .map(|x| json!({
+                "id": x.id,
+                "ingredient": x.ingredient,
+                "quantity": 100,
+                "units": "grams",
+                "preparation": ""
+            }))
+            .collect::<Vec<_>>(),
+        "steps": recipe
+            .steps
+            .iter()
+            .map(|x| json!({"id": x.id, "ordinal": x.ordinal, "instruction": x.instruction}))
+            .collect::<Vec<_>>()
+    });
+    let uri = format!("/recipe/{}", pie);
+    let (status, json) = send_authed_json(&mut app, "POST", &uri, Some(update.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["draft"], json!(true));
+    update["draft"] = json!(false);
+    let (status, json) = send_authed_json(&mut app, "POST", &uri, Some(update)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["draft"], json!(false));
+    assert_eq!(get_json(&mut app, &uri).await.0, StatusCode::OK);
+    let (_, json) = get_json(&mut app, "/recipe").await;
+    assert_eq!(titles(&json, "recipes"), vec!["Draft Pie", "Public Salad"]);
+
+    // A public fork of a private recipe does not give away its parent.
+    let mut secret = recipe_with_ingredients("Secret Sauce", &["leek"]);
+    secret["visibility"] = json!("private");
+    let secret = post_recipe(&mut app, secret).await;
+    let (status, fork) = send_authed_json(
+        &mut app,
+        "POST",
+        &format!("/recipe/{}/fork", secret.id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(fork["forked_from"], json!(secret.id));
+    let fork_uri = format!("/recipe/{}", fork["id"]);
+    sqlx::query("UPDATE recipe SET visibility = 'public' WHERE id = $1")
+        .bind(fork["id"].as_i64().unwrap() as i32)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, json) = get_json(&mut app, &fork_uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["forked_from"], json!(null));
+    assert_eq!(json["lineage"], json!([]));
+    let (_, json) = get_json(&mut app, "/recipe?title=Secret").await;
+    assert_eq!(json["recipes"][0]["lineage"], json!([]));
+    let (_, json) = send_authed_json(&mut app, "GET", &fork_uri, None).await;
+    assert_eq!(json["lineage"][0]["title"], json!("Secret Sauce"));
+}
@@ -73,10 +73,10 @@ const RecipeForm = ({ recipe }: { recipe?: MutateRecipeRequest }) => {
           <label htmlFor="yieldQuantity">Yield Quantity</label>
           <input
             id="yieldQuantity"
-            type="number"
+            placeholder="e.g. 1 1/2"
             {...register("yieldQuantity", {
               required: true,
-              valueAsNumber: true,
+              setValueAs: setEmptyOrStr,
             })}
           />
         </p>
@@ -113,10 +113,10 @@ const RecipeForm = ({ recipe }: { recipe?: MutateRecipeRequest }) => {
                 </label>
                 <input
                   id={`ingredients.${index}.quantity`}
-                  type="number"
+                  placeholder="e.g. 1 1/2"
                   {...register(`ingredients.${index}.quantity`, {
                     required: true,
-                    valueAsNumber: true,
+                    setValueAs: setEmptyOrStr,
                   })}
                 />
                 <label htmlFor={`ingredients.${index}.units`}>Units</label>
@@ -148,7 +148,7 @@ const RecipeForm = ({ recipe }: { recipe?: MutateRecipeRequest }) => {
           onClick={() =>
             ingredients.append({
               ingredient: "",
-              quantity: NaN,
+              quantity: "",
               units: "",
               preparation: "",
             })
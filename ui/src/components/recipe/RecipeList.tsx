@@ -3,25 +3,40 @@ import { apiClient, GetRecipeResponse } from "../../utils/api";
 import RecipeCard from "./RecipeCard";
 
 const RecipeList = () => {
-  const [data, setData] = useState<Set<GetRecipeResponse>>(new Set());
+  const [data, setData] = useState<Array<GetRecipeResponse>>([]);
+  const [page, setPage] = useState(1);
+  const [nextPage, setNextPage] = useState<number>();
   useEffect(() => {
     const fetchData = async () => {
-      await apiClient.getRecipes().then((response) => {
-        setData(response);
+      await apiClient.getRecipes({ page }).then((response) => {
+        setData(response.recipes);
+        setNextPage(response.nextPage);
       });
     };
     fetchData();
-  }, []);
+  }, [page]);
 
   return (
     <>
       <ul>
-        {[...data].map((recipe) => (
+        {data.map((recipe) => (
           <li>
             <RecipeCard recipe={recipe} headingLevel="h2" />
           </li>
         ))}
       </ul>
+      <nav>
+        {page > 1 && (
+          <button type="button" onClick={() => setPage(page - 1)}>
+            Previous
+          </button>
+        )}
+        {nextPage && (
+          <button type="button" onClick={() => setPage(nextPage)}>
+            Next
+          </button>
+        )}
+      </nav>
     </>
   );
 };
@@ -22,20 +22,45 @@ export interface GetTokenResponse {
   token: string;
 }
 
+// Quantities are exact and may be fractional. Responses render them as
+// cook-friendly strings such as "1 1/2" or "0.1"; requests accept those
+// strings or plain numbers.
 export interface GetRecipeIngredientResponse {
   id: number;
   ingredient: string;
-  quantity: number;
+  quantity: string;
   units: string;
   preparation: string;
+  subRecipeId?: number;
+}
+
+// Image URLs are relative to the API base URL.
+export interface GetImageResponse {
+  url: string;
+  thumbnailUrl: string;
 }
 
 export interface GetStepResponse {
   id: number;
   ordinal: number;
   instruction: string;
+  image?: GetImageResponse | null;
+}
+
+export interface GetRecipeGroupResponse {
+  name: string;
+  ingredients: Array<GetRecipeIngredientResponse>;
+  steps: Array<GetStepResponse>;
+}
+
+export interface GetRecipeAncestorResponse {
+  id: number;
+  title: string;
+  author: GetUserResponse;
 }
 
+export type RecipeVisibility = "private" | "unlisted" | "public";
+
 export interface GetRecipeResponse {
   id: number;
   title: string;
@@ -44,18 +69,92 @@ export interface GetRecipeResponse {
   prepTime?: number;
   cookTime?: number;
   inactiveTime?: number;
-  yieldQuantity: number;
+  yieldQuantity: string;
   yieldUnits: string;
   ingredients: Array<GetRecipeIngredientResponse>;
   steps: Array<GetStepResponse>;
+  groups: Array<GetRecipeGroupResponse>;
+  tags: Array<string>;
+  image?: GetImageResponse | null;
+  cookStats: GetCookStatsResponse;
+  reviewStats: GetReviewStatsResponse;
+  favorited?: boolean;
+  forkedFrom?: number | null;
+  lineage: Array<GetRecipeAncestorResponse>;
+  visibility: RecipeVisibility;
+  draft: boolean;
+}
+
+export interface GetParsedIngredientResponse {
+  line: string;
+  ingredient: string;
+  quantity: string;
+  quantityMax?: string | null;
+  units: string;
+  preparation: string;
+  warnings: Array<string>;
+}
+
+export interface GetParsedIngredientsResponse {
+  ingredients: Array<GetParsedIngredientResponse>;
+}
+
+export interface GetUnmappedFieldResponse {
+  field: string;
+  reason: string;
+}
+
+export interface ImportRecipeResponse {
+  recipe: GetRecipeResponse;
+  unmapped: Array<GetUnmappedFieldResponse>;
+}
+
+export type ImportDocumentType = "html" | "json-ld";
+
+export type RecipeExportFormat = "jsonld" | "html" | "cooklang" | "markdown";
+
+export type ArchiveConflictPolicy = "skip" | "rename" | "overwrite";
+
+export interface GetImportedRecipeResponse {
+  title: string;
+  outcome: "created" | "renamed" | "overwritten" | "skipped" | "failed";
+  newTitle?: string;
+  error?: string;
+  recipeId?: number;
+  warnings: Array<string>;
+}
+
+export interface ImportArchiveResponse {
+  recipes: Array<GetImportedRecipeResponse>;
+}
+
+export interface GetRecipePageResponse {
+  recipes: Array<GetRecipeResponse>;
+  page: number;
+  limit: number;
+  totalCount: number;
+  nextPage?: number;
+}
+
+export interface GetRecipesParams {
+  page?: number;
+  limit?: number;
+  author?: number;
+  title?: string;
+  maxTotalTime?: number;
+  yieldUnits?: string;
+  tags?: string;
+  sort?: "title" | "newest" | "total_time" | "rating";
 }
 
 export interface MutateRecipeIngredientRequest {
   id?: number;
   ingredient: string;
-  quantity: number;
+  quantity: string | number;
   units: string;
   preparation: string;
+  // Uses another recipe as this ingredient, measured in its yield units.
+  subRecipeId?: number;
 }
 
 export interface MutateStepRequest {
@@ -64,6 +163,12 @@ export interface MutateStepRequest {
   instruction: string;
 }
 
+export interface MutateRecipeGroupRequest {
+  name: string;
+  ingredients?: Array<MutateRecipeIngredientRequest>;
+  steps?: Array<MutateStepRequest>;
+}
+
 export interface MutateRecipeRequest {
   id?: number;
   title: string;
@@ -71,10 +176,260 @@ export interface MutateRecipeRequest {
   prepTime?: number;
   cookTime?: number;
   inactiveTime?: number;
-  yieldQuantity: number;
+  yieldQuantity: string | number;
   yieldUnits: string;
   ingredients: Array<MutateRecipeIngredientRequest>;
   steps: Array<MutateStepRequest>;
+  groups?: Array<MutateRecipeGroupRequest>;
+  tags?: Array<string>;
+  visibility?: RecipeVisibility;
+  draft?: boolean;
+}
+
+export interface ScaleRecipeParams {
+  yield?: string | number;
+  factor?: string | number;
+  promoteUnits?: boolean;
+}
+
+export interface GetShoppingListItemResponse {
+  id: number;
+  ingredientId: number;
+  name: string;
+  quantity: string | null;
+  units: string | null;
+  checked: boolean;
+  ordinal: number;
+}
+
+export interface GetShoppingListRecipeResponse {
+  recipeId: number;
+  title: string;
+  scale: string;
+}
+
+export interface GetShoppingListResponse {
+  id: number;
+  name: string;
+  items: Array<GetShoppingListItemResponse>;
+  recipes: Array<GetShoppingListRecipeResponse>;
+}
+
+export interface AddShoppingListRecipeRequest {
+  recipeId: number;
+  yield?: string | number;
+  factor?: string | number;
+}
+
+export interface AddShoppingListItemRequest {
+  name: string;
+  quantity?: string | number;
+  units?: string;
+}
+
+export interface UpdateShoppingListItemRequest {
+  name?: string;
+  quantity?: string | number;
+  units?: string;
+  checked?: boolean;
+}
+
+export type MealSlot = "breakfast" | "lunch" | "dinner" | "snack";
+
+export interface GetMealPlanEntryResponse {
+  id: number;
+  date: string;
+  slot: MealSlot;
+  recipeId: number;
+  recipeTitle: string;
+  servings: string;
+}
+
+export interface MutateMealPlanEntryRequest {
+  date: string;
+  slot: MealSlot;
+  recipeId: number;
+  servings: string | number;
+}
+
+export interface GetPantryItemResponse {
+  id: number;
+  ingredientId: number;
+  name: string;
+  quantity: string;
+  units: string;
+  location: string;
+  expiresOn: string | null;
+}
+
+export interface MutatePantryItemRequest {
+  name: string;
+  quantity: string | number;
+  units: string;
+  location?: string;
+  expiresOn?: string | null;
+}
+
+export interface AdjustPantryItemRequest {
+  add?: string | number;
+  remove?: string | number;
+  units?: string;
+}
+
+export interface CookRecipeRequest {
+  recipeId: number;
+  yield?: string | number;
+  factor?: string | number;
+  dryRun?: boolean;
+}
+
+export interface GetShortfallResponse {
+  name: string;
+  quantity: string;
+  units: string;
+}
+
+export interface GetCookReportResponse {
+  recipeId: number;
+  title: string;
+  scale: string;
+  used: Array<GetPantryItemResponse>;
+  shortfalls: Array<GetShortfallResponse>;
+}
+
+export interface GetCookStatsResponse {
+  timesCooked: number;
+  lastCooked: string | null;
+  averageRating: number | null;
+}
+
+export interface GetCookLogEntryResponse {
+  id: number;
+  cook: GetUserResponse;
+  recipeId: number;
+  recipeTitle: string;
+  cookedOn: string;
+  servings: string;
+  notes: string;
+  modifications: string;
+  rating: number | null;
+}
+
+export interface MutateCookLogEntryRequest {
+  recipeId: number;
+  cookedOn: string;
+  servings: string | number;
+  notes?: string;
+  modifications?: string;
+  rating?: number | null;
+}
+
+export interface GetReviewStatsResponse {
+  reviewCount: number;
+  averageRating: number | null;
+}
+
+export interface GetReviewResponse {
+  id: number;
+  recipeId: number;
+  reviewer: GetUserResponse;
+  rating: number;
+  body: string;
+  reply: string | null;
+  createdAt: string | null;
+  updatedAt: string | null;
+  repliedAt: string | null;
+}
+
+export interface MutateReviewRequest {
+  rating: number;
+  body?: string;
+}
+
+export interface GetCollectionRecipeResponse {
+  recipeId: number;
+  title: string;
+}
+
+export interface GetCollectionResponse {
+  id: number;
+  name: string;
+  owner: GetUserResponse;
+  shared: boolean;
+  recipes: Array<GetCollectionRecipeResponse>;
+}
+
+export interface MutateCollectionRequest {
+  name: string;
+  shared?: boolean;
+}
+
+export interface GetRevisionSummaryResponse {
+  revision: number;
+  createdAt: string;
+  title: string;
+}
+
+export interface GetRevisionIngredientResponse {
+  ingredient: string;
+  quantity: string;
+  units: string;
+  preparation: string;
+  group: string | null;
+  subRecipeId: number | null;
+}
+
+export interface GetRevisionStepResponse {
+  ordinal: number;
+  instruction: string;
+  group: string | null;
+}
+
+export interface GetRecipeRevisionResponse {
+  recipeId: number;
+  revision: number;
+  createdAt: string;
+  title: string;
+  description?: string;
+  prepTime?: number;
+  cookTime?: number;
+  inactiveTime?: number;
+  yieldQuantity: string;
+  yieldUnits: string;
+  ingredients: Array<GetRevisionIngredientResponse>;
+  steps: Array<GetRevisionStepResponse>;
+  groups: Array<{ name: string; ordinal: number }>;
+  tags: Array<string>;
+}
+
+export interface GetFieldChangeResponse {
+  field: string;
+  from: unknown;
+  to: unknown;
+}
+
+export interface GetListDiffResponse<T> {
+  added: Array<T>;
+  removed: Array<T>;
+  changed: Array<{ from: T; to: T }>;
+}
+
+export interface GetRevisionDiffResponse {
+  from: number;
+  to: number;
+  fields: Array<GetFieldChangeResponse>;
+  ingredients: GetListDiffResponse<GetRevisionIngredientResponse>;
+  steps: GetListDiffResponse<GetRevisionStepResponse>;
+}
+
+export type MeasurementSystem = "metric" | "imperial";
+
+export interface GetUnitResponse {
+  id: number;
+  name: string;
+  abbreviation: string | null;
+  dimension: string;
+  system: MeasurementSystem | null;
 }
 
 export interface BasicAuth {
@@ -122,7 +477,11 @@ class ApiClient {
       (config: InternalAxiosRequestConfig): InternalAxiosRequestConfig => {
         const newConfig: InternalAxiosRequestConfig = { ...config };
 
-        if (newConfig.headers["Content-Type"] === "multipart/form-data") {
+        if (
+          newConfig.headers["Content-Type"] === "multipart/form-data" ||
+          typeof config.data === "string" ||
+          config.data instanceof Blob
+        ) {
           return newConfig;
         }
 
@@ -180,15 +539,76 @@ class ApiClient {
     this.client.defaults.headers.common["Authorization"] = "";
   }
 
-  async getRecipes(): Promise<Set<GetRecipeResponse>> {
+  async getRecipes(params?: GetRecipesParams): Promise<GetRecipePageResponse> {
     return this.client
-      .get<Set<GetRecipeResponse>>("/recipe")
+      .get<GetRecipePageResponse>("/recipe", { params })
       .then((response) => response.data);
   }
 
-  async getRecipe(id: number): Promise<GetRecipeResponse> {
+  async getRecipe(
+    id: number,
+    units?: MeasurementSystem,
+    expand?: boolean,
+  ): Promise<GetRecipeResponse> {
     return this.client
-      .get<GetRecipeResponse>(`/recipe/${id}`)
+      .get<GetRecipeResponse>(`/recipe/${id}`, { params: { units, expand } })
+      .then((response) => response.data);
+  }
+
+  async exportRecipe(id: number, format: RecipeExportFormat): Promise<string> {
+    return this.client
+      .get<string>(`/recipe/${id}`, {
+        params: { format },
+        responseType: "text",
+      })
+      .then((response) => response.data);
+  }
+
+  async getScaledRecipe(
+    id: number,
+    params: ScaleRecipeParams,
+  ): Promise<GetRecipeResponse> {
+    return this.client
+      .get<GetRecipeResponse>(`/recipe/${id}/scaled`, { params })
+      .then((response) => response.data);
+  }
+
+  async getUnits(): Promise<Array<GetUnitResponse>> {
+    return this.client
+      .get<Array<GetUnitResponse>>("/unit")
+      .then((response) => response.data);
+  }
+
+  async setRecipeImage(id: number, image: Blob): Promise<GetImageResponse> {
+    return this.uploadImage(`/recipe/${id}/image`, image);
+  }
+
+  async removeRecipeImage(id: number): Promise<void> {
+    await this.client.delete(`/recipe/${id}/image`);
+  }
+
+  async setStepImage(
+    id: number,
+    stepId: number,
+    image: Blob,
+  ): Promise<GetImageResponse> {
+    return this.uploadImage(`/recipe/${id}/step/${stepId}/image`, image);
+  }
+
+  async removeStepImage(id: number, stepId: number): Promise<void> {
+    await this.client.delete(`/recipe/${id}/step/${stepId}/image`);
+  }
+
+  private async uploadImage(
+    url: string,
+    image: Blob,
+  ): Promise<GetImageResponse> {
+    const form = new FormData();
+    form.append("image", image);
+    return this.client
+      .post<GetImageResponse>(url, form, {
+        headers: { "Content-Type": "multipart/form-data" },
+      })
       .then((response) => response.data);
   }
 
@@ -200,6 +620,452 @@ class ApiClient {
       .then((response) => response.data);
   }
 
+  async forkRecipe(id: number): Promise<GetRecipeResponse> {
+    return this.client
+      .post<GetRecipeResponse>(`/recipe/${id}/fork`)
+      .then((response) => response.data);
+  }
+
+  async parseIngredientLines(
+    lines: Array<string>,
+  ): Promise<GetParsedIngredientsResponse> {
+    return this.client
+      .post<GetParsedIngredientsResponse>("/ingredient/parse", { lines })
+      .then((response) => response.data);
+  }
+
+  async importRecipe(
+    document: string,
+    type: ImportDocumentType,
+  ): Promise<ImportRecipeResponse> {
+    return this.client
+      .post<ImportRecipeResponse>("/recipe/import", document, {
+        headers: {
+          "Content-Type": type === "html" ? "text/html" : "application/ld+json",
+        },
+      })
+      .then((response) => response.data);
+  }
+
+  async importCooklang(
+    text: string,
+    title?: string,
+  ): Promise<ImportRecipeResponse> {
+    return this.client
+      .post<ImportRecipeResponse>("/recipe/import/cooklang", text, {
+        params: { title },
+        headers: { "Content-Type": "text/plain" },
+      })
+      .then((response) => response.data);
+  }
+
+  async exportLibrary(): Promise<Blob> {
+    return this.client
+      .get<Blob>("/recipe/archive", { responseType: "blob" })
+      .then((response) => response.data);
+  }
+
+  async importLibrary(
+    archive: Blob,
+    onConflict: ArchiveConflictPolicy,
+  ): Promise<ImportArchiveResponse> {
+    return this.client
+      .post<ImportArchiveResponse>("/recipe/archive", archive, {
+        params: { on_conflict: onConflict },
+        headers: { "Content-Type": "application/zip" },
+      })
+      .then((response) => response.data);
+  }
+
+  async getShoppingLists(): Promise<Array<GetShoppingListResponse>> {
+    return this.client
+      .get<Array<GetShoppingListResponse>>("/shopping-list")
+      .then((response) => response.data);
+  }
+
+  async getShoppingList(id: number): Promise<GetShoppingListResponse> {
+    return this.client
+      .get<GetShoppingListResponse>(`/shopping-list/${id}`)
+      .then((response) => response.data);
+  }
+
+  async createShoppingList(name: string): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>("/shopping-list", { name })
+      .then((response) => response.data);
+  }
+
+  async renameShoppingList(
+    id: number,
+    name: string,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>(`/shopping-list/${id}`, { name })
+      .then((response) => response.data);
+  }
+
+  async deleteShoppingList(id: number): Promise<GetShoppingListResponse> {
+    return this.client
+      .delete<GetShoppingListResponse>(`/shopping-list/${id}`)
+      .then((response) => response.data);
+  }
+
+  async addRecipeToShoppingList(
+    id: number,
+    request: AddShoppingListRecipeRequest,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>(`/shopping-list/${id}/recipe`, request)
+      .then((response) => response.data);
+  }
+
+  async addShoppingListItem(
+    id: number,
+    request: AddShoppingListItemRequest,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>(`/shopping-list/${id}/item`, request)
+      .then((response) => response.data);
+  }
+
+  async updateShoppingListItem(
+    id: number,
+    itemId: number,
+    request: UpdateShoppingListItemRequest,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>(
+        `/shopping-list/${id}/item/${itemId}`,
+        request,
+      )
+      .then((response) => response.data);
+  }
+
+  async removeShoppingListItem(
+    id: number,
+    itemId: number,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .delete<GetShoppingListResponse>(`/shopping-list/${id}/item/${itemId}`)
+      .then((response) => response.data);
+  }
+
+  async reorderShoppingListItems(
+    id: number,
+    itemIds: Array<number>,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>(`/shopping-list/${id}/order`, { itemIds })
+      .then((response) => response.data);
+  }
+
+  async getMealPlan(
+    from: string,
+    to: string,
+  ): Promise<Array<GetMealPlanEntryResponse>> {
+    return this.client
+      .get<Array<GetMealPlanEntryResponse>>("/meal-plan", {
+        params: { from, to },
+      })
+      .then((response) => response.data);
+  }
+
+  async createMealPlanEntry(
+    request: MutateMealPlanEntryRequest,
+  ): Promise<GetMealPlanEntryResponse> {
+    return this.client
+      .post<GetMealPlanEntryResponse>("/meal-plan", request)
+      .then((response) => response.data);
+  }
+
+  async updateMealPlanEntry(
+    id: number,
+    request: MutateMealPlanEntryRequest,
+  ): Promise<GetMealPlanEntryResponse> {
+    return this.client
+      .post<GetMealPlanEntryResponse>(`/meal-plan/${id}`, request)
+      .then((response) => response.data);
+  }
+
+  async deleteMealPlanEntry(id: number): Promise<GetMealPlanEntryResponse> {
+    return this.client
+      .delete<GetMealPlanEntryResponse>(`/meal-plan/${id}`)
+      .then((response) => response.data);
+  }
+
+  async exportMealPlanCalendar(from: string, to: string): Promise<string> {
+    return this.client
+      .get<string>("/meal-plan/calendar.ics", {
+        params: { from, to },
+        responseType: "text",
+      })
+      .then((response) => response.data);
+  }
+
+  async generateShoppingListFromMealPlan(
+    from: string,
+    to: string,
+    name?: string,
+  ): Promise<GetShoppingListResponse> {
+    return this.client
+      .post<GetShoppingListResponse>("/meal-plan/shopping-list", {
+        from,
+        to,
+        name,
+      })
+      .then((response) => response.data);
+  }
+
+  async getPantry(location?: string): Promise<Array<GetPantryItemResponse>> {
+    return this.client
+      .get<Array<GetPantryItemResponse>>("/pantry", { params: { location } })
+      .then((response) => response.data);
+  }
+
+  async getExpiringPantryItems(
+    days?: number,
+  ): Promise<Array<GetPantryItemResponse>> {
+    return this.client
+      .get<Array<GetPantryItemResponse>>("/pantry/expiring", {
+        params: { days },
+      })
+      .then((response) => response.data);
+  }
+
+  async createPantryItem(
+    request: MutatePantryItemRequest,
+  ): Promise<GetPantryItemResponse> {
+    return this.client
+      .post<GetPantryItemResponse>("/pantry", request)
+      .then((response) => response.data);
+  }
+
+  async updatePantryItem(
+    id: number,
+    request: MutatePantryItemRequest,
+  ): Promise<GetPantryItemResponse> {
+    return this.client
+      .post<GetPantryItemResponse>(`/pantry/${id}`, request)
+      .then((response) => response.data);
+  }
+
+  async adjustPantryItem(
+    id: number,
+    request: AdjustPantryItemRequest,
+  ): Promise<GetPantryItemResponse> {
+    return this.client
+      .post<GetPantryItemResponse>(`/pantry/${id}/adjust`, request)
+      .then((response) => response.data);
+  }
+
+  async deletePantryItem(id: number): Promise<GetPantryItemResponse> {
+    return this.client
+      .delete<GetPantryItemResponse>(`/pantry/${id}`)
+      .then((response) => response.data);
+  }
+
+  async cookRecipe(request: CookRecipeRequest): Promise<GetCookReportResponse> {
+    return this.client
+      .post<GetCookReportResponse>("/pantry/cook", request)
+      .then((response) => response.data);
+  }
+
+  async getRecipeCookLog(
+    recipeId: number,
+  ): Promise<Array<GetCookLogEntryResponse>> {
+    return this.client
+      .get<Array<GetCookLogEntryResponse>>(`/recipe/${recipeId}/cook-log`)
+      .then((response) => response.data);
+  }
+
+  async getUserCookLog(
+    userId: number,
+  ): Promise<Array<GetCookLogEntryResponse>> {
+    return this.client
+      .get<Array<GetCookLogEntryResponse>>(`/user/${userId}/cook-log`)
+      .then((response) => response.data);
+  }
+
+  async createCookLogEntry(
+    request: MutateCookLogEntryRequest,
+  ): Promise<GetCookLogEntryResponse> {
+    return this.client
+      .post<GetCookLogEntryResponse>("/cook-log", request)
+      .then((response) => response.data);
+  }
+
+  async updateCookLogEntry(
+    id: number,
+    request: MutateCookLogEntryRequest,
+  ): Promise<GetCookLogEntryResponse> {
+    return this.client
+      .post<GetCookLogEntryResponse>(`/cook-log/${id}`, request)
+      .then((response) => response.data);
+  }
+
+  async deleteCookLogEntry(id: number): Promise<GetCookLogEntryResponse> {
+    return this.client
+      .delete<GetCookLogEntryResponse>(`/cook-log/${id}`)
+      .then((response) => response.data);
+  }
+
+  async getReviews(recipeId: number): Promise<Array<GetReviewResponse>> {
+    return this.client
+      .get<Array<GetReviewResponse>>(`/recipe/${recipeId}/reviews`)
+      .then((response) => response.data);
+  }
+
+  async createReview(
+    recipeId: number,
+    request: MutateReviewRequest,
+  ): Promise<GetReviewResponse> {
+    return this.client
+      .post<GetReviewResponse>(`/recipe/${recipeId}/reviews`, request)
+      .then((response) => response.data);
+  }
+
+  async updateReview(
+    id: number,
+    request: MutateReviewRequest,
+  ): Promise<GetReviewResponse> {
+    return this.client
+      .post<GetReviewResponse>(`/review/${id}`, request)
+      .then((response) => response.data);
+  }
+
+  async deleteReview(id: number): Promise<GetReviewResponse> {
+    return this.client
+      .delete<GetReviewResponse>(`/review/${id}`)
+      .then((response) => response.data);
+  }
+
+  async replyToReview(
+    id: number,
+    reply: string | null,
+  ): Promise<GetReviewResponse> {
+    return this.client
+      .post<GetReviewResponse>(`/review/${id}/reply`, { reply })
+      .then((response) => response.data);
+  }
+
+  async getCollections(): Promise<Array<GetCollectionResponse>> {
+    return this.client
+      .get<Array<GetCollectionResponse>>("/collection")
+      .then((response) => response.data);
+  }
+
+  async getCollection(id: number): Promise<GetCollectionResponse> {
+    return this.client
+      .get<GetCollectionResponse>(`/collection/${id}`)
+      .then((response) => response.data);
+  }
+
+  async createCollection(
+    request: MutateCollectionRequest,
+  ): Promise<GetCollectionResponse> {
+    return this.client
+      .post<GetCollectionResponse>("/collection", request)
+      .then((response) => response.data);
+  }
+
+  async updateCollection(
+    id: number,
+    request: MutateCollectionRequest,
+  ): Promise<GetCollectionResponse> {
+    return this.client
+      .post<GetCollectionResponse>(`/collection/${id}`, request)
+      .then((response) => response.data);
+  }
+
+  async deleteCollection(id: number): Promise<GetCollectionResponse> {
+    return this.client
+      .delete<GetCollectionResponse>(`/collection/${id}`)
+      .then((response) => response.data);
+  }
+
+  async addCollectionRecipe(
+    id: number,
+    recipeId: number,
+  ): Promise<GetCollectionResponse> {
+    return this.client
+      .post<GetCollectionResponse>(`/collection/${id}/recipe`, { recipeId })
+      .then((response) => response.data);
+  }
+
+  async removeCollectionRecipe(
+    id: number,
+    recipeId: number,
+  ): Promise<GetCollectionResponse> {
+    return this.client
+      .delete<GetCollectionResponse>(`/collection/${id}/recipe/${recipeId}`)
+      .then((response) => response.data);
+  }
+
+  async reorderCollectionRecipes(
+    id: number,
+    recipeIds: Array<number>,
+  ): Promise<GetCollectionResponse> {
+    return this.client
+      .post<GetCollectionResponse>(`/collection/${id}/order`, { recipeIds })
+      .then((response) => response.data);
+  }
+
+  async getFavorites(): Promise<Array<GetCollectionRecipeResponse>> {
+    return this.client
+      .get<Array<GetCollectionRecipeResponse>>("/favorites")
+      .then((response) => response.data);
+  }
+
+  async addFavorite(recipeId: number): Promise<void> {
+    await this.client.post(`/recipe/${recipeId}/favorite`);
+  }
+
+  async removeFavorite(recipeId: number): Promise<void> {
+    await this.client.delete(`/recipe/${recipeId}/favorite`);
+  }
+
+  async getRecipeRevisions(
+    recipeId: number,
+  ): Promise<Array<GetRevisionSummaryResponse>> {
+    return this.client
+      .get<Array<GetRevisionSummaryResponse>>(`/recipe/${recipeId}/revisions`)
+      .then((response) => response.data);
+  }
+
+  async getRecipeRevision(
+    recipeId: number,
+    revision: number,
+  ): Promise<GetRecipeRevisionResponse> {
+    return this.client
+      .get<GetRecipeRevisionResponse>(
+        `/recipe/${recipeId}/revisions/${revision}`,
+      )
+      .then((response) => response.data);
+  }
+
+  async diffRecipeRevisions(
+    recipeId: number,
+    from: number,
+    to: number,
+  ): Promise<GetRevisionDiffResponse> {
+    return this.client
+      .get<GetRevisionDiffResponse>(`/recipe/${recipeId}/revisions/diff`, {
+        params: { from, to },
+      })
+      .then((response) => response.data);
+  }
+
+  async revertRecipe(
+    recipeId: number,
+    revision: number,
+  ): Promise<GetRecipeResponse> {
+    return this.client
+      .post<GetRecipeResponse>(
+        `/recipe/${recipeId}/revisions/${revision}/revert`,
+      )
+      .then((response) => response.data);
+  }
+
   async updateRecipe(
     updateRecipeRequest: MutateRecipeRequest,
   ): Promise<GetRecipeResponse> {